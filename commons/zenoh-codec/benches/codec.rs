@@ -92,6 +92,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -138,6 +139,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -179,6 +181,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -220,6 +223,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -248,6 +252,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -287,6 +292,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
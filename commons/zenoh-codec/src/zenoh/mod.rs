@@ -415,3 +415,63 @@ where
         Ok((ext::AttachmentType { buffer }, more))
     }
 }
+
+// Extension: TraceContext
+impl<W, const ID: u8> WCodec<(&ext::TraceContextType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::TraceContextType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+        let ext::TraceContextType {
+            trace_id,
+            span_id,
+            flags,
+        } = x;
+
+        let header: ZExtZBufHeader<{ ID }> =
+            ZExtZBufHeader::new(trace_id.len() + span_id.len() + 1);
+        self.write(&mut *writer, (&header, more))?;
+        writer.write_exact(trace_id)?;
+        writer.write_exact(span_id)?;
+        self.write(&mut *writer, *flags)?;
+
+        Ok(())
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::TraceContextType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::TraceContextType<{ ID }>, bool), Self::Error> {
+        let (h, more): (ZExtZBufHeader<{ ID }>, bool) = self.read(&mut *reader)?;
+
+        let mut trace_id = [0u8; 16];
+        let mut span_id = [0u8; 8];
+        let len = trace_id.len() + span_id.len() + 1;
+        if h.len < len {
+            return Err(DidntRead);
+        }
+        reader.read_exact(&mut trace_id)?;
+        reader.read_exact(&mut span_id)?;
+        let flags: u8 = self.codec.read(&mut *reader)?;
+        // Skip the fields added by future versions of the extension
+        if h.len > len {
+            reader.read_zslice(h.len - len)?;
+        }
+
+        Ok((
+            ext::TraceContextType {
+                trace_id,
+                span_id,
+                flags,
+            },
+            more,
+        ))
+    }
+}
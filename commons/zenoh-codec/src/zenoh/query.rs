@@ -80,6 +80,7 @@ where
             ext_consolidation,
             ext_body,
             ext_attachment,
+            ext_trace,
             ext_unknown,
         } = x;
 
@@ -92,6 +93,7 @@ where
             + ((ext_consolidation != &ext::ConsolidationType::default()) as u8)
             + (ext_body.is_some() as u8)
             + (ext_attachment.is_some() as u8)
+            + (ext_trace.is_some() as u8)
             + (ext_unknown.len() as u8);
         if n_exts != 0 {
             header |= flag::Z;
@@ -120,6 +122,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (att, n_exts != 0))?;
         }
+        if let Some(trace) = ext_trace.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (trace, n_exts != 0))?;
+        }
         for u in ext_unknown.iter() {
             n_exts -= 1;
             self.write(&mut *writer, (u, n_exts != 0))?;
@@ -164,6 +170,7 @@ where
         let mut ext_consolidation = ext::ConsolidationType::default();
         let mut ext_body: Option<ext::QueryBodyType> = None;
         let mut ext_attachment: Option<ext::AttachmentType> = None;
+        let mut ext_trace: Option<ext::TraceContextType> = None;
         let mut ext_unknown = Vec::new();
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
@@ -191,6 +198,11 @@ where
                     ext_attachment = Some(a);
                     has_ext = ext;
                 }
+                ext::TraceContext::ID => {
+                    let (t, ext): (ext::TraceContextType, bool) = eodec.read(&mut *reader)?;
+                    ext_trace = Some(t);
+                    has_ext = ext;
+                }
                 _ => {
                     let (u, ext) = extension::read(reader, "Query", ext)?;
                     ext_unknown.push(u);
@@ -205,6 +217,7 @@ where
             ext_consolidation,
             ext_body,
             ext_attachment,
+            ext_trace,
             ext_unknown,
         })
     }
@@ -46,6 +46,7 @@ where
             #[cfg(feature = "shared-memory")]
             ext_shm,
             ext_attachment,
+            ext_trace,
             ext_unknown,
             payload,
         } = x;
@@ -61,6 +62,7 @@ where
         let mut n_exts = (ext_sinfo.is_some()) as u8
             + ((ext_consolidation != &ext::ConsolidationType::default()) as u8)
             + (ext_attachment.is_some()) as u8
+            + (ext_trace.is_some() as u8)
             + (ext_unknown.len() as u8);
         #[cfg(feature = "shared-memory")]
         {
@@ -97,6 +99,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (att, n_exts != 0))?;
         }
+        if let Some(trace) = ext_trace.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (trace, n_exts != 0))?;
+        }
         for u in ext_unknown.iter() {
             n_exts -= 1;
             self.write(&mut *writer, (u, n_exts != 0))?;
@@ -160,6 +166,7 @@ where
         #[cfg(feature = "shared-memory")]
         let mut ext_shm: Option<ext::ShmType> = None;
         let mut ext_attachment: Option<ext::AttachmentType> = None;
+        let mut ext_trace: Option<ext::TraceContextType> = None;
         let mut ext_unknown = Vec::new();
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
@@ -188,6 +195,11 @@ where
                     ext_attachment = Some(a);
                     has_ext = ext;
                 }
+                ext::TraceContext::ID => {
+                    let (t, ext): (ext::TraceContextType, bool) = eodec.read(&mut *reader)?;
+                    ext_trace = Some(t);
+                    has_ext = ext;
+                }
                 _ => {
                     let (u, ext) = extension::read(reader, "Reply", ext)?;
                     ext_unknown.push(u);
@@ -219,6 +231,7 @@ where
             #[cfg(feature = "shared-memory")]
             ext_shm,
             ext_attachment,
+            ext_trace,
             ext_unknown,
             payload,
         })
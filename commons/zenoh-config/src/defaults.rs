@@ -98,6 +98,7 @@ pub mod routing {
     pub mod peer {
         pub const mode: &str = "peer_to_peer";
     }
+    pub const trace_spans: bool = false;
 }
 
 impl Default for TransportUnicastConf {
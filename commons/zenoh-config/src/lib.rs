@@ -311,12 +311,17 @@ validated_struct::validator! {
                 /// The routing strategy to use in peers. ("peer_to_peer" or "linkstate").
                 mode: Option<String>,
             },
+            /// When set to true, a `tracing` span is emitted for each routing decision taken
+            /// on a message that carries a trace context (default: false).
+            trace_spans: Option<bool>,
         },
 
         /// The declarations aggregation strategy.
         pub aggregation: #[derive(Default)]
         AggregationConf {
             /// A list of key-expressions for which all included subscribers will be aggregated into.
+            /// In routers, all subscriptions of clients and peers included in one of those
+            /// key-expressions are announced to other routers as a single subscription.
             subscribers: Vec<OwnedKeyExpr>,
             /// A list of key-expressions for which all included publishers will be aggregated into.
             publishers: Vec<OwnedKeyExpr>,
@@ -450,7 +455,9 @@ validated_struct::validator! {
             SharedMemoryConf {
                 /// Whether shared memory is enabled or not.
                 /// If set to `true`, the SHM buffer optimization support will be announced to other parties. (default `false`).
-                /// This option doesn't make SHM buffer optimization mandatory, the real support depends on other party setting
+                /// This option doesn't make SHM buffer optimization mandatory, the real support depends on other party setting.
+                /// When enabled in a router, SHM buffers are forwarded by reference between the local parties supporting it
+                /// and are only inlined when forwarded to the other parties.
                 enabled: bool,
             },
             pub auth: #[derive(Default)]
@@ -500,6 +507,14 @@ validated_struct::validator! {
 
         },
 
+        /// Configuration of the metrics exporter.
+        pub metrics: #[derive(Default)]
+        MetricsConf {
+            /// The address (e.g. `127.0.0.1:9464`) on which the metrics are served over HTTP on `/metrics`,
+            /// in OpenMetrics text format. No HTTP endpoint is started if unset.
+            pub listen: Option<String>,
+        },
+
         /// Configuration of the downsampling.
         downsampling: Vec<DownsamplingItemConf>,
 
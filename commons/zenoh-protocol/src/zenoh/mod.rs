@@ -290,4 +290,37 @@ pub mod ext {
             }
         }
     }
+
+    /// W3C trace context (see <https://www.w3.org/TR/trace-context/>)
+    ///
+    /// ```text
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// ~ trace_id: 16  ~
+    /// +---------------+
+    /// ~ span_id: 8    ~
+    /// +---------------+
+    /// |     flags     |
+    /// +---------------+
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TraceContextType<const ID: u8> {
+        pub trace_id: [u8; 16],
+        pub span_id: [u8; 8],
+        pub flags: u8,
+    }
+
+    impl<const ID: u8> TraceContextType<{ ID }> {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            Self {
+                trace_id: rng.gen(),
+                span_id: rng.gen(),
+                flags: rng.gen(),
+            }
+        }
+    }
 }
@@ -49,6 +49,7 @@ pub struct Put {
     pub encoding: Encoding,
     pub ext_sinfo: Option<ext::SourceInfoType>,
     pub ext_attachment: Option<ext::AttachmentType>,
+    pub ext_trace: Option<ext::TraceContextType>,
     #[cfg(feature = "shared-memory")]
     pub ext_shm: Option<ext::ShmType>,
     pub ext_unknown: Vec<ZExtUnknown>,
@@ -75,6 +76,11 @@ pub mod ext {
     /// # User attachment
     pub type Attachment = zextzbuf!(0x3, false);
     pub type AttachmentType = crate::zenoh::ext::AttachmentType<{ Attachment::ID }>;
+
+    /// # TraceContext extension
+    /// Used to carry the W3C trace context of the operation
+    pub type TraceContext = zextzbuf!(0x4, false);
+    pub type TraceContextType = crate::zenoh::ext::TraceContextType<{ TraceContext::ID }>;
 }
 
 impl Put {
@@ -94,10 +100,11 @@ impl Put {
         #[cfg(feature = "shared-memory")]
         let ext_shm = rng.gen_bool(0.5).then_some(ext::ShmType::rand());
         let ext_attachment = rng.gen_bool(0.5).then_some(ext::AttachmentType::rand());
+        let ext_trace = rng.gen_bool(0.5).then_some(ext::TraceContextType::rand());
         let mut ext_unknown = Vec::new();
         for _ in 0..rng.gen_range(0..4) {
             ext_unknown.push(ZExtUnknown::rand2(
-                iext::mid(ext::Attachment::ID) + 1,
+                iext::mid(ext::TraceContext::ID) + 1,
                 false,
             ));
         }
@@ -110,6 +117,7 @@ impl Put {
             #[cfg(feature = "shared-memory")]
             ext_shm,
             ext_attachment,
+            ext_trace,
             ext_unknown,
             payload,
         }
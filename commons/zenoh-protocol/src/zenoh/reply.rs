@@ -52,6 +52,7 @@ pub struct Reply {
     #[cfg(feature = "shared-memory")]
     pub ext_shm: Option<ext::ShmType>,
     pub ext_attachment: Option<ext::AttachmentType>,
+    pub ext_trace: Option<ext::TraceContextType>,
     pub ext_unknown: Vec<ZExtUnknown>,
     pub payload: ZBuf,
 }
@@ -83,6 +84,11 @@ pub mod ext {
     /// # User attachment
     pub type Attachment = zextzbuf!(0x4, false);
     pub type AttachmentType = crate::zenoh::ext::AttachmentType<{ Attachment::ID }>;
+
+    /// # TraceContext extension
+    /// Used to carry the W3C trace context of the operation
+    pub type TraceContext = zextzbuf!(0x5, false);
+    pub type TraceContextType = crate::zenoh::ext::TraceContextType<{ TraceContext::ID }>;
 }
 
 impl Reply {
@@ -103,10 +109,11 @@ impl Reply {
         #[cfg(feature = "shared-memory")]
         let ext_shm = rng.gen_bool(0.5).then_some(ext::ShmType::rand());
         let ext_attachment = rng.gen_bool(0.5).then_some(ext::AttachmentType::rand());
+        let ext_trace = rng.gen_bool(0.5).then_some(ext::TraceContextType::rand());
         let mut ext_unknown = Vec::new();
         for _ in 0..rng.gen_range(0..4) {
             ext_unknown.push(ZExtUnknown::rand2(
-                iext::mid(ext::Attachment::ID) + 1,
+                iext::mid(ext::TraceContext::ID) + 1,
                 false,
             ));
         }
@@ -120,6 +127,7 @@ impl Reply {
             #[cfg(feature = "shared-memory")]
             ext_shm,
             ext_attachment,
+            ext_trace,
             ext_unknown,
             payload,
         }
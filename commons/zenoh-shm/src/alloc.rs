@@ -0,0 +1,210 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{cmp, collections::binary_heap::BinaryHeap, fmt};
+
+const MIN_FREE_CHUNK_SIZE: usize = 1_024;
+
+/// A chunk of a shared memory segment, identified by its offset in the segment.
+#[derive(Eq, Copy, Clone, Debug)]
+pub struct Chunk {
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl Ord for Chunk {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.size.cmp(&other.size)
+    }
+}
+
+impl PartialOrd for Chunk {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Chunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+    }
+}
+
+/// The allocation strategy used by a [`SharedMemoryManager`](crate::SharedMemoryManager)
+/// to reserve chunks of its segment.
+///
+/// The allocator only deals with offsets and sizes, the [`SharedMemoryManager`](crate::SharedMemoryManager)
+/// takes care of the chunk headers and of the reference counting of the allocated buffers.
+pub trait SharedMemoryAllocator: Send + fmt::Debug {
+    /// Reserves a chunk of `len` bytes and returns its offset in the segment,
+    /// or `None` if no chunk large enough is available.
+    fn alloc(&mut self, len: usize) -> Option<usize>;
+
+    /// Releases a chunk previously returned by [`alloc`](SharedMemoryAllocator::alloc).
+    fn free(&mut self, chunk: Chunk);
+
+    /// Returns the amount of memory that it was able to de-fragment.
+    fn defragment(&mut self) -> usize {
+        0
+    }
+}
+
+/// The default [`SharedMemoryAllocator`].
+///
+/// The strategy taken is the same for some Unix System V implementations -- as described in the
+/// famous Bach's book --  in essence keep an ordered list of free slot and always look for the
+/// biggest as that will give the biggest left-over.
+#[derive(Debug)]
+pub struct BestFitAllocator {
+    free_list: BinaryHeap<Chunk>,
+}
+
+impl BestFitAllocator {
+    /// Creates a new allocator managing a segment of `size` bytes.
+    pub fn new(size: usize) -> Self {
+        let mut free_list = BinaryHeap::new();
+        free_list.push(Chunk { offset: 0, size });
+        Self { free_list }
+    }
+
+    fn try_merge_adjacent_chunks(a: &Chunk, b: &Chunk) -> Option<Chunk> {
+        if a.offset + a.size == b.offset {
+            Some(Chunk {
+                offset: a.offset,
+                size: a.size + b.size,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl SharedMemoryAllocator for BestFitAllocator {
+    fn alloc(&mut self, len: usize) -> Option<usize> {
+        match self.free_list.pop() {
+            Some(chunk) if chunk.size >= len => {
+                tracing::trace!("Allocator selected Chunk ({:?})", &chunk);
+                if chunk.size - len >= MIN_FREE_CHUNK_SIZE {
+                    let free_chunk = Chunk {
+                        offset: chunk.offset + len,
+                        size: chunk.size - len,
+                    };
+                    tracing::trace!("The allocation will leave a Free Chunk: {:?}", &free_chunk);
+                    self.free_list.push(free_chunk);
+                }
+                Some(chunk.offset)
+            }
+            Some(c) => {
+                self.free_list.push(c);
+                tracing::trace!(
+                    "BestFitAllocator::alloc({}) cannot find any available chunk\nBestFitAllocator::free_list = {:?}",
+                    len,
+                    self.free_list
+                );
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn free(&mut self, chunk: Chunk) {
+        self.free_list.push(chunk)
+    }
+
+    fn defragment(&mut self) -> usize {
+        if self.free_list.len() > 1 {
+            let mut fbs: Vec<Chunk> = self.free_list.drain().collect();
+            fbs.sort_by(|x, y| x.offset.cmp(&y.offset));
+            let mut current = fbs.remove(0);
+            let mut defrag_mem = 0;
+            let mut i = 0;
+            let n = fbs.len();
+            for chunk in fbs.iter() {
+                i += 1;
+                let next = *chunk;
+                match BestFitAllocator::try_merge_adjacent_chunks(&current, &next) {
+                    Some(c) => {
+                        current = c;
+                        defrag_mem += current.size;
+                        if i == n {
+                            self.free_list.push(current)
+                        }
+                    }
+                    None => {
+                        self.free_list.push(current);
+                        if i == n {
+                            self.free_list.push(next);
+                        } else {
+                            current = next;
+                        }
+                    }
+                }
+            }
+            defrag_mem
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn best_fit_alloc_free() {
+        let mut allocator = BestFitAllocator::new(4 * MIN_FREE_CHUNK_SIZE);
+
+        let a = allocator.alloc(MIN_FREE_CHUNK_SIZE).unwrap();
+        let b = allocator.alloc(MIN_FREE_CHUNK_SIZE).unwrap();
+        let c = allocator.alloc(2 * MIN_FREE_CHUNK_SIZE).unwrap();
+        assert_eq!(a, 0);
+        assert_eq!(b, MIN_FREE_CHUNK_SIZE);
+        assert_eq!(c, 2 * MIN_FREE_CHUNK_SIZE);
+        assert!(allocator.alloc(1).is_none());
+
+        allocator.free(Chunk {
+            offset: b,
+            size: MIN_FREE_CHUNK_SIZE,
+        });
+        assert!(allocator.alloc(MIN_FREE_CHUNK_SIZE + 1).is_none());
+        assert_eq!(allocator.alloc(MIN_FREE_CHUNK_SIZE), Some(b));
+    }
+
+    #[test]
+    fn best_fit_keeps_small_leftovers_in_chunk() {
+        let mut allocator = BestFitAllocator::new(MIN_FREE_CHUNK_SIZE + 8);
+
+        // The leftover is smaller than MIN_FREE_CHUNK_SIZE and is not made available
+        assert_eq!(allocator.alloc(16), Some(0));
+        assert!(allocator.alloc(8).is_none());
+    }
+
+    #[test]
+    fn best_fit_defragment() {
+        let mut allocator = BestFitAllocator::new(3 * MIN_FREE_CHUNK_SIZE);
+        let chunks: Vec<Chunk> = (0..3)
+            .map(|_| Chunk {
+                offset: allocator.alloc(MIN_FREE_CHUNK_SIZE).unwrap(),
+                size: MIN_FREE_CHUNK_SIZE,
+            })
+            .collect();
+        for chunk in chunks {
+            allocator.free(chunk);
+        }
+        assert!(allocator.alloc(2 * MIN_FREE_CHUNK_SIZE).is_none());
+
+        assert!(allocator.defragment() > 0);
+        assert_eq!(allocator.alloc(3 * MIN_FREE_CHUNK_SIZE), Some(0));
+    }
+}
@@ -0,0 +1,226 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{SharedMemoryBackend, SharedMemorySegment, SCHEME_SEPARATOR};
+use std::{
+    ffi::CString,
+    fs::{File, OpenOptions},
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        unix::fs::MetadataExt,
+    },
+};
+use zenoh_result::{zerror, ShmError, ZResult};
+
+const SCHEME: &str = "memfd";
+
+// Returns the path through which the segment with the given identifier can be opened,
+// and the inode of its file
+fn proc_path(id: &str) -> ZResult<(String, u64)> {
+    let parts: Option<Vec<&str>> = id
+        .strip_prefix(SCHEME)
+        .and_then(|s| s.strip_prefix(SCHEME_SEPARATOR))
+        .map(|s| s.split('/').collect());
+    match parts.as_deref() {
+        Some([pid, fd, ino]) => match ino.parse() {
+            Ok(ino) => Ok((format!("/proc/{pid}/fd/{fd}"), ino)),
+            Err(_) => Err(ShmError(zerror!("Invalid memfd segment id {}", id)).into()),
+        },
+        _ => Err(ShmError(zerror!("Invalid memfd segment id {}", id)).into()),
+    }
+}
+
+/// A segment backed by a `memfd` file descriptor, created or opened by the [`MemfdBackend`].
+pub struct MemfdSegment {
+    id: String,
+    // Keeps the file descriptor open as long as the segment is mapped
+    _file: File,
+    ptr: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for MemfdSegment {}
+unsafe impl Sync for MemfdSegment {}
+
+impl MemfdSegment {
+    fn map(id: String, file: File, len: usize) -> ZResult<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(ShmError(zerror!(
+                "Unable to map memfd segment {}: {}",
+                id,
+                std::io::Error::last_os_error()
+            ))
+            .into());
+        }
+        Ok(Self {
+            id,
+            _file: file,
+            ptr: ptr as *mut u8,
+            len,
+        })
+    }
+}
+
+impl Drop for MemfdSegment {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+    }
+}
+
+// Safety: the whole file is mapped by `map` and only unmapped on drop
+unsafe impl SharedMemorySegment for MemfdSegment {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A [`SharedMemoryBackend`] relying on anonymous `memfd` files.
+///
+/// The identifiers of its segments are `memfd://<pid>/<fd>/<inode>`: other processes open them
+/// through `/proc/<pid>/fd/<fd>`, checking that its inode did not change with a reuse of the pid, thus nothing is left in the file system once all the
+/// processes mapping a segment are gone. Readers keep a segment alive as long as they map it,
+/// so they should unmap it once its creator died (see [`SharedMemoryReader::unmap_dead_segments`](crate::SharedMemoryReader::unmap_dead_segments)).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemfdBackend;
+
+impl SharedMemoryBackend for MemfdBackend {
+    fn scheme(&self) -> &str {
+        SCHEME
+    }
+
+    fn create(&self, name: &str, size: usize) -> ZResult<Box<dyn SharedMemorySegment>> {
+        let c_name = CString::new(name)
+            .map_err(|e| ShmError(zerror!("Invalid memfd segment name {}: {}", name, e)))?;
+        let fd = unsafe { libc::memfd_create(c_name.as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(ShmError(zerror!(
+                "Unable to create memfd segment {}: {}",
+                name,
+                std::io::Error::last_os_error()
+            ))
+            .into());
+        }
+        let file = unsafe { File::from_raw_fd(fd) };
+        file.set_len(size as u64)
+            .map_err(|e| ShmError(zerror!("Unable to resize memfd segment {}: {}", name, e)))?;
+        let ino = file
+            .metadata()
+            .map_err(|e| ShmError(zerror!("Unable to stat memfd segment {}: {}", name, e)))?
+            .ino();
+        let id = format!(
+            "{SCHEME}{SCHEME_SEPARATOR}{}/{fd}/{ino}",
+            std::process::id()
+        );
+        tracing::trace!("Created memfd segment {} for {}", id, name);
+        Ok(Box::new(MemfdSegment::map(id, file, size)?))
+    }
+
+    fn open(&self, id: &str) -> ZResult<Box<dyn SharedMemorySegment>> {
+        let (path, ino) = proc_path(id)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| {
+                ShmError(zerror!(
+                    "Unable to bind shared memory segment {}: {}",
+                    id,
+                    e
+                ))
+            })?;
+        let metadata = file.metadata().map_err(|e| {
+            ShmError(zerror!(
+                "Unable to bind shared memory segment {}: {}",
+                id,
+                e
+            ))
+        })?;
+        if metadata.ino() != ino {
+            return Err(ShmError(zerror!(
+                "Unable to bind shared memory segment {}: its creator is dead",
+                id
+            ))
+            .into());
+        }
+        let len = metadata.len() as usize;
+        Ok(Box::new(MemfdSegment::map(id.to_string(), file, len)?))
+    }
+    fn is_alive(&self, id: &str) -> bool {
+        // The file descriptor disappears with the process that created the segment,
+        // and the inode tells whether it was reused by another process
+        proc_path(id).is_ok_and(|(path, ino)| {
+            std::fs::metadata(path).is_ok_and(|metadata| metadata.ino() == ino)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memfd_create_open() {
+        let segment = MemfdBackend.create("memfd_create_open", 4_096).unwrap();
+        assert!(segment.id().starts_with("memfd://"));
+        assert_eq!(segment.len(), 4_096);
+        assert!(MemfdBackend.is_alive(segment.id()));
+
+        let opened = MemfdBackend.open(segment.id()).unwrap();
+        assert_eq!(opened.id(), segment.id());
+        assert_eq!(opened.len(), segment.len());
+        assert_ne!(opened.as_ptr(), segment.as_ptr());
+
+        // Both mappings share the same memory
+        unsafe {
+            *segment.as_ptr().add(42) = 42;
+            assert_eq!(*opened.as_ptr().add(42), 42);
+        }
+    }
+
+    #[test]
+    fn memfd_reused_fd() {
+        let segment = MemfdBackend.create("memfd_reused_fd", 4_096).unwrap();
+        let (_, ino) = proc_path(segment.id()).unwrap();
+        // Another file opened with the same pid and fd as the creator of the segment
+        let (prefix, _) = segment.id().rsplit_once('/').unwrap();
+        let reused = format!("{prefix}/{}", ino + 1);
+        assert!(!MemfdBackend.is_alive(&reused));
+        assert!(MemfdBackend.open(&reused).is_err());
+    }
+
+    #[test]
+    fn memfd_invalid_id() {
+        assert!(MemfdBackend.open("memfd://invalid").is_err());
+        assert!(MemfdBackend.open("posix://1/2").is_err());
+        assert!(!MemfdBackend.is_alive("memfd://invalid"));
+        assert!(!MemfdBackend.is_alive(&format!("memfd://{}/{}/{}", u32::MAX, 0, 0)));
+    }
+}
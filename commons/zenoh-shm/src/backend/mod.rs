@@ -0,0 +1,169 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    collections::HashMap,
+    ops::AddAssign,
+    sync::{Arc, RwLock},
+};
+use zenoh_result::{zerror, ShmError, ZResult};
+
+#[cfg(target_os = "linux")]
+mod memfd;
+mod posix;
+
+#[cfg(target_os = "linux")]
+pub use memfd::MemfdBackend;
+pub use posix::PosixBackend;
+
+/// The separator between the scheme of a [`SharedMemoryBackend`] and the rest of a segment identifier.
+pub const SCHEME_SEPARATOR: &str = "://";
+
+/// A shared memory segment mapped in the current process.
+///
+/// # Safety
+/// The [`SharedMemoryManager`](crate::SharedMemoryManager) and the [`SharedMemoryReader`](crate::SharedMemoryReader)
+/// access the memory of a segment through raw pointers, thus implementors must guarantee that:
+/// - [`as_ptr`](SharedMemorySegment::as_ptr) returns the address of a mapping of at least
+///   [`len`](SharedMemorySegment::len) readable and writable bytes, aligned at least on 8 bytes;
+/// - both values never change and the mapping stays valid until the segment is dropped.
+pub unsafe trait SharedMemorySegment: Send + Sync {
+    /// The identifier allowing other processes to open this segment.
+    ///
+    /// Identifiers of segments which are not handled by the [`PosixBackend`] must be prefixed
+    /// by the scheme of their backend followed by [`SCHEME_SEPARATOR`] (e.g. `memfd://1234/5/6789`).
+    fn id(&self) -> &str;
+
+    /// The address at which the segment is mapped.
+    fn as_ptr(&self) -> *mut u8;
+
+    /// The size of the segment in bytes.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A kind of shared memory segments.
+///
+/// Backends are used by a [`SharedMemoryManager`](crate::SharedMemoryManager) to create its segment
+/// and by a [`SharedMemoryReader`](crate::SharedMemoryReader) to open the segments it receives buffers from.
+pub trait SharedMemoryBackend: Send + Sync {
+    /// The scheme prefixing the identifiers of the segments of this backend.
+    fn scheme(&self) -> &str;
+
+    /// Creates a new segment of `size` bytes.
+    fn create(&self, name: &str, size: usize) -> ZResult<Box<dyn SharedMemorySegment>>;
+
+    /// Opens the segment created by another process with the given identifier.
+    fn open(&self, id: &str) -> ZResult<Box<dyn SharedMemorySegment>>;
+
+    /// Returns false if the process which created the segment with the given identifier is known to be dead.
+    fn is_alive(&self, _id: &str) -> bool {
+        true
+    }
+
+    /// Reclaims the segments left behind by dead processes.
+    fn sweep(&self) -> ZResult<Reclaimed> {
+        Ok(Reclaimed::default())
+    }
+}
+
+/// The amount of shared memory reclaimed from dead processes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Reclaimed {
+    /// The number of segments.
+    pub segments: usize,
+    /// The total size of the segments in bytes.
+    pub bytes: usize,
+}
+
+impl AddAssign for Reclaimed {
+    fn add_assign(&mut self, rhs: Self) {
+        self.segments += rhs.segments;
+        self.bytes += rhs.bytes;
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref BACKENDS: RwLock<HashMap<String, Arc<dyn SharedMemoryBackend>>> = {
+        let mut backends: HashMap<String, Arc<dyn SharedMemoryBackend>> = HashMap::new();
+        #[cfg(target_os = "linux")]
+        backends.insert(MemfdBackend.scheme().to_string(), Arc::new(MemfdBackend));
+        RwLock::new(backends)
+    };
+}
+
+/// Registers a [`SharedMemoryBackend`], allowing all the [`SharedMemoryReader`](crate::SharedMemoryReader)s
+/// of the process to open the segments whose identifier starts with its scheme.
+///
+/// A previously registered backend with the same scheme is replaced.
+pub fn register_backend(backend: Arc<dyn SharedMemoryBackend>) {
+    tracing::debug!("Register shared memory backend {}", backend.scheme());
+    BACKENDS
+        .write()
+        .unwrap()
+        .insert(backend.scheme().to_string(), backend);
+}
+
+/// Returns the [`SharedMemoryBackend`] able to open the segment with the given identifier.
+///
+/// Identifiers without scheme are handled by the [`PosixBackend`].
+pub fn get_backend(id: &str) -> ZResult<Arc<dyn SharedMemoryBackend>> {
+    match id.split_once(SCHEME_SEPARATOR) {
+        Some((scheme, _)) => BACKENDS
+            .read()
+            .unwrap()
+            .get(scheme)
+            .cloned()
+            .ok_or_else(|| {
+                ShmError(zerror!(
+                    "No shared memory backend registered for scheme {}",
+                    scheme
+                ))
+                .into()
+            }),
+        None => Ok(Arc::new(PosixBackend)),
+    }
+}
+
+/// Reclaims the segments left behind by dead processes with the [`PosixBackend`]
+/// and all the registered backends.
+///
+/// It is meant to be called at startup, before creating any [`SharedMemoryManager`](crate::SharedMemoryManager).
+/// The reclaimed memory is accounted in [`metrics::cleanup`](crate::metrics::cleanup).
+pub fn sweep_orphaned_segments() -> Reclaimed {
+    let mut backends: Vec<Arc<dyn SharedMemoryBackend>> = vec![Arc::new(PosixBackend)];
+    backends.extend(BACKENDS.read().unwrap().values().cloned());
+    let mut reclaimed = Reclaimed::default();
+    for backend in backends {
+        match backend.sweep() {
+            Ok(r) => reclaimed += r,
+            Err(e) => tracing::warn!(
+                "Unable to sweep {} shared memory segments: {}",
+                backend.scheme(),
+                e
+            ),
+        }
+    }
+    if reclaimed.segments > 0 {
+        tracing::info!(
+            "Reclaimed {} orphaned shared memory segments ({} bytes)",
+            reclaimed.segments,
+            reclaimed.bytes
+        );
+    }
+    crate::metrics::record_reclaimed(reclaimed);
+    reclaimed
+}
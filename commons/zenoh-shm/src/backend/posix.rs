@@ -0,0 +1,224 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{Reclaimed, SharedMemoryBackend, SharedMemorySegment};
+use advisory_lock::{AdvisoryFileLock, FileLockMode};
+use shared_memory::{Shmem, ShmemConf, ShmemError};
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+};
+use zenoh_result::{zerror, ShmError, ZResult};
+
+const ZENOH_SHM_PREFIX: &str = "zenoh_shm_zid";
+const LOCK_SUFFIX: &str = ".lock";
+
+fn lock_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{path}{LOCK_SUFFIX}"))
+}
+
+// The owner of a segment holds an exclusive lock on its lock file for the lifetime of the segment.
+// The lock is released by the OS when the owner dies, even if it crashed.
+fn is_locked(lock: &Path) -> bool {
+    match OpenOptions::new().read(true).write(true).open(lock) {
+        Ok(file) => match file.try_lock(FileLockMode::Exclusive) {
+            Ok(()) => {
+                let _ = file.unlock();
+                false
+            }
+            Err(_) => true,
+        },
+        Err(_) => false,
+    }
+}
+
+/// A segment created or opened by the [`PosixBackend`].
+pub struct PosixSegment {
+    path: String,
+    shmem: Shmem,
+    // Only set for the segments created by this process
+    lock: Option<File>,
+}
+
+unsafe impl Send for PosixSegment {}
+unsafe impl Sync for PosixSegment {}
+
+// Safety: `Shmem` keeps the whole segment mapped until it is dropped
+unsafe impl SharedMemorySegment for PosixSegment {
+    fn id(&self) -> &str {
+        &self.path
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        self.shmem.as_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.shmem.len()
+    }
+}
+
+impl Drop for PosixSegment {
+    fn drop(&mut self) {
+        if let Some(lock) = self.lock.take() {
+            let _ = lock.unlock();
+            let _ = fs::remove_file(lock_path(&self.path));
+        }
+    }
+}
+
+/// The default [`SharedMemoryBackend`], relying on POSIX shared memory objects
+/// referenced by a link file in the temporary directory.
+///
+/// The identifiers of its segments are the paths of their link file and have no scheme.
+/// The creator of a segment holds a lock on a `.lock` file next to the link file,
+/// which allows other processes to detect when it died.
+///
+/// Only the segments with a lock file which is no longer locked, and laid out by a
+/// [`SharedMemoryManager`](crate::SharedMemoryManager) of this version, are reclaimed by its sweep:
+/// the other ones may belong to live processes of other versions of zenoh, which don't take locks.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PosixBackend;
+
+impl SharedMemoryBackend for PosixBackend {
+    fn scheme(&self) -> &str {
+        "posix"
+    }
+
+    fn create(&self, name: &str, size: usize) -> ZResult<Box<dyn SharedMemorySegment>> {
+        let mut temp_dir = std::env::temp_dir();
+        let file_name: String = format!("{ZENOH_SHM_PREFIX}_{name}");
+        temp_dir.push(file_name);
+        let path: String = temp_dir
+            .to_str()
+            .ok_or_else(|| ShmError(zerror!("Unable to parse tmp directory: {:?}", temp_dir)))?
+            .to_string();
+        // The lock is taken before creating the segment so that a sweeping process
+        // never sees a segment without a live owner.
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path(&path))
+            .map_err(|e| ShmError(zerror!("Unable to create lock file for {}: {}", path, e)))?;
+        lock.try_lock(FileLockMode::Exclusive).map_err(|e| {
+            ShmError(zerror!(
+                "Unable to open SharedMemoryManager: SharedMemory {} is owned by another process: {}",
+                path,
+                e
+            ))
+        })?;
+        tracing::trace!("Creating file at: {}", path);
+        let shmem = match ShmemConf::new().size(size).flink(path.clone()).create() {
+            Ok(m) => m,
+            Err(ShmemError::LinkExists) => {
+                return Err(ShmError(zerror!(
+                    "Unable to open SharedMemoryManager: SharedMemory already exists"
+                ))
+                .into())
+            }
+            Err(e) => {
+                return Err(ShmError(zerror!("Unable to open SharedMemoryManager: {}", e)).into())
+            }
+        };
+        Ok(Box::new(PosixSegment {
+            path,
+            shmem,
+            lock: Some(lock),
+        }))
+    }
+
+    fn open(&self, id: &str) -> ZResult<Box<dyn SharedMemorySegment>> {
+        match ShmemConf::new().flink(id).open() {
+            Ok(shmem) => Ok(Box::new(PosixSegment {
+                path: id.to_string(),
+                shmem,
+                lock: None,
+            })),
+            Err(e) => Err(ShmError(zerror!(
+                "Unable to bind shared memory segment {}: {:?}",
+                id,
+                e
+            ))
+            .into()),
+        }
+    }
+
+    fn is_alive(&self, id: &str) -> bool {
+        // Segments created without lock file are considered alive as long as they exist
+        let lock = lock_path(id);
+        if lock.exists() {
+            is_locked(&lock)
+        } else {
+            Path::new(id).exists()
+        }
+    }
+
+    fn sweep(&self) -> ZResult<Reclaimed> {
+        let mut reclaimed = Reclaimed::default();
+        let temp_dir = std::env::temp_dir();
+        let entries = fs::read_dir(&temp_dir).map_err(|e| {
+            ShmError(zerror!(
+                "Unable to read tmp directory {:?}: {}",
+                temp_dir,
+                e
+            ))
+        })?;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+            if !file_name.starts_with(ZENOH_SHM_PREFIX) {
+                continue;
+            }
+            // Segments are handled with their lock file
+            let Some(entry_path) = entry.path().to_str().map(str::to_string) else {
+                continue;
+            };
+            let Some(path) = entry_path.strip_suffix(LOCK_SUFFIX) else {
+                continue;
+            };
+            let lock = lock_path(path);
+            // A lock file without link file belongs to a segment being created,
+            // whose creator may not have taken the lock yet.
+            if !Path::new(path).exists() || is_locked(&lock) {
+                continue;
+            }
+            let Ok(shmem) = ShmemConf::new().flink(path).open() else {
+                continue;
+            };
+            let mut segment = PosixSegment {
+                path: path.to_string(),
+                shmem,
+                lock: None,
+            };
+            if let Err(e) = crate::check_segment_layout(&segment) {
+                tracing::debug!("Not reclaiming shared memory segment: {}", e);
+                continue;
+            }
+            // Taking the ownership of the segment makes it unlinked when dropped
+            segment.shmem.set_owner(true);
+            tracing::debug!(
+                "Reclaiming orphaned shared memory segment {} ({} bytes)",
+                path,
+                segment.len()
+            );
+            reclaimed.segments += 1;
+            reclaimed.bytes += segment.len();
+            let _ = fs::remove_file(&lock);
+        }
+        Ok(reclaimed)
+    }
+}
@@ -11,24 +11,44 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use shared_memory::{Shmem, ShmemConf, ShmemError};
 use std::{
     any::Any,
-    cmp,
-    collections::{binary_heap::BinaryHeap, HashMap},
+    collections::HashMap,
     fmt, mem,
-    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    sync::{
+        atomic::{AtomicPtr, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 use zenoh_buffers::ZSliceBuffer;
 use zenoh_result::{zerror, ShmError, ZResult};
 
-const MIN_FREE_CHUNK_SIZE: usize = 1_024;
+pub mod alloc;
+pub mod backend;
+pub mod metrics;
+mod notify;
+
+use alloc::{BestFitAllocator, Chunk, SharedMemoryAllocator};
+use backend::{PosixBackend, Reclaimed, SharedMemoryBackend, SharedMemorySegment};
+
 const ACCOUNTED_OVERHEAD: usize = 4_096;
-const ZENOH_SHM_PREFIX: &str = "zenoh_shm_zid";
 
-// Chunk header
+// Segment header, holding the counter of the buffers released in the segment (see `notify`),
+// followed by a magic number and the version of the layout of the segment and its chunks
+const SEGMENT_HEADER_SIZE: usize = 64;
+const SEGMENT_MAGIC_OFFSET: usize = 8;
+const SEGMENT_MAGIC: &[u8; 8] = b"ZENOHSHM";
+const SEGMENT_VERSION_OFFSET: usize = SEGMENT_MAGIC_OFFSET + SEGMENT_MAGIC.len();
+// To be incremented with any change of the headers of the segments or of the chunks
+const SEGMENT_LAYOUT_VERSION: u32 = 1;
+
+// Chunk header: the reference count of the chunk followed by its generation,
+// which changes each time the chunk is collected or allocated again
 type ChunkHeaderType = AtomicUsize;
-const CHUNK_HEADER_SIZE: usize = std::mem::size_of::<ChunkHeaderType>();
+const CHUNK_HEADER_SIZE: usize = 2 * std::mem::size_of::<ChunkHeaderType>();
+
+// The generation of the chunks which have been garbage collected
+const COLLECTED_GENERATION: usize = 0;
 
 fn align_addr_at(addr: usize, align: usize) -> usize {
     match addr % align {
@@ -37,29 +57,68 @@ fn align_addr_at(addr: usize, align: usize) -> usize {
     }
 }
 
-#[derive(Eq, Copy, Clone, Debug)]
-struct Chunk {
-    base_addr: *mut u8,
-    offset: usize,
-    size: usize,
+fn write_segment_header(segment: &dyn SharedMemorySegment) {
+    let mut header = [0u8; SEGMENT_HEADER_SIZE - SEGMENT_MAGIC_OFFSET];
+    header[..SEGMENT_MAGIC.len()].copy_from_slice(SEGMENT_MAGIC);
+    header[SEGMENT_VERSION_OFFSET - SEGMENT_MAGIC_OFFSET..][..4]
+        .copy_from_slice(&SEGMENT_LAYOUT_VERSION.to_le_bytes());
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            header.as_ptr(),
+            segment.as_ptr().add(SEGMENT_MAGIC_OFFSET),
+            header.len(),
+        )
+    };
 }
 
-impl Ord for Chunk {
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        self.size.cmp(&other.size)
-    }
+// Checks that the segment has been laid out by a manager of this version, whose chunks can be read.
+// The segments of the other versions are rejected, in which case the transports don't use shared memory.
+pub(crate) fn check_segment_layout(segment: &dyn SharedMemorySegment) -> ZResult<()> {
+    if segment.len() < SEGMENT_HEADER_SIZE {
+        return Err(ShmError(zerror!(
+            "Shared memory segment {} of {} bytes is too small",
+            segment.id(),
+            segment.len()
+        ))
+        .into());
+    }
+    let mut header = [0u8; SEGMENT_VERSION_OFFSET + 4 - SEGMENT_MAGIC_OFFSET];
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            segment.as_ptr().add(SEGMENT_MAGIC_OFFSET),
+            header.as_mut_ptr(),
+            header.len(),
+        )
+    };
+    let (magic, version) = header.split_at(SEGMENT_MAGIC.len());
+    if magic != SEGMENT_MAGIC {
+        return Err(ShmError(zerror!(
+            "Shared memory segment {} has not been created by a SharedMemoryManager of this version",
+            segment.id()
+        ))
+        .into());
+    }
+    let version = u32::from_le_bytes(version.try_into().unwrap());
+    if version != SEGMENT_LAYOUT_VERSION {
+        return Err(ShmError(zerror!(
+            "Shared memory segment {} has layout version {} instead of {}",
+            segment.id(),
+            version,
+            SEGMENT_LAYOUT_VERSION
+        ))
+        .into());
+    }
+    Ok(())
 }
 
-impl PartialOrd for Chunk {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl PartialEq for Chunk {
-    fn eq(&self, other: &Self) -> bool {
-        self.size == other.size
-    }
+// Returns true if a chunk of `size` bytes at `offset` lies within the segment
+// and its header is properly aligned.
+fn is_valid_chunk(segment: &dyn SharedMemorySegment, offset: usize, size: usize) -> bool {
+    offset >= SEGMENT_HEADER_SIZE
+        && offset
+            .checked_add(size)
+            .map_or(false, |end| end <= segment.len())
+        && (segment.as_ptr() as usize + offset) % mem::align_of::<ChunkHeaderType>() == 0
 }
 
 /// Information about a [`SharedMemoryBuf`].
@@ -95,6 +154,8 @@ pub struct SharedMemoryBuf {
     pub buf: AtomicPtr<u8>,
     pub len: usize,
     pub info: SharedMemoryBufInfo,
+    // Keeps the segment mapped as long as the buffer is alive
+    segment: Arc<dyn SharedMemorySegment>,
 }
 
 impl std::fmt::Debug for SharedMemoryBuf {
@@ -143,7 +204,9 @@ impl SharedMemoryBuf {
 
     pub fn dec_ref_count(&self) {
         let rc = self.rc_ptr.load(Ordering::SeqCst);
-        unsafe { (*rc).fetch_sub(1, Ordering::SeqCst) };
+        if unsafe { (*rc).fetch_sub(1, Ordering::SeqCst) } == 1 {
+            notify::notify_release(self.segment.as_ref());
+        }
     }
 
     pub fn as_slice(&self) -> &[u8] {
@@ -185,6 +248,7 @@ impl Clone for SharedMemoryBuf {
             buf: AtomicPtr::new(bp),
             len: self.len,
             info: self.info.clone(),
+            segment: self.segment.clone(),
         }
     }
 }
@@ -192,13 +256,17 @@ impl Clone for SharedMemoryBuf {
 /*************************************/
 /*       SHARED MEMORY READER        */
 /*************************************/
+/// A reader of [`SharedMemoryBuf`]s allocated by other processes.
+///
+/// Segments are opened through the [`SharedMemoryBackend`] registered for the scheme of their
+/// identifier (see [`backend::register_backend`]).
+///
+/// Buffers keep their segment mapped, so a segment is actually unmapped once it has been
+/// removed from the reader and all the buffers read from it have been dropped.
 pub struct SharedMemoryReader {
-    segments: HashMap<String, Shmem>,
+    segments: HashMap<String, Arc<dyn SharedMemorySegment>>,
 }
 
-unsafe impl Send for SharedMemoryReader {}
-unsafe impl Sync for SharedMemoryReader {}
-
 impl SharedMemoryReader {
     pub fn new() -> Self {
         Self {
@@ -206,20 +274,24 @@ impl SharedMemoryReader {
         }
     }
 
+    /// Maps the segment of the buffer, which must have been laid out by a [`SharedMemoryManager`]
+    /// of the same layout version.
     pub fn connect_map_to_shm(&mut self, info: &SharedMemoryBufInfo) -> ZResult<()> {
-        match ShmemConf::new().flink(&info.shm_manager).open() {
-            Ok(shm) => {
-                self.segments.insert(info.shm_manager.clone(), shm);
+        let segment = backend::get_backend(&info.shm_manager)
+            .and_then(|b| b.open(&info.shm_manager))
+            .and_then(|segment| {
+                check_segment_layout(segment.as_ref())?;
+                Ok(segment)
+            });
+        match segment {
+            Ok(segment) => {
+                self.segments
+                    .insert(info.shm_manager.clone(), segment.into());
                 Ok(())
             }
             Err(e) => {
-                let e = zerror!(
-                    "Unable to bind shared memory segment {}: {:?}",
-                    info.shm_manager,
-                    e
-                );
                 tracing::trace!("{}", e);
-                Err(ShmError(e).into())
+                Err(e)
             }
         }
     }
@@ -229,6 +301,19 @@ impl SharedMemoryReader {
         // that the sender of this buffer has incremented for us.
         match self.segments.get(&info.shm_manager) {
             Some(shm) => {
+                if info.length < CHUNK_HEADER_SIZE
+                    || !is_valid_chunk(shm.as_ref(), info.offset, info.length)
+                {
+                    let e = zerror!(
+                        "Invalid chunk (offset: {}, length: {}) for shared memory segment {} of {} bytes",
+                        info.offset,
+                        info.length,
+                        info.shm_manager,
+                        shm.len()
+                    );
+                    tracing::trace!("{}", e);
+                    return Err(ShmError(e).into());
+                }
                 let base_ptr = shm.as_ptr();
                 let rc = unsafe { base_ptr.add(info.offset) as *mut ChunkHeaderType };
                 let rc_ptr = AtomicPtr::<ChunkHeaderType>::new(rc);
@@ -238,6 +323,7 @@ impl SharedMemoryReader {
                     buf: AtomicPtr::new(buf),
                     len: info.length - CHUNK_HEADER_SIZE,
                     info: info.clone(),
+                    segment: shm.clone(),
                 };
                 Ok(shmb)
             }
@@ -257,6 +343,28 @@ impl SharedMemoryReader {
             self.try_read_shmbuf(info)
         })
     }
+
+    /// Unmaps the segments whose creator died, returning the amount of memory unmapped.
+    ///
+    /// The buffers already read from those segments remain valid until dropped.
+    pub fn unmap_dead_segments(&mut self) -> Reclaimed {
+        let mut unmapped = Reclaimed::default();
+        self.segments.retain(|id, segment| {
+            let alive = backend::get_backend(id).map_or(true, |b| b.is_alive(id));
+            if !alive {
+                tracing::debug!(
+                    "Unmapping shared memory segment {} of a dead process ({} bytes)",
+                    id,
+                    segment.len()
+                );
+                unmapped.segments += 1;
+                unmapped.bytes += segment.len();
+            }
+            alive
+        });
+        metrics::record_unmapped(unmapped);
+        unmapped
+    }
 }
 
 impl Default for SharedMemoryReader {
@@ -275,65 +383,78 @@ impl fmt::Debug for SharedMemoryReader {
 /// A shared memory segment manager.
 ///
 /// Allows to access a shared memory segment and reserve some parts of this segment for writing.
+/// The segment is provided by a [`SharedMemoryBackend`] and its parts are reserved by a
+/// [`SharedMemoryAllocator`].
 pub struct SharedMemoryManager {
-    segment_path: String,
     size: usize,
     available: usize,
-    own_segment: Shmem,
-    free_list: BinaryHeap<Chunk>,
+    own_segment: Arc<dyn SharedMemorySegment>,
+    allocator: Box<dyn SharedMemoryAllocator>,
     busy_list: Vec<Chunk>,
     alignment: usize,
+    generation: usize,
 }
 
-unsafe impl Send for SharedMemoryManager {}
-
 impl SharedMemoryManager {
     /// Creates a new SharedMemoryManager managing allocations of a region of the
     /// given size.
     pub fn make(id: String, size: usize) -> ZResult<SharedMemoryManager> {
-        let mut temp_dir = std::env::temp_dir();
-        let file_name: String = format!("{ZENOH_SHM_PREFIX}_{id}");
-        temp_dir.push(file_name);
-        let path: String = temp_dir
-            .to_str()
-            .ok_or_else(|| ShmError(zerror!("Unable to parse tmp directory: {:?}", temp_dir)))?
-            .to_string();
-        tracing::trace!("Creating file at: {}", path);
+        Self::make_with(id, size, &PosixBackend, |len| {
+            Box::new(BestFitAllocator::new(len))
+        })
+    }
+
+    /// Creates a new SharedMemoryManager managing allocations of a region of the
+    /// given size, created by the given backend and reserved by the given allocator.
+    ///
+    /// The allocator is built by the `allocator` closure, which receives the size it has to manage:
+    /// the manager accounts for the chunk headers by adding some overhead to the region.
+    pub fn make_with<F>(
+        id: String,
+        size: usize,
+        backend: &dyn SharedMemoryBackend,
+        allocator: F,
+    ) -> ZResult<SharedMemoryManager>
+    where
+        F: FnOnce(usize) -> Box<dyn SharedMemoryAllocator>,
+    {
         let real_size = size + ACCOUNTED_OVERHEAD;
-        let shmem = match ShmemConf::new()
-            .size(real_size)
-            .flink(path.clone())
-            .create()
-        {
-            Ok(m) => m,
-            Err(ShmemError::LinkExists) => {
-                return Err(ShmError(zerror!(
-                    "Unable to open SharedMemoryManager: SharedMemory already exists"
-                ))
-                .into())
-            }
-            Err(e) => {
-                return Err(ShmError(zerror!("Unable to open SharedMemoryManager: {}", e)).into())
-            }
-        };
-        let base_ptr = shmem.as_ptr();
+        let segment = backend.create(&id, real_size)?;
+        Self::from_segment(segment, allocator)
+    }
 
-        let mut free_list = BinaryHeap::new();
-        let chunk = Chunk {
-            base_addr: base_ptr,
-            offset: 0,
-            size: real_size,
-        };
-        free_list.push(chunk);
-        let busy_list = vec![];
+    /// Creates a new SharedMemoryManager managing allocations in an existing segment,
+    /// e.g. a segment exported by a device driver.
+    ///
+    /// The allocator is built by the `allocator` closure, which receives the size it has to manage:
+    /// the size of the segment minus the header the manager keeps at its beginning.
+    /// Readers must be able to open the segment through the [`SharedMemoryBackend`]
+    /// registered for the scheme of its identifier.
+    pub fn from_segment<F>(
+        segment: Box<dyn SharedMemorySegment>,
+        allocator: F,
+    ) -> ZResult<SharedMemoryManager>
+    where
+        F: FnOnce(usize) -> Box<dyn SharedMemoryAllocator>,
+    {
+        let real_size = segment.len();
+        if real_size <= ACCOUNTED_OVERHEAD {
+            return Err(ShmError(zerror!(
+                "Unable to open SharedMemoryManager: segment {} is too small",
+                segment.id()
+            ))
+            .into());
+        }
+        notify::releases(segment.as_ref()).store(0, Ordering::SeqCst);
+        write_segment_header(segment.as_ref());
         let shm = SharedMemoryManager {
-            segment_path: path,
-            size,
-            available: real_size,
-            own_segment: shmem,
-            free_list,
-            busy_list,
+            size: real_size - ACCOUNTED_OVERHEAD,
+            available: real_size - SEGMENT_HEADER_SIZE,
+            own_segment: segment.into(),
+            allocator: allocator(real_size - SEGMENT_HEADER_SIZE),
+            busy_list: vec![],
             alignment: mem::align_of::<ChunkHeaderType>(),
+            generation: COLLECTED_GENERATION + 1,
         };
         tracing::trace!(
             "Created SharedMemoryManager for {:?}",
@@ -342,128 +463,152 @@ impl SharedMemoryManager {
         Ok(shm)
     }
 
-    fn free_chunk_map_to_shmbuf(&self, chunk: &Chunk) -> SharedMemoryBuf {
+    /// Returns the identifier of the managed segment.
+    pub fn segment_id(&self) -> &str {
+        self.own_segment.id()
+    }
+
+    // The chunk must have been checked with `is_valid_chunk`
+    fn free_chunk_map_to_shmbuf(&mut self, chunk: &Chunk) -> SharedMemoryBuf {
         let info = SharedMemoryBufInfo {
             offset: chunk.offset,
             length: chunk.size,
-            shm_manager: self.segment_path.clone(),
+            shm_manager: self.own_segment.id().to_string(),
             kind: 0,
         };
-        let rc = chunk.base_addr as *mut ChunkHeaderType;
-        unsafe { (*rc).store(1, Ordering::SeqCst) };
+        let base_addr = unsafe { self.own_segment.as_ptr().add(chunk.offset) };
+        let rc = base_addr as *mut ChunkHeaderType;
+        unsafe {
+            (*rc).store(1, Ordering::SeqCst);
+            (*rc.add(1)).store(self.generation, Ordering::SeqCst);
+        }
+        self.generation = self
+            .generation
+            .wrapping_add(1)
+            .max(COLLECTED_GENERATION + 1);
         let rc_ptr = AtomicPtr::<ChunkHeaderType>::new(rc);
         SharedMemoryBuf {
             rc_ptr,
-            buf: AtomicPtr::<u8>::new(unsafe { chunk.base_addr.add(CHUNK_HEADER_SIZE) }),
+            buf: AtomicPtr::<u8>::new(unsafe { base_addr.add(CHUNK_HEADER_SIZE) }),
             len: chunk.size - CHUNK_HEADER_SIZE,
             info,
+            segment: self.own_segment.clone(),
         }
     }
 
+    fn try_alloc(&mut self, required_len: usize) -> ZResult<Option<SharedMemoryBuf>> {
+        if self.available < required_len {
+            self.garbage_collect();
+        }
+        if self.available < required_len {
+            return Ok(None);
+        }
+        let Some(offset) = self.allocator.alloc(required_len) else {
+            return Ok(None);
+        };
+        // The allocator manages the segment after its header
+        let chunk = offset.checked_add(SEGMENT_HEADER_SIZE).map(|offset| Chunk {
+            offset,
+            size: required_len,
+        });
+        // The allocator is not trusted to stay within the segment
+        let Some(chunk) =
+            chunk.filter(|c| is_valid_chunk(self.own_segment.as_ref(), c.offset, c.size))
+        else {
+            return Err(ShmError(zerror!(
+                "SharedMemoryManager::alloc({}) got an invalid chunk at offset {} for segment {} of {} bytes from allocator {:?}",
+                required_len,
+                offset,
+                self.own_segment.id(),
+                self.own_segment.len(),
+                self.allocator
+            ))
+            .into());
+        };
+        self.available -= required_len;
+        let shm_buf = self.free_chunk_map_to_shmbuf(&chunk);
+        tracing::trace!("The allocated Chunk is ({:?})", &chunk);
+        tracing::trace!("Allocated Shared Memory Buffer: {:?}", &shm_buf);
+        self.busy_list.push(chunk);
+        Ok(Some(shm_buf))
+    }
+
     pub fn alloc(&mut self, len: usize) -> ZResult<SharedMemoryBuf> {
         tracing::trace!("SharedMemoryManager::alloc({})", len);
         // Always allocate a size that will keep the proper alignment requirements
         let required_len = align_addr_at(len + CHUNK_HEADER_SIZE, self.alignment);
-        if self.available < required_len {
-            self.garbage_collect();
+        match self.try_alloc(required_len)? {
+            Some(shm_buf) => Ok(shm_buf),
+            None if self.available >= required_len => {
+                let e = zerror!("SharedMemoryManager::alloc({}) cannot find any available chunk\nSharedMemoryManager::allocator = {:?}", len, self.allocator);
+                tracing::trace!("{}", e);
+                Err(e.into())
+            }
+            None => {
+                let e = zerror!( "SharedMemoryManager does not have sufficient free memory to allocate {} bytes, try de-fragmenting!", len);
+                tracing::warn!("{}", e);
+                Err(e.into())
+            }
         }
-        if self.available >= required_len {
-            // The strategy taken is the same for some Unix System V implementations -- as described in the
-            // famous Bach's book --  in essence keep an ordered list of free slot and always look for the
-            // biggest as that will give the biggest left-over.
-            match self.free_list.pop() {
-                Some(mut chunk) if chunk.size >= required_len => {
-                    self.available -= required_len;
-                    tracing::trace!("Allocator selected Chunk ({:?})", &chunk);
-                    if chunk.size - required_len >= MIN_FREE_CHUNK_SIZE {
-                        let free_chunk = Chunk {
-                            base_addr: unsafe { chunk.base_addr.add(required_len) },
-                            offset: chunk.offset + required_len,
-                            size: chunk.size - required_len,
-                        };
-                        tracing::trace!(
-                            "The allocation will leave a Free Chunk: {:?}",
-                            &free_chunk
-                        );
-                        self.free_list.push(free_chunk);
-                    }
-                    chunk.size = required_len;
-                    let shm_buf = self.free_chunk_map_to_shmbuf(&chunk);
-                    tracing::trace!("The allocated Chunk is ({:?})", &chunk);
-                    tracing::trace!("Allocated Shared Memory Buffer: {:?}", &shm_buf);
-                    self.busy_list.push(chunk);
-                    Ok(shm_buf)
-                }
-                Some(c) => {
-                    self.free_list.push(c);
-                    let e = zerror!("SharedMemoryManager::alloc({}) cannot find any available chunk\nSharedMemoryManager::free_list = {:?}", len, self.free_list);
-                    Err(e.into())
-                }
-                None => {
-                    let e = zerror!("SharedMemoryManager::alloc({}) cannot find any available chunk\nSharedMemoryManager::free_list = {:?}", len, self.free_list);
-                    tracing::trace!("{}", e);
-                    Err(e.into())
-                }
+    }
+
+    /// Allocates a buffer of `len` bytes, waiting for the readers of previously allocated buffers
+    /// to release enough memory if needed.
+    ///
+    /// Fails only if `len` exceeds the size of the managed region. It must be called
+    /// within a tokio runtime, as waiting for memory to be released blocks a thread of its blocking pool.
+    pub async fn alloc_async(&mut self, len: usize) -> ZResult<SharedMemoryBuf> {
+        tracing::trace!("SharedMemoryManager::alloc_async({})", len);
+        let required_len = align_addr_at(len + CHUNK_HEADER_SIZE, self.alignment);
+        if required_len > self.size + ACCOUNTED_OVERHEAD - SEGMENT_HEADER_SIZE {
+            return Err(zerror!(
+                "SharedMemoryManager::alloc_async({}) exceeds the size of the managed region ({} bytes)",
+                len,
+                self.size
+            )
+            .into());
+        }
+        loop {
+            // Read before looking for released chunks, so that no release is missed while waiting
+            let seen = notify::releases(self.own_segment.as_ref()).load(Ordering::SeqCst);
+            if let Some(shm_buf) = self.try_alloc(required_len)? {
+                return Ok(shm_buf);
             }
-        } else {
-            let e = zerror!( "SharedMemoryManager does not have sufficient free memory to allocate {} bytes, try de-fragmenting!", len);
-            tracing::warn!("{}", e);
-            Err(e.into())
+            if self.garbage_collect() > 0 || self.defragment() > 0 {
+                continue;
+            }
+            notify::wait_release(self.own_segment.clone(), seen).await;
         }
     }
 
-    fn is_free_chunk(chunk: &Chunk) -> bool {
-        let rc_ptr = chunk.base_addr as *mut ChunkHeaderType;
+    /// Returns a [`ReleaseListener`] notifying when the given buffer allocated by this manager
+    /// has been released by all its holders, local or remote.
+    pub fn release_listener(&self, buf: &SharedMemoryBuf) -> ZResult<ReleaseListener> {
+        if buf.info.shm_manager != self.own_segment.id() {
+            return Err(ShmError(zerror!(
+                "SharedMemoryBuf was not allocated by SharedMemoryManager {}",
+                self.own_segment.id()
+            ))
+            .into());
+        }
+        let rc = buf.rc_ptr.load(Ordering::SeqCst);
+        Ok(ReleaseListener {
+            segment: self.own_segment.clone(),
+            offset: buf.info.offset,
+            generation: unsafe { (*rc.add(1)).load(Ordering::SeqCst) },
+        })
+    }
+
+    // The chunks of the busy list have been checked with `is_valid_chunk` when allocated
+    fn is_free_chunk(&self, chunk: &Chunk) -> bool {
+        let rc_ptr = unsafe { self.own_segment.as_ptr().add(chunk.offset) } as *mut ChunkHeaderType;
         let rc = unsafe { (*rc_ptr).load(Ordering::SeqCst) };
         rc == 0
     }
 
-    fn try_merge_adjacent_chunks(a: &Chunk, b: &Chunk) -> Option<Chunk> {
-        let end_addr = unsafe { a.base_addr.add(a.size) };
-        if end_addr == b.base_addr {
-            Some(Chunk {
-                base_addr: a.base_addr,
-                size: a.size + b.size,
-                offset: a.offset,
-            })
-        } else {
-            None
-        }
-    }
     // Returns the amount of memory that it was able to de-fragment
     pub fn defragment(&mut self) -> usize {
-        if self.free_list.len() > 1 {
-            let mut fbs: Vec<Chunk> = self.free_list.drain().collect();
-            fbs.sort_by(|x, y| x.offset.partial_cmp(&y.offset).unwrap());
-            let mut current = fbs.remove(0);
-            let mut defrag_mem = 0;
-            let mut i = 0;
-            let n = fbs.len();
-            for chunk in fbs.iter() {
-                i += 1;
-                let next = *chunk;
-                match SharedMemoryManager::try_merge_adjacent_chunks(&current, &next) {
-                    Some(c) => {
-                        current = c;
-                        defrag_mem += current.size;
-                        if i == n {
-                            self.free_list.push(current)
-                        }
-                    }
-                    None => {
-                        self.free_list.push(current);
-                        if i == n {
-                            self.free_list.push(next);
-                        } else {
-                            current = next;
-                        }
-                    }
-                }
-            }
-            defrag_mem
-        } else {
-            0
-        }
+        self.allocator.defragment()
     }
 
     /// Returns the amount of memory freed
@@ -471,29 +616,84 @@ impl SharedMemoryManager {
         tracing::trace!("Running Garbage Collector");
 
         let mut freed = 0;
-        let (free, busy) = self
-            .busy_list
-            .iter()
-            .partition(|&c| SharedMemoryManager::is_free_chunk(c));
+        let (free, busy): (Vec<Chunk>, Vec<Chunk>) =
+            self.busy_list.iter().partition(|&c| self.is_free_chunk(c));
         self.busy_list = busy;
 
         for f in free {
             freed += f.size;
             tracing::trace!("Garbage Collecting Chunk: {:?}", f);
-            self.free_list.push(f)
+            let generation = unsafe {
+                &*(self.own_segment.as_ptr().add(f.offset) as *const ChunkHeaderType).add(1)
+            };
+            generation.store(COLLECTED_GENERATION, Ordering::SeqCst);
+            self.allocator.free(Chunk {
+                offset: f.offset - SEGMENT_HEADER_SIZE,
+                size: f.size,
+            })
+        }
+        if freed > 0 {
+            // Wakes up the listeners of the collected chunks, whose generation changed
+            notify::notify_release(self.own_segment.as_ref());
         }
         self.available += freed;
         freed
     }
 }
 
+/// Notifies when a [`SharedMemoryBuf`] has been released by all its holders.
+///
+/// It keeps the segment of the buffer mapped, so it can outlive its [`SharedMemoryManager`].
+/// Once released, the memory of the buffer is reclaimed by the next garbage collection
+/// of the [`SharedMemoryManager`] and may be allocated again.
+pub struct ReleaseListener {
+    segment: Arc<dyn SharedMemorySegment>,
+    offset: usize,
+    generation: usize,
+}
+
+impl ReleaseListener {
+    /// Returns true if the buffer has been released.
+    pub fn is_released(&self) -> bool {
+        let rc_ptr = unsafe { self.segment.as_ptr().add(self.offset) } as *mut ChunkHeaderType;
+        // Another generation means that the chunk has been collected, and maybe allocated again
+        unsafe {
+            (*rc_ptr.add(1)).load(Ordering::SeqCst) != self.generation
+                || (*rc_ptr).load(Ordering::SeqCst) == 0
+        }
+    }
+
+    /// Waits for the buffer to be released.
+    ///
+    /// It must be called within a tokio runtime, as waiting blocks a thread of its blocking pool.
+    pub async fn released(&self) {
+        loop {
+            let seen = notify::releases(self.segment.as_ref()).load(Ordering::SeqCst);
+            if self.is_released() {
+                return;
+            }
+            notify::wait_release(self.segment.clone(), seen).await;
+        }
+    }
+}
+
+impl fmt::Debug for ReleaseListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReleaseListener")
+            .field("segment_id", &self.segment.id())
+            .field("offset", &self.offset)
+            .field("released", &self.is_released())
+            .finish()
+    }
+}
+
 impl fmt::Debug for SharedMemoryManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SharedMemoryManager")
-            .field("segment_path", &self.segment_path)
+            .field("segment_id", &self.own_segment.id())
             .field("size", &self.size)
             .field("available", &self.available)
-            .field("free_list.len", &self.free_list.len())
+            .field("allocator", &self.allocator)
             .field("busy_list.len", &self.busy_list.len())
             .finish()
     }
@@ -524,3 +724,171 @@ impl ZSliceBuffer for SharedMemoryBuf {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct OutOfBoundsAllocator(usize);
+
+    impl SharedMemoryAllocator for OutOfBoundsAllocator {
+        fn alloc(&mut self, _len: usize) -> Option<usize> {
+            Some(self.0)
+        }
+
+        fn free(&mut self, _chunk: Chunk) {}
+    }
+
+    fn segment(name: &str, size: usize) -> Box<dyn SharedMemorySegment> {
+        PosixBackend
+            .create(&format!("{name}_{}", std::process::id()), size)
+            .unwrap()
+    }
+
+    #[test]
+    fn from_segment() {
+        let seg = segment("from_segment", 2 * ACCOUNTED_OVERHEAD);
+        let id = seg.id().to_string();
+        let mut shm =
+            SharedMemoryManager::from_segment(seg, |len| Box::new(BestFitAllocator::new(len)))
+                .unwrap();
+        assert_eq!(shm.segment_id(), id);
+
+        let mut buf = shm.alloc(ACCOUNTED_OVERHEAD).unwrap();
+        assert_eq!(buf.len(), ACCOUNTED_OVERHEAD);
+        unsafe { buf.as_mut_slice() }.fill(42);
+
+        let mut reader = SharedMemoryReader::new();
+        let read = reader.read_shmbuf(&buf.info).unwrap();
+        assert_eq!(read.as_slice(), buf.as_slice());
+    }
+
+    #[test]
+    fn from_segment_too_small() {
+        let seg = segment("from_segment_too_small", ACCOUNTED_OVERHEAD);
+        assert!(
+            SharedMemoryManager::from_segment(seg, |len| Box::new(BestFitAllocator::new(len)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn allocator_out_of_bounds() {
+        let size = 2 * ACCOUNTED_OVERHEAD;
+        for offset in [size, size - CHUNK_HEADER_SIZE, usize::MAX, 1] {
+            let seg = segment("allocator_out_of_bounds", size);
+            let mut shm =
+                SharedMemoryManager::from_segment(seg, |_| Box::new(OutOfBoundsAllocator(offset)))
+                    .unwrap();
+            assert!(shm.alloc(16).is_err());
+        }
+    }
+
+    const WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+    fn manager(name: &str) -> SharedMemoryManager {
+        SharedMemoryManager::make(format!("{name}_{}", std::process::id()), ACCOUNTED_OVERHEAD)
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn alloc_async_waits_for_release() {
+        let mut shm = manager("alloc_async_waits_for_release");
+        let len = ACCOUNTED_OVERHEAD;
+        let buf = shm.alloc(len).unwrap();
+        assert!(shm.alloc(len).is_err());
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            drop(buf);
+        });
+        let buf = tokio::time::timeout(WAIT_TIMEOUT, shm.alloc_async(len))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(buf.len(), len);
+        release.await.unwrap();
+
+        assert!(shm.alloc_async(2 * ACCOUNTED_OVERHEAD).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn release_listener_remote_release() {
+        let mut shm = manager("release_listener_remote_release");
+        let buf = shm.alloc(16).unwrap();
+        let listener = shm.release_listener(&buf).unwrap();
+
+        // The buffer is released through another mapping of the segment, as a remote reader would do
+        let mut reader = SharedMemoryReader::new();
+        buf.inc_ref_count();
+        let read = reader.read_shmbuf(&buf.info).unwrap();
+        drop(buf);
+        assert!(!listener.is_released());
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            drop(read);
+        });
+        tokio::time::timeout(WAIT_TIMEOUT, listener.released())
+            .await
+            .unwrap();
+        assert!(listener.is_released());
+        release.await.unwrap();
+    }
+
+    #[test]
+    fn release_listener_reallocated_chunk() {
+        let mut shm = manager("release_listener_reallocated_chunk");
+        let buf = shm.alloc(16).unwrap();
+        let offset = buf.info.offset;
+        let listener = shm.release_listener(&buf).unwrap();
+        drop(buf);
+        assert!(listener.is_released());
+
+        // The chunk is allocated again once collected, but the listener is still released
+        assert!(shm.garbage_collect() > 0);
+        shm.defragment();
+        let buf = shm.alloc(16).unwrap();
+        assert_eq!(buf.info.offset, offset);
+        assert!(listener.is_released());
+        assert!(!shm.release_listener(&buf).unwrap().is_released());
+    }
+
+    #[test]
+    fn reader_out_of_bounds() {
+        let mut shm = manager("reader_out_of_bounds");
+        let buf = shm.alloc(16).unwrap();
+        let mut reader = SharedMemoryReader::new();
+        reader.read_shmbuf(&buf.info).unwrap();
+
+        let mut info = buf.info.clone();
+        info.offset = 2 * ACCOUNTED_OVERHEAD;
+        assert!(reader.read_shmbuf(&info).is_err());
+        info.offset = 0;
+        info.length = 0;
+        assert!(reader.read_shmbuf(&info).is_err());
+    }
+
+    #[test]
+    fn reader_layout_version() {
+        // A segment not laid out by a manager
+        let seg = segment("reader_layout_version_raw", 2 * ACCOUNTED_OVERHEAD);
+        let info = SharedMemoryBufInfo::new(SEGMENT_HEADER_SIZE, 16, seg.id().to_string(), 0);
+        assert!(SharedMemoryReader::new().read_shmbuf(&info).is_err());
+
+        // A segment laid out by a manager of another version
+        let mut shm = manager("reader_layout_version");
+        let buf = shm.alloc(16).unwrap();
+        assert!(SharedMemoryReader::new().read_shmbuf(&buf.info).is_ok());
+        let version = (SEGMENT_LAYOUT_VERSION + 1).to_le_bytes();
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                version.as_ptr(),
+                shm.own_segment.as_ptr().add(SEGMENT_VERSION_OFFSET),
+                version.len(),
+            )
+        };
+        assert!(SharedMemoryReader::new().read_shmbuf(&buf.info).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::backend::Reclaimed;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static RECLAIMED_SEGMENTS: AtomicUsize = AtomicUsize::new(0);
+static RECLAIMED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static UNMAPPED_SEGMENTS: AtomicUsize = AtomicUsize::new(0);
+static UNMAPPED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Process-wide counters of the shared memory recovered from dead processes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CleanupMetrics {
+    /// Orphaned segments removed by [`sweep_orphaned_segments`](crate::backend::sweep_orphaned_segments).
+    pub reclaimed_segments: usize,
+    /// Bytes of the orphaned segments removed by [`sweep_orphaned_segments`](crate::backend::sweep_orphaned_segments).
+    pub reclaimed_bytes: usize,
+    /// Segments of dead processes unmapped by [`SharedMemoryReader`](crate::SharedMemoryReader)s.
+    pub unmapped_segments: usize,
+    /// Bytes of the segments of dead processes unmapped by [`SharedMemoryReader`](crate::SharedMemoryReader)s.
+    pub unmapped_bytes: usize,
+}
+
+/// Returns the current values of the cleanup counters.
+pub fn cleanup() -> CleanupMetrics {
+    CleanupMetrics {
+        reclaimed_segments: RECLAIMED_SEGMENTS.load(Ordering::Relaxed),
+        reclaimed_bytes: RECLAIMED_BYTES.load(Ordering::Relaxed),
+        unmapped_segments: UNMAPPED_SEGMENTS.load(Ordering::Relaxed),
+        unmapped_bytes: UNMAPPED_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+pub(crate) fn record_reclaimed(reclaimed: Reclaimed) {
+    RECLAIMED_SEGMENTS.fetch_add(reclaimed.segments, Ordering::Relaxed);
+    RECLAIMED_BYTES.fetch_add(reclaimed.bytes, Ordering::Relaxed);
+}
+
+pub(crate) fn record_unmapped(unmapped: Reclaimed) {
+    UNMAPPED_SEGMENTS.fetch_add(unmapped.segments, Ordering::Relaxed);
+    UNMAPPED_BYTES.fetch_add(unmapped.bytes, Ordering::Relaxed);
+}
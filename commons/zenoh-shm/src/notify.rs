@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Notification of the release of the buffers of a segment.
+//!
+//! Each segment starts with a counter of the buffers released in it, incremented by the process
+//! releasing a buffer, local or remote. Waiters block on this counter until it changes: with a
+//! futex on Linux, which works across processes, and with a process-wide event elsewhere,
+//! in which case only the releases of the local process are notified.
+use crate::backend::SharedMemorySegment;
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// Bounds the time a blocking thread keeps waiting once its waiting task has been dropped
+const WAIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Returns the counter of the buffers released in the segment.
+pub(crate) fn releases(segment: &dyn SharedMemorySegment) -> &AtomicU32 {
+    // The segment starts with its header, and is aligned at least on 8 bytes
+    unsafe { &*(segment.as_ptr() as *const AtomicU32) }
+}
+
+/// Notifies the waiters of the segment that a buffer has been released.
+pub(crate) fn notify_release(segment: &dyn SharedMemorySegment) {
+    let releases = releases(segment);
+    releases.fetch_add(1, Ordering::SeqCst);
+    sys::wake_all(releases);
+}
+
+/// Waits for a buffer of the segment to be released, unless the counter of its releases
+/// already differs from `seen`. It may also return spuriously.
+pub(crate) async fn wait_release(segment: Arc<dyn SharedMemorySegment>, seen: u32) {
+    let _ = tokio::task::spawn_blocking(move || {
+        sys::wait(releases(segment.as_ref()), seen, WAIT_TIMEOUT)
+    })
+    .await;
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::{sync::atomic::AtomicU32, time::Duration};
+
+    // The futexes are not private, so that they can be woken up by other processes
+    pub(super) fn wait(word: &AtomicU32, seen: u32, timeout: Duration) {
+        let timeout = libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        };
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word as *const AtomicU32,
+                libc::FUTEX_WAIT,
+                seen,
+                &timeout as *const libc::timespec,
+            )
+        };
+    }
+
+    pub(super) fn wake_all(word: &AtomicU32) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word as *const AtomicU32,
+                libc::FUTEX_WAKE,
+                i32::MAX,
+            )
+        };
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use event_listener::Event;
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    static RELEASED: Event = Event::new();
+
+    pub(super) fn wait(word: &AtomicU32, seen: u32, timeout: Duration) {
+        let mut listener = RELEASED.listen();
+        if word.load(Ordering::SeqCst) == seen {
+            listener.as_mut().wait_timeout(timeout);
+        }
+    }
+
+    pub(super) fn wake_all(_word: &AtomicU32) {
+        RELEASED.notify(usize::MAX);
+    }
+}
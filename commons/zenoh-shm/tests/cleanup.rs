@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(target_os = "linux")]
+use shared_memory::ShmemConf;
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+use zenoh_shm::{
+    alloc::BestFitAllocator,
+    backend::{sweep_orphaned_segments, MemfdBackend, PosixBackend, SharedMemoryBackend},
+    metrics, SharedMemoryBufInfo, SharedMemoryManager, SharedMemoryReader,
+};
+
+const OWNER_ENV: &str = "ZENOH_SHM_TEST_SEGMENT_OWNER";
+const SEGMENT_MARKER: &str = "segment: ";
+const SEGMENT_SIZE: usize = 4_096;
+
+// Creates a segment managed by a manager, or only created by the backend for `posix-raw`,
+// and waits to be killed, when run by `spawn_owner`
+#[test]
+fn segment_owner() {
+    let Ok(backend) = std::env::var(OWNER_ENV) else {
+        return;
+    };
+    let name = format!("cleanup_{}", std::process::id());
+    if backend == "posix-raw" {
+        let segment = PosixBackend.create(&name, SEGMENT_SIZE).unwrap();
+        println!("{SEGMENT_MARKER}{}", segment.id());
+        std::thread::sleep(Duration::from_secs(60));
+        return;
+    }
+    let backend: &dyn SharedMemoryBackend = match backend.as_str() {
+        "memfd" => &MemfdBackend,
+        _ => &PosixBackend,
+    };
+    let manager = SharedMemoryManager::make_with(name, SEGMENT_SIZE, backend, |len| {
+        Box::new(BestFitAllocator::new(len))
+    })
+    .unwrap();
+    println!("{SEGMENT_MARKER}{}", manager.segment_id());
+    std::thread::sleep(Duration::from_secs(60));
+}
+
+// Runs `segment_owner` in a child process, returning it with the id of its segment
+fn spawn_owner(backend: &str) -> (Child, String) {
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["segment_owner", "--exact", "--nocapture"])
+        .env(OWNER_ENV, backend)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let id = stdout
+        .lines()
+        .map(Result::unwrap)
+        .find_map(|line| {
+            line.split_once(SEGMENT_MARKER)
+                .map(|(_, id)| id.to_string())
+        })
+        .unwrap();
+    (child, id)
+}
+
+fn kill(mut child: Child) {
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+// Creates a segment the way versions of zenoh without lock files did, and leaves it behind
+fn leave_lockless_segment() -> String {
+    let mut path = std::env::temp_dir();
+    path.push(format!(
+        "zenoh_shm_zid_cleanup_lockless_{}",
+        std::process::id()
+    ));
+    let path = path.to_str().unwrap().to_string();
+    let mut shmem = ShmemConf::new()
+        .size(SEGMENT_SIZE)
+        .flink(&path)
+        .create()
+        .unwrap();
+    shmem.set_owner(false);
+    path
+}
+
+fn remove_segment(path: &str) {
+    let mut shmem = ShmemConf::new().flink(path).open().unwrap();
+    shmem.set_owner(true);
+    let _ = std::fs::remove_file(format!("{path}.lock"));
+}
+
+#[test]
+fn sweep_posix_segments() {
+    let (child, id) = spawn_owner("posix");
+    let (raw_child, raw) = spawn_owner("posix-raw");
+    let lockless = leave_lockless_segment();
+    assert!(Path::new(&id).exists());
+    assert!(PosixBackend.is_alive(&id));
+
+    kill(child);
+    kill(raw_child);
+    assert!(!PosixBackend.is_alive(&id));
+    assert!(!PosixBackend.is_alive(&raw));
+
+    let before = metrics::cleanup();
+    sweep_orphaned_segments();
+    assert!(!Path::new(&id).exists());
+    assert!(!Path::new(&format!("{id}.lock")).exists());
+    assert!(metrics::cleanup().reclaimed_segments > before.reclaimed_segments);
+    assert!(metrics::cleanup().reclaimed_bytes >= before.reclaimed_bytes + SEGMENT_SIZE);
+
+    // Segments without lock file, or not laid out by a manager of this version, are left alone
+    assert!(Path::new(&lockless).exists());
+    assert!(Path::new(&raw).exists());
+    remove_segment(&lockless);
+    remove_segment(&raw);
+}
+
+#[test]
+fn unmap_memfd_segment() {
+    let (child, id) = spawn_owner("memfd");
+    let mut reader = SharedMemoryReader::new();
+    reader
+        .connect_map_to_shm(&SharedMemoryBufInfo::new(0, 0, id.clone(), 0))
+        .unwrap();
+    assert!(MemfdBackend.is_alive(&id));
+    assert_eq!(reader.unmap_dead_segments().segments, 0);
+
+    kill(child);
+    assert!(!MemfdBackend.is_alive(&id));
+    let before = metrics::cleanup();
+    let unmapped = reader.unmap_dead_segments();
+    assert_eq!(unmapped.segments, 1);
+    assert!(unmapped.bytes >= SEGMENT_SIZE);
+    assert!(metrics::cleanup().unmapped_segments > before.unmapped_segments);
+    assert!(MemfdBackend.open(&id).is_err());
+}
@@ -46,21 +46,8 @@ async fn main() -> Result<(), zenoh::Error> {
     println!("Press CTRL-C to quit...");
     for idx in 0..(K * N as u32) {
         tokio::time::sleep(Duration::from_secs(1)).await;
-        let mut sbuf = match shm.alloc(1024) {
-            Ok(buf) => buf,
-            Err(_) => {
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                println!(
-                    "After failing allocation the GC collected: {} bytes -- retrying",
-                    shm.garbage_collect()
-                );
-                println!(
-                    "Trying to de-fragment memory... De-fragmented {} bytes",
-                    shm.defragment()
-                );
-                shm.alloc(1024).unwrap()
-            }
-        };
+        // Wait for enough memory to be released by the subscribers if needed.
+        let mut sbuf = shm.alloc_async(1024).await?;
 
         // We reserve a small space at the beginning of the buffer to include the iteration index
         // of the write. This is simply to have the same format as zn_pub.
@@ -584,6 +584,7 @@ mod tests {
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
                 ext_attachment: None,
+                ext_trace: None,
                 ext_unknown: vec![],
                 payload: ZBuf::from(vec![0u8; 8]),
             }),
@@ -790,6 +790,7 @@ mod tests {
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
                     ext_attachment: None,
+                    ext_trace: None,
                     ext_unknown: vec![],
                     payload,
                 }),
@@ -918,6 +919,7 @@ mod tests {
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
                     ext_attachment: None,
+                    ext_trace: None,
                     ext_unknown: vec![],
                     payload,
                 }),
@@ -1035,6 +1037,7 @@ mod tests {
                             #[cfg(feature = "shared-memory")]
                             ext_shm: None,
                             ext_attachment: None,
+                            ext_trace: None,
                             ext_unknown: vec![],
                             payload,
                         }),
@@ -45,14 +45,19 @@ macro_rules! stats_struct {
         }
     };
     (@increment $vis:vis $field_name:ident $field_type:ident) => {};
-    (@openmetrics($stats:expr, $string:expr) $field_name:ident) => {
+    (@openmetrics($stats:expr, $string:expr, $suffix:expr) $field_name:ident) => {
         $string.push_str(stringify!($field_name));
+        $string.push_str($suffix);
         $string.push_str(" ");
         $string.push_str($stats.$field_name.to_string().as_str());
         $string.push_str("\n");
     };
-    (@openmetrics($stats:expr, $string:expr) $field_name:ident $field_type:ident) => {
-        $string.push_str(&$stats.$field_name.sub_openmetrics_text(stringify!($field_name)));
+    (@openmetrics($stats:expr, $string:expr, $suffix:expr) $field_name:ident $field_type:ident) => {
+        $string.push_str(
+            &$stats
+                .$field_name
+                .sub_openmetrics_text(&[stringify!($field_name), $suffix].concat()),
+        );
     };
     (@openmetrics_val($stats:expr) $field_name:ident) => {
         $stats.$field_name.to_string().as_str()
@@ -133,7 +138,18 @@ macro_rules! stats_struct {
                     s
                 }
 
+                /// The metrics in OpenMetrics text format, named as in the admin space.
                 $vis fn openmetrics_text(&self) -> String {
+                    self.openmetrics_text_with_suffix("")
+                }
+
+                /// The metrics in OpenMetrics text format, where the counter samples carry
+                /// the `_total` suffix required by OpenMetrics.
+                $vis fn openmetrics_text_with_totals(&self) -> String {
+                    self.openmetrics_text_with_suffix("_total")
+                }
+
+                fn openmetrics_text_with_suffix(&self, counter_suffix: &str) -> String {
                     let mut s = String::new();
                     $(
                         $(
@@ -150,7 +166,9 @@ macro_rules! stats_struct {
                             s.push_str($type);
                             s.push_str("\n");
                         )?
-                        stats_struct!(@openmetrics(self, s) $field_name $($field_type)?);
+                        let types: &[&str] = &[$($type)?];
+                        let suffix = if types.contains(&"counter") { counter_suffix } else { "" };
+                        stats_struct!(@openmetrics(self, s, suffix) $field_name $($field_type)?);
                     )*
                     s
                 }
@@ -379,6 +379,23 @@ impl TransportManager {
                 }
             });
 
+        #[cfg(feature = "shared-memory")]
+        {
+            let cancellation_token = this.task_controller.get_cancellation_token();
+            let shm = this.shm().clone();
+            this.task_controller
+                .spawn_with_rt(zenoh_runtime::ZRuntime::Net, async move {
+                    loop {
+                        tokio::select! {
+                            _ = tokio::time::sleep(crate::unicast::shared_memory_unicast::WATCHDOG_PERIOD) => {
+                                shm.reader.write().await.unmap_dead_segments();
+                            }
+                            _ = cancellation_token.cancelled() => { break; }
+                        }
+                    }
+                });
+        }
+
         this
     }
 
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use rand::{Rng, SeedableRng};
+use std::{sync::Once, time::Duration};
 use tokio::sync::RwLock;
 use zenoh_core::zerror;
 use zenoh_crypto::PseudoRng;
@@ -20,6 +21,10 @@ use zenoh_shm::{SharedMemoryBuf, SharedMemoryManager, SharedMemoryReader};
 
 pub(crate) type Challenge = u64;
 const NAME: &str = "zshm";
+// The period at which the segments of dead processes are unmapped
+pub(crate) const WATCHDOG_PERIOD: Duration = Duration::from_secs(1);
+
+static SWEEP: Once = Once::new();
 
 /*************************************/
 /*          Authenticator            */
@@ -36,6 +41,11 @@ unsafe impl Sync for SharedMemoryUnicast {}
 
 impl SharedMemoryUnicast {
     pub fn make() -> ZResult<SharedMemoryUnicast> {
+        // Reclaim the segments left behind by crashed processes before creating ours
+        SWEEP.call_once(|| {
+            zenoh_shm::backend::sweep_orphaned_segments();
+        });
+
         // Create a challenge for session establishment
         let mut prng = PseudoRng::from_entropy();
         let nonce = prng.gen::<Challenge>();
@@ -300,6 +300,7 @@ mod tests {
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
                 ext_attachment: None,
+                ext_trace: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -197,6 +197,7 @@ async fn transport_concurrent(endpoint01: Vec<EndPoint>, endpoint02: Vec<EndPoin
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
                 ext_attachment: None,
+                ext_trace: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -302,6 +303,7 @@ async fn transport_concurrent(endpoint01: Vec<EndPoint>, endpoint02: Vec<EndPoin
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
                 ext_attachment: None,
+                ext_trace: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -83,6 +83,7 @@ async fn run(endpoint: &EndPoint, channel: Channel, msg_size: usize) {
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
         }
         .into(),
@@ -302,6 +302,7 @@ async fn transport_intermittent(endpoint: &EndPoint, lowlatency_transport: bool)
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
                 ext_attachment: None,
+                ext_trace: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -298,6 +298,7 @@ async fn single_run(router_handler: Arc<SHRouter>, client_transport: TransportUn
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
                     ext_attachment: None,
+                    ext_trace: None,
                     ext_unknown: vec![],
                 }
                 .into(),
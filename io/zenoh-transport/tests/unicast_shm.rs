@@ -271,6 +271,7 @@ mod tests {
                     ext_sinfo: None,
                     ext_shm: None,
                     ext_attachment: None,
+                    ext_trace: None,
                     ext_unknown: vec![],
                 }
                 .into(),
@@ -319,6 +320,7 @@ mod tests {
                     ext_sinfo: None,
                     ext_shm: None,
                     ext_attachment: None,
+                    ext_trace: None,
                     ext_unknown: vec![],
                 }
                 .into(),
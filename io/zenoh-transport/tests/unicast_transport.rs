@@ -484,6 +484,7 @@ async fn test_transport(
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
             ext_attachment: None,
+            ext_trace: None,
             ext_unknown: vec![],
         }
         .into(),
@@ -21,16 +21,27 @@ use zenoh::{key_expr::keyexpr, prelude::OwnedKeyExpr, Result as ZResult};
 use zenoh_plugin_trait::{PluginStartArgs, StructVersion};
 use zenoh_result::{bail, zerror, Error};
 
+/// The accepted forms of `backend_search_dirs`: a single directory or a list of directories.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum SearchDirsSchema {
+    Dir(String),
+    Dirs(Vec<String>),
+}
+
 #[derive(JsonSchema, Debug, Clone, AsMut, AsRef)]
 pub struct PluginConfig {
     #[schemars(skip)]
     pub name: String,
     #[schemars(with = "Option<bool>")]
     pub required: bool,
+    #[schemars(with = "Option<SearchDirsSchema>")]
     pub backend_search_dirs: Option<Vec<String>>,
-    #[schemars(with = "Map<String, Value>")]
+    pub snapshots_dir: Option<String>,
+    #[schemars(with = "Option<Map<String, Value>>")]
     pub volumes: Vec<VolumeConfig>,
-    #[schemars(with = "Map<String, Value>")]
+    #[schemars(with = "Option<Map<String, Value>>")]
     pub storages: Vec<StorageConfig>,
     #[as_ref]
     #[as_mut]
@@ -58,6 +69,9 @@ pub struct StorageConfig {
     pub volume_id: String,
     pub volume_cfg: Value,
     pub garbage_collection_config: GarbageCollectionConfig,
+    pub lifespans: Vec<LifespanConfig>,
+    // Note: LimitsConfig is optional. The storage is unbounded in its absence
+    pub limits: Option<LimitsConfig>,
     // Note: ReplicaConfig is optional. Alignment will be performed only if it is a replica
     pub replica_config: Option<ReplicaConfig>,
 }
@@ -67,11 +81,13 @@ pub struct ReplicaConfig {
     pub publication_interval: Duration,
     pub propagation_delay: Duration,
     pub delta: Duration,
+    pub alignment_bandwidth: Option<u64>,
+    pub alignment_batch_size: usize,
 }
 
 impl StructVersion for VolumeConfig {
     fn struct_version() -> u64 {
-        1
+        2
     }
     fn struct_features() -> &'static str {
         concatcp!(zenoh::FEATURES, crate::FEATURES)
@@ -94,6 +110,11 @@ impl Default for ReplicaConfig {
             // Higher the frequency of updates, lower the delta should be chosen
             // To be efficient, delta should be the time containing no more than 100,000 samples
             delta: Duration::from_millis(1000),
+            // The maximum average rate, in bytes per second, at which a replica downloads missing data from others
+            // By default, the alignment is not limited
+            alignment_bandwidth: None,
+            // The maximum number of entries requested at once to another replica during an alignment
+            alignment_batch_size: 100,
         }
     }
 }
@@ -117,6 +138,150 @@ impl Default for GarbageCollectionConfig {
     }
 }
 
+// The default lifespan of the values stored for the keys matching a key expression.
+// If several key expressions match a key, the shortest lifespan applies.
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct LifespanConfig {
+    pub key_expr: OwnedKeyExpr,
+    pub lifespan: Duration,
+}
+
+// The limits of the values stored by a storage, and the policy applied when a new value exceeds them.
+// The limits apply to the latest value of each key, its size being the size of its payload.
+#[derive(JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LimitsConfig {
+    pub max_keys: Option<usize>,
+    pub max_bytes: Option<usize>,
+    pub quotas: Vec<QuotaConfig>,
+    pub eviction: EvictionPolicy,
+}
+
+// The limits of the values stored for the keys matching a key expression, in addition to the limits of the storage
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct QuotaConfig {
+    pub key_expr: OwnedKeyExpr,
+    pub max_keys: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+// The policy applied when a new value exceeds the limits of a storage
+#[derive(JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    // Evict the least recently written or queried values
+    #[default]
+    Lru,
+    // Evict the values with the oldest timestamps
+    OldestTimestamp,
+    // Keep the stored values and discard the new one
+    RejectNew,
+}
+
+impl EvictionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionPolicy::Lru => "lru",
+            EvictionPolicy::OldestTimestamp => "oldest_timestamp",
+            EvictionPolicy::RejectNew => "reject_new",
+        }
+    }
+}
+
+impl LimitsConfig {
+    pub fn to_json_value(&self) -> Value {
+        let mut result = serde_json::Map::new();
+        if let Some(max_keys) = self.max_keys {
+            result.insert("max_keys".into(), max_keys.into());
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            result.insert("max_bytes".into(), max_bytes.into());
+        }
+        if !self.quotas.is_empty() {
+            result.insert(
+                "quotas".into(),
+                Value::Object(
+                    self.quotas
+                        .iter()
+                        .map(|q| {
+                            let mut quota = serde_json::Map::new();
+                            if let Some(max_keys) = q.max_keys {
+                                quota.insert("max_keys".into(), max_keys.into());
+                            }
+                            if let Some(max_bytes) = q.max_bytes {
+                                quota.insert("max_bytes".into(), max_bytes.into());
+                            }
+                            (q.key_expr.to_string(), Value::Object(quota))
+                        })
+                        .collect(),
+                ),
+            );
+        }
+        result.insert("eviction".into(), self.eviction.as_str().into());
+        Value::Object(result)
+    }
+
+    fn try_from(storage_name: &str, config: &Value) -> ZResult<Self> {
+        let config = config.as_object().ok_or_else(|| {
+            zerror!(
+                "Invalid type for field `limits` of storage `{}`. Only objects are accepted.",
+                storage_name
+            )
+        })?;
+        let (max_keys, max_bytes) = LimitsConfig::try_limits(storage_name, "limits", config)?;
+        let quotas = match config.get("quotas") {
+            Some(Value::Object(quotas)) => {
+                let mut result = Vec::with_capacity(quotas.len());
+                for (key_expr, quota) in quotas {
+                    let key_expr = OwnedKeyExpr::try_from(key_expr.as_str()).map_err(|e| {
+                        zerror!("Invalid key expression `{}` in `quotas` of storage `{}`: {}", key_expr, storage_name, e)
+                    })?;
+                    let quota = quota.as_object().ok_or_else(|| {
+                        zerror!("Invalid quota for `{}` in `quotas` of storage `{}`. Only objects with `max_keys` or `max_bytes` are accepted.", key_expr, storage_name)
+                    })?;
+                    let (max_keys, max_bytes) = LimitsConfig::try_limits(storage_name, "quotas", quota)?;
+                    result.push(QuotaConfig {
+                        key_expr,
+                        max_keys,
+                        max_bytes,
+                    });
+                }
+                result
+            }
+            None => Vec::new(),
+            _ => bail!("Invalid type for field `quotas` in `limits` of storage `{}`. Only objects mapping key expressions to limits are accepted.", storage_name),
+        };
+        let eviction = match config.get("eviction").map(|e| e.as_str()) {
+            None => EvictionPolicy::default(),
+            Some(Some("lru")) => EvictionPolicy::Lru,
+            Some(Some("oldest_timestamp")) => EvictionPolicy::OldestTimestamp,
+            Some(Some("reject_new")) => EvictionPolicy::RejectNew,
+            Some(_) => bail!("Invalid value for field `eviction` in `limits` of storage `{}`. Only \"lru\", \"oldest_timestamp\" and \"reject_new\" are accepted.", storage_name),
+        };
+        Ok(LimitsConfig {
+            max_keys,
+            max_bytes,
+            quotas,
+            eviction,
+        })
+    }
+
+    fn try_limits(
+        storage_name: &str,
+        field: &str,
+        config: &Map<String, Value>,
+    ) -> ZResult<(Option<usize>, Option<usize>)> {
+        let mut limits = [None, None];
+        for (limit, name) in limits.iter_mut().zip(["max_keys", "max_bytes"]) {
+            if let Some(value) = config.get(name) {
+                match value.as_u64() {
+                    Some(n) if n > 0 => *limit = Some(n as usize),
+                    _ => bail!("Invalid value for `{}` in `{}` of storage `{}`. Only positive integer values are accepted.", name, field, storage_name),
+                }
+            }
+        }
+        Ok((limits[0], limits[1]))
+    }
+}
+
 #[derive(Debug)]
 pub enum ConfigDiff {
     DeleteVolume(VolumeConfig),
@@ -170,6 +335,14 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             None => None,
             _ => bail!("`backend_search_dirs` field of {}'s configuration must be a string or array of strings", name.as_ref())
         };
+        let snapshots_dir = match value.get("snapshots_dir") {
+            Some(serde_json::Value::String(dir)) => Some(dir.clone()),
+            None => None,
+            _ => bail!(
+                "`snapshots_dir` field of {}'s configuration must be a string",
+                name.as_ref()
+            ),
+        };
         let volumes = match value.get("volumes") {
             Some(configs) => VolumeConfig::try_from(name.as_ref(), configs)?,
             None => Vec::new(),
@@ -196,13 +369,20 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             name: name.into(),
             required,
             backend_search_dirs,
+            snapshots_dir,
             volumes,
             storages,
             rest: value
                 .into_iter()
                 .filter(|&(k, _v)| {
-                    !["__required__", "backend_search_dirs", "volumes", "storages"]
-                        .contains(&k.as_str())
+                    ![
+                        "__required__",
+                        "backend_search_dirs",
+                        "snapshots_dir",
+                        "volumes",
+                        "storages",
+                    ]
+                    .contains(&k.as_str())
                 })
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
@@ -343,6 +523,20 @@ impl StorageConfig {
                 _ => unreachable!(),
             },
         );
+        if !self.lifespans.is_empty() {
+            result.insert(
+                "lifespans".into(),
+                Value::Object(
+                    self.lifespans
+                        .iter()
+                        .map(|l| (l.key_expr.to_string(), l.lifespan.as_secs_f64().into()))
+                        .collect(),
+                ),
+            );
+        }
+        if let Some(limits) = &self.limits {
+            result.insert("limits".into(), limits.to_json_value());
+        }
         Value::Object(result)
     }
     fn try_from<V: AsObject>(plugin_name: &str, storage_name: &str, config: &V) -> ZResult<Self> {
@@ -447,6 +641,28 @@ impl StorageConfig {
             }
             None => GarbageCollectionConfig::default(),
         };
+        let lifespans = match config.get("lifespans") {
+            Some(Value::Object(lifespans)) => {
+                let mut result = Vec::with_capacity(lifespans.len());
+                for (key_expr, lifespan) in lifespans {
+                    let key_expr = OwnedKeyExpr::try_from(key_expr.as_str()).map_err(|e| {
+                        zerror!("Invalid key expression `{}` in `lifespans` of storage `{}`: {}", key_expr, storage_name, e)
+                    })?;
+                    let lifespan = match lifespan.as_f64() {
+                        Some(secs) if secs > 0.0 => Duration::from_secs_f64(secs),
+                        _ => bail!("Invalid lifespan for `{}` in `lifespans` of storage `{}`. Only positive numbers of seconds are accepted.", key_expr, storage_name),
+                    };
+                    result.push(LifespanConfig { key_expr, lifespan });
+                }
+                result
+            }
+            None => Vec::new(),
+            _ => bail!("Invalid type for field `lifespans` of storage `{}`. Only objects mapping key expressions to durations in seconds are accepted.", storage_name),
+        };
+        let limits = match config.get("limits") {
+            Some(limits) => Some(LimitsConfig::try_from(storage_name, limits)?),
+            None => None,
+        };
         let replica_config = match config.get("replica_config") {
             Some(s) => {
                 let mut replica_config = ReplicaConfig::default();
@@ -475,6 +691,20 @@ impl StorageConfig {
                         bail!("Invalid type for field `delta` in `replica_config` of storage `{}`. Only integer values are accepted.", plugin_name)
                     }
                 }
+                if let Some(b) = s.get("alignment_bandwidth") {
+                    let b = b.to_string().parse::<u64>();
+                    if let Ok(b) = b {
+                        replica_config.alignment_bandwidth = Some(b)
+                    } else {
+                        bail!("Invalid type for field `alignment_bandwidth` in `replica_config` of storage `{}`. Only integer values are accepted.", plugin_name)
+                    }
+                }
+                if let Some(b) = s.get("alignment_batch_size") {
+                    match b.to_string().parse::<usize>() {
+                        Ok(b) if b > 0 => replica_config.alignment_batch_size = b,
+                        _ => bail!("Invalid value for field `alignment_batch_size` in `replica_config` of storage `{}`. Only positive integer values are accepted.", plugin_name),
+                    }
+                }
                 Some(replica_config)
             }
             None => None,
@@ -487,6 +717,8 @@ impl StorageConfig {
             volume_id,
             volume_cfg,
             garbage_collection_config,
+            lifespans,
+            limits,
             replica_config,
         })
     }
@@ -0,0 +1,314 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! The selector parameters allowing to filter and project the values replied by a storage.
+//!
+//! - `_filter=<condition>[,<condition>...]`: only the values meeting all the conditions are replied.
+//!   A condition is either a field path alone, met if the field exists, or `<path><operator><literal>` where:
+//!   - the path selects a field of a JSON value, with its dot-separated names (or indexes in arrays): `room.sensors.0`;
+//!   - the operator is one of `==`, `!=`, `<`, `<=`, `>`, `>=`;
+//!   - the literal is a JSON literal (`20.5`, `"kitchen"`, `true`, `null`), or a string if it isn't valid JSON (`kitchen`).
+//!
+//!   Numbers are compared numerically and strings lexicographically. Other JSON values only support `==` and `!=`.
+//!   A condition is never met by a value that isn't JSON, or that misses the field.
+//! - `_encoding=<encoding>`: only the values whose encoding starts with `encoding` are replied
+//!   (`_encoding=text` matches both `text/plain` and `text/json`).
+//! - `_fields=<path>[,<path>...]`: the JSON values are replied with the given fields only, with the `application/json`
+//!   encoding. The values that aren't JSON objects are replied unchanged.
+//!
+//! Literals containing `,` must be quoted: `_filter=name=="Doe, John"`.
+//!
+//! Storages evaluate the filters with [`ValueFilter::apply`] on the data retrieved by their backend.
+//! A backend able to evaluate some parts of a filter itself (e.g. with the indexes of a database)
+//! declares them in [`Storage::filter_pushdown`](crate::Storage::filter_pushdown), and only the remaining parts
+//! are evaluated by the storage.
+use crate::StoredData;
+use serde_json::{Map, Value as JsonValue};
+use std::cmp::Ordering;
+use std::fmt;
+use zenoh::prelude::{KnownEncoding, Parameters, SplitBuffer};
+use zenoh::value::Value;
+use zenoh_result::{bail, zerror, ZResult};
+
+pub const FILTER_KEY: &str = "_filter";
+pub const ENCODING_KEY: &str = "_encoding";
+pub const FIELDS_KEY: &str = "_fields";
+
+/// The path of a field in a JSON value: the names of the nested fields, or the indexes in arrays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPath(Vec<String>);
+
+impl FieldPath {
+    fn parse(path: &str) -> ZResult<Self> {
+        let segments: Vec<String> = path.trim().split('.').map(str::to_string).collect();
+        if segments.iter().any(|s| s.is_empty()) {
+            bail!("Invalid field path: `{}`", path)
+        }
+        Ok(FieldPath(segments))
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Returns the field of a JSON value at this path, if any.
+    pub fn get<'a>(&self, value: &'a JsonValue) -> Option<&'a JsonValue> {
+        self.0.iter().try_fold(value, |value, segment| match value {
+            JsonValue::Object(fields) => fields.get(segment),
+            JsonValue::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+    }
+
+    // Copies the field at this path from a JSON value into a projection, creating the enclosing objects
+    fn copy(&self, from: &JsonValue, to: &mut Map<String, JsonValue>) {
+        let Some(field) = self.get(from) else {
+            return;
+        };
+        let (last, parents) = self.0.split_last().unwrap();
+        let mut to = to;
+        for segment in parents {
+            let entry = to
+                .entry(segment.clone())
+                .or_insert_with(|| JsonValue::Object(Map::new()));
+            to = match entry {
+                JsonValue::Object(fields) => fields,
+                _ => return,
+            };
+        }
+        to.insert(last.clone(), field.clone());
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join("."))
+    }
+}
+
+/// A comparison operator of a [`Condition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    // The operators, longest first so that `<=` isn't parsed as `<`
+    const ALL: [(&'static str, Operator); 6] = [
+        ("==", Operator::Eq),
+        ("!=", Operator::Ne),
+        ("<=", Operator::Le),
+        (">=", Operator::Ge),
+        ("<", Operator::Lt),
+        (">", Operator::Gt),
+    ];
+
+    fn eval(&self, field: &JsonValue, literal: &JsonValue) -> bool {
+        let ordering = match (field, literal) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                _ => None,
+            },
+            (JsonValue::String(a), JsonValue::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        match (self, ordering) {
+            (Operator::Eq, Some(o)) => o == Ordering::Equal,
+            (Operator::Ne, Some(o)) => o != Ordering::Equal,
+            (Operator::Eq, None) => field == literal,
+            (Operator::Ne, None) => field != literal,
+            (Operator::Lt, Some(o)) => o == Ordering::Less,
+            (Operator::Le, Some(o)) => o != Ordering::Greater,
+            (Operator::Gt, Some(o)) => o == Ordering::Greater,
+            (Operator::Ge, Some(o)) => o != Ordering::Less,
+            (_, None) => false,
+        }
+    }
+}
+
+/// A condition of a `_filter` parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub path: FieldPath,
+    /// The comparison of the field with a literal, or `None` if the field must only exist.
+    pub comparison: Option<(Operator, JsonValue)>,
+}
+
+impl Condition {
+    fn parse(condition: &str) -> ZResult<Self> {
+        let Some(start) = condition.find(['=', '!', '<', '>']) else {
+            return Ok(Condition {
+                path: FieldPath::parse(condition)?,
+                comparison: None,
+            });
+        };
+        let (path, rest) = condition.split_at(start);
+        let (operator, literal) = Operator::ALL
+            .iter()
+            .find_map(|(symbol, operator)| rest.strip_prefix(symbol).map(|l| (*operator, l)))
+            .ok_or_else(|| zerror!("Invalid operator in condition `{}`", condition))?;
+        let literal = literal.trim();
+        let literal = serde_json::from_str(literal)
+            .unwrap_or_else(|_| JsonValue::String(literal.to_string()));
+        Ok(Condition {
+            path: FieldPath::parse(path)?,
+            comparison: Some((operator, literal)),
+        })
+    }
+
+    pub fn eval(&self, value: &JsonValue) -> bool {
+        match (self.path.get(value), &self.comparison) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(field), Some((operator, literal))) => operator.eval(field, literal),
+        }
+    }
+}
+
+/// The parts of a [`ValueFilter`] evaluated by a storage itself, see [`Storage::filter_pushdown`](crate::Storage::filter_pushdown).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterPushdown {
+    pub conditions: bool,
+    pub encoding: bool,
+    pub fields: bool,
+}
+
+/// The filter and projection requested by the parameters of a query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueFilter {
+    pub conditions: Vec<Condition>,
+    pub encoding: Option<String>,
+    pub fields: Option<Vec<FieldPath>>,
+}
+
+impl ValueFilter {
+    /// Parses the filter parameters of a selector.
+    pub fn from_parameters(parameters: &str) -> ZResult<Self> {
+        let [filter, encoding, fields] =
+            parameters.get_parameters([FILTER_KEY, ENCODING_KEY, FIELDS_KEY])?;
+        let conditions = match filter {
+            Some(filter) => split_list(&filter)
+                .iter()
+                .map(|c| Condition::parse(c))
+                .collect::<ZResult<_>>()
+                .map_err(|e| zerror!("Invalid value for `{}`: {}", FILTER_KEY, e))?,
+            None => Vec::new(),
+        };
+        let fields = fields
+            .map(|fields| {
+                split_list(&fields)
+                    .iter()
+                    .map(|f| FieldPath::parse(f))
+                    .collect::<ZResult<_>>()
+                    .map_err(|e| zerror!("Invalid value for `{}`: {}", FIELDS_KEY, e))
+            })
+            .transpose()?;
+        Ok(ValueFilter {
+            conditions,
+            encoding: encoding.map(|e| e.to_string()),
+            fields,
+        })
+    }
+
+    /// Returns true if no filter or projection was requested.
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty() && self.encoding.is_none() && self.fields.is_none()
+    }
+
+    /// Returns the parts of this filter that remain to be evaluated after the given pushdown.
+    pub fn residual(&self, pushdown: FilterPushdown) -> ValueFilter {
+        ValueFilter {
+            conditions: if pushdown.conditions {
+                Vec::new()
+            } else {
+                self.conditions.clone()
+            },
+            encoding: self.encoding.clone().filter(|_| !pushdown.encoding),
+            fields: self.fields.clone().filter(|_| !pushdown.fields),
+        }
+    }
+
+    /// Returns true if a value meets the conditions and the encoding of this filter.
+    pub fn matches(&self, value: &Value) -> bool {
+        if let Some(encoding) = &self.encoding {
+            if !value.encoding.to_string().starts_with(encoding.as_str()) {
+                return false;
+            }
+        }
+        if self.conditions.is_empty() {
+            return true;
+        }
+        match parse_json(value) {
+            Some(json) => self.conditions.iter().all(|c| c.eval(&json)),
+            None => false,
+        }
+    }
+
+    /// Returns the projection of a value on the fields of this filter.
+    pub fn project(&self, value: Value) -> Value {
+        let Some(fields) = &self.fields else {
+            return value;
+        };
+        let Some(json @ JsonValue::Object(_)) = parse_json(&value) else {
+            return value;
+        };
+        let mut projection = Map::new();
+        for field in fields {
+            field.copy(&json, &mut projection);
+        }
+        Value::from(JsonValue::Object(projection).to_string())
+            .encoding(KnownEncoding::AppJson.into())
+    }
+
+    /// Returns the projection of the data if it matches this filter, `None` otherwise.
+    pub fn apply(&self, data: StoredData) -> Option<StoredData> {
+        if !self.matches(&data.value) {
+            return None;
+        }
+        Some(StoredData {
+            value: self.project(data.value),
+            timestamp: data.timestamp,
+        })
+    }
+}
+
+fn parse_json(value: &Value) -> Option<JsonValue> {
+    serde_json::from_slice(&value.payload.contiguous()).ok()
+}
+
+// Splits a comma-separated list, ignoring the commas in double-quoted strings
+fn split_list(list: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in list.chars() {
+        match c {
+            ',' if !quoted => {
+                items.push(std::mem::take(&mut item));
+                continue;
+            }
+            '"' if !escaped => quoted = !quoted,
+            _ => {}
+        }
+        escaped = c == '\\' && !escaped;
+        item.push(c);
+    }
+    items.push(item);
+    items
+}
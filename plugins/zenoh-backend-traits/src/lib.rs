@@ -135,6 +135,7 @@
 
 use async_trait::async_trait;
 use const_format::concatcp;
+use futures::stream::{self, BoxStream, StreamExt};
 use std::sync::Arc;
 use zenoh::prelude::{KeyExpr, OwnedKeyExpr, Sample, Selector};
 use zenoh::queryable::ReplyBuilder;
@@ -145,7 +146,10 @@ use zenoh_plugin_trait::{PluginControl, PluginInstance, PluginStatusRec, StructV
 use zenoh_util::concat_enabled_features;
 
 pub mod config;
+pub mod filter;
+pub mod pagination;
 use config::StorageConfig;
+use filter::{FilterPushdown, ValueFilter};
 
 // No features are actually used in this crate, but this dummy list allows to demonstrate how to combine feature lists
 // from multiple crates. See impl `PluginStructVersion` for `VolumeConfig` below.
@@ -196,6 +200,9 @@ pub struct StoredData {
     pub timestamp: Timestamp,
 }
 
+/// A stream of the data retrieved from a storage, with their key.
+pub type StoredDataStream<'a> = BoxStream<'a, ZResult<(Option<OwnedKeyExpr>, StoredData)>>;
+
 /// Trait to be implemented by a Backend.
 ///
 #[async_trait]
@@ -223,7 +230,7 @@ pub type VolumeInstance = Box<dyn Volume + 'static>;
 
 impl StructVersion for VolumeInstance {
     fn struct_version() -> u64 {
-        1
+        2
     }
     fn struct_features() -> &'static str {
         concatcp!(zenoh::FEATURES, crate::FEATURES)
@@ -276,6 +283,34 @@ pub trait Storage: Send + Sync {
         parameters: &str,
     ) -> ZResult<Vec<StoredData>>;
 
+    /// Function to retrieve the samples associated with several keys, as a stream.
+    /// It allows replying to queries on many keys without retrieving all their samples first.
+    /// The samples of each key are returned in the order of `keys`, and an error for a key
+    /// doesn't stop the stream.
+    /// The default implementation calls [`Storage::get`] for each key, when the stream is polled.
+    async fn get_stream<'a>(
+        &'a mut self,
+        keys: Vec<Option<OwnedKeyExpr>>,
+        parameters: &'a str,
+    ) -> ZResult<StoredDataStream<'a>> {
+        Ok(get_stream_from_get(self, keys, parameters))
+    }
+
+    /// Returns the capability of this storage, when it depends on the configuration of the storage.
+    /// The default implementation returns `None`: the storage has the capability of its volume
+    /// (see [`Volume::get_capability`]).
+    fn get_capability(&self) -> Option<Capability> {
+        None
+    }
+
+    /// Returns the parts of a value filter (see [`filter`]) that this storage evaluates itself
+    /// when retrieving data with the same `parameters` in [`Storage::get`] and [`Storage::get_stream`].
+    /// The remaining parts are evaluated by the storage manager on the retrieved data.
+    /// The default implementation evaluates nothing.
+    fn filter_pushdown(&self, _filter: &ValueFilter) -> FilterPushdown {
+        FilterPushdown::default()
+    }
+
     /// Function called to get the list of all storage content (key, timestamp)
     /// The latest Timestamp corresponding to each key is either the timestamp of the delete or put whichever is the latest.
     /// Remember to fetch the entry corresponding to the `None` key
@@ -327,3 +362,24 @@ impl Query {
         self.q.reply(Ok(sample))
     }
 }
+
+// Streams the samples of the given keys, calling `get` on the storage for each of them
+fn get_stream_from_get<'a, S: Storage + ?Sized>(
+    storage: &'a mut S,
+    keys: Vec<Option<OwnedKeyExpr>>,
+    parameters: &'a str,
+) -> StoredDataStream<'a> {
+    stream::unfold(
+        (storage, keys.into_iter()),
+        move |(storage, mut keys)| async move {
+            let key = keys.next()?;
+            let data: Vec<_> = match storage.get(key.clone(), parameters).await {
+                Ok(data) => data.into_iter().map(|d| Ok((key.clone(), d))).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            Some((stream::iter(data), (storage, keys)))
+        },
+    )
+    .flatten()
+    .boxed()
+}
@@ -0,0 +1,208 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! The selector parameters allowing to page through the replies of a storage.
+//!
+//! - `_limit=<n>`: at most `n` replies are sent.
+//! - `_offset=<n>`: the first `n` replies are skipped.
+//! - `_order=asc|desc`: the replies are sorted by key, in ascending or descending order.
+//!   The replies of a same key are sorted by timestamp, in ascending order.
+//! - `_after=<key>@<timestamp>`: only the replies after the reply of `key` with `timestamp` are sent.
+//!   It is the continuation token of a paginated query: passing the key and the timestamp of the
+//!   last reply of a page returns the next page, even if the page ended in the middle of the
+//!   history of a key. `_after=<key>` skips all the replies of `key`.
+//!
+//! As pages are only consistent if keys are sorted, the replies are sorted in ascending order
+//! when any of those parameters is used without `_order`.
+use crate::StoredData;
+use futures::{Stream, StreamExt};
+use std::cmp::Ordering;
+use std::pin::Pin;
+use std::str::FromStr;
+use zenoh::prelude::{OwnedKeyExpr, Parameters};
+use zenoh::time::Timestamp;
+use zenoh_result::{bail, zerror, ZResult};
+
+pub const LIMIT_KEY: &str = "_limit";
+pub const OFFSET_KEY: &str = "_offset";
+pub const ORDER_KEY: &str = "_order";
+pub const AFTER_KEY: &str = "_after";
+
+/// The order of the keys in the replies of a storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOrder {
+    Ascending,
+    Descending,
+}
+
+impl KeyOrder {
+    fn cmp(&self, a: &OwnedKeyExpr, b: &OwnedKeyExpr) -> Ordering {
+        match self {
+            KeyOrder::Ascending => a.as_str().cmp(b.as_str()),
+            KeyOrder::Descending => b.as_str().cmp(a.as_str()),
+        }
+    }
+}
+
+/// The continuation token of a paginated query: the key and the timestamp of the last reply of
+/// the previous page. Without timestamp, all the replies of the key are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageToken {
+    pub key: OwnedKeyExpr,
+    pub timestamp: Option<Timestamp>,
+}
+
+impl FromStr for PageToken {
+    type Err = zenoh_result::Error;
+
+    // The timestamp follows the last `@`, which may also start a chunk of the key
+    fn from_str(s: &str) -> ZResult<Self> {
+        if let Some((key, timestamp)) = s.rsplit_once('@') {
+            if let Ok(timestamp) = Timestamp::from_str(timestamp) {
+                return Ok(PageToken {
+                    key: OwnedKeyExpr::try_from(key)?,
+                    timestamp: Some(timestamp),
+                });
+            }
+        }
+        Ok(PageToken {
+            key: OwnedKeyExpr::try_from(s)?,
+            timestamp: None,
+        })
+    }
+}
+
+/// The pagination requested by the parameters of a query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pagination {
+    pub limit: Option<usize>,
+    pub offset: usize,
+    pub order: Option<KeyOrder>,
+    pub after: Option<PageToken>,
+}
+
+impl Pagination {
+    /// Parses the pagination parameters of a selector.
+    pub fn from_parameters(parameters: &str) -> ZResult<Self> {
+        fn parse_usize(key: &str, value: Option<String>) -> ZResult<Option<usize>> {
+            value
+                .map(|v| {
+                    v.parse::<usize>()
+                        .map_err(|e| zerror!("Invalid value for `{}`: {} ({})", key, v, e).into())
+                })
+                .transpose()
+        }
+
+        let [limit, offset, order, after] = parameters
+            .get_parameters([LIMIT_KEY, OFFSET_KEY, ORDER_KEY, AFTER_KEY])?
+            .map(|v| v.map(|v| v.to_string()));
+        let limit = parse_usize(LIMIT_KEY, limit)?;
+        let offset = parse_usize(OFFSET_KEY, offset)?.unwrap_or(0);
+        let order = match order.as_deref() {
+            None => None,
+            Some("asc") => Some(KeyOrder::Ascending),
+            Some("desc") => Some(KeyOrder::Descending),
+            Some(o) => bail!(
+                "Invalid value for `{}`: {}. Only `asc` and `desc` are accepted",
+                ORDER_KEY,
+                o
+            ),
+        };
+        let after = after
+            .as_deref()
+            .map(PageToken::from_str)
+            .transpose()
+            .map_err(|e| zerror!("Invalid value for `{}`: {}", AFTER_KEY, e))?;
+        let mut pagination = Pagination {
+            limit,
+            offset,
+            order,
+            after,
+        };
+        if pagination.order.is_none() && pagination.is_paginated() {
+            pagination.order = Some(KeyOrder::Ascending);
+        }
+        Ok(pagination)
+    }
+
+    /// Returns true if any pagination parameter was given.
+    pub fn is_paginated(&self) -> bool {
+        self.limit.is_some() || self.offset > 0 || self.order.is_some() || self.after.is_some()
+    }
+
+    /// Sorts the given keys in the requested order and removes the keys before the continuation token.
+    pub fn select_keys(&self, keys: &mut Vec<OwnedKeyExpr>) {
+        let Some(order) = self.order else {
+            return;
+        };
+        keys.sort_by(|a, b| order.cmp(a, b));
+        if let Some(after) = &self.after {
+            keys.retain(|k| match order.cmp(k, &after.key) {
+                Ordering::Greater => true,
+                Ordering::Equal => after.timestamp.is_some(),
+                Ordering::Less => false,
+            });
+        }
+    }
+
+    /// Collects the replies of the page from the samples of the keys selected by
+    /// [`Pagination::select_keys`], in which the samples of each key follow each other.
+    /// The samples following the page are not polled.
+    pub async fn select_page<S>(&self, samples: S) -> Vec<(OwnedKeyExpr, StoredData)>
+    where
+        S: Stream<Item = (OwnedKeyExpr, StoredData)> + Unpin,
+    {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        let mut page = Vec::new();
+        if limit == 0 {
+            return page;
+        }
+        if self.order.is_none() {
+            return samples.skip(self.offset).take(limit).collect().await;
+        }
+        let mut skipped = 0;
+        let mut samples = samples.peekable();
+        while let Some((key, data)) = samples.next().await {
+            // the samples of a key are sorted by timestamp
+            let mut history = vec![data];
+            while let Some((_, data)) = Pin::new(&mut samples)
+                .next_if(|(next, _)| *next == key)
+                .await
+            {
+                history.push(data);
+            }
+            history.sort_by_key(|data| data.timestamp);
+            for data in history {
+                if !self.is_after_token(&key, &data.timestamp) {
+                    continue;
+                }
+                if skipped < self.offset {
+                    skipped += 1;
+                    continue;
+                }
+                page.push((key.clone(), data));
+                if page.len() == limit {
+                    return page;
+                }
+            }
+        }
+        page
+    }
+
+    fn is_after_token(&self, key: &OwnedKeyExpr, timestamp: &Timestamp) -> bool {
+        match &self.after {
+            Some(after) if *key == after.key => after.timestamp.map_or(false, |ts| *timestamp > ts),
+            _ => true,
+        }
+    }
+}
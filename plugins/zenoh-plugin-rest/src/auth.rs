@@ -0,0 +1,175 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::AuthConfig;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tide::{Middleware, Next, Request, Response, StatusCode};
+use zenoh::prelude::{keyexpr, OwnedKeyExpr};
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The kind of access to a key expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Read,
+    Write,
+}
+
+/// The key expressions an authenticated user can read and write.
+#[derive(Debug)]
+pub(crate) struct Permissions {
+    pub(crate) user: String,
+    read: Vec<OwnedKeyExpr>,
+    write: Vec<OwnedKeyExpr>,
+}
+
+impl Permissions {
+    /// Returns true if the key expression is included in one of the key expressions granted for the access.
+    pub(crate) fn allows(&self, access: Access, key_expr: &keyexpr) -> bool {
+        let granted = match access {
+            Access::Read => &self.read,
+            Access::Write => &self.write,
+        };
+        granted.iter().any(|ke| ke.includes(key_expr))
+    }
+}
+
+/// Authenticates the requests with the `Authorization` header, using the `Basic` or `Bearer` scheme.
+pub(crate) struct Authenticator {
+    // the password and permissions of the users, by name
+    passwords: HashMap<String, (String, Arc<Permissions>)>,
+    // the permissions of the users, by token
+    tokens: HashMap<String, Arc<Permissions>>,
+}
+
+impl Authenticator {
+    pub(crate) fn new(config: &AuthConfig) -> ZResult<Self> {
+        let mut passwords = HashMap::new();
+        let mut tokens = HashMap::new();
+        for (user, user_config) in &config.users {
+            let key_exprs = |key_exprs: &[String]| {
+                key_exprs
+                    .iter()
+                    .map(|ke| {
+                        OwnedKeyExpr::try_from(ke.as_str()).map_err(|e| {
+                            zerror!("Invalid key expression `{}` for user `{}`: {}", ke, user, e)
+                                .into()
+                        })
+                    })
+                    .collect::<ZResult<Vec<_>>>()
+            };
+            let permissions = Arc::new(Permissions {
+                user: user.clone(),
+                read: key_exprs(&user_config.read)?,
+                write: key_exprs(&user_config.write)?,
+            });
+            if user_config.password.is_none() && user_config.token.is_none() {
+                bail!("User `{}` has neither a password nor a token", user)
+            }
+            if let Some(password) = &user_config.password {
+                passwords.insert(user.clone(), (password.clone(), permissions.clone()));
+            }
+            if let Some(token) = &user_config.token {
+                if tokens.insert(token.clone(), permissions).is_some() {
+                    bail!("The token of user `{}` is used by another user", user)
+                }
+            }
+        }
+        Ok(Authenticator { passwords, tokens })
+    }
+
+    /// Returns the permissions of the user authenticated by an `Authorization` header, if any.
+    pub(crate) fn authenticate(&self, authorization: Option<&str>) -> Option<Arc<Permissions>> {
+        let (scheme, credentials) = authorization?.trim().split_once(' ')?;
+        let credentials = credentials.trim();
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = b64_std_engine.decode(credentials).ok()?;
+            let decoded = String::from_utf8(decoded).ok()?;
+            let (user, password) = decoded.split_once(':')?;
+            let (expected, permissions) = self.passwords.get(user)?;
+            constant_time_eq(password.as_bytes(), expected.as_bytes()).then(|| permissions.clone())
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            self.tokens
+                .iter()
+                .find(|(token, _)| constant_time_eq(credentials.as_bytes(), token.as_bytes()))
+                .map(|(_, permissions)| permissions.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// A tide middleware rejecting the requests without valid credentials,
+/// and attaching the [`Permissions`] of the user to the others.
+pub(crate) struct AuthMiddleware(pub(crate) Arc<Authenticator>);
+
+#[async_trait::async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for AuthMiddleware {
+    async fn handle(&self, mut req: Request<State>, next: Next<'_, State>) -> tide::Result {
+        let authorization = req.header("authorization").map(|h| h.last().as_str());
+        match self.0.authenticate(authorization) {
+            Some(permissions) => {
+                tracing::trace!("Request authenticated as user `{}`", permissions.user);
+                req.set_ext(permissions);
+                Ok(next.run(req).await)
+            }
+            None => Ok(Response::builder(StatusCode::Unauthorized)
+                .header("WWW-Authenticate", r#"Basic realm="zenoh""#)
+                .header("Access-Control-Allow-Origin", "*")
+                .build()),
+        }
+    }
+}
+
+// Compares secrets in a time independent of the position of their first difference
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Access, Authenticator};
+    use crate::config::AuthConfig;
+    use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+    use zenoh::prelude::keyexpr;
+
+    #[test]
+    fn test_authenticate() {
+        let config: AuthConfig = serde_json::from_str(
+            r#"{"users": {
+                "alice": {"password": "secret", "read": ["demo/**"], "write": ["demo/alice/**"]},
+                "bot": {"token": "t0k3n", "read": ["demo/bot/*"]}
+            }}"#,
+        )
+        .unwrap();
+        let auth = Authenticator::new(&config).unwrap();
+
+        let basic = format!("Basic {}", b64_std_engine.encode("alice:secret"));
+        let alice = auth.authenticate(Some(&basic)).unwrap();
+        assert_eq!(alice.user, "alice");
+        assert!(alice.allows(Access::Read, keyexpr::new("demo/bot/a").unwrap()));
+        assert!(alice.allows(Access::Write, keyexpr::new("demo/alice/a").unwrap()));
+        assert!(!alice.allows(Access::Write, keyexpr::new("demo/bot/a").unwrap()));
+        assert!(!alice.allows(Access::Read, keyexpr::new("**").unwrap()));
+
+        let bot = auth.authenticate(Some("Bearer t0k3n")).unwrap();
+        assert_eq!(bot.user, "bot");
+        assert!(!bot.allows(Access::Write, keyexpr::new("demo/bot/a").unwrap()));
+
+        let wrong = format!("Basic {}", b64_std_engine.encode("alice:wrong"));
+        assert!(auth.authenticate(Some(&wrong)).is_none());
+        assert!(auth.authenticate(Some("Bearer wrong")).is_none());
+        assert!(auth.authenticate(None).is_none());
+    }
+}
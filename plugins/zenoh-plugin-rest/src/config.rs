@@ -14,22 +14,127 @@
 use schemars::JsonSchema;
 use serde::de::{Unexpected, Visitor};
 use serde::{de, Deserialize, Deserializer};
+use std::collections::BTreeMap;
 use std::fmt;
 
 const DEFAULT_HTTP_INTERFACE: &str = "[::]";
+const DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 10000;
+const DEFAULT_WEBHOOK_MAX_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
 
 #[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(deserialize_with = "deserialize_http_port")]
+    #[schemars(with = "HttpPortSchema")]
     pub http_port: String,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// The origins of the web pages allowed to access the REST API, through CORS requests and WebSocket connections.
+    /// When empty, any page can send CORS requests without credentials, but WebSocket connections are only accepted
+    /// from the pages served on the same origin as the REST API, or from clients sending no `Origin` header.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_path")]
+    #[schemars(with = "Option<PathSchema>")]
     __path__: Option<Vec<String>>,
     __required__: Option<bool>,
     __config__: Option<String>,
     __plugin__: Option<String>,
 }
 
+/// The certificate and private key of the HTTPS server, as paths to PEM files.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    pub certificate: String,
+    pub private_key: String,
+}
+
+/// The users allowed to access the REST API, by name.
+/// In the absence of this configuration, any client can read and write any key.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    pub users: BTreeMap<String, UserConfig>,
+}
+
+/// The credentials of a user, for HTTP Basic authentication with its name and password,
+/// or bearer token authentication with its token, and the key expressions it can read and write.
+/// The credentials are never reported in the admin space.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub read: Vec<String>,
+    #[serde(default)]
+    pub write: Vec<String>,
+}
+
+/// A queryable answering the queries on a key expression with the responses of an HTTP service.
+///
+/// A query on `key/expr?params` is forwarded as a `GET <url>/key/expr?params` request,
+/// or as a `POST` carrying the value of the query if any.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub key_expr: String,
+    pub url: String,
+    #[serde(default)]
+    pub complete: bool,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    #[serde(default = "default_webhook_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_webhook_max_response_bytes")]
+    pub max_response_bytes: u64,
+}
+
+/// How the response of a webhook is mapped to replies.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The body is the value of a single reply, on the key expression of the query
+    /// or on the key given by the `X-Zenoh-Key` response header.
+    #[default]
+    Raw,
+    /// The body is a JSON array of replies: `[{"key": "a/b", "value": ..., "encoding": "..."}]`.
+    Samples,
+}
+
+/// The accepted forms of `http_port`, as described by [`HttpPortVisitor`].
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum HttpPortSchema {
+    Port(u16),
+    Address(String),
+}
+
+/// The accepted forms of `__path__`, as described by [`deserialize_path`].
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum PathSchema {
+    Path(String),
+    Paths(Vec<String>),
+}
+
+fn default_webhook_timeout_ms() -> u64 {
+    DEFAULT_WEBHOOK_TIMEOUT_MS
+}
+
+fn default_webhook_max_response_bytes() -> u64 {
+    DEFAULT_WEBHOOK_MAX_RESPONSE_BYTES
+}
+
 impl From<&Config> for serde_json::Value {
     fn from(c: &Config) -> Self {
         serde_json::to_value(c).unwrap()
@@ -216,4 +321,50 @@ mod tests {
         assert_eq!(__path__, None);
         assert_eq!(__required__, None);
     }
+
+    #[test]
+    fn test_auth_credentials_not_serialized() {
+        let config = serde_json::from_str::<Config>(
+            r#"{"http_port": 8080, "auth": {"users": {"alice": {"password": "secret", "token": "t0ken", "read": ["demo/**"]}}}}"#,
+        )
+        .unwrap();
+        let user = &config.auth.as_ref().unwrap().users["alice"];
+        assert_eq!(user.password.as_deref(), Some("secret"));
+        assert_eq!(user.token.as_deref(), Some("t0ken"));
+
+        let value = serde_json::Value::from(&config);
+        let user = &value["auth"]["users"]["alice"];
+        assert!(user.get("password").is_none());
+        assert!(user.get("token").is_none());
+        assert_eq!(user["read"][0], "demo/**");
+    }
+
+    #[test]
+    fn test_webhooks() {
+        use super::WebhookFormat;
+
+        let config = serde_json::from_str::<Config>(
+            r#"{"http_port": 8080, "webhooks": [
+                {"key_expr": "legacy/**", "url": "http://localhost:9000"},
+                {"key_expr": "other/**", "url": "https://example.com", "complete": true, "format": "samples", "timeout_ms": 500, "max_response_bytes": 1024}
+            ]}"#,
+        )
+        .unwrap();
+        let [first, second] = config.webhooks.as_slice() else {
+            panic!("Expected 2 webhooks")
+        };
+        assert_eq!(first.format, WebhookFormat::Raw);
+        assert!(!first.complete);
+        assert_eq!(first.timeout_ms, 10000);
+        assert_eq!(first.max_response_bytes, 16 * 1024 * 1024);
+        assert_eq!(second.format, WebhookFormat::Samples);
+        assert!(second.complete);
+        assert_eq!(second.timeout_ms, 500);
+        assert_eq!(second.max_response_bytes, 1024);
+
+        assert!(serde_json::from_str::<Config>(
+            r#"{"http_port": 8080, "webhooks": [{"key_expr": "a", "url": "http://b", "format": "xml"}]}"#
+        )
+        .is_err());
+    }
 }
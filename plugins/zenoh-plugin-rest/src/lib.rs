@@ -18,7 +18,6 @@
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 use async_std::prelude::FutureExt;
-use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
 use futures::StreamExt;
 use http_types::Method;
 use std::convert::TryFrom;
@@ -29,7 +28,6 @@ use tide::sse::Sender;
 use tide::{Request, Response, Server, StatusCode};
 use zenoh::plugins::{RunningPluginTrait, ZenohPlugin};
 use zenoh::prelude::r#async::*;
-use zenoh::properties::Properties;
 use zenoh::query::{QueryConsolidation, Reply};
 use zenoh::runtime::Runtime;
 use zenoh::selector::TIME_RANGE_KEY;
@@ -37,7 +35,16 @@ use zenoh::Session;
 use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin, PluginControl};
 use zenoh_result::{bail, zerror, ZResult};
 
+use auth::{Access, AuthMiddleware, Authenticator, Permissions};
+use schema::SchemaVersion;
+use tls::TlsListener;
+
+mod auth;
 mod config;
+mod schema;
+mod tls;
+mod webhook;
+mod websocket;
 pub use config::Config;
 
 const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
@@ -45,97 +52,108 @@ lazy_static::lazy_static! {
     static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
 }
 const RAW_KEY: &str = "_raw";
-
-fn value_to_json(value: Value) -> String {
-    // @TODO: transcode to JSON when implemented in Value
-    match &value.encoding {
-        p if p.starts_with(KnownEncoding::TextPlain)
-            || p.starts_with(KnownEncoding::AppXWwwFormUrlencoded) =>
-        {
-            // convert to Json string for special characters escaping
-            serde_json::json!(value.to_string()).to_string()
-        }
-        p if p.starts_with(KnownEncoding::AppProperties) => {
-            // convert to Json string for special characters escaping
-            serde_json::json!(*Properties::from(value.to_string())).to_string()
-        }
-        p if p.starts_with(KnownEncoding::AppJson)
-            || p.starts_with(KnownEncoding::AppInteger)
-            || p.starts_with(KnownEncoding::AppFloat) =>
-        {
-            value.to_string()
-        }
-        _ => {
-            format!(r#""{}""#, b64_std_engine.encode(value.payload.contiguous()))
-        }
-    }
+const FORMAT_KEY: &str = "_format";
+
+/// The formats of the responses to queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// The payload of the first reply, with its encoding as content type
+    Raw,
+    /// A JSON array of replies
+    Json,
+    /// An HTML definition list of replies
+    Html,
+    /// A JSON reply per line
+    Ndjson,
 }
 
-fn sample_to_json(sample: Sample) -> String {
-    let encoding = sample.value.encoding.to_string();
-    format!(
-        r#"{{ "key": "{}", "value": {}, "encoding": "{}", "time": "{}" }}"#,
-        sample.key_expr.as_str(),
-        value_to_json(sample.value),
-        encoding,
-        if let Some(ts) = sample.timestamp {
-            ts.to_string()
-        } else {
-            "None".to_string()
+/// A format requested by the `_format` parameter, with the version of the schema if requested.
+type RequestedFormat = (Format, Option<SchemaVersion>);
+
+impl Format {
+    // The format of a `_format` value, with the version of the schema given by its `-v1` suffix, if any
+    fn from_name(name: &str) -> Option<RequestedFormat> {
+        match name {
+            "raw" => Some((Format::Raw, None)),
+            "json" => Some((Format::Json, None)),
+            "json-v1" => Some((Format::Json, Some(SchemaVersion::V1))),
+            "html" => Some((Format::Html, None)),
+            "ndjson" => Some((Format::Ndjson, None)),
+            "ndjson-v1" => Some((Format::Ndjson, Some(SchemaVersion::V1))),
+            _ => None,
         }
-    )
-}
+    }
 
-fn result_to_json(sample: Result<Sample, Value>) -> String {
-    match sample {
-        Ok(sample) => sample_to_json(sample),
-        Err(err) => {
-            let encoding = err.encoding.to_string();
-            format!(
-                r#"{{ "key": "ERROR", "value": {}, "encoding": "{}"}}"#,
-                value_to_json(err),
-                encoding,
-            )
+    fn from_mime(mime: &str) -> Option<Format> {
+        match mime {
+            "application/json" => Some(Format::Json),
+            "text/html" => Some(Format::Html),
+            "application/x-ndjson" => Some(Format::Ndjson),
+            _ => None,
         }
     }
 }
 
-async fn to_json(results: flume::Receiver<Reply>) -> String {
-    let values = results
+async fn to_json_values(results: flume::Receiver<Reply>, version: SchemaVersion) -> Vec<String> {
+    results
         .stream()
-        .filter_map(move |reply| async move { Some(result_to_json(reply.sample)) })
-        .collect::<Vec<String>>()
+        .map(|reply| version.reply_to_json(&reply.sample))
+        .collect()
         .await
-        .join(",\n");
-    format!("[\n{values}\n]\n")
 }
 
-async fn to_json_response(results: flume::Receiver<Reply>) -> Response {
-    response(
+async fn to_json_response(results: flume::Receiver<Reply>, version: SchemaVersion) -> Response {
+    let values = to_json_values(results, version).await.join(",\n");
+    let mut response = response(
         StatusCode::Ok,
         Mime::from_str("application/json").unwrap(),
-        &to_json(results).await,
-    )
+        &format!("[\n{values}\n]\n"),
+    );
+    response.insert_header(schema::SCHEMA_VERSION_HEADER, version.as_str());
+    response
 }
 
-fn sample_to_html(sample: Sample) -> String {
-    format!(
-        "<dt>{}</dt>\n<dd>{}</dd>\n",
-        sample.key_expr.as_str(),
-        String::from_utf8_lossy(&sample.payload.contiguous())
-    )
+async fn to_ndjson_response(results: flume::Receiver<Reply>, version: SchemaVersion) -> Response {
+    let mut body = String::new();
+    for value in to_json_values(results, version).await {
+        body.push_str(&value);
+        body.push('\n');
+    }
+    let mut response = response(StatusCode::Ok, "application/x-ndjson", &body);
+    response.insert_header(schema::SCHEMA_VERSION_HEADER, version.as_str());
+    response
 }
 
-fn result_to_html(sample: Result<Sample, Value>) -> String {
-    match sample {
-        Ok(sample) => sample_to_html(sample),
-        Err(err) => {
-            format!(
-                "<dt>ERROR</dt>\n<dd>{}</dd>\n",
-                String::from_utf8_lossy(&err.payload.contiguous())
-            )
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
         }
     }
+    escaped
+}
+
+fn result_to_html(sample: Result<Sample, Value>) -> String {
+    // the value as represented in JSON: the text, the JSON value, or the base64 encoded payload
+    let (key, json) = match &sample {
+        Ok(sample) => (sample.key_expr.as_str(), schema::sample_to_json(sample)),
+        Err(value) => ("ERROR", schema::error_to_json(value)["error"].take()),
+    };
+    let value = match &json["value"] {
+        serde_json::Value::String(s) => s.clone(),
+        value => value.to_string(),
+    };
+    format!(
+        "<dt>{}</dt>\n<dd>{}</dd>\n",
+        escape_html(key),
+        escape_html(&value)
+    )
 }
 
 async fn to_html(results: flume::Receiver<Reply>) -> String {
@@ -153,21 +171,50 @@ async fn to_html_response(results: flume::Receiver<Reply>) -> Response {
 }
 
 async fn to_raw_response(results: flume::Receiver<Reply>) -> Response {
-    match results.recv_async().await {
+    let value = match results.recv_async().await {
         Ok(reply) => match reply.sample {
-            Ok(sample) => response(
-                StatusCode::Ok,
-                sample.value.encoding.to_string().as_ref(),
-                String::from_utf8_lossy(&sample.payload.contiguous()).as_ref(),
-            ),
-            Err(value) => response(
-                StatusCode::Ok,
-                value.encoding.to_string().as_ref(),
-                String::from_utf8_lossy(&value.payload.contiguous()).as_ref(),
-            ),
+            Ok(sample) => sample.value,
+            Err(value) => value,
         },
-        Err(_) => response(StatusCode::Ok, "", ""),
+        Err(_) => return response(StatusCode::Ok, "", ""),
+    };
+    let mut builder = Response::builder(StatusCode::Ok)
+        .header("Access-Control-Allow-Origin", "*")
+        .body(value.payload.contiguous().to_vec());
+    if let Ok(mime) = Mime::from_str(&value.encoding.to_string()) {
+        builder = builder.content_type(mime);
     }
+    builder.build()
+}
+
+fn query_consolidation(selector: &Selector) -> QueryConsolidation {
+    if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
+        QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
+    } else {
+        QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
+    }
+}
+
+// The response to a request whose user isn't allowed to access its key expression, if any
+fn forbidden<State>(req: &Request<State>, access: Access, zid: &str) -> Option<Response> {
+    let permissions = req.ext::<Arc<Permissions>>()?;
+    // invalid key expressions are reported by the handlers
+    let key_expr = path_to_key_expr(req.url().path(), zid).ok()?;
+    if permissions.allows(access, &key_expr) {
+        return None;
+    }
+    let operation = match access {
+        Access::Read => "read",
+        Access::Write => "write",
+    };
+    Some(response(
+        StatusCode::Forbidden,
+        "text/plain",
+        &format!(
+            "User `{}` is not allowed to {} `{}`",
+            permissions.user, operation, key_expr
+        ),
+    ))
 }
 
 fn method_to_kind(method: Method) -> SampleKind {
@@ -203,6 +250,10 @@ impl Plugin for RestPlugin {
     const PLUGIN_VERSION: &'static str = plugin_version!();
     const PLUGIN_LONG_VERSION: &'static str = plugin_long_version!();
 
+    fn config_schema() -> Option<serde_json::Value> {
+        serde_json::to_value(schemars::schema_for!(Config)).ok()
+    }
+
     fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
         // Try to initiate login.
         // Required in case of dynamic lib, otherwise no logs.
@@ -217,16 +268,26 @@ impl Plugin for RestPlugin {
 
         let conf: Config = serde_json::from_value(plugin_conf.clone())
             .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
-        let task = async_std::task::spawn(run(runtime.clone(), conf.clone()));
-        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
-        if let Ok(Err(e)) = task {
+        let mut task = async_std::task::spawn(run(runtime.clone(), conf.clone()));
+        let result =
+            async_std::task::block_on((&mut task).timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = result {
             bail!("REST server failed within 1ms: {e}")
         }
-        Ok(Box::new(RunningPlugin(conf)))
+        Ok(Box::new(RunningPlugin(conf, Some(task))))
     }
 }
 
-struct RunningPlugin(Config);
+struct RunningPlugin(Config, Option<async_std::task::JoinHandle<ZResult<()>>>);
+
+/// The server is cancelled when the plugin is stopped, which releases its port
+impl Drop for RunningPlugin {
+    fn drop(&mut self) {
+        if let Some(task) = self.1.take() {
+            async_std::task::block_on(task.cancel());
+        }
+    }
+}
 
 impl PluginControl for RunningPlugin {}
 
@@ -280,18 +341,27 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
 
 async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     tracing::trace!("Incoming GET request: {:?}", req);
+    if let Some(forbidden) = forbidden(&req, Access::Read, &req.state().1) {
+        return Ok(forbidden);
+    }
+    if websocket::is_upgrade(&req) {
+        return websocket::upgrade(req).await;
+    }
 
-    let first_accept = match req.header("accept") {
-        Some(accept) => accept[0]
-            .to_string()
-            .split(';')
-            .next()
-            .unwrap()
-            .split(',')
-            .next()
-            .unwrap()
-            .to_string(),
-        None => "application/json".to_string(),
+    // the first media type accepted, whose `v` parameter is the requested version of the schema
+    let (first_accept, accept_version) = match req.header("accept") {
+        Some(accept) => {
+            let accept = accept[0].to_string();
+            let mut media_type = accept.split(',').next().unwrap().split(';');
+            let mime = media_type.next().unwrap().trim().to_string();
+            let version = if media_type.any(|parameter| parameter.trim() == "v=1") {
+                SchemaVersion::V1
+            } else {
+                SchemaVersion::V0
+            };
+            (mime, version)
+        }
+        None => ("application/json".to_string(), SchemaVersion::V0),
     };
     if first_accept == "text/event-stream" {
         Ok(tide::sse::upgrade(
@@ -323,7 +393,11 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                     loop {
                         let sample = sub.recv_async().await.unwrap();
                         match sender
-                            .send(&sample.kind.to_string(), sample_to_json(sample), None)
+                            .send(
+                                &sample.kind.to_string(),
+                                accept_version.sample_to_json(&sample),
+                                None,
+                            )
                             .timeout(std::time::Duration::new(10, 0))
                             .await
                         {
@@ -368,18 +442,22 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                 ))
             }
         };
-        let query_part = url.query();
-        let selector = if let Some(q) = query_part {
-            Selector::from(key_expr).with_parameters(q)
-        } else {
-            key_expr.into()
+        let (format, parameters) = match split_format(url.query().unwrap_or_default()) {
+            Ok(split) => split,
+            Err(e) => return Ok(response(StatusCode::BadRequest, "text/plain", &e)),
         };
-        let consolidation = if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
-        } else {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
-        };
-        let raw = selector.decode().any(|(k, _)| k.as_ref() == RAW_KEY);
+        let version = format
+            .and_then(|(_, version)| version)
+            .unwrap_or(accept_version);
+        let selector = Selector::from(key_expr).with_parameters(&parameters);
+        let consolidation = query_consolidation(&selector);
+        let format = format.map(|(format, _)| format).unwrap_or_else(|| {
+            if selector.decode().any(|(k, _)| k.as_ref() == RAW_KEY) {
+                Format::Raw
+            } else {
+                Format::from_mime(&first_accept).unwrap_or(Format::Json)
+            }
+        });
         let mut query = req.state().0.get(&selector).consolidation(consolidation);
         if !body.is_empty() {
             let encoding: Encoding = req
@@ -389,15 +467,12 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
             query = query.with_value(Value::from(body).encoding(encoding));
         }
         match query.res().await {
-            Ok(receiver) => {
-                if raw {
-                    Ok(to_raw_response(receiver).await)
-                } else if first_accept == "text/html" {
-                    Ok(to_html_response(receiver).await)
-                } else {
-                    Ok(to_json_response(receiver).await)
-                }
-            }
+            Ok(receiver) => Ok(match format {
+                Format::Raw => to_raw_response(receiver).await,
+                Format::Json => to_json_response(receiver, version).await,
+                Format::Html => to_html_response(receiver).await,
+                Format::Ndjson => to_ndjson_response(receiver, version).await,
+            }),
             Err(e) => Ok(response(
                 StatusCode::InternalServerError,
                 "text/plain",
@@ -407,8 +482,40 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
     }
 }
 
+// Splits the `_format` parameter, which only concerns the response, from the parameters of the query
+fn split_format(query: &str) -> Result<(Option<RequestedFormat>, String), String> {
+    let mut format = None;
+    let mut parameters = Vec::new();
+    for parameter in query.split('&') {
+        match parameter.split_once('=') {
+            Some((FORMAT_KEY, name)) => match Format::from_name(name) {
+                Some(f) => format = Some(f),
+                None => {
+                    return Err(format!(
+                        "Invalid value for `{FORMAT_KEY}`: `{name}` (expected raw, json, json-v1, html, ndjson or ndjson-v1)"
+                    ))
+                }
+            },
+            _ if parameter.is_empty() => {}
+            _ => parameters.push(parameter),
+        }
+    }
+    Ok((format, parameters.join("&")))
+}
+
+async fn openapi(_req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    Ok(response(
+        StatusCode::Ok,
+        "application/json",
+        schema::OPENAPI,
+    ))
+}
+
 async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     tracing::trace!("Incoming PUT request: {:?}", req);
+    if let Some(forbidden) = forbidden(&req, Access::Write, &req.state().1) {
+        return Ok(forbidden);
+    }
     match req.body_bytes().await {
         Ok(bytes) => {
             let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
@@ -458,10 +565,15 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
     // But cannot be done twice in case of static link.
     zenoh_util::try_init_log_from_env();
 
+    let authenticator = conf.auth.as_ref().map(Authenticator::new).transpose()?;
+    let tls = conf.tls.as_ref().map(tls::server_config).transpose()?;
+
     let zid = runtime.zid().to_string();
-    let session = zenoh::init(runtime).res().await.unwrap();
+    let session = Arc::new(zenoh::init(runtime).res().await.unwrap());
+    // the queryables of the webhooks live as long as the server
+    let _webhooks = webhook::declare_webhooks(&session, &conf.webhooks).await?;
 
-    let mut app = Server::with_state((Arc::new(session), zid));
+    let mut app = Server::with_state((session, zid));
     app.with(
         tide::security::CorsMiddleware::new()
             .allow_methods(
@@ -469,10 +581,21 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
                     .parse::<http_types::headers::HeaderValue>()
                     .unwrap(),
             )
-            .allow_origin(tide::security::Origin::from("*"))
+            .allow_origin(if conf.allowed_origins.is_empty() {
+                tide::security::Origin::from("*")
+            } else {
+                tide::security::Origin::from(conf.allowed_origins.clone())
+            })
             .allow_credentials(false),
     );
+    app.with(websocket::OriginMiddleware {
+        allowed_origins: conf.allowed_origins.clone(),
+    });
+    if let Some(authenticator) = authenticator {
+        app.with(AuthMiddleware(Arc::new(authenticator)));
+    }
 
+    app.at(schema::OPENAPI_PATH).get(openapi);
     app.at("/")
         .get(query)
         .post(query)
@@ -486,7 +609,11 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
         .patch(write)
         .delete(write);
 
-    if let Err(e) = app.listen(conf.http_port).await {
+    let listened = match tls {
+        Some(tls) => app.listen(TlsListener::new(conf.http_port, tls)).await,
+        None => app.listen(conf.http_port).await,
+    };
+    if let Err(e) = listened {
         tracing::error!("Unable to start http server for REST: {:?}", e);
         return Err(e.into());
     }
@@ -0,0 +1,354 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The JSON representation of samples and errors in the responses of the REST plugin,
+//! described by the `Sample` and `Error` schemas of the OpenAPI document served on [`OPENAPI_PATH`].
+//!
+//! A sample is represented as:
+//! ```json
+//! {
+//!   "key": "demo/example",
+//!   "value": "hello",
+//!   "value_type": "text",
+//!   "encoding": "text/plain",
+//!   "kind": "PUT",
+//!   "timestamp": "2024-01-01T00:00:00.000000000Z/f8c1e3a4b5d6e7f8",
+//!   "attachment": [{"key": "user", "value": "alice"}]
+//! }
+//! ```
+//! where `value_type` tells how `value` is represented:
+//! - `json`: the JSON value, for the valid JSON values of the `application/json`, `text/json`,
+//!   `application/integer` and `application/float` encodings;
+//! - `text`: a string, for the valid UTF-8 values of the other textual encodings (`text/*`,
+//!   `application/x-www-form-urlencoded`, `application/properties`, `application/xml`, `application/sql`...);
+//! - `base64`: the base64 encoding of the payload, for all the other values.
+//!
+//! `timestamp` and `attachment` are `null` for samples without them.
+//! An error replied to a query is represented as `{"error": {"value": ..., "value_type": ..., "encoding": ...}}`.
+//!
+//! This representation is version 1 of the schema, used by the WebSocket endpoint. The responses to queries and the
+//! server-sent events keep the representation of version 0 unless version 1 is requested, with the `v=1` parameter
+//! of the media type of the `Accept` header (e.g. `application/json; v=1`), or with the `json-v1` and `ndjson-v1`
+//! values of the `_format` parameter. In version 0, a sample is represented as
+//! `{ "key": "demo/example", "value": "hello", "encoding": "text/plain", "time": "<timestamp>" }`
+//! with `"None"` as time for samples without timestamp, and an error as `{ "key": "ERROR", "value": ..., "encoding": ... }`.
+//!
+//! Any incompatible change of a representation increments the version,
+//! which is given by the `X-Zenoh-Schema-Version` header of the JSON responses.
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use serde_json::{json, Map, Value as JsonValue};
+use zenoh::prelude::r#async::*;
+use zenoh::properties::Properties;
+
+/// The header giving the version of the JSON representation of a response.
+pub(crate) const SCHEMA_VERSION_HEADER: &str = "X-Zenoh-Schema-Version";
+/// The path of the OpenAPI document describing the REST API.
+pub(crate) const OPENAPI_PATH: &str = "/@/openapi.json";
+/// The OpenAPI document describing the REST API.
+pub(crate) const OPENAPI: &str = include_str!("../openapi.json");
+
+/// The versions of the JSON representation of the responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SchemaVersion {
+    /// The representation of the first versions of the REST plugin, used by default
+    V0,
+    /// The representation described by the OpenAPI document
+    V1,
+}
+
+impl SchemaVersion {
+    /// The value of the `X-Zenoh-Schema-Version` header.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            SchemaVersion::V0 => "0",
+            SchemaVersion::V1 => "1",
+        }
+    }
+
+    /// The JSON representation of a reply to a query in this version.
+    pub(crate) fn reply_to_json(&self, reply: &Result<Sample, Value>) -> String {
+        match self {
+            SchemaVersion::V0 => v0_reply_to_json(reply),
+            SchemaVersion::V1 => reply_to_json(reply).to_string(),
+        }
+    }
+
+    /// The JSON representation of a sample in this version.
+    pub(crate) fn sample_to_json(&self, sample: &Sample) -> String {
+        match self {
+            SchemaVersion::V0 => v0_sample_to_json(sample),
+            SchemaVersion::V1 => sample_to_json(sample).to_string(),
+        }
+    }
+}
+
+const JSON_ENCODINGS: [KnownEncoding; 4] = [
+    KnownEncoding::AppJson,
+    KnownEncoding::TextJson,
+    KnownEncoding::AppInteger,
+    KnownEncoding::AppFloat,
+];
+
+fn is_textual(encoding: &Encoding) -> bool {
+    [
+        KnownEncoding::AppXWwwFormUrlencoded,
+        KnownEncoding::AppProperties,
+        KnownEncoding::AppXml,
+        KnownEncoding::AppXhtmlXml,
+        KnownEncoding::AppSql,
+    ]
+    .into_iter()
+    .any(|e| encoding.starts_with(e))
+        || encoding.to_string().starts_with("text/")
+}
+
+/// Inserts the `value`, `value_type` and `encoding` of a value in a JSON object.
+pub(crate) fn insert_value(object: &mut Map<String, JsonValue>, value: &Value) {
+    let payload = value.payload.contiguous();
+    let (value_type, json) = if JSON_ENCODINGS
+        .into_iter()
+        .any(|e| value.encoding.starts_with(e))
+    {
+        match serde_json::from_slice::<JsonValue>(&payload) {
+            Ok(json) => ("json", json),
+            Err(_) => ("base64", json!(b64_std_engine.encode(&payload))),
+        }
+    } else if is_textual(&value.encoding) {
+        match std::str::from_utf8(&payload) {
+            Ok(text) => ("text", json!(text)),
+            Err(_) => ("base64", json!(b64_std_engine.encode(&payload))),
+        }
+    } else {
+        ("base64", json!(b64_std_engine.encode(&payload)))
+    };
+    object.insert("value".into(), json);
+    object.insert("value_type".into(), json!(value_type));
+    object.insert("encoding".into(), json!(value.encoding.to_string()));
+}
+
+/// The JSON representation of a sample.
+pub(crate) fn sample_to_json(sample: &Sample) -> JsonValue {
+    let mut object = Map::new();
+    object.insert("key".into(), json!(sample.key_expr.as_str()));
+    insert_value(&mut object, &sample.value);
+    object.insert("kind".into(), json!(sample.kind.to_string()));
+    object.insert(
+        "timestamp".into(),
+        json!(sample.timestamp.map(|ts| ts.to_string())),
+    );
+    let attachment = sample.attachment().map(|attachment| {
+        attachment
+            .iter()
+            .map(|(key, value)| {
+                json!({
+                    "key": String::from_utf8_lossy(&key),
+                    "value": String::from_utf8_lossy(&value),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    object.insert("attachment".into(), json!(attachment));
+    JsonValue::Object(object)
+}
+
+/// The JSON representation of an error replied to a query.
+pub(crate) fn error_to_json(value: &Value) -> JsonValue {
+    let mut error = Map::new();
+    insert_value(&mut error, value);
+    json!({ "error": error })
+}
+
+/// The JSON representation of a reply to a query.
+pub(crate) fn reply_to_json(reply: &Result<Sample, Value>) -> JsonValue {
+    match reply {
+        Ok(sample) => sample_to_json(sample),
+        Err(value) => error_to_json(value),
+    }
+}
+
+fn v0_value_to_json(value: &Value) -> String {
+    match &value.encoding {
+        p if p.starts_with(KnownEncoding::TextPlain)
+            || p.starts_with(KnownEncoding::AppXWwwFormUrlencoded) =>
+        {
+            // convert to Json string for special characters escaping
+            json!(value.to_string()).to_string()
+        }
+        p if p.starts_with(KnownEncoding::AppProperties) => {
+            // convert to Json string for special characters escaping
+            json!(*Properties::from(value.to_string())).to_string()
+        }
+        p if p.starts_with(KnownEncoding::AppJson)
+            || p.starts_with(KnownEncoding::AppInteger)
+            || p.starts_with(KnownEncoding::AppFloat) =>
+        {
+            value.to_string()
+        }
+        _ => {
+            format!(r#""{}""#, b64_std_engine.encode(value.payload.contiguous()))
+        }
+    }
+}
+
+fn v0_sample_to_json(sample: &Sample) -> String {
+    format!(
+        r#"{{ "key": "{}", "value": {}, "encoding": "{}", "time": "{}" }}"#,
+        sample.key_expr.as_str(),
+        v0_value_to_json(&sample.value),
+        sample.value.encoding,
+        if let Some(ts) = sample.timestamp {
+            ts.to_string()
+        } else {
+            "None".to_string()
+        }
+    )
+}
+
+fn v0_reply_to_json(reply: &Result<Sample, Value>) -> String {
+    match reply {
+        Ok(sample) => v0_sample_to_json(sample),
+        Err(err) => {
+            format!(
+                r#"{{ "key": "ERROR", "value": {}, "encoding": "{}"}}"#,
+                v0_value_to_json(err),
+                err.encoding,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_types() {
+        let sample =
+            |value: Value| sample_to_json(&Sample::new(keyexpr::new("demo/a").unwrap(), value));
+
+        let json = sample(Value::from(r#"{"a": 1}"#).encoding(KnownEncoding::AppJson.into()));
+        assert_eq!(json["value_type"], "json");
+        assert_eq!(json["value"]["a"], 1);
+        assert_eq!(json["timestamp"], JsonValue::Null);
+        assert_eq!(json["attachment"], JsonValue::Null);
+
+        let json = sample(Value::from("not json").encoding(KnownEncoding::AppJson.into()));
+        assert_eq!(json["value_type"], "base64");
+
+        let json = sample(Value::from("<b>hi</b>").encoding(KnownEncoding::TextHtml.into()));
+        assert_eq!(json["value_type"], "text");
+        assert_eq!(json["value"], "<b>hi</b>");
+
+        let json =
+            sample(Value::from(vec![0u8, 159, 146, 150]).encoding(KnownEncoding::TextPlain.into()));
+        assert_eq!(json["value_type"], "base64");
+        assert_eq!(json["value"], b64_std_engine.encode([0u8, 159, 146, 150]));
+
+        let json = sample(Value::from(vec![1u8, 2]));
+        assert_eq!(json["value_type"], "base64");
+        assert_eq!(json["encoding"], "application/octet-stream");
+
+        let error = error_to_json(&Value::from("boom"));
+        assert_eq!(error["error"]["value"], "boom");
+        assert_eq!(error["error"]["value_type"], "text");
+    }
+
+    #[test]
+    fn test_v0() {
+        let sample = Sample::new(
+            keyexpr::new("demo/a").unwrap(),
+            Value::from("a=1;b=2").encoding(KnownEncoding::AppProperties.into()),
+        );
+        let json: JsonValue =
+            serde_json::from_str(&SchemaVersion::V0.sample_to_json(&sample)).unwrap();
+        assert_eq!(json["key"], "demo/a");
+        assert_eq!(json["value"], json!({"a": "1", "b": "2"}));
+        assert_eq!(json["encoding"], "application/properties");
+        assert_eq!(json["time"], "None");
+
+        let error: JsonValue =
+            serde_json::from_str(&SchemaVersion::V0.reply_to_json(&Err(Value::from("boom"))))
+                .unwrap();
+        assert_eq!(
+            error,
+            json!({"key": "ERROR", "value": "boom", "encoding": "text/plain"})
+        );
+    }
+
+    // The properties of an object schema of the OpenAPI document, sorted
+    fn openapi_properties(openapi: &JsonValue, schema: &str) -> Vec<String> {
+        let schema = &openapi["components"]["schemas"][schema];
+        let mut properties: Vec<String> = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        let mut required: Vec<String> = serde_json::from_value(schema["required"].clone()).unwrap();
+        properties.sort();
+        required.sort();
+        assert_eq!(properties, required);
+        properties
+    }
+
+    fn json_properties(json: &JsonValue) -> Vec<String> {
+        let mut properties: Vec<String> = json.as_object().unwrap().keys().cloned().collect();
+        properties.sort();
+        properties
+    }
+
+    #[test]
+    fn test_openapi() {
+        let openapi: JsonValue = serde_json::from_str(OPENAPI).unwrap();
+        let sample = Sample::new(keyexpr::new("demo/a").unwrap(), Value::from("hello"));
+        let error = Value::from("boom");
+
+        let json = sample_to_json(&sample);
+        assert_eq!(
+            json_properties(&json),
+            openapi_properties(&openapi, "Sample")
+        );
+        let json = error_to_json(&error);
+        assert_eq!(
+            json_properties(&json),
+            openapi_properties(&openapi, "Error")
+        );
+        let inner = &openapi["components"]["schemas"]["Error"]["properties"]["error"];
+        let mut required: Vec<String> = serde_json::from_value(inner["required"].clone()).unwrap();
+        required.sort();
+        assert_eq!(json_properties(&json["error"]), required);
+        let value_types = &openapi["components"]["schemas"]["ValueType"]["enum"];
+        for value_type in ["json", "text", "base64"] {
+            assert!(value_types.as_array().unwrap().contains(&json!(value_type)));
+        }
+
+        let json = serde_json::from_str(&SchemaVersion::V0.sample_to_json(&sample)).unwrap();
+        assert_eq!(
+            json_properties(&json),
+            openapi_properties(&openapi, "V0Sample")
+        );
+        let json = serde_json::from_str(&SchemaVersion::V0.reply_to_json(&Err(error))).unwrap();
+        assert_eq!(
+            json_properties(&json),
+            openapi_properties(&openapi, "V0Error")
+        );
+
+        let versions = &openapi["components"]["responses"]["Replies"]["headers"]
+            [SCHEMA_VERSION_HEADER]["schema"]["enum"];
+        assert_eq!(
+            versions,
+            &json!([SchemaVersion::V0.as_str(), SchemaVersion::V1.as_str()])
+        );
+    }
+}
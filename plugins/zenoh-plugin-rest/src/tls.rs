@@ -0,0 +1,178 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::TlsConfig;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::{io, task};
+use futures_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use futures_rustls::rustls::ServerConfig;
+use futures_rustls::TlsAcceptor;
+use std::fmt;
+use std::io::Cursor;
+use std::sync::Arc;
+use tide::listener::{ListenInfo, Listener, ToListener};
+use tide::Server;
+use zenoh_result::{zerror, ZResult};
+
+/// Loads the certificate chain and private key of the HTTPS server.
+pub(crate) fn server_config(config: &TlsConfig) -> ZResult<Arc<ServerConfig>> {
+    let certificate = std::fs::read(&config.certificate)
+        .map_err(|e| zerror!("Error reading certificate '{}': {}", config.certificate, e))?;
+    let private_key = std::fs::read(&config.private_key)
+        .map_err(|e| zerror!("Error reading private key '{}': {}", config.private_key, e))?;
+    let certs: Vec<CertificateDer> = rustls_pemfile::certs(&mut Cursor::new(&certificate))
+        .collect::<Result<_, _>>()
+        .map_err(|e| {
+            zerror!(
+                "Error processing certificate '{}': {}",
+                config.certificate,
+                e
+            )
+        })?;
+    let key: PrivateKeyDer = rustls_pemfile::private_key(&mut Cursor::new(&private_key))
+        .map_err(|e| {
+            zerror!(
+                "Error processing private key '{}': {}",
+                config.private_key,
+                e
+            )
+        })?
+        .ok_or_else(|| zerror!("No private key found in '{}'", config.private_key))?;
+
+    // Install ring based rustls CryptoProvider, ignoring the error if another one is already installed.
+    futures_rustls::rustls::crypto::ring::default_provider()
+        .install_default()
+        .ok();
+    let server_config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| zerror!("Invalid certificate or private key: {}", e))?;
+    Ok(Arc::new(server_config))
+}
+
+/// A tide [`Listener`] accepting HTTPS connections.
+pub(crate) struct TlsListener<State> {
+    addr: String,
+    acceptor: TlsAcceptor,
+    listener: Option<TcpListener>,
+    server: Option<Server<State>>,
+}
+
+impl<State> TlsListener<State> {
+    pub(crate) fn new(addr: String, config: Arc<ServerConfig>) -> Self {
+        TlsListener {
+            addr,
+            acceptor: TlsAcceptor::from(config),
+            listener: None,
+            server: None,
+        }
+    }
+}
+
+fn handle_tls<State: Clone + Send + Sync + 'static>(
+    app: Server<State>,
+    acceptor: TlsAcceptor,
+    stream: TcpStream,
+) {
+    task::spawn(async move {
+        let local_addr = stream.local_addr().ok();
+        let peer_addr = stream.peer_addr().ok();
+        let stream = match acceptor.accept(stream).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::debug!("TLS handshake with {:?} failed: {}", peer_addr, e);
+                return;
+            }
+        };
+        // async-h1 needs a cloneable stream to read and write concurrently
+        let stream = async_dup::Arc::new(async_dup::Mutex::new(stream));
+        let fut = async_h1::accept(stream, |mut req| async {
+            req.set_local_addr(local_addr);
+            req.set_peer_addr(peer_addr);
+            app.respond(req).await
+        });
+        if let Err(e) = fut.await {
+            tracing::debug!("HTTPS error with {:?}: {}", peer_addr, e);
+        }
+    });
+}
+
+#[async_trait::async_trait]
+impl<State> Listener<State> for TlsListener<State>
+where
+    State: Clone + Send + Sync + 'static,
+{
+    async fn bind(&mut self, server: Server<State>) -> io::Result<()> {
+        self.server = Some(server);
+        self.listener = Some(TcpListener::bind(self.addr.as_str()).await?);
+        Ok(())
+    }
+
+    async fn accept(&mut self) -> io::Result<()> {
+        let server = self
+            .server
+            .take()
+            .expect("`Listener::bind` must be called before `Listener::accept`");
+        let listener = self
+            .listener
+            .take()
+            .expect("`Listener::bind` must be called before `Listener::accept`");
+        let mut incoming = listener.incoming();
+        while let Some(stream) = incoming.next().await {
+            match stream {
+                Ok(stream) => handle_tls(server.clone(), self.acceptor.clone(), stream),
+                Err(e) => {
+                    tracing::warn!("Error accepting HTTPS connection: {}", e);
+                    task::sleep(std::time::Duration::from_millis(100)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn info(&self) -> Vec<ListenInfo> {
+        vec![ListenInfo::new(self.to_string(), "tcp".into(), true)]
+    }
+}
+
+impl<State> ToListener<State> for TlsListener<State>
+where
+    State: Clone + Send + Sync + 'static,
+{
+    type Listener = Self;
+
+    fn to_listener(self) -> io::Result<Self::Listener> {
+        Ok(self)
+    }
+}
+
+impl<State> fmt::Debug for TlsListener<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsListener")
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+impl<State> fmt::Display for TlsListener<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.listener {
+            Some(listener) => match listener.local_addr() {
+                Ok(addr) => write!(f, "https://{addr}"),
+                Err(_) => write!(f, "https://{}", self.addr),
+            },
+            None => write!(f, "https://{}", self.addr),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::{WebhookConfig, WebhookFormat};
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use futures::AsyncReadExt;
+use futures_rustls::rustls::pki_types::ServerName;
+use futures_rustls::rustls::{ClientConfig, RootCertStore};
+use futures_rustls::TlsConnector;
+use http_types::{Method, Url};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::queryable::{Query, Queryable};
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The response header giving the key of the reply of a webhook with the `raw` format.
+const KEY_HEADER: &str = "x-zenoh-key";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhookSample {
+    #[serde(default)]
+    key: Option<String>,
+    value: JsonValue,
+    #[serde(default)]
+    encoding: Option<String>,
+}
+
+struct Webhook {
+    url: Url,
+    format: WebhookFormat,
+    timeout: Duration,
+    max_response_bytes: u64,
+    tls: Option<TlsConnector>,
+}
+
+/// Declares the queryables of the webhooks, which are undeclared when dropped.
+pub(crate) async fn declare_webhooks(
+    session: &Arc<Session>,
+    configs: &[WebhookConfig],
+) -> ZResult<Vec<Queryable<'static, ()>>> {
+    let mut tls = None;
+    let mut queryables = Vec::with_capacity(configs.len());
+    for config in configs {
+        let url = Url::parse(&config.url)
+            .map_err(|e| zerror!("Invalid webhook URL '{}': {}", config.url, e))?;
+        let tls = match url.scheme() {
+            "http" => None,
+            "https" => Some(tls.get_or_insert_with(tls_connector).clone()),
+            scheme => bail!("Unsupported scheme '{}' for webhook '{}'", scheme, url),
+        };
+        let webhook = Arc::new(Webhook {
+            url,
+            format: config.format,
+            timeout: Duration::from_millis(config.timeout_ms),
+            max_response_bytes: config.max_response_bytes,
+            tls,
+        });
+        tracing::debug!("Declare webhook queryable on {}", config.key_expr);
+        let queryable = session
+            .declare_queryable(config.key_expr.as_str())
+            .complete(config.complete)
+            .callback(move |query| {
+                async_std::task::spawn(webhook.clone().answer(query));
+            })
+            .res()
+            .await?;
+        queryables.push(queryable);
+    }
+    Ok(queryables)
+}
+
+fn tls_connector() -> TlsConnector {
+    // Install ring based rustls CryptoProvider, ignoring the error if another one is already installed.
+    futures_rustls::rustls::crypto::ring::default_provider()
+        .install_default()
+        .ok();
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+impl Webhook {
+    async fn answer(self: Arc<Self>, query: Query) {
+        let replies = match self.request(&query).timeout(self.timeout).await {
+            Ok(Ok(replies)) => replies,
+            Ok(Err(e)) => vec![Err(Value::from(e.to_string()))],
+            Err(_) => vec![Err(Value::from(format!("Webhook {} timed out", self.url)))],
+        };
+        for reply in replies {
+            if let Err(e) = query.reply(reply).res().await {
+                tracing::debug!("Error replying to query on {}: {}", query.key_expr(), e);
+            }
+        }
+    }
+
+    // Forwards a query to the HTTP service, and maps its response to replies
+    async fn request(&self, query: &Query) -> ZResult<Vec<Result<Sample, Value>>> {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .map_err(|_| zerror!("Invalid webhook URL '{}'", self.url))?
+            .pop_if_empty()
+            .extend(query.key_expr().as_str().split('/'));
+        if !query.parameters().is_empty() {
+            url.set_query(Some(query.parameters()));
+        }
+        let mut request = match query.value() {
+            Some(value) => {
+                let mut request = http_types::Request::new(Method::Post, url.clone());
+                request.set_body(&*value.payload.contiguous());
+                if let Ok(mime) = value.encoding.to_string().parse::<http_types::Mime>() {
+                    request.set_content_type(mime);
+                }
+                request
+            }
+            None => http_types::Request::new(Method::Get, url.clone()),
+        };
+        request.insert_header("connection", "close");
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| zerror!("Missing host in webhook URL '{}'", url))?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| zerror!("Missing port in webhook URL '{}'", url))?;
+        let stream = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| zerror!("Error connecting to webhook {}: {}", url, e))?;
+        let response = match &self.tls {
+            Some(tls) => {
+                let server_name = ServerName::try_from(host.to_string())
+                    .map_err(|e| zerror!("Invalid host in webhook URL '{}': {}", url, e))?;
+                let stream = tls
+                    .connect(server_name, stream)
+                    .await
+                    .map_err(|e| zerror!("TLS error with webhook {}: {}", url, e))?;
+                async_h1::connect(stream, request).await
+            }
+            None => async_h1::connect(stream, request).await,
+        };
+        let mut response = response.map_err(|e| zerror!("Error requesting {}: {}", url, e))?;
+
+        // the body is read up to one byte beyond the maximum, to detect the larger ones
+        if response
+            .len()
+            .is_some_and(|len| len as u64 > self.max_response_bytes)
+        {
+            bail!(
+                "The response of {} exceeds {} bytes",
+                url,
+                self.max_response_bytes
+            )
+        }
+        let mut body = Vec::new();
+        response
+            .take_body()
+            .take(self.max_response_bytes + 1)
+            .read_to_end(&mut body)
+            .await
+            .map_err(|e| zerror!("Error reading the response of {}: {}", url, e))?;
+        if body.len() as u64 > self.max_response_bytes {
+            bail!(
+                "The response of {} exceeds {} bytes",
+                url,
+                self.max_response_bytes
+            )
+        }
+        let encoding: Encoding = response
+            .content_type()
+            .map(|m| m.to_string().into())
+            .unwrap_or_default();
+        if !response.status().is_success() {
+            return Ok(vec![Err(Value::from(body).encoding(encoding))]);
+        }
+        match self.format {
+            WebhookFormat::Raw => {
+                let key_expr = match response.header(KEY_HEADER) {
+                    Some(key) => KeyExpr::try_from(key.last().as_str().to_string())?,
+                    None => query.key_expr().clone(),
+                };
+                Ok(vec![Ok(Sample::new(
+                    key_expr,
+                    Value::from(body).encoding(encoding),
+                ))])
+            }
+            WebhookFormat::Samples => {
+                let samples: Vec<WebhookSample> = serde_json::from_slice(&body)
+                    .map_err(|e| zerror!("Invalid samples from webhook {}: {}", url, e))?;
+                samples
+                    .into_iter()
+                    .map(|sample| {
+                        let key_expr = match sample.key {
+                            Some(key) => KeyExpr::try_from(key)?,
+                            None => query.key_expr().clone(),
+                        };
+                        let value = match sample.value {
+                            JsonValue::String(s) => Value::from(s),
+                            json => Value::from(json.to_string())
+                                .encoding(KnownEncoding::AppJson.into()),
+                        };
+                        let value = match sample.encoding {
+                            Some(encoding) => value.encoding(encoding.into()),
+                            None => value,
+                        };
+                        Ok(Ok(Sample::new(key_expr, value)))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The WebSocket endpoint of the REST plugin, multiplexing several operations over a single connection.
+//!
+//! The client sends commands as JSON text messages, each with an `op` and an `id` chosen by the client:
+//! - `{"op": "subscribe", "id": 1, "key_expr": "demo/**", "binary": false}`: subscribe to a key expression.
+//!   The samples are sent as `sample` events with the id of the subscription.
+//! - `{"op": "liveliness", "id": 2, "key_expr": "group/**"}`: subscribe to the liveliness tokens of a key expression.
+//!   The appearance and disappearance of tokens are sent as `alive` and `dropped` events.
+//! - `{"op": "unsubscribe", "id": 1}`: undeclare the subscription (or liveliness subscription) with the given id.
+//! - `{"op": "put", "id": 3, "key_expr": "demo/a", "value": "text", "encoding": "text/plain"}`: put a value.
+//! - `{"op": "delete", "id": 4, "key_expr": "demo/a"}`: delete a key.
+//! - `{"op": "get", "id": 5, "selector": "demo/**?_time=[now(-1h)..]", "binary": false}`: query a selector,
+//!   optionally with a `value` and `encoding`. The replies are streamed as `reply` and `reply_error` events,
+//!   followed by a `done` event.
+//!
+//! A `put` can also be sent as a binary message: its JSON command without `value`, a newline, then the raw payload.
+//! With `"binary": true`, the samples and replies are sent the same way: a JSON event without `value`, a newline,
+//! then the raw payload. Otherwise their values are sent in JSON, as in version 1 of the JSON representation of the REST API.
+//!
+//! Each command is acknowledged with an `ok` event, or an `error` event with a `message`:
+//! `{"id": 1, "event": "ok"}`.
+//! The `ok` event of a `get` is sent before its `reply` events.
+//! When authentication is configured, the commands are subject to the permissions of the user of the connection.
+//!
+//! As browsers don't apply the CORS policy to WebSocket connections, but send them the credentials they cached
+//! for the REST API, the upgrades are only accepted from the origins allowed by the `allowed_origins` configuration,
+//! from the origin of the REST API itself, or from clients sending no `Origin` header.
+use crate::auth::{Access, Permissions};
+use crate::{path_to_key_expr, query_consolidation, schema};
+use async_std::task;
+use async_tungstenite::tungstenite::handshake::derive_accept_key;
+use async_tungstenite::tungstenite::protocol::Role;
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketStream;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tide::{Middleware, Next, Request, Response, StatusCode};
+use zenoh::prelude::r#async::*;
+use zenoh::query::Reply;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+// The maximum number of messages waiting to be sent on a connection.
+// Beyond, the samples of subscriptions are dropped.
+const QUEUE_SIZE: usize = 1024;
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+enum Command {
+    Subscribe {
+        id: u64,
+        key_expr: String,
+        #[serde(default)]
+        binary: bool,
+    },
+    Liveliness {
+        id: u64,
+        key_expr: String,
+    },
+    Unsubscribe {
+        id: u64,
+    },
+    Put {
+        id: u64,
+        key_expr: String,
+        #[serde(default)]
+        value: Option<String>,
+        #[serde(default)]
+        encoding: Option<String>,
+    },
+    Delete {
+        id: u64,
+        key_expr: String,
+    },
+    Get {
+        id: u64,
+        selector: String,
+        #[serde(default)]
+        value: Option<String>,
+        #[serde(default)]
+        encoding: Option<String>,
+        #[serde(default)]
+        binary: bool,
+    },
+}
+
+impl Command {
+    fn id(&self) -> u64 {
+        match self {
+            Command::Subscribe { id, .. }
+            | Command::Liveliness { id, .. }
+            | Command::Unsubscribe { id }
+            | Command::Put { id, .. }
+            | Command::Delete { id, .. }
+            | Command::Get { id, .. } => *id,
+        }
+    }
+}
+
+/// Returns true if the request asks for an upgrade to the WebSocket protocol.
+pub(crate) fn is_upgrade<State>(req: &Request<State>) -> bool {
+    req.header("upgrade")
+        .is_some_and(|h| h.last().as_str().eq_ignore_ascii_case("websocket"))
+}
+
+/// A tide middleware rejecting the upgrades to the WebSocket protocol requested from an origin that isn't allowed.
+pub(crate) struct OriginMiddleware {
+    pub(crate) allowed_origins: Vec<String>,
+}
+
+impl OriginMiddleware {
+    // An origin is allowed if configured, or if it is the origin of the REST API (same host and port)
+    fn allows(&self, origin: &str, host: Option<&str>) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(origin))
+            || origin
+                .split_once("://")
+                .is_some_and(|(_, authority)| Some(authority) == host)
+    }
+}
+
+#[async_trait::async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for OriginMiddleware {
+    async fn handle(&self, req: Request<State>, next: Next<'_, State>) -> tide::Result {
+        if is_upgrade(&req) {
+            if let Some(origin) = req.header("origin").map(|h| h.last().as_str()) {
+                let host = req.header("host").map(|h| h.last().as_str());
+                if !self.allows(origin, host) {
+                    tracing::debug!("WebSocket upgrade rejected from origin {}", origin);
+                    return Ok(crate::response(
+                        StatusCode::Forbidden,
+                        "text/plain",
+                        &format!("WebSocket connections are not allowed from origin `{origin}`"),
+                    ));
+                }
+            }
+        }
+        Ok(next.run(req).await)
+    }
+}
+
+/// Accepts the upgrade of a request to the WebSocket protocol, and serves the commands received on the connection.
+pub(crate) async fn upgrade(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    let Some(key) = req.header("sec-websocket-key") else {
+        return Ok(crate::response(
+            StatusCode::BadRequest,
+            "text/plain",
+            "Missing Sec-WebSocket-Key header",
+        ));
+    };
+    let mut response = Response::new(StatusCode::SwitchingProtocols);
+    response.insert_header("upgrade", "websocket");
+    response.insert_header("connection", "Upgrade");
+    response.insert_header(
+        "sec-websocket-accept",
+        derive_accept_key(key.last().as_str().as_bytes()),
+    );
+    let http_response: &mut http_types::Response = response.as_mut();
+    let upgrade = http_response.recv_upgrade().await;
+
+    let (session, zid) = req.state().clone();
+    let permissions = req.ext::<Arc<Permissions>>().cloned();
+    task::spawn(async move {
+        if let Some(connection) = upgrade.await {
+            let stream = WebSocketStream::from_raw_socket(connection, Role::Server, None).await;
+            Connection {
+                session,
+                zid,
+                permissions,
+                subscribers: HashMap::new(),
+            }
+            .serve(stream)
+            .await;
+        }
+    });
+    Ok(response)
+}
+
+struct Connection {
+    session: Arc<Session>,
+    zid: String,
+    permissions: Option<Arc<Permissions>>,
+    // the subscribers and liveliness subscribers, by command id
+    subscribers: HashMap<u64, Subscriber<'static, ()>>,
+}
+
+impl Connection {
+    async fn serve<S>(mut self, stream: WebSocketStream<S>)
+    where
+        S: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sink, mut stream) = stream.split();
+        let (tx, rx) = flume::bounded::<Message>(QUEUE_SIZE);
+        let writer = task::spawn(async move {
+            while let Ok(message) = rx.recv_async().await {
+                if let Err(e) = sink.send(message).await {
+                    tracing::debug!("WebSocket error: {}", e);
+                    break;
+                }
+            }
+        });
+
+        while let Some(message) = stream.next().await {
+            let (command, payload) = match message {
+                Ok(Message::Text(text)) => (serde_json::from_str::<Command>(&text), None),
+                Ok(Message::Binary(bytes)) => match bytes.iter().position(|b| *b == b'\n') {
+                    Some(i) => (
+                        serde_json::from_slice::<Command>(&bytes[..i]),
+                        Some(bytes[i + 1..].to_vec()),
+                    ),
+                    None => (serde_json::from_slice::<Command>(&bytes), Some(Vec::new())),
+                },
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::debug!("WebSocket error: {}", e);
+                    break;
+                }
+            };
+            let event = match command {
+                Ok(command) => {
+                    let id = command.id();
+                    match self.execute(command, payload, &tx).await {
+                        Ok(replies) => {
+                            let ok = json!({"id": id, "event": "ok"});
+                            if tx.send_async(Message::Text(ok.to_string())).await.is_err() {
+                                break;
+                            }
+                            // the replies of a `get` are forwarded once it is acknowledged
+                            if let Some(replies) = replies {
+                                task::spawn(replies.forward(tx.clone()));
+                            }
+                            continue;
+                        }
+                        Err(e) => json!({"id": id, "event": "error", "message": e.to_string()}),
+                    }
+                }
+                Err(e) => json!({"id": null, "event": "error", "message": e.to_string()}),
+            };
+            if tx
+                .send_async(Message::Text(event.to_string()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+        // undeclare the subscribers before closing the connection
+        self.subscribers.clear();
+        drop(tx);
+        writer.await;
+    }
+
+    // Executes a command, returning the pending replies of a `get`
+    async fn execute(
+        &mut self,
+        command: Command,
+        payload: Option<Vec<u8>>,
+        tx: &flume::Sender<Message>,
+    ) -> ZResult<Option<PendingReplies>> {
+        if payload.is_some() && !matches!(command, Command::Put { value: None, .. }) {
+            bail!("Only a `put` without `value` can be sent as a binary message")
+        }
+        match command {
+            Command::Subscribe {
+                id,
+                key_expr,
+                binary,
+            } => {
+                let key_expr = self.key_expr(&key_expr, Access::Read)?;
+                self.check_id(id)?;
+                let tx = tx.clone();
+                let subscriber = self
+                    .session
+                    .declare_subscriber(key_expr)
+                    .callback(move |sample| {
+                        if tx.try_send(message(id, "sample", &sample, binary)).is_err() {
+                            tracing::debug!("WebSocket queue full, sample dropped");
+                        }
+                    })
+                    .res()
+                    .await?;
+                self.subscribers.insert(id, subscriber);
+            }
+            Command::Liveliness { id, key_expr } => {
+                let key_expr = self.key_expr(&key_expr, Access::Read)?;
+                self.check_id(id)?;
+                let tx = tx.clone();
+                let subscriber = self
+                    .session
+                    .liveliness()
+                    .declare_subscriber(key_expr)
+                    .callback(move |sample| {
+                        let event = match sample.kind {
+                            SampleKind::Put => "alive",
+                            SampleKind::Delete => "dropped",
+                        };
+                        let event =
+                            json!({"id": id, "event": event, "key": sample.key_expr.as_str()});
+                        if tx.try_send(Message::Text(event.to_string())).is_err() {
+                            tracing::debug!("WebSocket queue full, liveliness event dropped");
+                        }
+                    })
+                    .res()
+                    .await?;
+                self.subscribers.insert(id, subscriber);
+            }
+            Command::Unsubscribe { id } => match self.subscribers.remove(&id) {
+                Some(subscriber) => subscriber.undeclare().res().await?,
+                None => bail!("No subscription with id {}", id),
+            },
+            Command::Put {
+                key_expr,
+                value,
+                encoding,
+                ..
+            } => {
+                let key_expr = self.key_expr(&key_expr, Access::Write)?;
+                let payload = match (value, payload) {
+                    (Some(value), _) => value.into_bytes(),
+                    (None, Some(payload)) => payload,
+                    (None, None) => Vec::new(),
+                };
+                let encoding: Encoding = encoding.map(Encoding::from).unwrap_or_default();
+                self.session
+                    .put(key_expr, payload)
+                    .encoding(encoding)
+                    .res()
+                    .await?;
+            }
+            Command::Delete { key_expr, .. } => {
+                let key_expr = self.key_expr(&key_expr, Access::Write)?;
+                self.session.delete(key_expr).res().await?;
+            }
+            Command::Get {
+                id,
+                selector,
+                value,
+                encoding,
+                binary,
+            } => {
+                let (key_expr, parameters) = selector.split_once('?').unwrap_or((&selector, ""));
+                let key_expr = self.key_expr(key_expr, Access::Read)?;
+                let selector = Selector::from(key_expr).with_parameters(parameters);
+                let mut query = self
+                    .session
+                    .get(&selector)
+                    .consolidation(query_consolidation(&selector));
+                if let Some(value) = value {
+                    let encoding: Encoding = encoding.map(Encoding::from).unwrap_or_default();
+                    query = query.with_value(Value::from(value.into_bytes()).encoding(encoding));
+                }
+                let replies = query.res().await?;
+                return Ok(Some(PendingReplies {
+                    id,
+                    replies,
+                    binary,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    // The key expression of a command, if the user of the connection has the given access to it
+    fn key_expr(&self, key_expr: &str, access: Access) -> ZResult<OwnedKeyExpr> {
+        let key_expr = path_to_key_expr(key_expr, &self.zid)?.into_owned();
+        if let Some(permissions) = &self.permissions {
+            if !permissions.allows(access, &key_expr) {
+                let operation = match access {
+                    Access::Read => "read",
+                    Access::Write => "write",
+                };
+                bail!(
+                    "User `{}` is not allowed to {} `{}`",
+                    permissions.user,
+                    operation,
+                    key_expr
+                )
+            }
+        }
+        Ok(key_expr.into())
+    }
+
+    fn check_id(&self, id: u64) -> ZResult<()> {
+        if self.subscribers.contains_key(&id) {
+            return Err(zerror!("A subscription with id {} already exists", id).into());
+        }
+        Ok(())
+    }
+}
+
+// The replies of a `get`, to forward as `reply` and `reply_error` events followed by a `done` event
+struct PendingReplies {
+    id: u64,
+    replies: flume::Receiver<Reply>,
+    binary: bool,
+}
+
+impl PendingReplies {
+    async fn forward(self, tx: flume::Sender<Message>) {
+        while let Ok(reply) = self.replies.recv_async().await {
+            if tx
+                .send_async(reply_message(self.id, reply, self.binary))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+        let done = json!({"id": self.id, "event": "done"});
+        let _ = tx.send_async(Message::Text(done.to_string())).await;
+    }
+}
+
+// The message of a `sample` or `reply` event: the JSON representation of the sample with the id of the command,
+// or in binary mode, this representation without value followed by the raw payload
+fn message(id: u64, event: &str, sample: &Sample, binary: bool) -> Message {
+    let mut json = schema::sample_to_json(sample);
+    json["id"] = json!(id);
+    json["event"] = json!(event);
+    if !binary {
+        return Message::Text(json.to_string());
+    }
+    if let JsonValue::Object(object) = &mut json {
+        object.remove("value");
+        object.remove("value_type");
+    }
+    let mut bytes = json.to_string().into_bytes();
+    bytes.push(b'\n');
+    bytes.extend_from_slice(&sample.payload.contiguous());
+    Message::Binary(bytes)
+}
+
+fn reply_message(id: u64, reply: Reply, binary: bool) -> Message {
+    match reply.sample {
+        Ok(sample) => message(id, "reply", &sample, binary),
+        Err(value) => {
+            let mut json = schema::error_to_json(&value);
+            json["id"] = json!(id);
+            json["event"] = json!("reply_error");
+            Message::Text(json.to_string())
+        }
+    }
+}
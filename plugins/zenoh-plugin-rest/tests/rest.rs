@@ -0,0 +1,131 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the JSON representation of the responses to queries -
+// 1. version 0 is used by default
+// 2. version 1 is used when requested by the `_format` parameter or the `Accept` header
+// 3. the `_format` parameter isn't forwarded with the query
+// 4. the port is released when the plugin is stopped, so that it can be started again
+
+use async_std::net::{TcpListener, TcpStream};
+use async_std::task;
+use http_types::{Method, Request, Url};
+use serde_json::{json, Value as JsonValue};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_plugin_rest::RestPlugin;
+use zenoh_plugin_trait::Plugin;
+
+const PORT: u16 = 18044;
+
+// The schema version and body of the response to a GET request
+async fn get(path: &str, accept: Option<&str>) -> (Option<String>, String) {
+    let url = Url::parse(&format!("http://127.0.0.1:{PORT}{path}")).unwrap();
+    let mut request = Request::new(Method::Get, url);
+    if let Some(accept) = accept {
+        request.insert_header("accept", accept);
+    }
+    let stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    let mut response = async_h1::connect(stream, request).await.unwrap();
+    let version = response
+        .header("x-zenoh-schema-version")
+        .map(|h| h.last().to_string());
+    (version, response.body_string().await.unwrap())
+}
+
+async fn test_rest() {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/rest",
+            &format!(r#"{{ http_port: "127.0.0.1:{PORT}" }}"#),
+        )
+        .unwrap();
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let rest = RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::init(runtime.clone()).res_async().await.unwrap();
+    // replies the parameters of the query, or an error on `demo/error`
+    let _queryable = session
+        .declare_queryable("demo/*")
+        .callback(|query| {
+            task::block_on(async move {
+                let reply = if query.key_expr().as_str() == "demo/error" {
+                    Err(Value::from("boom"))
+                } else {
+                    Ok(Sample::new(query.key_expr().clone(), query.parameters()))
+                };
+                query.reply(reply).res_async().await.unwrap();
+            })
+        })
+        .res_async()
+        .await
+        .unwrap();
+    task::sleep(Duration::from_secs(1)).await;
+
+    let (version, body) = get("/demo/query?x=1", None).await;
+    assert_eq!(version.as_deref(), Some("0"));
+    let replies: JsonValue = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        replies,
+        json!([{"key": "demo/query", "value": "x=1", "encoding": "text/plain", "time": "None"}])
+    );
+    let (_, body) = get("/demo/error", None).await;
+    let replies: JsonValue = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        replies,
+        json!([{"key": "ERROR", "value": "boom", "encoding": "text/plain"}])
+    );
+
+    let (version, body) = get("/demo/query?_format=ndjson-v1&x=1", None).await;
+    assert_eq!(version.as_deref(), Some("1"));
+    let replies: Vec<JsonValue> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["value"], "x=1");
+    assert_eq!(replies[0]["timestamp"], JsonValue::Null);
+    let (_, body) = get("/demo/error?_format=ndjson-v1", None).await;
+    let reply: JsonValue = serde_json::from_str(body.trim()).unwrap();
+    assert_eq!(reply["error"]["value"], "boom");
+
+    let (version, body) = get(
+        "/demo/query?x=1&_format=json",
+        Some("application/json; v=1"),
+    )
+    .await;
+    assert_eq!(version.as_deref(), Some("1"));
+    let replies: JsonValue = serde_json::from_str(&body).unwrap();
+    assert_eq!(replies[0]["value"], "x=1");
+    assert_eq!(replies[0]["value_type"], "text");
+
+    let (version, _) = get("/demo/query?_format=xml", None).await;
+    assert_eq!(version, None);
+
+    drop(rest);
+    drop(TcpListener::bind(("127.0.0.1", PORT)).await.unwrap());
+    let _rest = RestPlugin::start("rest", &runtime).unwrap();
+    task::sleep(Duration::from_secs(1)).await;
+    let (version, _) = get("/demo/query", None).await;
+    assert_eq!(version.as_deref(), Some("0"));
+}
+
+#[test]
+fn rest_test() {
+    task::block_on(async { test_rest().await });
+}
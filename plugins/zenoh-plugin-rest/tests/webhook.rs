@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the webhooks of the REST plugin with a local HTTP service -
+// 1. a query is forwarded with its key expression and parameters
+// 2. the responses in the `raw` and `samples` formats are mapped to replies
+// 3. the responses larger than the configured maximum are replied as errors
+
+use async_std::net::TcpListener;
+use async_std::task;
+use futures::StreamExt;
+use http_types::{Request, Response, StatusCode};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_plugin_rest::RestPlugin;
+use zenoh_plugin_trait::Plugin;
+
+const PORT: u16 = 18043;
+
+// Answers the requests, sending their path and query on the channel
+async fn serve(listener: TcpListener, requests: flume::Sender<String>) {
+    while let Some(Ok(stream)) = listener.incoming().next().await {
+        let requests = requests.clone();
+        task::spawn(async move {
+            let _ = async_h1::accept(stream, |req: Request| {
+                let requests = requests.clone();
+                async move {
+                    let path = req.url().path().to_string();
+                    let target = match req.url().query() {
+                        Some(query) => format!("{path}?{query}"),
+                        None => path.clone(),
+                    };
+                    requests.send_async(target).await.unwrap();
+                    let mut response = Response::new(StatusCode::Ok);
+                    if path.starts_with("/api/hooks/raw") {
+                        response.insert_header("x-zenoh-key", "hooks/raw/renamed");
+                        response.set_body("hello");
+                    } else if path.starts_with("/api/hooks/samples") {
+                        response.set_body(
+                            r#"[{"key": "hooks/samples/a", "value": "one"},
+                                {"key": "hooks/samples/b", "value": {"two": 2}}]"#,
+                        );
+                    } else {
+                        response.set_body("a response beyond the maximum size");
+                    }
+                    Ok(response)
+                }
+            })
+            .await;
+        });
+    }
+}
+
+async fn get(session: &zenoh::Session, selector: &str) -> Vec<Reply> {
+    session
+        .get(selector)
+        .res_async()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+async fn test_webhook() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/api", listener.local_addr().unwrap());
+    let (tx, requests) = flume::unbounded();
+    task::spawn(serve(listener, tx));
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/rest",
+            &format!(
+                r#"{{
+                    http_port: "127.0.0.1:{PORT}",
+                    webhooks: [
+                        {{ key_expr: "hooks/raw/**", url: "{url}" }},
+                        {{ key_expr: "hooks/samples/**", url: "{url}", format: "samples" }},
+                        {{ key_expr: "hooks/large/**", url: "{url}", max_response_bytes: 8 }},
+                    ],
+                }}"#
+            ),
+        )
+        .unwrap();
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let _rest = RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::init(runtime).res_async().await.unwrap();
+    task::sleep(Duration::from_secs(1)).await;
+
+    // the raw body is replied on the key of the response header
+    let replies = get(&session, "hooks/raw/*?x=1;y=2").await;
+    assert_eq!(
+        requests.recv_async().await.unwrap(),
+        "/api/hooks/raw/*?x=1;y=2"
+    );
+    assert_eq!(replies.len(), 1);
+    let sample = replies[0].sample.as_ref().unwrap();
+    assert_eq!(sample.key_expr.as_str(), "hooks/raw/renamed");
+    assert_eq!(sample.value.to_string(), "hello");
+
+    // each sample of the response is a reply
+    let replies = get(&session, "hooks/samples/**").await;
+    assert_eq!(
+        requests.recv_async().await.unwrap(),
+        "/api/hooks/samples/**"
+    );
+    let mut samples: Vec<(String, String)> = replies
+        .into_iter()
+        .map(|reply| {
+            let sample = reply.sample.unwrap();
+            (sample.key_expr.to_string(), sample.value.to_string())
+        })
+        .collect();
+    samples.sort();
+    assert_eq!(
+        samples,
+        vec![
+            ("hooks/samples/a".to_string(), "one".to_string()),
+            ("hooks/samples/b".to_string(), r#"{"two":2}"#.to_string()),
+        ]
+    );
+
+    // a response too large is an error
+    let replies = get(&session, "hooks/large/a").await;
+    assert_eq!(requests.recv_async().await.unwrap(), "/api/hooks/large/a");
+    assert_eq!(replies.len(), 1);
+    let error = replies[0].sample.as_ref().unwrap_err().to_string();
+    assert!(error.contains("exceeds 8 bytes"), "{error}");
+}
+
+#[test]
+fn webhook_test() {
+    task::block_on(async { test_webhook().await });
+}
@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the WebSocket endpoint of the REST plugin over a real socket -
+// 1. the upgrades from a foreign origin are rejected
+// 2. subscribe, put (in text and binary messages), get and unsubscribe
+// 3. the commands on key expressions the user isn't allowed to access are rejected
+
+use async_std::net::TcpStream;
+use async_std::task;
+use async_tungstenite::tungstenite::client::IntoClientRequest;
+use async_tungstenite::tungstenite::{Error, Message};
+use async_tungstenite::WebSocketStream;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value as JsonValue};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_plugin_rest::RestPlugin;
+use zenoh_plugin_trait::Plugin;
+
+const PORT: u16 = 18042;
+
+async fn connect(origin: Option<&str>) -> Result<WebSocketStream<TcpStream>, Error> {
+    let mut request = format!("ws://127.0.0.1:{PORT}/")
+        .into_client_request()
+        .unwrap();
+    let credentials = b64_std_engine.encode("alice:secret");
+    let headers = request.headers_mut();
+    headers.insert(
+        "authorization",
+        format!("Basic {credentials}").parse().unwrap(),
+    );
+    if let Some(origin) = origin {
+        headers.insert("origin", origin.parse().unwrap());
+    }
+    let stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    async_tungstenite::client_async(request, stream)
+        .await
+        .map(|(ws, _)| ws)
+}
+
+async fn send(ws: &mut WebSocketStream<TcpStream>, command: JsonValue) {
+    ws.send(Message::Text(command.to_string())).await.unwrap();
+}
+
+// The next event, with its raw payload if sent in a binary message
+async fn recv(ws: &mut WebSocketStream<TcpStream>) -> (JsonValue, Option<Vec<u8>>) {
+    let message = async_std::future::timeout(Duration::from_secs(5), ws.next())
+        .await
+        .expect("No event received")
+        .unwrap()
+        .unwrap();
+    match message {
+        Message::Text(text) => (serde_json::from_str(&text).unwrap(), None),
+        Message::Binary(bytes) => {
+            let i = bytes.iter().position(|b| *b == b'\n').unwrap();
+            let event = serde_json::from_slice(&bytes[..i]).unwrap();
+            (event, Some(bytes[i + 1..].to_vec()))
+        }
+        message => panic!("Unexpected message {message:?}"),
+    }
+}
+
+// The next events, sorted by id and event, as their order depends on the tasks sending them
+async fn recv_all(
+    ws: &mut WebSocketStream<TcpStream>,
+    count: usize,
+) -> Vec<(JsonValue, Option<Vec<u8>>)> {
+    let mut events = Vec::with_capacity(count);
+    for _ in 0..count {
+        events.push(recv(ws).await);
+    }
+    events.sort_by_key(|(event, _)| (event["id"].as_u64(), event["event"].to_string()));
+    events
+}
+
+async fn test_websocket() {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/rest",
+            &format!(
+                r#"{{
+                    http_port: "127.0.0.1:{PORT}",
+                    auth: {{
+                        users: {{
+                            alice: {{ password: "secret", read: ["demo/**"], write: ["demo/alice/**"] }},
+                        }},
+                    }},
+                }}"#
+            ),
+        )
+        .unwrap();
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let _rest = RestPlugin::start("rest", &runtime).unwrap();
+    let session = zenoh::init(runtime).res_async().await.unwrap();
+    let _queryable = session
+        .declare_queryable("demo/query")
+        .callback(|query| {
+            task::block_on(async move {
+                let sample = Sample::new(query.key_expr().clone(), "answer");
+                query.reply(Ok(sample)).res_async().await.unwrap();
+            })
+        })
+        .res_async()
+        .await
+        .unwrap();
+    task::sleep(Duration::from_secs(1)).await;
+
+    // the upgrades are only accepted from the origin of the REST API, or without origin
+    match connect(Some("http://evil.example.com")).await {
+        Err(Error::Http(response)) => assert_eq!(response.status(), 403),
+        other => panic!("Unexpected upgrade {:?}", other.map(|_| ())),
+    }
+    connect(None).await.unwrap();
+    let mut ws = connect(Some(&format!("http://127.0.0.1:{PORT}")))
+        .await
+        .unwrap();
+
+    // the samples of a subscription
+    send(
+        &mut ws,
+        json!({"op": "subscribe", "id": 1, "key_expr": "demo/**"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await.0, json!({"id": 1, "event": "ok"}));
+    send(
+        &mut ws,
+        json!({"op": "put", "id": 2, "key_expr": "demo/alice/a", "value": "hello", "encoding": "text/plain"}),
+    )
+    .await;
+    let events = recv_all(&mut ws, 2).await;
+    assert_eq!(events[0].0["event"], "sample");
+    assert_eq!(events[0].0["key"], "demo/alice/a");
+    assert_eq!(events[0].0["value"], "hello");
+    assert_eq!(events[1].0, json!({"id": 2, "event": "ok"}));
+
+    // the binary framing of the puts and the samples
+    send(
+        &mut ws,
+        json!({"op": "subscribe", "id": 3, "key_expr": "demo/alice/b", "binary": true}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await.0, json!({"id": 3, "event": "ok"}));
+    let mut put = json!({"op": "put", "id": 4, "key_expr": "demo/alice/b"})
+        .to_string()
+        .into_bytes();
+    put.push(b'\n');
+    put.extend_from_slice(&[0, 1, 2, b'\n']);
+    ws.send(Message::Binary(put)).await.unwrap();
+    let events = recv_all(&mut ws, 3).await;
+    assert_eq!(events[0].0["id"], 1);
+    assert_eq!(events[0].1, None);
+    assert_eq!(events[1].0["id"], 3);
+    assert_eq!(events[1].0["key"], "demo/alice/b");
+    assert_eq!(events[1].0.get("value"), None);
+    assert_eq!(events[1].1, Some(vec![0, 1, 2, b'\n']));
+    assert_eq!(events[2].0, json!({"id": 4, "event": "ok"}));
+
+    // the replies of a query follow its acknowledgment
+    send(
+        &mut ws,
+        json!({"op": "get", "id": 5, "selector": "demo/query"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await.0, json!({"id": 5, "event": "ok"}));
+    let (reply, _) = recv(&mut ws).await;
+    assert_eq!(reply["event"], "reply");
+    assert_eq!(reply["value"], "answer");
+    assert_eq!(recv(&mut ws).await.0, json!({"id": 5, "event": "done"}));
+
+    // no sample is sent once unsubscribed
+    send(&mut ws, json!({"op": "unsubscribe", "id": 1})).await;
+    assert_eq!(recv(&mut ws).await.0, json!({"id": 1, "event": "ok"}));
+    send(
+        &mut ws,
+        json!({"op": "put", "id": 6, "key_expr": "demo/alice/c", "value": "bye"}),
+    )
+    .await;
+    assert_eq!(recv(&mut ws).await.0, json!({"id": 6, "event": "ok"}));
+    send(&mut ws, json!({"op": "unsubscribe", "id": 1})).await;
+    assert_eq!(recv(&mut ws).await.0["event"], "error");
+
+    // the permissions of the user
+    send(
+        &mut ws,
+        json!({"op": "put", "id": 7, "key_expr": "demo/bob/a", "value": "intrusion"}),
+    )
+    .await;
+    let (error, _) = recv(&mut ws).await;
+    assert_eq!(error["id"], 7);
+    assert_eq!(error["event"], "error");
+    assert!(error["message"].as_str().unwrap().contains("not allowed"));
+    send(
+        &mut ws,
+        json!({"op": "subscribe", "id": 8, "key_expr": "other/**"}),
+    )
+    .await;
+    let (error, _) = recv(&mut ws).await;
+    assert_eq!(error["id"], 8);
+    assert_eq!(error["event"], "error");
+
+    ws.close(None).await.unwrap();
+}
+
+#[test]
+fn websocket_test() {
+    task::block_on(async { test_websocket().await });
+}
@@ -36,8 +36,10 @@ pub(crate) async fn create_and_start_storage(
     zenoh: Arc<Session>,
 ) -> ZResult<Sender<StorageMessage>> {
     tracing::trace!("Create storage '{}'", &admin_key);
-    let capability = backend.get_capability();
     let storage = backend.create_storage(config.clone()).await?;
+    let capability = storage
+        .get_capability()
+        .unwrap_or_else(|| backend.get_capability());
     let store_intercept = StoreIntercept {
         storage,
         capability,
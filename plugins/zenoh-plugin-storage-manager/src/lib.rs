@@ -22,8 +22,10 @@
 use async_std::task;
 use flume::Sender;
 use memory_backend::MemoryBackend;
+use persistent_backend::PersistentBackend;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex;
 use storages_mgt::StorageMessage;
@@ -43,14 +45,19 @@ use zenoh_plugin_trait::Plugin;
 use zenoh_plugin_trait::PluginControl;
 use zenoh_plugin_trait::PluginReport;
 use zenoh_plugin_trait::PluginStatusRec;
-use zenoh_result::ZResult;
-use zenoh_util::LibLoader;
+use zenoh_result::{zerror, ZResult};
+use zenoh_util::{zenoh_home, LibLoader};
 
 mod backends_mgt;
 use backends_mgt::*;
 mod memory_backend;
+mod persistent_backend;
+mod quotas;
 mod replica;
+mod snapshot;
+use snapshot::{snapshot_path, EXPORT_KEY, IMPORT_KEY};
 mod storages_mgt;
+mod writes;
 
 #[cfg(feature = "dynamic_plugin")]
 zenoh_plugin_trait::declare_plugin!(StoragesPlugin);
@@ -65,6 +72,10 @@ impl Plugin for StoragesPlugin {
     type StartArgs = Runtime;
     type Instance = zenoh::plugins::RunningPlugin;
 
+    fn config_schema() -> Option<serde_json::Value> {
+        serde_json::to_value(schemars::schema_for!(PluginConfig)).ok()
+    }
+
     fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<Self::Instance> {
         zenoh_util::try_init_log_from_env();
         tracing::debug!("StorageManager plugin {}", Self::PLUGIN_VERSION);
@@ -84,6 +95,7 @@ struct StorageRuntimeInner {
     name: String,
     runtime: Runtime,
     session: Arc<Session>,
+    snapshots_dir: PathBuf,
     storages: HashMap<String, HashMap<String, Sender<StorageMessage>>>,
     plugins_manager: PluginsManager,
 }
@@ -103,17 +115,21 @@ impl StorageRuntimeInner {
         let PluginConfig {
             name,
             backend_search_dirs,
+            snapshots_dir,
             volumes,
             storages,
             ..
         } = config;
+        let snapshots_dir = snapshots_dir
+            .map(PathBuf::from)
+            .unwrap_or_else(|| zenoh_home().join("snapshots"));
         let lib_loader = backend_search_dirs
             .map(|search_dirs| LibLoader::new(&search_dirs, false))
             .unwrap_or_default();
 
-        let plugins_manager =
-            PluginsManager::dynamic(lib_loader.clone(), BACKEND_LIB_PREFIX)
-                .declare_static_plugin::<MemoryBackend, &str>(MEMORY_BACKEND_NAME, true);
+        let plugins_manager = PluginsManager::dynamic(lib_loader.clone(), BACKEND_LIB_PREFIX)
+            .declare_static_plugin::<MemoryBackend, &str>(MEMORY_BACKEND_NAME, true)
+            .declare_static_plugin::<PersistentBackend, &str>(PERSISTENT_BACKEND_NAME, true);
 
         let session = Arc::new(zenoh::init(runtime.clone()).res_sync()?);
 
@@ -123,27 +139,28 @@ impl StorageRuntimeInner {
             name,
             runtime,
             session,
+            snapshots_dir,
             storages: Default::default(),
             plugins_manager,
         };
-        new_self
-            .spawn_volume(&VolumeConfig {
-                name: MEMORY_BACKEND_NAME.into(),
-                backend: None,
-                paths: None,
-                required: false,
-                rest: Default::default(),
-            })
-            .map_or_else(
-                |e| {
-                    tracing::error!(
-                        "Cannot spawn static volume '{}': {}",
-                        MEMORY_BACKEND_NAME,
-                        e
-                    )
-                },
-                |_| (),
-            );
+        // The static volumes are spawned with their default configuration, unless configured
+        for static_volume in [MEMORY_BACKEND_NAME, PERSISTENT_BACKEND_NAME] {
+            if volumes.iter().any(|v| v.name() == static_volume) {
+                continue;
+            }
+            new_self
+                .spawn_volume(&VolumeConfig {
+                    name: static_volume.into(),
+                    backend: None,
+                    paths: None,
+                    required: false,
+                    rest: Default::default(),
+                })
+                .map_or_else(
+                    |e| tracing::error!("Cannot spawn static volume '{}': {}", static_volume, e),
+                    |_| (),
+                );
+        }
         for volume in &volumes {
             new_self.spawn_volume(volume).map_or_else(
                 |e| tracing::error!("Cannot spawn volume '{}': {}", volume.name(), e),
@@ -349,6 +366,19 @@ impl RunningPluginTrait for StorageRuntime {
                 });
             }
         });
+        let [export, import] = selector.get_parameters([EXPORT_KEY, IMPORT_KEY])?;
+        // exporting writes files on the disk of the router, hence requires the same permission as importing
+        let can_write = guard.runtime.config().lock().adminspace.permissions().write;
+        let path = |name: &str, storage: &str| {
+            if can_write {
+                snapshot_path(&guard.snapshots_dir, name, storage)
+            } else {
+                Err(zerror!(
+                    "Exporting or importing a snapshot requires adminspace.permissions.write"
+                )
+                .into())
+            }
+        };
         with_extended_string(&mut key, &["/storages/"], |key| {
             for storages in guard.storages.values() {
                 for (storage, handle) in storages {
@@ -357,11 +387,29 @@ impl RunningPluginTrait for StorageRuntime {
                             .unwrap()
                             .intersects(&selector.key_expr)
                         {
-                            if let Ok(value) = task::block_on(async {
-                                let (tx, rx) = async_std::channel::bounded(1);
-                                let _ = handle.send(StorageMessage::GetStatus(tx));
-                                rx.recv().await
-                            }) {
+                            let value = match (&export, &import) {
+                                (Some(name), _) => snapshot_status(
+                                    EXPORT_KEY,
+                                    name,
+                                    path(name, storage),
+                                    handle,
+                                    StorageMessage::Export,
+                                ),
+                                (None, Some(name)) => snapshot_status(
+                                    IMPORT_KEY,
+                                    name,
+                                    path(name, storage),
+                                    handle,
+                                    StorageMessage::Import,
+                                ),
+                                (None, None) => task::block_on(async {
+                                    let (tx, rx) = async_std::channel::bounded(1);
+                                    let _ = handle.send(StorageMessage::GetStatus(tx));
+                                    rx.recv().await
+                                })
+                                .ok(),
+                            };
+                            if let Some(value) = value {
                                 responses.push(zenoh::plugins::Response::new(key.clone(), value))
                             }
                         }
@@ -373,8 +421,37 @@ impl RunningPluginTrait for StorageRuntime {
     }
 }
 
+// Sends a snapshot export or import message to a storage, and returns its outcome as a status
+fn snapshot_status(
+    operation: &str,
+    name: &str,
+    path: ZResult<PathBuf>,
+    handle: &Sender<StorageMessage>,
+    message: fn(PathBuf, async_std::channel::Sender<ZResult<usize>>) -> StorageMessage,
+) -> Option<serde_json::Value> {
+    let result = match &path {
+        Ok(path) => task::block_on(async {
+            let (tx, rx) = async_std::channel::bounded(1);
+            let _ = handle.send(message(path.clone(), tx));
+            rx.recv().await
+        })
+        .ok()?,
+        Err(e) => Err(zerror!("{}", e).into()),
+    };
+    let file = path.as_deref().map_or(Path::new(name), |p| p);
+    let status = match result {
+        Ok(entries) => serde_json::json!({ "file": file, "entries": entries }),
+        Err(e) => {
+            tracing::warn!("Storage snapshot {} of '{}' failed: {}", operation, name, e);
+            serde_json::json!({ "file": file, "error": e.to_string() })
+        }
+    };
+    Some(serde_json::json!({ operation: status }))
+}
+
 const BACKEND_LIB_PREFIX: &str = "zenoh_backend_";
 const MEMORY_BACKEND_NAME: &str = "memory";
+const PERSISTENT_BACKEND_NAME: &str = "persistent";
 
 fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix: &mut String,
@@ -13,14 +13,15 @@
 //
 use async_std::sync::RwLock;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use zenoh::prelude::r#async::*;
 use zenoh::time::Timestamp;
 use zenoh_backend_traits::config::{StorageConfig, VolumeConfig};
 use zenoh_backend_traits::*;
 use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin};
-use zenoh_result::ZResult;
+use zenoh_result::{bail, ZResult};
 
 use crate::MEMORY_BACKEND_NAME;
 
@@ -50,6 +51,7 @@ impl Volume for MemoryBackend {
     }
 
     fn get_capability(&self) -> Capability {
+        // The storages keep the latest value of their keys by default (see `MemoryStorage::get_capability`)
         Capability {
             persistence: Persistence::Volatile,
             history: History::Latest,
@@ -90,18 +92,102 @@ impl Drop for MemoryBackend {
     }
 }
 
+// The history kept per key by a storage, configured in its `volume` object:
+// `history: "all"` keeps all the values, optionally bounded by `max_samples` and `max_age` (in seconds).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct HistoryConfig {
+    all: bool,
+    max_samples: Option<usize>,
+    max_age: Option<Duration>,
+}
+
+impl HistoryConfig {
+    fn try_from(config: &StorageConfig) -> ZResult<Self> {
+        let mut history = HistoryConfig::default();
+        let volume_cfg = match &config.volume_cfg {
+            serde_json::Value::Object(volume_cfg) => volume_cfg,
+            _ => return Ok(history),
+        };
+        match volume_cfg.get("history") {
+            None => {}
+            Some(serde_json::Value::String(h)) if h == "latest" => {}
+            Some(serde_json::Value::String(h)) if h == "all" => history.all = true,
+            Some(h) => bail!(
+                "Invalid `history` for storage `{}`: {}. Only \"latest\" and \"all\" are accepted",
+                config.name,
+                h
+            ),
+        }
+        if let Some(max_samples) = volume_cfg.get("max_samples") {
+            match max_samples.as_u64() {
+                Some(n) if n > 0 => history.max_samples = Some(n as usize),
+                _ => bail!(
+                    "Invalid `max_samples` for storage `{}`: {}. It must be a positive integer",
+                    config.name,
+                    max_samples
+                ),
+            }
+        }
+        if let Some(max_age) = volume_cfg.get("max_age") {
+            match max_age.as_f64() {
+                Some(secs) if secs > 0.0 => history.max_age = Some(Duration::from_secs_f64(secs)),
+                _ => bail!(
+                    "Invalid `max_age` for storage `{}`: {}. It must be a positive number of seconds",
+                    config.name,
+                    max_age
+                ),
+            }
+        }
+        if !history.all && (history.max_samples.is_some() || history.max_age.is_some()) {
+            bail!(
+                "`max_samples` and `max_age` of storage `{}` require `history: \"all\"`",
+                config.name
+            );
+        }
+        Ok(history)
+    }
+
+    // Returns the oldest time of the values to keep
+    fn time_limit(&self) -> Option<SystemTime> {
+        self.max_age
+            .and_then(|max_age| SystemTime::now().checked_sub(max_age))
+    }
+}
+
+// The values of a key, sorted by timestamp
+type KeyHistory = VecDeque<StoredData>;
+
 struct MemoryStorage {
     config: StorageConfig,
-    map: Arc<RwLock<HashMap<Option<OwnedKeyExpr>, StoredData>>>,
+    history: HistoryConfig,
+    map: Arc<RwLock<HashMap<Option<OwnedKeyExpr>, KeyHistory>>>,
 }
 
 impl MemoryStorage {
     async fn new(properties: StorageConfig) -> ZResult<MemoryStorage> {
         Ok(MemoryStorage {
+            history: HistoryConfig::try_from(&properties)?,
             config: properties,
             map: Arc::new(RwLock::new(HashMap::new())),
         })
     }
+
+    // Removes the values of a key exceeding the bounds of the history
+    fn evict(&self, values: &mut KeyHistory) {
+        if let Some(max_samples) = self.history.max_samples {
+            while values.len() > max_samples {
+                values.pop_front();
+            }
+        }
+        if let Some(time_limit) = self.history.time_limit() {
+            while values
+                .front()
+                .is_some_and(|v| v.timestamp.get_time().to_system_time() < time_limit)
+            {
+                values.pop_front();
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -110,6 +196,19 @@ impl Storage for MemoryStorage {
         self.config.to_json_value()
     }
 
+    // The history depends on the configuration of the storage (see `HistoryConfig`)
+    fn get_capability(&self) -> Option<Capability> {
+        Some(Capability {
+            persistence: Persistence::Volatile,
+            history: if self.history.all {
+                History::All
+            } else {
+                History::Latest
+            },
+            read_cost: 0,
+        })
+    }
+
     async fn put(
         &mut self,
         key: Option<OwnedKeyExpr>,
@@ -118,38 +217,80 @@ impl Storage for MemoryStorage {
     ) -> ZResult<StorageInsertionResult> {
         tracing::trace!("put for {:?}", key);
         let mut map = self.map.write().await;
-        match map.entry(key) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                e.insert(StoredData { value, timestamp });
-                return Ok(StorageInsertionResult::Replaced);
-            }
+        let values = match map.entry(key) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
             std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(StoredData { value, timestamp });
+                e.insert(VecDeque::from([StoredData { value, timestamp }]));
                 return Ok(StorageInsertionResult::Inserted);
             }
+        };
+        if !self.history.all {
+            if values.back().is_some_and(|v| v.timestamp > timestamp) {
+                return Ok(StorageInsertionResult::Outdated);
+            }
+            values.clear();
+            values.push_back(StoredData { value, timestamp });
+            return Ok(StorageInsertionResult::Replaced);
+        }
+        // Samples may be received out of order
+        let result = match values.binary_search_by(|v| v.timestamp.cmp(&timestamp)) {
+            Ok(i) => {
+                values[i] = StoredData { value, timestamp };
+                StorageInsertionResult::Replaced
+            }
+            Err(i) => {
+                values.insert(i, StoredData { value, timestamp });
+                StorageInsertionResult::Inserted
+            }
+        };
+        self.evict(values);
+        if values.iter().all(|v| v.timestamp != timestamp) {
+            return Ok(StorageInsertionResult::Outdated);
         }
+        Ok(result)
     }
 
     async fn delete(
         &mut self,
         key: Option<OwnedKeyExpr>,
-        _timestamp: Timestamp,
+        timestamp: Timestamp,
     ) -> ZResult<StorageInsertionResult> {
         tracing::trace!("delete for {:?}", key);
-        self.map.write().await.remove_entry(&key);
+        let mut map = self.map.write().await;
+        if self.history.all {
+            // Only the values up to the deletion are removed from the history
+            if let Some(values) = map.get_mut(&key) {
+                values.retain(|v| v.timestamp > timestamp);
+                if !values.is_empty() {
+                    return Ok(StorageInsertionResult::Deleted);
+                }
+            }
+        }
+        map.remove_entry(&key);
         return Ok(StorageInsertionResult::Deleted);
     }
 
     async fn get(
         &mut self,
         key: Option<OwnedKeyExpr>,
-        _parameters: &str,
+        parameters: &str,
     ) -> ZResult<Vec<StoredData>> {
         tracing::trace!("get for {:?}", key);
-        // @TODO: use parameters???
-        match self.map.read().await.get(&key) {
-            Some(v) => Ok(vec![v.clone()]),
-            None => Err(format!("Key {:?} is not present", key).into()),
+        let time_range = parameters.time_range()?.map(|t| t.resolve());
+        let mut map = self.map.write().await;
+        let values = match map.get_mut(&key) {
+            Some(values) => values,
+            None => bail!("Key {:?} is not present", key),
+        };
+        self.evict(values);
+        match time_range {
+            // Without time range, only the latest value is returned
+            None => Ok(values.back().cloned().into_iter().collect()),
+            Some(time_range) => Ok(values
+                .iter()
+                .filter(|v| time_range.contains(v.timestamp.get_time().to_system_time()))
+                .cloned()
+                .collect()),
         }
     }
 
@@ -157,7 +298,9 @@ impl Storage for MemoryStorage {
         let map = self.map.read().await;
         let mut result = Vec::with_capacity(map.len());
         for (k, v) in map.iter() {
-            result.push((k.clone(), v.timestamp));
+            if let Some(latest) = v.back() {
+                result.push((k.clone(), latest.timestamp));
+            }
         }
         Ok(result)
     }
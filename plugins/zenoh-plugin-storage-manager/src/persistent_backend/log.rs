@@ -0,0 +1,479 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crc::{Crc, CRC_32_ISO_HDLC};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use zenoh::buffers::{buffer::SplitBuffer, ZBuf};
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::StoredData;
+use zenoh_result::{bail, zerror, ZResult};
+
+// Each record is made of a header (the length and the CRC of its body) followed by its body
+const HEADER_SIZE: u64 = 8;
+const PUT: u8 = 0;
+const DELETE: u8 = 1;
+const NO_KEY: u32 = u32::MAX;
+// The log is not compacted below this size
+const COMPACTION_MIN_SIZE: u64 = 1024 * 1024;
+const COMPACTION_SUFFIX: &str = ".compact";
+
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+enum Record {
+    Put(Option<OwnedKeyExpr>, StoredData),
+    Delete(Option<OwnedKeyExpr>, Timestamp),
+}
+
+impl Record {
+    fn encode(&self) -> Vec<u8> {
+        fn push_bytes(body: &mut Vec<u8>, bytes: &[u8]) {
+            body.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            body.extend_from_slice(bytes);
+        }
+        fn push_key(body: &mut Vec<u8>, key: &Option<OwnedKeyExpr>) {
+            match key {
+                Some(key) => push_bytes(body, key.as_bytes()),
+                None => body.extend_from_slice(&NO_KEY.to_le_bytes()),
+            }
+        }
+
+        let mut body = vec![];
+        match self {
+            Record::Put(key, data) => {
+                body.push(PUT);
+                push_key(&mut body, key);
+                push_bytes(&mut body, data.timestamp.to_string().as_bytes());
+                push_bytes(&mut body, data.value.encoding.to_string().as_bytes());
+                push_bytes(&mut body, &data.value.payload.contiguous());
+            }
+            Record::Delete(key, timestamp) => {
+                body.push(DELETE);
+                push_key(&mut body, key);
+                push_bytes(&mut body, timestamp.to_string().as_bytes());
+            }
+        }
+        let mut record = Vec::with_capacity(HEADER_SIZE as usize + body.len());
+        record.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        record.extend_from_slice(&CRC32.checksum(&body).to_le_bytes());
+        record.extend_from_slice(&body);
+        record
+    }
+
+    fn decode(body: &[u8]) -> ZResult<Self> {
+        struct Reader<'a>(&'a [u8]);
+        impl<'a> Reader<'a> {
+            fn take(&mut self, len: usize) -> ZResult<&'a [u8]> {
+                if self.0.len() < len {
+                    bail!("Truncated record");
+                }
+                let (head, tail) = self.0.split_at(len);
+                self.0 = tail;
+                Ok(head)
+            }
+            fn u32(&mut self) -> ZResult<u32> {
+                Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+            }
+            fn bytes(&mut self) -> ZResult<&'a [u8]> {
+                let len = self.u32()? as usize;
+                self.take(len)
+            }
+            fn str(&mut self) -> ZResult<&'a str> {
+                std::str::from_utf8(self.bytes()?).map_err(|e| zerror!("{}", e).into())
+            }
+            fn key(&mut self) -> ZResult<Option<OwnedKeyExpr>> {
+                let len = self.u32()?;
+                if len == NO_KEY {
+                    return Ok(None);
+                }
+                let key =
+                    std::str::from_utf8(self.take(len as usize)?).map_err(|e| zerror!("{}", e))?;
+                Ok(Some(OwnedKeyExpr::from_str(key)?))
+            }
+            fn timestamp(&mut self) -> ZResult<Timestamp> {
+                let ts = self.str()?;
+                Timestamp::from_str(ts)
+                    .map_err(|e| zerror!("Invalid timestamp {}: {:?}", ts, e).into())
+            }
+        }
+
+        let mut reader = Reader(body);
+        match reader.take(1)?[0] {
+            PUT => {
+                let key = reader.key()?;
+                let timestamp = reader.timestamp()?;
+                let encoding = Encoding::from(reader.str()?.to_string());
+                let payload = ZBuf::from(reader.bytes()?.to_vec());
+                Ok(Record::Put(
+                    key,
+                    StoredData {
+                        value: Value::new(payload).encoding(encoding),
+                        timestamp,
+                    },
+                ))
+            }
+            DELETE => {
+                let key = reader.key()?;
+                Ok(Record::Delete(key, reader.timestamp()?))
+            }
+            kind => bail!("Unknown record kind {}", kind),
+        }
+    }
+}
+
+// The location in the log of the latest put or delete of a key
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    timestamp: Timestamp,
+    offset: u64,
+    len: u64,
+    deleted: bool,
+}
+
+/// An append-only log of the puts and deletes of a storage.
+///
+/// The latest put or delete of each key is located by an index rebuilt when opening the log.
+/// Records are checksummed so that a partially written record (e.g. after a crash) is detected
+/// and discarded at recovery. The log is rewritten with only the latest records of the keys
+/// once more than half of it is made of overwritten records. The deletes are kept as tombstones
+/// until they are older than `tombstones_lifespan`, so that the timestamps of the deletions
+/// remain known for the alignment of the replicas.
+pub(crate) struct Log {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    live: u64,
+    sync: bool,
+    tombstones_lifespan: Duration,
+    index: HashMap<Option<OwnedKeyExpr>, Entry>,
+}
+
+impl Log {
+    pub(crate) fn open(path: PathBuf, sync: bool, tombstones_lifespan: Duration) -> ZResult<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| zerror!("Unable to create directory {:?}: {}", dir, e))?;
+        }
+        // A compaction was interrupted before replacing the log, which is still complete
+        let compacted = compaction_path(&path);
+        if compacted.exists() {
+            tracing::warn!("Removing interrupted compaction of {:?}", path);
+            fs::remove_file(&compacted)
+                .map_err(|e| zerror!("Unable to remove {:?}: {}", compacted, e))?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| zerror!("Unable to open {:?}: {}", path, e))?;
+        let mut log = Log {
+            path,
+            file,
+            len: 0,
+            live: 0,
+            sync,
+            tombstones_lifespan,
+            index: HashMap::new(),
+        };
+        log.recover()?;
+        log.compact_if_needed()?;
+        Ok(log)
+    }
+
+    // Rebuilds the index from the records of the log, truncating the log after the last valid record
+    fn recover(&mut self) -> ZResult<()> {
+        let file_len = self.file.metadata()?.len();
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(self.file.try_clone()?);
+        let mut offset = 0;
+        let mut header = [0u8; HEADER_SIZE as usize];
+        loop {
+            match reader.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+            let body_len = u32::from_le_bytes(header[..4].try_into().unwrap()) as u64;
+            let crc = u32::from_le_bytes(header[4..].try_into().unwrap());
+            if offset + HEADER_SIZE + body_len > file_len {
+                break;
+            }
+            let mut body = vec![0u8; body_len as usize];
+            reader.read_exact(&mut body)?;
+            if CRC32.checksum(&body) != crc {
+                break;
+            }
+            let record = match Record::decode(&body) {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Invalid record in {:?}: {}", self.path, e);
+                    break;
+                }
+            };
+            let len = HEADER_SIZE + body_len;
+            self.apply(record, offset, len);
+            offset += len;
+        }
+        if offset < file_len {
+            tracing::warn!(
+                "Discarding {} bytes of incomplete records at the end of {:?}",
+                file_len - offset,
+                self.path
+            );
+            self.file.set_len(offset)?;
+            self.file.sync_all()?;
+        }
+        self.len = offset;
+        tracing::debug!(
+            "Recovered {} keys from {:?} ({} bytes)",
+            self.index.len(),
+            self.path,
+            self.len
+        );
+        Ok(())
+    }
+
+    fn apply(&mut self, record: Record, offset: u64, len: u64) {
+        let (key, timestamp, deleted) = match record {
+            Record::Put(key, data) => (key, data.timestamp, false),
+            Record::Delete(key, timestamp) => (key, timestamp, true),
+        };
+        let entry = Entry {
+            timestamp,
+            offset,
+            len,
+            deleted,
+        };
+        self.live += len;
+        if let Some(previous) = self.index.insert(key, entry) {
+            self.live -= previous.len;
+        }
+    }
+
+    fn append(&mut self, record: Record) -> ZResult<()> {
+        let bytes = record.encode();
+        self.file.seek(SeekFrom::Start(self.len))?;
+        self.file.write_all(&bytes)?;
+        if self.sync {
+            self.file.sync_data()?;
+        }
+        let offset = self.len;
+        self.len += bytes.len() as u64;
+        self.apply(record, offset, bytes.len() as u64);
+        self.compact_if_needed()
+    }
+
+    pub(crate) fn put(&mut self, key: Option<OwnedKeyExpr>, data: StoredData) -> ZResult<()> {
+        self.append(Record::Put(key, data))
+    }
+
+    pub(crate) fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> ZResult<()> {
+        self.append(Record::Delete(key, timestamp))
+    }
+
+    /// Returns the timestamp of the latest put or delete of the key, and whether it was deleted.
+    pub(crate) fn latest(&self, key: &Option<OwnedKeyExpr>) -> Option<(&Timestamp, bool)> {
+        self.index.get(key).map(|e| (&e.timestamp, e.deleted))
+    }
+
+    pub(crate) fn get(&mut self, key: &Option<OwnedKeyExpr>) -> ZResult<Option<StoredData>> {
+        let Some(entry) = self.index.get(key).copied().filter(|e| !e.deleted) else {
+            return Ok(None);
+        };
+        match Record::decode(&self.read_record(&entry)?[HEADER_SIZE as usize..])? {
+            Record::Put(_, data) => Ok(Some(data)),
+            Record::Delete(..) => bail!("Index of {:?} is corrupted", self.path),
+        }
+    }
+
+    /// Returns the keys with the timestamps of their latest put or delete.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&Option<OwnedKeyExpr>, &Timestamp)> {
+        self.index.iter().map(|(k, e)| (k, &e.timestamp))
+    }
+
+    fn read_record(&mut self, entry: &Entry) -> ZResult<Vec<u8>> {
+        let mut record = vec![0u8; entry.len as usize];
+        self.file.seek(SeekFrom::Start(entry.offset))?;
+        self.file.read_exact(&mut record)?;
+        Ok(record)
+    }
+
+    fn compact_if_needed(&mut self) -> ZResult<()> {
+        if self.len >= COMPACTION_MIN_SIZE && self.len > 2 * self.live {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    // Writes the latest values and the recent tombstones to a new log, then atomically replaces
+    // the current log with it
+    fn compact(&mut self) -> ZResult<()> {
+        tracing::debug!(
+            "Compacting {:?} ({} bytes, {} live bytes)",
+            self.path,
+            self.len,
+            self.live
+        );
+        let compacted = compaction_path(&self.path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&compacted)
+            .map_err(|e| zerror!("Unable to open {:?}: {}", compacted, e))?;
+        let mut index = HashMap::with_capacity(self.index.len());
+        let mut offset = 0;
+        let horizon = SystemTime::now()
+            .checked_sub(self.tombstones_lifespan)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let entries: Vec<_> = self
+            .index
+            .iter()
+            .filter(|(_, e)| !e.deleted || e.timestamp.get_time().to_system_time() >= horizon)
+            .map(|(k, e)| (k.clone(), *e))
+            .collect();
+        for (key, entry) in entries {
+            let record = self.read_record(&entry)?;
+            file.write_all(&record)?;
+            index.insert(key, Entry { offset, ..entry });
+            offset += entry.len;
+        }
+        file.sync_all()?;
+        fs::rename(&compacted, &self.path)
+            .map_err(|e| zerror!("Unable to replace {:?}: {}", self.path, e))?;
+        sync_parent_dir(&self.path);
+        self.file = file;
+        self.index = index;
+        self.len = offset;
+        self.live = offset;
+        Ok(())
+    }
+}
+
+fn compaction_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(COMPACTION_SUFFIX);
+    path.into()
+}
+
+// Makes the renaming of a file durable
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Err(e) = File::open(dir).and_then(|d| d.sync_all()) {
+            tracing::warn!("Unable to sync directory {:?}: {}", dir, e);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh::time::new_reception_timestamp;
+
+    const LIFESPAN: Duration = Duration::from_secs(86400);
+
+    fn data(value: &str) -> StoredData {
+        StoredData {
+            value: value.into(),
+            timestamp: new_reception_timestamp(),
+        }
+    }
+
+    #[test]
+    fn compaction_and_recovery() {
+        let path = std::env::temp_dir()
+            .join(format!("zenoh_log_test_{}", std::process::id()))
+            .join("data.log");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        let key = Some(OwnedKeyExpr::from_str("a/b").unwrap());
+        let mut log = Log::open(path.clone(), false, LIFESPAN).unwrap();
+        let value = "x".repeat(1024);
+        for _ in 0..2048 {
+            log.put(key.clone(), data(&value)).unwrap();
+        }
+        log.put(None, data("none")).unwrap();
+        // overwritten values have been compacted
+        assert!(log.len < COMPACTION_MIN_SIZE);
+        let entries: HashMap<_, _> = log.entries().map(|(k, ts)| (k.clone(), *ts)).collect();
+        drop(log);
+
+        let mut log = Log::open(path.clone(), false, LIFESPAN).unwrap();
+        let recovered: HashMap<_, _> = log.entries().map(|(k, ts)| (k.clone(), *ts)).collect();
+        assert_eq!(entries, recovered);
+        assert_eq!(log.get(&key).unwrap().unwrap().value.to_string(), value);
+        log.delete(None, new_reception_timestamp()).unwrap();
+        drop(log);
+
+        let mut log = Log::open(path.clone(), false, LIFESPAN).unwrap();
+        assert!(log.get(&None).unwrap().is_none());
+        // the delete is kept as a tombstone
+        assert_eq!(log.entries().count(), 2);
+        assert!(log.latest(&None).unwrap().1);
+        drop(log);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn tombstones_compaction() {
+        let path = std::env::temp_dir()
+            .join(format!("zenoh_log_tombstones_test_{}", std::process::id()))
+            .join("data.log");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        let recent = Some(OwnedKeyExpr::from_str("recent").unwrap());
+        let old = Some(OwnedKeyExpr::from_str("old").unwrap());
+        let now = new_reception_timestamp();
+        let old_timestamp = Timestamp::new(
+            *now.get_time() - zenoh::time::NTP64::from(2 * LIFESPAN),
+            *now.get_id(),
+        );
+        let mut log = Log::open(path.clone(), false, LIFESPAN).unwrap();
+        let deleted_at = new_reception_timestamp();
+        log.delete(recent.clone(), deleted_at).unwrap();
+        log.delete(old.clone(), old_timestamp).unwrap();
+        let value = "x".repeat(1024);
+        for _ in 0..2048 {
+            log.put(None, data(&value)).unwrap();
+        }
+        // the compaction only dropped the tombstone older than the lifespan
+        assert!(log.len < COMPACTION_MIN_SIZE);
+        assert_eq!(log.latest(&recent), Some((&deleted_at, true)));
+        assert!(log.latest(&old).is_none());
+        drop(log);
+
+        let log = Log::open(path.clone(), false, LIFESPAN).unwrap();
+        assert_eq!(log.latest(&recent), Some((&deleted_at, true)));
+        assert!(log.latest(&old).is_none());
+        assert_eq!(log.entries().count(), 2);
+        drop(log);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
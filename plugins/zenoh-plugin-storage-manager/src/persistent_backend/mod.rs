@@ -0,0 +1,200 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::config::{StorageConfig, VolumeConfig};
+use zenoh_backend_traits::*;
+use zenoh_core::zlock;
+use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin};
+use zenoh_result::{bail, ZResult};
+use zenoh_util::zenoh_home;
+
+use crate::PERSISTENT_BACKEND_NAME;
+
+mod log;
+
+use log::Log;
+
+// The name of the log file in the directory of each storage
+const LOG_FILENAME: &str = "data.log";
+
+/// A built-in backend persisting each storage in an append-only log.
+///
+/// The volume accepts the following options:
+///  - `dir`: the directory containing the storages (`$ZENOH_HOME/persistent` by default)
+///  - `sync`: whether each write is synchronized to disk before being acknowledged (`true` by default)
+pub struct PersistentBackend {
+    config: VolumeConfig,
+    dir: PathBuf,
+    sync: bool,
+}
+
+impl Plugin for PersistentBackend {
+    type StartArgs = VolumeConfig;
+    type Instance = VolumeInstance;
+
+    const DEFAULT_NAME: &'static str = PERSISTENT_BACKEND_NAME;
+    const PLUGIN_VERSION: &'static str = plugin_version!();
+    const PLUGIN_LONG_VERSION: &'static str = plugin_long_version!();
+
+    fn start(_: &str, args: &VolumeConfig) -> ZResult<VolumeInstance> {
+        let dir = match args.rest.get("dir") {
+            None => zenoh_home().join(PERSISTENT_BACKEND_NAME),
+            Some(serde_json::Value::String(dir)) => PathBuf::from(dir),
+            Some(dir) => bail!(
+                "Invalid `dir` for volume `{}`: {}. It must be a string",
+                args.name,
+                dir
+            ),
+        };
+        let sync = match args.rest.get("sync") {
+            None => true,
+            Some(serde_json::Value::Bool(sync)) => *sync,
+            Some(sync) => bail!(
+                "Invalid `sync` for volume `{}`: {}. It must be a boolean",
+                args.name,
+                sync
+            ),
+        };
+        Ok(Box::new(PersistentBackend {
+            config: args.clone(),
+            dir,
+            sync,
+        }))
+    }
+}
+
+#[async_trait]
+impl Volume for PersistentBackend {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    fn get_capability(&self) -> Capability {
+        Capability {
+            persistence: Persistence::Durable,
+            history: History::Latest,
+            read_cost: 0,
+        }
+    }
+
+    async fn create_storage(&self, properties: StorageConfig) -> ZResult<Box<dyn Storage>> {
+        tracing::debug!(
+            "Create Persistent Storage with configuration: {:?}",
+            properties
+        );
+        let path = self.dir.join(&properties.name).join(LOG_FILENAME);
+        let sync = self.sync;
+        let lifespan = properties.garbage_collection_config.lifespan;
+        let log = async_std::task::spawn_blocking(move || Log::open(path, sync, lifespan)).await?;
+        Ok(Box::new(PersistentStorage {
+            config: properties,
+            log: Arc::new(Mutex::new(log)),
+        }))
+    }
+
+    fn incoming_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+
+    fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+}
+
+struct PersistentStorage {
+    config: StorageConfig,
+    log: Arc<Mutex<Log>>,
+}
+
+impl PersistentStorage {
+    // Runs an operation on the log in a blocking thread, as it reads and writes its file
+    async fn with_log<T, F>(&self, f: F) -> ZResult<T>
+    where
+        F: FnOnce(&mut Log) -> ZResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let log = self.log.clone();
+        async_std::task::spawn_blocking(move || f(&mut zlock!(log))).await
+    }
+}
+
+#[async_trait]
+impl Storage for PersistentStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    async fn put(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        value: Value,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        tracing::trace!("put for {:?}", key);
+        self.with_log(move |log| {
+            let result = match log.latest(&key) {
+                Some((latest, _)) if *latest > timestamp => {
+                    return Ok(StorageInsertionResult::Outdated)
+                }
+                Some((_, false)) => StorageInsertionResult::Replaced,
+                Some((_, true)) | None => StorageInsertionResult::Inserted,
+            };
+            log.put(key, StoredData { value, timestamp })?;
+            Ok(result)
+        })
+        .await
+    }
+
+    // The delete is logged even if the key is not stored, so that its timestamp is known
+    async fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        tracing::trace!("delete for {:?}", key);
+        self.with_log(move |log| {
+            if matches!(log.latest(&key), Some((latest, _)) if *latest > timestamp) {
+                return Ok(StorageInsertionResult::Outdated);
+            }
+            log.delete(key, timestamp)?;
+            Ok(StorageInsertionResult::Deleted)
+        })
+        .await
+    }
+
+    // A deleted key has no value
+    async fn get(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        _parameters: &str,
+    ) -> ZResult<Vec<StoredData>> {
+        tracing::trace!("get for {:?}", key);
+        self.with_log(move |log| match log.latest(&key) {
+            Some((_, false)) => Ok(log.get(&key)?.into_iter().collect()),
+            Some((_, true)) => Ok(vec![]),
+            None => bail!("Key {:?} is not present", key),
+        })
+        .await
+    }
+
+    // The entries include the deleted keys, with the timestamps of their deletions
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        self.with_log(|log| Ok(log.entries().map(|(k, ts)| (k.clone(), *ts)).collect()))
+            .await
+    }
+}
@@ -0,0 +1,221 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// The usage of a storage with limits: the number of keys and bytes of the latest values,
+// for the whole storage and for each of its quotas, and the order in which the values are evicted.
+
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use zenoh::key_expr::OwnedKeyExpr;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::config::{EvictionPolicy, LimitsConfig};
+
+// The rank of a value in the eviction order: its timestamp for the oldest-timestamp policy,
+// then a sequence number increased on each write, and on each query for the LRU policy
+type Rank = (Option<Timestamp>, u64);
+
+struct Entry {
+    bytes: usize,
+    rank: Rank,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Counters {
+    keys: usize,
+    bytes: usize,
+}
+
+// The limits and usage of the whole storage (without key expression) or of a quota
+struct Scope<'a> {
+    key_expr: Option<&'a OwnedKeyExpr>,
+    max_keys: Option<usize>,
+    max_bytes: Option<usize>,
+    usage: Counters,
+}
+
+impl Scope<'_> {
+    fn includes(&self, key: &OwnedKeyExpr) -> bool {
+        self.key_expr.map_or(true, |ke| ke.includes(key))
+    }
+
+    fn is_exceeded(&self) -> bool {
+        self.max_keys.is_some_and(|max| self.usage.keys > max)
+            || self.max_bytes.is_some_and(|max| self.usage.bytes > max)
+    }
+}
+
+pub(crate) struct Usage {
+    limits: LimitsConfig,
+    entries: HashMap<OwnedKeyExpr, Entry>,
+    order: BTreeMap<Rank, OwnedKeyExpr>,
+    sequence: u64,
+    total: Counters,
+    // the counters of the quotas, in the order of the configuration
+    quotas: Vec<Counters>,
+    evictions: u64,
+    rejections: u64,
+}
+
+impl Usage {
+    pub(crate) fn new(limits: LimitsConfig) -> Usage {
+        Usage {
+            quotas: vec![Counters::default(); limits.quotas.len()],
+            limits,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            sequence: 0,
+            total: Counters::default(),
+            evictions: 0,
+            rejections: 0,
+        }
+    }
+
+    // The keys to evict before storing a value of the given size for a key, None if the value must be rejected
+    pub(crate) fn admit(&self, key: &OwnedKeyExpr, bytes: usize) -> Option<Vec<OwnedKeyExpr>> {
+        let replaced = self.entries.get(key).map(|e| e.bytes);
+        // the usage of each scope with the new value: the whole storage, then the matching quotas
+        let mut scopes = vec![Scope {
+            key_expr: None,
+            max_keys: self.limits.max_keys,
+            max_bytes: self.limits.max_bytes,
+            usage: self.total,
+        }];
+        for (quota, usage) in self.limits.quotas.iter().zip(&self.quotas) {
+            if quota.key_expr.includes(key) {
+                scopes.push(Scope {
+                    key_expr: Some(&quota.key_expr),
+                    max_keys: quota.max_keys,
+                    max_bytes: quota.max_bytes,
+                    usage: *usage,
+                });
+            }
+        }
+        for scope in scopes.iter_mut() {
+            if replaced.is_none() {
+                scope.usage.keys += 1;
+            }
+            scope.usage.bytes = scope.usage.bytes - replaced.unwrap_or_default() + bytes;
+        }
+
+        let mut evicted = Vec::new();
+        let mut evicted_keys = HashSet::new();
+        // the quotas are satisfied first, their evictions also counting for the whole storage
+        while let Some(exceeded) = scopes.iter().rposition(Scope::is_exceeded) {
+            if self.limits.eviction == EvictionPolicy::RejectNew {
+                return None;
+            }
+            let scope = &scopes[exceeded];
+            let candidate = self
+                .order
+                .values()
+                .find(|k| *k != key && !evicted_keys.contains(*k) && scope.includes(k))?;
+            let candidate_bytes = self.entries[candidate].bytes;
+            for scope in scopes.iter_mut().filter(|s| s.includes(candidate)) {
+                scope.usage.keys -= 1;
+                scope.usage.bytes -= candidate_bytes;
+            }
+            evicted_keys.insert(candidate);
+            evicted.push(candidate.clone());
+        }
+        Some(evicted)
+    }
+
+    pub(crate) fn insert(&mut self, key: OwnedKeyExpr, bytes: usize, timestamp: Timestamp) {
+        self.remove(&key);
+        self.add(&key, bytes);
+        let rank = self.next_rank(timestamp);
+        self.order.insert(rank, key.clone());
+        self.entries.insert(key, Entry { bytes, rank });
+    }
+
+    pub(crate) fn remove(&mut self, key: &OwnedKeyExpr) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.rank);
+            self.sub(key, entry.bytes);
+        }
+    }
+
+    // Records a query of a key, moving it to the end of the eviction order for the LRU policy
+    pub(crate) fn touch(&mut self, key: &OwnedKeyExpr) {
+        if self.limits.eviction != EvictionPolicy::Lru {
+            return;
+        }
+        self.sequence += 1;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.order.remove(&entry.rank);
+            entry.rank = (None, self.sequence);
+            self.order.insert(entry.rank, key.clone());
+        }
+    }
+
+    pub(crate) fn record_eviction(&mut self) {
+        self.evictions += 1;
+    }
+
+    pub(crate) fn record_rejection(&mut self) {
+        self.rejections += 1;
+    }
+
+    pub(crate) fn to_json_value(&self) -> Value {
+        let quotas: Map<String, Value> = self
+            .limits
+            .quotas
+            .iter()
+            .zip(&self.quotas)
+            .map(|(quota, counters)| {
+                (
+                    quota.key_expr.to_string(),
+                    json!({"keys": counters.keys, "bytes": counters.bytes}),
+                )
+            })
+            .collect();
+        json!({
+            "keys": self.total.keys,
+            "bytes": self.total.bytes,
+            "quotas": quotas,
+            "evictions": self.evictions,
+            "rejections": self.rejections,
+        })
+    }
+
+    fn next_rank(&mut self, timestamp: Timestamp) -> Rank {
+        self.sequence += 1;
+        match self.limits.eviction {
+            EvictionPolicy::OldestTimestamp => (Some(timestamp), self.sequence),
+            _ => (None, self.sequence),
+        }
+    }
+
+    fn add(&mut self, key: &OwnedKeyExpr, bytes: usize) {
+        self.total.keys += 1;
+        self.total.bytes += bytes;
+        for (quota, counters) in self.limits.quotas.iter().zip(self.quotas.iter_mut()) {
+            if quota.key_expr.includes(key) {
+                counters.keys += 1;
+                counters.bytes += bytes;
+            }
+        }
+    }
+
+    fn sub(&mut self, key: &OwnedKeyExpr, bytes: usize) {
+        self.total.keys -= 1;
+        self.total.bytes -= bytes;
+        for (quota, counters) in self.limits.quotas.iter().zip(self.quotas.iter_mut()) {
+            if quota.key_expr.includes(key) {
+                counters.keys -= 1;
+                counters.bytes -= bytes;
+            }
+        }
+    }
+}
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::digest::*;
+use super::merkle::MerkleContent;
 use super::Snapshotter;
 use async_std::sync::Arc;
 use std::cmp::Ordering;
@@ -34,6 +35,7 @@ enum AlignComponent {
     Intervals(Vec<u64>),
     Subintervals(Vec<u64>),
     Contents(Vec<LogEntry>),
+    Merkle(Vec<String>),
 }
 #[derive(Debug)]
 enum AlignData {
@@ -41,6 +43,7 @@ enum AlignData {
     Subinterval(u64, u64),
     Content(u64, BTreeSet<LogEntry>),
     Data(OwnedKeyExpr, (Value, Timestamp)),
+    Merkle(String, MerkleContent),
 }
 
 impl AlignQueryable {
@@ -118,6 +121,13 @@ impl AlignQueryable {
                             let sample = Sample::new(k, v).with_timestamp(ts);
                             query.reply(Ok(sample)).res().await.unwrap();
                         }
+                        AlignData::Merkle(path, content) => {
+                            let sample = Sample::new(
+                                query.key_expr().clone(),
+                                serde_json::to_string(&(path, content)).unwrap(),
+                            );
+                            query.reply(Ok(sample)).res().await.unwrap();
+                        }
                     }
                 }
             }
@@ -171,6 +181,15 @@ impl AlignQueryable {
                 }
                 result
             }
+            AlignComponent::Merkle(paths) => {
+                let mut result = Vec::new();
+                for path in paths {
+                    if let Some(content) = self.snapshotter.get_merkle_content(&path).await {
+                        result.push(AlignData::Merkle(path, content));
+                    }
+                }
+                result
+            }
         }
     }
 
@@ -201,6 +220,14 @@ impl AlignQueryable {
                     .map(|x| x.parse::<u64>().unwrap())
                     .collect::<Vec<u64>>(),
             ))
+        } else if properties.contains_key(super::MERKLE) {
+            match serde_json::from_str(properties.get(super::MERKLE).unwrap()) {
+                Ok(paths) => Some(AlignComponent::Merkle(paths)),
+                Err(e) => {
+                    tracing::error!("[ALIGN QUERYABLE] Invalid Merkle tree paths: {}", e);
+                    None
+                }
+            }
         } else if properties.contains_key(super::CONTENTS) {
             let contents = serde_json::from_str(properties.get(super::CONTENTS).unwrap()).unwrap();
             Some(AlignComponent::Contents(contents))
@@ -12,16 +12,20 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use super::{Digest, EraType, LogEntry, Snapshotter};
-use super::{CONTENTS, ERA, INTERVALS, SUBINTERVALS};
-use async_std::sync::{Arc, RwLock};
+use super::merkle::MerkleContent;
+use super::{Digest, LogEntry, Snapshotter};
+use super::{CONTENTS, MERKLE};
+use async_std::sync::{Arc, Mutex, RwLock};
+use async_std::task::sleep;
 use flume::{Receiver, Sender};
-use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str;
+use std::time::{Duration, Instant};
 use zenoh::key_expr::{KeyExpr, OwnedKeyExpr};
 use zenoh::prelude::r#async::*;
-use zenoh::time::Timestamp;
 use zenoh::Session;
+use zenoh_backend_traits::config::ReplicaConfig;
 
 pub struct Aligner {
     session: Arc<Session>,
@@ -30,6 +34,79 @@ pub struct Aligner {
     rx_digest: Receiver<(String, Digest)>,
     tx_sample: Sender<Sample>,
     digests_processed: RwLock<HashSet<u64>>,
+    bandwidth: Option<u64>,
+    batch_size: usize,
+    // the entries still to be retrieved from another replica, to resume an interrupted alignment
+    transfer: Mutex<Option<Transfer>>,
+    progress: Arc<RwLock<AlignmentProgress>>,
+}
+
+struct Transfer {
+    from: String,
+    pending: VecDeque<LogEntry>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlignmentState {
+    #[default]
+    Idle,
+    // comparing the Merkle trees of the replicas
+    Comparing,
+    // retrieving the missing entries
+    Transferring,
+    // waiting for the next digest of the source to resume the transfer
+    Interrupted,
+}
+
+// The progress of the alignment of a replica, reported in the admin status of the storage
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct AlignmentProgress {
+    pub state: AlignmentState,
+    // the replica from which the missing entries are retrieved
+    pub source: Option<String>,
+    // the number of entries found missing
+    pub missing: usize,
+    // the number of missing entries retrieved so far
+    pub retrieved: usize,
+    // the number of bytes received from the source
+    pub bytes_received: u64,
+    // the number of completed alignments
+    pub alignments: u64,
+}
+
+// Limits the average rate at which bytes are received during an alignment:
+// each request is delayed until the bytes received so far are within the bandwidth
+struct Throttle {
+    bandwidth: Option<u64>,
+    start: Instant,
+    bytes: u64,
+}
+
+impl Throttle {
+    fn new(bandwidth: Option<u64>) -> Self {
+        Throttle {
+            bandwidth,
+            start: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    // Waits until the next request may be sent
+    async fn acquire(&self) {
+        if let Some(bandwidth) = self.bandwidth {
+            let expected = Duration::from_secs_f64(self.bytes as f64 / bandwidth as f64);
+            let elapsed = self.start.elapsed();
+            if expected > elapsed {
+                sleep(expected - elapsed).await;
+            }
+        }
+    }
+
+    // Accounts for the bytes of a reply, as soon as it is received
+    fn record(&mut self, bytes: u64) {
+        self.bytes += bytes;
+    }
 }
 
 impl Aligner {
@@ -39,6 +116,8 @@ impl Aligner {
         rx_digest: Receiver<(String, Digest)>,
         tx_sample: Sender<Sample>,
         snapshotter: Arc<Snapshotter>,
+        replica_config: &ReplicaConfig,
+        progress: Arc<RwLock<AlignmentProgress>>,
     ) {
         let aligner = Aligner {
             session,
@@ -47,32 +126,44 @@ impl Aligner {
             rx_digest,
             tx_sample,
             digests_processed: RwLock::new(HashSet::new()),
+            bandwidth: replica_config.alignment_bandwidth,
+            batch_size: replica_config.alignment_batch_size,
+            transfer: Mutex::new(None),
+            progress,
         };
         aligner.start().await;
     }
 
     pub async fn start(&self) {
         while let Ok((from, incoming_digest)) = self.rx_digest.recv_async().await {
-            if self.in_processed(incoming_digest.checksum).await {
-                tracing::trace!(
-                    "[ALIGNER]Skipping already processed digest: {}",
-                    incoming_digest.checksum
-                );
-                continue;
-            } else if self.snapshotter.get_digest().await.checksum == incoming_digest.checksum {
-                tracing::trace!(
-                    "[ALIGNER]Skipping matching digest: {}",
-                    incoming_digest.checksum
-                );
-                continue;
-            } else {
-                // process this digest
-                tracing::debug!(
-                    "[ALIGNER]Processing digest: {:?} from {}",
-                    incoming_digest,
-                    from
-                );
-                self.process_incoming_digest(incoming_digest, &from).await;
+            // digests queue up during a long alignment: only the latest one of each replica is relevant
+            let mut digests = HashMap::new();
+            digests.insert(from, incoming_digest);
+            while let Ok((from, incoming_digest)) = self.rx_digest.try_recv() {
+                digests.insert(from, incoming_digest);
+            }
+            for (from, incoming_digest) in digests {
+                if self.in_processed(incoming_digest.checksum).await {
+                    tracing::trace!(
+                        "[ALIGNER]Skipping already processed digest: {}",
+                        incoming_digest.checksum
+                    );
+                    continue;
+                } else if self.snapshotter.get_digest().await.checksum == incoming_digest.checksum {
+                    tracing::trace!(
+                        "[ALIGNER]Skipping matching digest: {}",
+                        incoming_digest.checksum
+                    );
+                    continue;
+                } else {
+                    // process this digest
+                    tracing::debug!(
+                        "[ALIGNER]Processing digest: {:?} from {}",
+                        incoming_digest,
+                        from
+                    );
+                    self.process_incoming_digest(incoming_digest, &from).await;
+                }
             }
         }
     }
@@ -85,231 +176,158 @@ impl Aligner {
     //identify alignment requirements
     async fn process_incoming_digest(&self, other: Digest, from: &str) {
         let checksum = other.checksum;
-        let timestamp = other.timestamp;
-        let (missing_content, no_content_err) = self.get_missing_content(&other, from).await;
+        let mut throttle = Throttle::new(self.bandwidth);
+
+        // resume the interrupted transfer from this replica, if any
+        // a transfer interrupted with another replica is abandoned: its entries will be found again by comparing the trees
+        let transfer = self.transfer.lock().await.take();
+        if let Some(transfer) = transfer.filter(|t| t.from == from) {
+            tracing::debug!(
+                "[ALIGNER] Resuming the retrieval of {} entries from {from}",
+                transfer.pending.len()
+            );
+            self.retrieve_missing_data(transfer.pending, from, &mut throttle)
+                .await;
+            // the digest is processed on the next round, once the interrupted transfer is complete
+            return;
+        }
+
+        {
+            let mut progress = self.progress.write().await;
+            progress.state = AlignmentState::Comparing;
+            progress.source = Some(from.to_string());
+            progress.missing = 0;
+            progress.retrieved = 0;
+            progress.bytes_received = 0;
+        }
+        let (missing_content, no_content_err) = self.get_missing_content(from, &mut throttle).await;
         tracing::debug!(
             "[ALIGNER] Missing {} entries; query corresponding samples",
             missing_content.len()
         );
+        self.progress.write().await.missing = missing_content.len();
 
         // If missing content is not identified, it showcases some problem
         // The problem will be addressed in the future rounds, hence will not count as processed
-        if !missing_content.is_empty() {
-            let (missing_data, no_data_err) = self
-                .get_missing_data(&missing_content, timestamp, from)
-                .await;
+        let no_data_err = self
+            .retrieve_missing_data(missing_content, from, &mut throttle)
+            .await;
+        if no_content_err && no_data_err {
+            let mut processed = self.digests_processed.write().await;
+            (*processed).insert(checksum);
+        }
+    }
 
-            // Missing data might be empty since some samples in digest might be outdated
-            tracing::debug!("[ALIGNER] Received {} queried samples", missing_data.len());
-            tracing::trace!("[ALIGNER] Received queried samples: {missing_data:?}");
+    // Retrieve the missing entries from the other replica by batches, within the bandwidth limit.
+    // If a batch fails, the remaining entries are kept to resume the transfer with the next digest of this replica.
+    async fn retrieve_missing_data(
+        &self,
+        mut pending: VecDeque<LogEntry>,
+        from: &str,
+        throttle: &mut Throttle,
+    ) -> bool {
+        self.progress.write().await.state = AlignmentState::Transferring;
+        while !pending.is_empty() {
+            let mut batch = Vec::new();
+            while batch.len() < self.batch_size {
+                let Some(entry) = pending.pop_front() else {
+                    break;
+                };
+                // entries might have been received meanwhile, from publications or other replicas
+                if !self.snapshotter.is_logged(&entry).await {
+                    batch.push(entry);
+                }
+            }
+            if batch.is_empty() {
+                continue;
+            }
+            let properties = format!("{}={}", CONTENTS, serde_json::to_string(&batch).unwrap());
+            let (replies, no_err) = self.perform_query(from, properties, throttle).await;
+            if !no_err {
+                tracing::warn!(
+                    "[ALIGNER] Alignment with {from} interrupted, {} entries remaining",
+                    pending.len() + batch.len()
+                );
+                for entry in batch.into_iter().rev() {
+                    pending.push_front(entry);
+                }
+                *self.transfer.lock().await = Some(Transfer {
+                    from: from.to_string(),
+                    pending,
+                });
+                self.progress.write().await.state = AlignmentState::Interrupted;
+                return false;
+            }
 
-            for (key, (ts, value)) in missing_data {
-                let sample = Sample::new(key, value).with_timestamp(ts);
+            // Missing data might be less than requested since some entries might be outdated
+            tracing::debug!("[ALIGNER] Received {} queried samples", replies.len());
+            self.progress.write().await.retrieved += replies.len();
+            for sample in replies {
                 tracing::debug!("[ALIGNER] Adding {:?} to storage", sample);
                 self.tx_sample.send_async(sample).await.unwrap_or_else(|e| {
                     tracing::error!("[ALIGNER] Error adding sample to storage: {}", e)
                 });
             }
-
-            if no_content_err && no_data_err {
-                let mut processed = self.digests_processed.write().await;
-                (*processed).insert(checksum);
-            }
         }
+        let mut progress = self.progress.write().await;
+        progress.state = AlignmentState::Idle;
+        progress.alignments += 1;
+        true
     }
 
-    async fn get_missing_data(
+    // Descend the Merkle tree of the other replica from its root, only into the nodes differing from the local ones,
+    // down to the leaves containing the missing entries
+    async fn get_missing_content(
         &self,
-        missing_content: &[LogEntry],
-        timestamp: Timestamp,
         from: &str,
-    ) -> (HashMap<OwnedKeyExpr, (Timestamp, Value)>, bool) {
-        let mut result = HashMap::new();
-        let properties = format!(
-            "timestamp={}&{}={}",
-            timestamp,
-            CONTENTS,
-            serde_json::to_string(missing_content).unwrap()
-        );
-        let (replies, no_err) = self.perform_query(from, properties.clone()).await;
-
-        for sample in replies {
-            result.insert(
-                sample.key_expr.into(),
-                (sample.timestamp.unwrap(), sample.value),
-            );
-        }
-        (result, no_err)
-    }
-
-    async fn get_missing_content(&self, other: &Digest, from: &str) -> (Vec<LogEntry>, bool) {
+        throttle: &mut Throttle,
+    ) -> (VecDeque<LogEntry>, bool) {
         tracing::debug!("[ALIGNER] Get missing content from {from} ...");
-        // get my digest
-        let this = &self.snapshotter.get_digest().await;
-
-        let cold_alignment =
-            self.perform_era_alignment(&EraType::Cold, this, from.to_string(), other);
-        let warm_alignment =
-            self.perform_era_alignment(&EraType::Warm, this, from.to_string(), other);
-        let hot_alignment =
-            self.perform_era_alignment(&EraType::Hot, this, from.to_string(), other);
-
-        let ((cold_data, no_cold_err), (warm_data, no_warm_err), (hot_data, no_hot_err)) =
-            futures::join!(cold_alignment, warm_alignment, hot_alignment);
-        tracing::debug!("[ALIGNER] Missing content from {from} in Cold era: {cold_data:?}");
-        tracing::debug!("[ALIGNER] Missing content from {from} in Warm era: {warm_data:?}");
-        tracing::debug!("[ALIGNER] Missing content from {from} in Hot era: {hot_data:?}");
-        (
-            [cold_data, warm_data, hot_data].concat(),
-            no_cold_err && no_warm_err && no_hot_err,
-        )
-    }
-
-    // perform era alignment
-    // for a misaligned era, get missing intervals, then subintervals and then log entry
-    async fn perform_era_alignment(
-        &self,
-        era: &EraType,
-        this: &Digest,
-        other_rep: String,
-        other: &Digest,
-    ) -> (Vec<LogEntry>, bool) {
-        if !this.era_has_diff(era, &other.eras) {
-            return (Vec::new(), true);
-        }
-        // get era diff
-        let (diff_intervals, no_era_err) =
-            self.get_interval_diff(era, this, other, &other_rep).await;
-        // get interval diff
-        let (diff_subintervals, no_int_err) = self
-            .get_subinterval_diff(era, diff_intervals, this, other, &other_rep)
-            .await;
-        // get subinterval diff
-        let (diff_content, no_sub_err) = self
-            .get_content_diff(diff_subintervals, this, other, &other_rep)
-            .await;
-        (diff_content, no_era_err && no_int_err && no_sub_err)
-    }
-
-    async fn get_interval_diff(
-        &self,
-        era: &EraType,
-        this: &Digest,
-        other: &Digest,
-        other_rep: &str,
-    ) -> (HashSet<u64>, bool) {
-        let (other_intervals, no_err) = if era.eq(&EraType::Cold) {
-            let properties = format!("timestamp={}&{}=cold", other.timestamp, ERA);
-            let (reply_content, mut no_err) = self.perform_query(other_rep, properties).await;
-            let mut other_intervals: HashMap<u64, u64> = HashMap::new();
-            // expecting sample.value to be a vec of intervals with their checksum
-            for each in reply_content {
-                match serde_json::from_str(&each.value.to_string()) {
-                    Ok((i, c)) => {
-                        other_intervals.insert(i, c);
-                    }
-                    Err(e) => {
-                        tracing::error!("[ALIGNER] Error decoding reply: {}", e);
-                        no_err = false;
-                    }
-                };
-            }
-            (other_intervals, no_err)
-        } else {
-            // get the diff of intervals from the digest itself
-            (other.get_era_content(era), true)
-        };
-        // get era diff
-        (this.get_interval_diff(other_intervals), no_err)
-    }
-
-    async fn get_subinterval_diff(
-        &self,
-        era: &EraType,
-        diff_intervals: HashSet<u64>,
-        this: &Digest,
-        other: &Digest,
-        other_rep: &str,
-    ) -> (HashSet<u64>, bool) {
-        if !diff_intervals.is_empty() {
-            let (other_subintervals, no_err) = if era.eq(&EraType::Hot) {
-                // get subintervals for mismatching intervals from other
-                (other.get_interval_content(diff_intervals), true)
-            } else {
-                let mut diff_string = Vec::new();
-                for each_int in diff_intervals {
-                    diff_string.push(each_int.to_string());
-                }
-                let properties = format!(
-                    "timestamp={}&{}=[{}]",
-                    other.timestamp,
-                    INTERVALS,
-                    diff_string.join(",")
-                );
-                // expecting sample.value to be a vec of subintervals with their checksum
-                let (reply_content, mut no_err) = self.perform_query(other_rep, properties).await;
-                let mut other_subintervals: HashMap<u64, u64> = HashMap::new();
-                for each in reply_content {
-                    match serde_json::from_str(&each.value.to_string()) {
-                        Ok((i, c)) => {
-                            other_subintervals.insert(i, c);
+        let mut missing = VecDeque::new();
+        let mut no_err = true;
+        let mut paths = vec![String::new()];
+        while !paths.is_empty() {
+            let mut next_paths = Vec::new();
+            for chunk in paths.chunks(self.batch_size) {
+                let properties = format!("{}={}", MERKLE, serde_json::to_string(chunk).unwrap());
+                let (replies, no_query_err) = self.perform_query(from, properties, throttle).await;
+                no_err = no_err && no_query_err;
+                for each in replies {
+                    let value = each.value.to_string();
+                    match serde_json::from_str(&value) {
+                        Ok((path, MerkleContent::Children(children))) => {
+                            let path: String = path;
+                            next_paths
+                                .extend(self.snapshotter.get_merkle_diff(&path, &children).await);
+                        }
+                        Ok((_, MerkleContent::Entries(entries))) => {
+                            for entry in entries {
+                                if !self.snapshotter.is_logged(&entry).await {
+                                    missing.push_back(entry);
+                                }
+                            }
                         }
                         Err(e) => {
                             tracing::error!("[ALIGNER] Error decoding reply: {}", e);
                             no_err = false;
                         }
-                    };
-                }
-                (other_subintervals, no_err)
-            };
-            // get intervals diff
-            (this.get_subinterval_diff(other_subintervals), no_err)
-        } else {
-            (HashSet::new(), true)
-        }
-    }
-
-    async fn get_content_diff(
-        &self,
-        diff_subintervals: HashSet<u64>,
-        this: &Digest,
-        other: &Digest,
-        other_rep: &str,
-    ) -> (Vec<LogEntry>, bool) {
-        if !diff_subintervals.is_empty() {
-            let mut diff_string = Vec::new();
-            for each_sub in diff_subintervals {
-                diff_string.push(each_sub.to_string());
-            }
-            let properties = format!(
-                "timestamp={}&{}=[{}]",
-                other.timestamp,
-                SUBINTERVALS,
-                diff_string.join(",")
-            );
-            // expecting sample.value to be a vec of log entries with their checksum
-            let (reply_content, mut no_err) = self.perform_query(other_rep, properties).await;
-            let mut other_content: HashMap<u64, Vec<LogEntry>> = HashMap::new();
-            for each in reply_content {
-                match serde_json::from_str(&each.value.to_string()) {
-                    Ok((i, c)) => {
-                        other_content.insert(i, c);
-                    }
-                    Err(e) => {
-                        tracing::error!("[ALIGNER] Error decoding reply: {}", e);
-                        no_err = false;
                     }
-                };
+                }
             }
-            // get subintervals diff
-            let result = this.get_full_content_diff(other_content);
-            (result, no_err)
-        } else {
-            (Vec::new(), true)
+            paths = next_paths;
         }
+        tracing::debug!("[ALIGNER] Missing content from {from}: {missing:?}");
+        (missing, no_err)
     }
 
-    async fn perform_query(&self, from: &str, properties: String) -> (Vec<Sample>, bool) {
+    // Query the other replica once allowed by the throttle, accounting for the bytes of its replies
+    async fn perform_query(
+        &self,
+        from: &str,
+        properties: String,
+        throttle: &mut Throttle,
+    ) -> (Vec<Sample>, bool) {
+        throttle.acquire().await;
         let mut no_err = true;
         let selector = KeyExpr::from(&self.digest_key)
             .join(&from)
@@ -334,6 +352,11 @@ impl Aligner {
                                 sample.key_expr.as_str(),
                                 sample.value
                             );
+                            let bytes = (sample.key_expr.as_str().len()
+                                + sample.value.payload.len())
+                                as u64;
+                            throttle.record(bytes);
+                            self.progress.write().await.bytes_received += bytes;
                             return_val.push(sample);
                         }
                         Err(err) => {
@@ -358,3 +381,135 @@ impl Aligner {
         (return_val, no_err)
     }
 }
+
+// An aligner retrieving the missing entries from a queryable standing for the replica `remote`,
+// which fails the first `failures` requests
+#[cfg(test)]
+async fn test_aligner(
+    failures: usize,
+) -> (
+    Aligner,
+    zenoh::queryable::Queryable<'static, ()>,
+    Receiver<Sample>,
+) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use zenoh::prelude::sync::SyncResolve;
+
+    let mut config = zenoh::config::Config::default();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = Arc::new(zenoh::open(config).res_async().await.unwrap());
+    let requests = AtomicUsize::new(0);
+    let queryable = session
+        .declare_queryable("test/digest/remote")
+        .callback(move |query| {
+            if requests.fetch_add(1, Ordering::SeqCst) < failures {
+                query.reply(Err("unavailable".into())).res_sync().unwrap();
+                return;
+            }
+            let properties = query.selector().parameters_stringmap().unwrap();
+            let entries: Vec<LogEntry> = serde_json::from_str(&properties[CONTENTS]).unwrap();
+            for entry in entries {
+                let sample = Sample::new(entry.key, "value").with_timestamp(entry.timestamp);
+                query.reply(Ok(sample)).res_sync().unwrap();
+            }
+        })
+        .res_async()
+        .await
+        .unwrap();
+
+    let replica_config = ReplicaConfig {
+        alignment_batch_size: 1,
+        ..Default::default()
+    };
+    let (_, rx_update) = flume::unbounded();
+    let snapshotter = Snapshotter::new(rx_update, &Vec::new(), &replica_config).await;
+    let (_, rx_digest) = flume::unbounded();
+    let (tx_sample, rx_sample) = flume::unbounded();
+    let aligner = Aligner {
+        session,
+        digest_key: OwnedKeyExpr::new("test/digest").unwrap(),
+        snapshotter: Arc::new(snapshotter),
+        rx_digest,
+        tx_sample,
+        digests_processed: RwLock::new(HashSet::new()),
+        bandwidth: None,
+        batch_size: replica_config.alignment_batch_size,
+        transfer: Mutex::new(None),
+        progress: Arc::new(RwLock::new(AlignmentProgress::default())),
+    };
+    (aligner, queryable, rx_sample)
+}
+
+#[test]
+fn test_resume_transfer() {
+    async_std::task::block_on(async {
+        let (aligner, _queryable, rx_sample) = test_aligner(1).await;
+        let pending: VecDeque<LogEntry> = ["test/a", "test/b", "test/c"]
+            .into_iter()
+            .map(|key| LogEntry {
+                timestamp: zenoh::time::new_reception_timestamp(),
+                key: OwnedKeyExpr::new(key).unwrap(),
+            })
+            .collect();
+
+        // the failed request interrupts the transfer, keeping all the entries to retrieve
+        let completed = aligner
+            .retrieve_missing_data(pending.clone(), "remote", &mut Throttle::new(None))
+            .await;
+        assert!(!completed);
+        {
+            let transfer = aligner.transfer.lock().await;
+            let transfer = transfer.as_ref().unwrap();
+            assert_eq!(transfer.from, "remote");
+            assert_eq!(transfer.pending, pending);
+        }
+        assert_eq!(
+            aligner.progress.read().await.state,
+            AlignmentState::Interrupted
+        );
+        assert!(rx_sample.is_empty());
+
+        // the next digest of the replica resumes the transfer
+        let digest = aligner.snapshotter.get_digest().await;
+        aligner.process_incoming_digest(digest, "remote").await;
+        assert!(aligner.transfer.lock().await.is_none());
+        let received: Vec<_> = rx_sample
+            .drain()
+            .map(|sample| LogEntry {
+                timestamp: sample.timestamp.unwrap(),
+                key: sample.key_expr.into(),
+            })
+            .collect();
+        assert_eq!(received, Vec::from(pending));
+        let progress = aligner.progress.read().await;
+        assert_eq!(progress.state, AlignmentState::Idle);
+        assert_eq!(progress.retrieved, 3);
+        assert_eq!(
+            progress.bytes_received,
+            3 * ("test/a".len() + "value".len()) as u64
+        );
+        assert_eq!(progress.alignments, 1);
+    });
+}
+
+#[test]
+fn test_throttle() {
+    async_std::task::block_on(async {
+        // the first request is never delayed
+        let mut throttle = Throttle::new(Some(1000));
+        let start = Instant::now();
+        throttle.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // a request waits until the bytes received so far are within the bandwidth
+        throttle.record(500);
+        throttle.acquire().await;
+        assert!(throttle.start.elapsed() >= Duration::from_millis(500));
+
+        let mut unlimited = Throttle::new(None);
+        unlimited.record(u32::MAX as u64);
+        let start = Instant::now();
+        unlimited.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+    });
+}
@@ -765,73 +765,6 @@ impl Digest {
     }
 }
 
-// functions for alignment
-impl Digest {
-    // check if the other era has more content
-    pub fn era_has_diff(&self, era: &EraType, other: &HashMap<EraType, Interval>) -> bool {
-        match (other.get(era), self.eras.get(era)) {
-            (Some(other_era), Some(my_era)) => other_era.checksum != my_era.checksum,
-            (Some(_), None) => true,
-            _ => false,
-        }
-    }
-
-    // return mismatching intervals in an era
-    pub fn get_interval_diff(&self, other_intervals: HashMap<u64, u64>) -> HashSet<u64> {
-        let mut mis_int = HashSet::new();
-        for (key, other_int) in &other_intervals {
-            if let Some(int) = self.intervals.get(key) {
-                if *other_int != int.checksum {
-                    mis_int.insert(*key);
-                }
-            } else {
-                mis_int.insert(*key);
-            }
-        }
-        mis_int
-    }
-
-    // return mismatching subintervals in an interval
-    pub fn get_subinterval_diff(&self, other_subintervals: HashMap<u64, u64>) -> HashSet<u64> {
-        let mut mis_sub = HashSet::new();
-        for (key, other_sub) in &other_subintervals {
-            if let Some(sub) = self.subintervals.get(key) {
-                if *other_sub != sub.checksum {
-                    mis_sub.insert(*key);
-                }
-            } else {
-                mis_sub.insert(*key);
-            }
-        }
-        mis_sub
-    }
-
-    // get missing content from a given list of subintervals
-    pub fn get_full_content_diff(
-        &self,
-        other_subintervals: HashMap<u64, Vec<LogEntry>>,
-    ) -> Vec<LogEntry> {
-        let mut result = Vec::new();
-        for (sub, content) in other_subintervals {
-            let mut other = self.get_content_diff(sub, content.clone());
-            result.append(&mut other);
-        }
-        result
-    }
-
-    //return missing content in a subinterval
-    pub fn get_content_diff(&self, subinterval: u64, content: Vec<LogEntry>) -> Vec<LogEntry> {
-        if let Some(sub) = self.subintervals.get(&subinterval) {
-            content
-                .into_iter()
-                .filter(|c| !sub.content.contains(c))
-                .collect()
-        } else {
-            content
-        }
-    }
-}
-
 #[test]
 fn test_create_digest_empty_initial() {
     async_std::task::block_on(async {
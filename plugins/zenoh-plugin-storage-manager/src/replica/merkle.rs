@@ -0,0 +1,246 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// The Merkle tree of the stable log of a replica, used to locate the differences between replicas.
+// Each key is placed in a leaf according to the hash of the key: the path of a leaf is made of the first
+// MERKLE_DEPTH hexadecimal digits of this hash, each digit selecting a child among MERKLE_FANOUT.
+// The checksum of a leaf covers its log entries, and the checksum of an inner node covers its children.
+// Two replicas compare the checksums of the children of the mismatching nodes, level by level,
+// until they reach the mismatching leaves, whose log entries are then exchanged.
+
+use super::LogEntry;
+use crc::{Crc, CRC_64_ECMA_182};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use zenoh::key_expr::OwnedKeyExpr;
+use zenoh::time::Timestamp;
+
+pub const MERKLE_DEPTH: usize = 4;
+const MERKLE_FANOUT: usize = 16;
+const CRC64: Crc<u64> = Crc::<u64>::new(&CRC_64_ECMA_182);
+
+// The content of a node, as exchanged between replicas
+#[derive(Eq, PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub enum MerkleContent {
+    // the checksums of the non-empty children of an inner node, by path
+    Children(BTreeMap<String, u64>),
+    // the log entries of a leaf, by key
+    Entries(Vec<LogEntry>),
+}
+
+#[derive(Default)]
+pub struct MerkleTree {
+    // the checksums of the non-empty nodes, by path
+    checksums: HashMap<String, u64>,
+    // the log entries of the non-empty leaves, by path
+    leaves: HashMap<String, HashMap<OwnedKeyExpr, Timestamp>>,
+}
+
+impl MerkleTree {
+    pub fn new(log: &HashMap<OwnedKeyExpr, Timestamp>) -> MerkleTree {
+        let mut tree = MerkleTree::default();
+        for (key, timestamp) in log {
+            tree.leaves
+                .entry(MerkleTree::leaf_path(key))
+                .or_default()
+                .insert(key.clone(), *timestamp);
+        }
+        let paths: Vec<String> = tree.leaves.keys().cloned().collect();
+        for path in paths {
+            tree.update_path(&path);
+        }
+        tree
+    }
+
+    pub fn checksum(&self, path: &str) -> u64 {
+        self.checksums.get(path).copied().unwrap_or_default()
+    }
+
+    pub fn insert(&mut self, key: OwnedKeyExpr, timestamp: Timestamp) {
+        let path = MerkleTree::leaf_path(&key);
+        self.leaves
+            .entry(path.clone())
+            .or_default()
+            .insert(key, timestamp);
+        self.update_path(&path);
+    }
+
+    // The content of a node, None if the path is invalid
+    pub fn content(&self, path: &str) -> Option<MerkleContent> {
+        if path.len() > MERKLE_DEPTH || !path.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        if path.len() == MERKLE_DEPTH {
+            let entries = self
+                .leaves
+                .get(path)
+                .map(|leaf| {
+                    MerkleTree::sorted(leaf)
+                        .map(|(key, timestamp)| LogEntry {
+                            timestamp: *timestamp,
+                            key: key.clone(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(MerkleContent::Entries(entries))
+        } else {
+            Some(MerkleContent::Children(self.children(path)))
+        }
+    }
+
+    // The paths of the children of a node whose checksum differs from the given ones,
+    // including the local children missing from the given ones
+    pub fn diff_children(&self, path: &str, other: &BTreeMap<String, u64>) -> Vec<String> {
+        let mut result: Vec<String> = other
+            .iter()
+            .filter(|(child, checksum)| self.checksum(child) != **checksum)
+            .map(|(child, _)| child.clone())
+            .collect();
+        for child in self.children(path).into_keys() {
+            if !other.contains_key(&child) {
+                result.push(child);
+            }
+        }
+        result
+    }
+
+    fn children(&self, path: &str) -> BTreeMap<String, u64> {
+        let mut children = BTreeMap::new();
+        for digit in 0..MERKLE_FANOUT {
+            let child = format!("{path}{digit:x}");
+            if let Some(checksum) = self.checksums.get(&child) {
+                children.insert(child, *checksum);
+            }
+        }
+        children
+    }
+
+    // Recompute the checksums of a leaf and of all its ancestors
+    fn update_path(&mut self, leaf_path: &str) {
+        let checksum = match self.leaves.get(leaf_path) {
+            Some(leaf) => {
+                let mut hasher = CRC64.digest();
+                for (key, timestamp) in MerkleTree::sorted(leaf) {
+                    hasher.update(format!("{timestamp}-{key}").as_bytes());
+                }
+                Some(hasher.finalize())
+            }
+            None => None,
+        };
+        self.set_checksum(leaf_path, checksum);
+        for len in (0..leaf_path.len()).rev() {
+            let path = &leaf_path[..len];
+            let children = self.children(path);
+            let checksum = if children.is_empty() {
+                None
+            } else {
+                let mut hasher = CRC64.digest();
+                for (child, checksum) in children {
+                    hasher.update(format!("{child}:{checksum}").as_bytes());
+                }
+                Some(hasher.finalize())
+            };
+            self.set_checksum(path, checksum);
+        }
+    }
+
+    fn set_checksum(&mut self, path: &str, checksum: Option<u64>) {
+        match checksum {
+            Some(checksum) => self.checksums.insert(path.to_string(), checksum),
+            None => self.checksums.remove(path),
+        };
+    }
+
+    fn sorted(
+        leaf: &HashMap<OwnedKeyExpr, Timestamp>,
+    ) -> impl Iterator<Item = (&OwnedKeyExpr, &Timestamp)> {
+        let mut entries: Vec<_> = leaf.iter().collect();
+        entries.sort_unstable_by(|(k1, _), (k2, _)| k1.as_str().cmp(k2.as_str()));
+        entries.into_iter()
+    }
+
+    fn leaf_path(key: &OwnedKeyExpr) -> String {
+        let hash = CRC64.checksum(key.as_bytes());
+        format!("{hash:016x}")[..MERKLE_DEPTH].to_string()
+    }
+}
+
+// A log of the given keys, with times in seconds
+#[cfg(test)]
+fn test_log(entries: &[(&str, u64)]) -> HashMap<OwnedKeyExpr, Timestamp> {
+    let id = zenoh::time::new_reception_timestamp();
+    entries
+        .iter()
+        .map(|(key, time)| {
+            (
+                OwnedKeyExpr::new(*key).unwrap(),
+                Timestamp::new(zenoh::time::NTP64(*time << 32), *id.get_id()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_merkle_updates() {
+    let log = test_log(&[("demo/a", 1), ("demo/b", 2), ("demo/c", 3)]);
+    let tree = MerkleTree::new(&log);
+    assert_ne!(tree.checksum(""), 0);
+
+    // the checksums don't depend on the order of the updates
+    let mut updated = MerkleTree::default();
+    let mut entries: Vec<_> = MerkleTree::sorted(&log).collect();
+    entries.reverse();
+    for (key, timestamp) in entries {
+        updated.insert(key.clone(), *timestamp);
+    }
+    assert_eq!(updated.checksum(""), tree.checksum(""));
+
+    // an update changes the checksums of the path of its key only
+    let (key, timestamp) = test_log(&[("demo/a", 4)]).into_iter().next().unwrap();
+    let leaf = MerkleTree::leaf_path(&key);
+    updated.insert(key.clone(), timestamp);
+    for len in 0..=MERKLE_DEPTH {
+        assert_ne!(updated.checksum(&leaf[..len]), tree.checksum(&leaf[..len]));
+    }
+    for other in log.keys().filter(|k| MerkleTree::leaf_path(k) != leaf) {
+        let other = MerkleTree::leaf_path(other);
+        assert_eq!(updated.checksum(&other), tree.checksum(&other));
+    }
+    updated.insert(key.clone(), log[&key]);
+    assert_eq!(updated.checksum(""), tree.checksum(""));
+}
+
+#[test]
+fn test_merkle_diff() {
+    let this = MerkleTree::new(&test_log(&[("demo/a", 1), ("demo/b", 2)]));
+    let other = MerkleTree::new(&test_log(&[("demo/a", 1), ("demo/b", 3)]));
+
+    // descend from the root to the only mismatching leaf
+    let mut paths = vec![String::new()];
+    let mut entries = Vec::new();
+    while let Some(path) = paths.pop() {
+        match other.content(&path).unwrap() {
+            MerkleContent::Children(children) => {
+                paths.extend(this.diff_children(&path, &children));
+            }
+            MerkleContent::Entries(leaf) => entries.extend(leaf),
+        }
+    }
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].key.as_str(), "demo/b");
+
+    assert!(this.content("xyz").is_none());
+    assert!(this.content("00000").is_none());
+}
@@ -34,19 +34,22 @@ use zenoh_backend_traits::config::{ReplicaConfig, StorageConfig};
 pub mod align_queryable;
 pub mod aligner;
 pub mod digest;
+pub mod merkle;
 pub mod snapshotter;
 pub mod storage;
 
 pub use align_queryable::AlignQueryable;
-pub use aligner::Aligner;
+pub use aligner::{Aligner, AlignmentProgress};
 pub use digest::{Digest, DigestConfig, EraType, LogEntry};
 pub use snapshotter::Snapshotter;
 pub use storage::{ReplicationService, StorageService};
 
+// The era, intervals and subintervals of the digest are still served to replicas aligning by era
 const ERA: &str = "era";
 const INTERVALS: &str = "intervals";
 const SUBINTERVALS: &str = "subintervals";
 const CONTENTS: &str = "contents";
+const MERKLE: &str = "merkle";
 pub const EPOCH_START: SystemTime = SystemTime::UNIX_EPOCH;
 
 pub const ALIGN_PREFIX: &str = "@-digest";
@@ -61,6 +64,7 @@ pub const SUBINTERVAL_CHUNKS: usize = 10;
 // The `Aligner` identifies mismatches in the contents of the storage with respect to the other storage
 // `Aligner` generates a list of missing updates that is then send to the `StorageService`
 // When a `StorageService` receives an update, it sends a log to the `Snapshotter`
+// The `StorageService` serves its change feed from the log of the `Snapshotter`
 
 pub struct Replica {
     // TODO: Discuss if we need to add -<storage_type> for uniqueness
@@ -140,12 +144,15 @@ impl Replica {
         )
         .fuse();
         // aligner
+        let alignment = Arc::new(RwLock::new(AlignmentProgress::default()));
         let aligner = Aligner::start_aligner(
             replica.session.clone(),
             digest_key,
             rx_digest,
             tx_sample,
             snapshotter.clone(),
+            &config,
+            alignment.clone(),
         )
         .fuse();
         // digest pub
@@ -159,6 +166,8 @@ impl Replica {
             empty_start: startup_entries.is_empty(),
             aligner_updates: rx_sample,
             log_propagation: tx_log,
+            snapshotter: snapshotter.clone(),
+            alignment,
         };
         // channel to pipe the receiver to storage
         let storage_task = StorageService::start(
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::merkle::{MerkleContent, MerkleTree};
 use super::{Digest, DigestConfig, LogEntry};
 use async_std::stream::{interval, StreamExt};
 use async_std::sync::Arc;
@@ -18,7 +19,7 @@ use async_std::sync::RwLock;
 use async_std::task::sleep;
 use flume::Receiver;
 use futures::join;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::time::Duration;
 use zenoh::key_expr::OwnedKeyExpr;
@@ -45,6 +46,8 @@ pub struct ReplicationInfo {
     last_interval: RwLock<u64>,
     // the current stable digest
     digest: Arc<RwLock<Digest>>,
+    // the Merkle tree of the stable log
+    merkle: RwLock<MerkleTree>,
 }
 
 // this class takes care of managing logs, digests and keeps snapshot time and interval updated
@@ -87,6 +90,7 @@ impl Snapshotter {
                     Vec::new(),
                     last_interval,
                 ))),
+                merkle: RwLock::new(MerkleTree::default()),
             },
         };
         snapshotter.initialize_log(initial_entries).await;
@@ -186,6 +190,7 @@ impl Snapshotter {
             }
         }
         drop(volatile_log);
+        *replica_data.merkle.write().await = MerkleTree::new(&stable_log);
         drop(stable_log);
 
         drop(last_snapshot_time);
@@ -253,6 +258,7 @@ impl Snapshotter {
                 });
             }
             drop(log);
+            replica_data.merkle.write().await.insert(key.clone(), ts);
             new_stable_content.insert(LogEntry { timestamp: ts, key });
         }
         let mut digest = replica_data.digest.write().await;
@@ -296,6 +302,12 @@ impl Snapshotter {
         drop(stable);
         drop(volatile);
 
+        let mut merkle = replica_data.merkle.write().await;
+        for entry in &new_stable {
+            merkle.insert(entry.key.clone(), entry.timestamp);
+        }
+        drop(merkle);
+
         let mut volatile = replica_data.volatile_log.write().await;
         *volatile = remains_volatile;
         drop(volatile);
@@ -329,4 +341,45 @@ impl Snapshotter {
     pub async fn get_digest(&self) -> Digest {
         self.content.digest.read().await.clone()
     }
+
+    // Expose the content of a node of the Merkle tree
+    pub async fn get_merkle_content(&self, path: &str) -> Option<MerkleContent> {
+        self.content.merkle.read().await.content(path)
+    }
+
+    // Compare the children of a node of the Merkle tree with the ones of another replica
+    pub async fn get_merkle_diff(&self, path: &str, other: &BTreeMap<String, u64>) -> Vec<String> {
+        self.content.merkle.read().await.diff_children(path, other)
+    }
+
+    // Expose the latest change of each key logged after the given timestamp, in timestamp order
+    pub async fn get_log_after(&self, cursor: Option<&Timestamp>) -> Vec<LogEntry> {
+        let replica_data = &self.content;
+        let mut log = replica_data.stable_log.read().await.clone();
+        for (key, timestamp) in replica_data.volatile_log.read().await.iter() {
+            match log.get(key) {
+                Some(ts) if ts >= timestamp => (),
+                _ => {
+                    log.insert(key.clone(), *timestamp);
+                }
+            }
+        }
+        let mut entries: Vec<LogEntry> = log
+            .into_iter()
+            .filter(|(_, ts)| cursor.map_or(true, |cursor| ts > cursor))
+            .map(|(key, timestamp)| LogEntry { timestamp, key })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    // Check whether the log already has the given entry, or a newer one for the same key
+    pub async fn is_logged(&self, entry: &LogEntry) -> bool {
+        let replica_data = &self.content;
+        let logged = |log: &HashMap<OwnedKeyExpr, Timestamp>| {
+            log.get(&entry.key).is_some_and(|ts| *ts >= entry.timestamp)
+        };
+        logged(&*replica_data.stable_log.read().await)
+            || logged(&*replica_data.volatile_log.read().await)
+    }
 }
@@ -11,32 +11,58 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::{AlignmentProgress, LogEntry, Snapshotter};
 use crate::backends_mgt::StoreIntercept;
+use crate::quotas::Usage;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
 use crate::storages_mgt::StorageMessage;
+use crate::writes::{conflicts_to_json, parse_writes, WRITE_KEY};
 use async_std::sync::Arc;
 use async_std::sync::{Mutex, RwLock};
+use async_std::task;
 use async_trait::async_trait;
 use flume::{Receiver, Sender};
-use futures::select;
-use std::collections::{HashMap, HashSet};
+use futures::{future, select, StreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::path::Path;
 use std::str::{self, FromStr};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zenoh::buffers::ZBuf;
 use zenoh::prelude::r#async::*;
 use zenoh::query::ConsolidationMode;
+use zenoh::sample::AttachmentBuilder;
 use zenoh::time::{Timestamp, NTP64};
 use zenoh::{Result as ZResult, Session};
-use zenoh_backend_traits::config::{GarbageCollectionConfig, StorageConfig};
+use zenoh_backend_traits::config::{GarbageCollectionConfig, LifespanConfig, StorageConfig};
+use zenoh_backend_traits::filter::ValueFilter;
+use zenoh_backend_traits::pagination::{KeyOrder, PageToken, Pagination};
 use zenoh_backend_traits::{Capability, History, Persistence, StorageInsertionResult, StoredData};
 use zenoh_keyexpr::key_expr::OwnedKeyExpr;
 use zenoh_keyexpr::keyexpr_tree::impls::KeyedSetProvider;
 use zenoh_keyexpr::keyexpr_tree::{support::NonWild, support::UnknownWildness, KeBoxTree};
 use zenoh_keyexpr::keyexpr_tree::{IKeyExprTree, IKeyExprTreeMut};
-use zenoh_result::bail;
+use zenoh_result::{bail, zerror};
 use zenoh_util::{zenoh_home, Timed, TimedEvent, Timer};
 
 pub const WILDCARD_UPDATES_FILENAME: &str = "wildcard_updates";
 pub const TOMBSTONE_FILENAME: &str = "tombstones";
+/// The attachment key allowing publishers to set the lifespan of a value, in seconds.
+pub const TTL_ATTACHMENT_KEY: &str = "ttl";
+/// The selector parameter requesting the changes of a storage after the given timestamp (all the changes if empty).
+/// Only the latest change of each key is returned, in timestamp order: the intermediate changes of a key are
+/// coalesced, so that a consumer of the changes gets the current state of the keys, not their whole history.
+/// As the deletions are forgotten after the `lifespan` of the `garbage_collection` of the storage, a cursor older
+/// than this lifespan is replied with an error: the consumer must then resync with all the changes.
+pub const CHANGES_KEY: &str = "_changes";
+/// The attachment key indicating the kind of a change: `PUT` or `DELETE`.
+pub const KIND_ATTACHMENT_KEY: &str = "kind";
+/// The attachment key set to `true` on each change, as the feed only returns the latest change of each key.
+pub const COALESCED_ATTACHMENT_KEY: &str = "coalesced";
+// The period at which expired values are removed
+const EXPIRATION_CHECK_PERIOD: Duration = Duration::from_secs(1);
+// The maximum number of replies retrieved from the storage at once
+const REPLIES_CHUNK_SIZE: usize = 256;
 
 #[derive(Clone)]
 struct Update {
@@ -48,6 +74,8 @@ pub struct ReplicationService {
     pub empty_start: bool,
     pub aligner_updates: Receiver<Sample>,
     pub log_propagation: Sender<(OwnedKeyExpr, Timestamp)>,
+    pub snapshotter: Arc<Snapshotter>,
+    pub alignment: Arc<RwLock<AlignmentProgress>>,
 }
 
 pub struct StorageService {
@@ -59,10 +87,95 @@ pub struct StorageService {
     storage: Mutex<Box<dyn zenoh_backend_traits::Storage>>,
     capability: Capability,
     tombstones: Arc<RwLock<KeBoxTree<Timestamp, NonWild, KeyedSetProvider>>>,
+    // the duration after which the tombstones are garbage collected
+    tombstones_lifespan: Duration,
     wildcard_updates: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     replication: Option<ReplicationService>,
+    lifespans: Vec<LifespanConfig>,
+    expirations: Mutex<KeyTimestamps>,
+    // the timestamp of the latest change of each key, if the storage isn't replicated
+    // (the `Snapshotter` of a replica logs the changes)
+    changes: Arc<Mutex<KeyTimestamps>>,
+    // the usage of the storage, if it has limits
+    usage: Option<Mutex<Usage>>,
+}
+
+// A timestamp for each key, ordered by timestamp: the expiration timestamps of the stored values,
+// or the timestamps of the latest changes of the keys
+#[derive(Default)]
+struct KeyTimestamps {
+    by_key: HashMap<OwnedKeyExpr, Timestamp>,
+    by_time: BTreeMap<Timestamp, HashSet<OwnedKeyExpr>>,
+}
+
+impl KeyTimestamps {
+    fn insert(&mut self, key: OwnedKeyExpr, expiration: Timestamp) {
+        self.remove(&key);
+        self.by_time
+            .entry(expiration)
+            .or_default()
+            .insert(key.clone());
+        self.by_key.insert(key, expiration);
+    }
+
+    fn remove(&mut self, key: &OwnedKeyExpr) {
+        if let Some(expiration) = self.by_key.remove(key) {
+            if let Some(keys) = self.by_time.get_mut(&expiration) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.by_time.remove(&expiration);
+                }
+            }
+        }
+    }
+
+    // Inserts the timestamp of a key, unless the key has a newer one
+    fn insert_if_newer(&mut self, key: OwnedKeyExpr, timestamp: Timestamp) {
+        if self.by_key.get(&key).map_or(true, |ts| *ts < timestamp) {
+            self.insert(key, timestamp);
+        }
+    }
+
+    // Removes a key, if its timestamp is the given one
+    fn remove_if(&mut self, key: &OwnedKeyExpr, timestamp: &Timestamp) {
+        if self.by_key.get(key) == Some(timestamp) {
+            self.remove(key);
+        }
+    }
+
+    // The keys with a timestamp after the given one, in timestamp order
+    fn after(&self, cursor: Option<&Timestamp>) -> Vec<LogEntry> {
+        let range = match cursor {
+            Some(cursor) => self.by_time.range((Excluded(*cursor), Unbounded)),
+            None => self.by_time.range(..),
+        };
+        range
+            .flat_map(|(timestamp, keys)| {
+                keys.iter().map(|key| LogEntry {
+                    timestamp: *timestamp,
+                    key: key.clone(),
+                })
+            })
+            .collect()
+    }
+
+    // Removes and returns the values expired at the given time
+    fn pop_expired(&mut self, now: &NTP64) -> Vec<(OwnedKeyExpr, Timestamp)> {
+        let mut expired = Vec::new();
+        while let Some(entry) = self.by_time.first_entry() {
+            if entry.key().get_time() > now {
+                break;
+            }
+            let (expiration, keys) = entry.remove_entry();
+            for key in keys {
+                self.by_key.remove(&key);
+                expired.push((key, expiration));
+            }
+        }
+        expired
+    }
 }
 
 impl StorageService {
@@ -84,10 +197,15 @@ impl StorageService {
             storage: Mutex::new(store_intercept.storage),
             capability: store_intercept.capability,
             tombstones: Arc::new(RwLock::new(KeBoxTree::default())),
+            tombstones_lifespan: config.garbage_collection_config.lifespan,
             wildcard_updates: Arc::new(RwLock::new(KeBoxTree::default())),
             in_interceptor: store_intercept.in_interceptor,
             out_interceptor: store_intercept.out_interceptor,
             replication,
+            lifespans: config.lifespans,
+            expirations: Mutex::new(KeyTimestamps::default()),
+            changes: Arc::new(Mutex::new(KeyTimestamps::default())),
+            usage: config.limits.map(|limits| Mutex::new(Usage::new(limits))),
         };
         if storage_service
             .capability
@@ -127,6 +245,10 @@ impl StorageService {
         gc_config: GarbageCollectionConfig,
     ) {
         self.initialize_if_empty().await;
+        self.initialize_expirations().await;
+        self.initialize_changes().await;
+        self.initialize_usage().await;
+        let mut expiration_ticks = async_std::stream::interval(EXPIRATION_CHECK_PERIOD).fuse();
 
         // start periodic GC event
         let t = Timer::default();
@@ -136,6 +258,7 @@ impl StorageService {
                 config: gc_config,
                 tombstones: self.tombstones.clone(),
                 wildcard_updates: self.wildcard_updates.clone(),
+                changes: self.changes.clone(),
             },
         );
         t.add_async(gc).await;
@@ -193,12 +316,18 @@ impl StorageService {
                     // on aligner update
                     update = aligner_updates.recv_async() => {
                         match update {
-                            Ok(sample) => self.process_sample(sample).await,
+                            Ok(sample) => {
+                                self.process_sample(sample).await;
+                            }
                             Err(e) => {
                                 tracing::error!("Error in receiving aligner update: {}", e);
                             }
                         }
                     },
+                    // on expiration check
+                    _ = expiration_ticks.next() => {
+                        self.expire().await;
+                    },
                     // on storage handle drop
                     message = rx.recv_async() => {
                         match message {
@@ -207,9 +336,13 @@ impl StorageService {
                                 return
                             },
                             Ok(StorageMessage::GetStatus(tx)) => {
-                                let storage = self.storage.lock().await;
-                                std::mem::drop(tx.send(storage.get_admin_status()).await);
-                                drop(storage);
+                                std::mem::drop(tx.send(self.get_status().await).await);
+                            }
+                            Ok(StorageMessage::Export(path, tx)) => {
+                                std::mem::drop(tx.send(self.export(&path).await).await);
+                            }
+                            Ok(StorageMessage::Import(path, tx)) => {
+                                std::mem::drop(tx.send(self.import(&path).await).await);
                             }
                             Err(e) => {
                                 tracing::error!("Storage Message Channel Error: {}", e);
@@ -237,6 +370,10 @@ impl StorageService {
                     query = storage_queryable.recv_async() => {
                         self.reply_query(query).await;
                     },
+                    // on expiration check
+                    _ = expiration_ticks.next() => {
+                        self.expire().await;
+                    },
                     // on storage handle drop
                     message = rx.recv_async() => {
                         match message {
@@ -245,9 +382,13 @@ impl StorageService {
                                 return
                             },
                             Ok(StorageMessage::GetStatus(tx)) => {
-                                let storage = self.storage.lock().await;
-                                std::mem::drop(tx.send(storage.get_admin_status()).await);
-                                drop(storage);
+                                std::mem::drop(tx.send(self.get_status().await).await);
+                            }
+                            Ok(StorageMessage::Export(path, tx)) => {
+                                std::mem::drop(tx.send(self.export(&path).await).await);
+                            }
+                            Ok(StorageMessage::Import(path, tx)) => {
+                                std::mem::drop(tx.send(self.import(&path).await).await);
                             }
                             Err(e) => {
                                 tracing::error!("Storage Message Channel Error: {}", e);
@@ -260,8 +401,9 @@ impl StorageService {
     }
 
     // The storage should only simply save the key, sample pair while put and retrieve the same during get
-    // the trimming during PUT and GET should be handled by the plugin
-    async fn process_sample(&self, sample: Sample) {
+    // the trimming during PUT and GET should be handled by the plugin.
+    // Returns the result of the insertion for each key matching the sample, Outdated if the sample was dropped.
+    async fn process_sample(&self, sample: Sample) -> Vec<ZResult<StorageInsertionResult>> {
         tracing::trace!("[STORAGE] Processing sample: {}", sample);
         // Call incoming data interceptor (if any)
         let sample = if let Some(ref interceptor) = self.in_interceptor {
@@ -286,6 +428,7 @@ impl StorageService {
             matching_keys
         );
 
+        let mut results = Vec::with_capacity(matching_keys.len());
         for k in matching_keys {
             if !self
                 .is_deleted(&k.clone(), sample.get_timestamp().unwrap())
@@ -325,10 +468,26 @@ impl StorageService {
                     Ok(stripped) => stripped,
                     Err(e) => {
                         tracing::error!("{}", e);
-                        return;
+                        results.push(Err(e));
+                        continue;
                     }
                 };
                 let mut storage = self.storage.lock().await;
+                let evicted = if sample.kind == SampleKind::Put {
+                    match self.admit(&k, &sample_to_store).await {
+                        Some(evicted) => evicted,
+                        None => {
+                            results.push(Err(zerror!(
+                                "the limits of storage '{}' are reached",
+                                self.name
+                            )
+                            .into()));
+                            continue;
+                        }
+                    }
+                } else {
+                    Vec::new()
+                };
                 let result = if sample.kind == SampleKind::Put {
                     storage
                         .put(
@@ -348,9 +507,21 @@ impl StorageService {
                     Err("sample kind not implemented".into())
                 };
                 drop(storage);
+                if let Ok(result) = &result {
+                    if !matches!(result, StorageInsertionResult::Outdated) {
+                        self.update_expiration(&k, &sample, &sample_to_store).await;
+                        self.update_usage(&k, &sample_to_store).await;
+                        if self.replication.is_none() {
+                            self.changes
+                                .lock()
+                                .await
+                                .insert_if_newer(k.clone(), sample_to_store.timestamp.unwrap());
+                        }
+                        self.evict(evicted).await;
+                    }
+                }
                 if self.replication.is_some()
-                    && result.is_ok()
-                    && !matches!(result.unwrap(), StorageInsertionResult::Outdated)
+                    && matches!(&result, Ok(r) if !matches!(r, StorageInsertionResult::Outdated))
                 {
                     let sending = self
                         .replication
@@ -365,10 +536,334 @@ impl StorageService {
                         }
                     }
                 }
+                results.push(result);
+            } else {
+                results.push(Ok(StorageInsertionResult::Outdated));
+            }
+        }
+        results
+    }
+
+    // Returns the lifespan of a sample, set by its publisher or configured for its key
+    fn lifespan(&self, key: &OwnedKeyExpr, sample: &Sample) -> Option<Duration> {
+        if let Some(ttl) = sample
+            .attachment()
+            .and_then(|a| a.get(&TTL_ATTACHMENT_KEY.as_bytes()))
+        {
+            match str::from_utf8(ttl.as_slice()).map(str::parse::<f64>) {
+                Ok(Ok(secs)) if secs > 0.0 => return Some(Duration::from_secs_f64(secs)),
+                _ => tracing::warn!(
+                    "Storage '{}' received an invalid `{}` attachment for {}",
+                    self.name,
+                    TTL_ATTACHMENT_KEY,
+                    key
+                ),
+            }
+        }
+        self.configured_lifespan(key)
+    }
+
+    fn configured_lifespan(&self, key: &OwnedKeyExpr) -> Option<Duration> {
+        self.lifespans
+            .iter()
+            .filter(|l| l.key_expr.includes(key))
+            .map(|l| l.lifespan)
+            .min()
+    }
+
+    async fn update_expiration(&self, key: &OwnedKeyExpr, sample: &Sample, stored: &Sample) {
+        let mut expirations = self.expirations.lock().await;
+        match (stored.kind, self.lifespan(key, sample)) {
+            (SampleKind::Put, Some(lifespan)) => {
+                expirations.insert(key.clone(), expiration(stored.timestamp.unwrap(), lifespan));
+            }
+            _ => expirations.remove(key),
+        }
+    }
+
+    // Schedules the expiration of the values stored before a restart, according to the configured lifespans
+    async fn initialize_expirations(&self) {
+        if self.lifespans.is_empty() {
+            return;
+        }
+        let entries = match self.storage.lock().await.get_all_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(
+                    "Storage '{}' raised an error while retrieving keys: {}",
+                    self.name,
+                    e
+                );
+                return;
+            }
+        };
+        let mut expirations = self.expirations.lock().await;
+        for (key, timestamp) in entries {
+            let key = match key {
+                Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
+                None => self.strip_prefix.clone().unwrap(),
+            };
+            if self.is_tombstone(&key, &timestamp).await {
+                continue;
+            }
+            if let Some(lifespan) = self.configured_lifespan(&key) {
+                expirations.insert(key, expiration(timestamp, lifespan));
+            }
+        }
+    }
+
+    // Logs the latest change of each key stored or deleted before a restart, if the storage isn't replicated
+    async fn initialize_changes(&self) {
+        if self.replication.is_some() {
+            return;
+        }
+        let entries = match self.storage.lock().await.get_all_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(
+                    "Storage '{}' raised an error while retrieving keys: {}",
+                    self.name,
+                    e
+                );
+                Vec::new()
+            }
+        };
+        let deletions: Vec<(OwnedKeyExpr, Timestamp)> = self
+            .tombstones
+            .read()
+            .await
+            .key_value_pairs()
+            .map(|(key, timestamp)| (key, *timestamp))
+            .collect();
+        let mut changes = self.changes.lock().await;
+        for (key, timestamp) in entries {
+            let key = match key {
+                Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
+                None => self.strip_prefix.clone().unwrap(),
+            };
+            changes.insert_if_newer(key, timestamp);
+        }
+        for (key, timestamp) in deletions {
+            changes.insert_if_newer(key, timestamp);
+        }
+    }
+
+    // Deletes the expired values. The deletions are timestamped with the expiration time of the values,
+    // so that all the replicas of the storage log the same deletions.
+    async fn expire(&self) {
+        let now = NTP64::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap());
+        let expired = self.expirations.lock().await.pop_expired(&now);
+        for (key, expiration) in expired {
+            tracing::trace!("[STORAGE] Value of {} expired", key);
+            let mut sample =
+                Sample::new(KeyExpr::from(key), Value::empty()).with_timestamp(expiration);
+            sample.kind = SampleKind::Delete;
+            self.process_sample(sample).await;
+        }
+    }
+
+    // Returns the keys to evict to store a sample within the limits of the storage, None if the sample is rejected
+    async fn admit(&self, key: &OwnedKeyExpr, sample: &Sample) -> Option<Vec<OwnedKeyExpr>> {
+        let Some(usage) = &self.usage else {
+            return Some(Vec::new());
+        };
+        let mut usage = usage.lock().await;
+        let evicted = usage.admit(key, sample.value.payload.len());
+        if evicted.is_none() {
+            tracing::debug!(
+                "Storage '{}' rejected a value for {}: its limits are reached",
+                self.name,
+                key
+            );
+            usage.record_rejection();
+        }
+        evicted
+    }
+
+    async fn update_usage(&self, key: &OwnedKeyExpr, stored: &Sample) {
+        if let Some(usage) = &self.usage {
+            let mut usage = usage.lock().await;
+            match stored.kind {
+                SampleKind::Put => usage.insert(
+                    key.clone(),
+                    stored.value.payload.len(),
+                    stored.timestamp.unwrap(),
+                ),
+                SampleKind::Delete => usage.remove(key),
+            }
+        }
+    }
+
+    // Removes the evicted values from the storage. Evictions are local to the storage:
+    // they are neither marked with tombstones nor propagated to the replicas.
+    async fn evict(&self, evicted: Vec<OwnedKeyExpr>) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        for key in evicted {
+            let Some(timestamp) = self.get_current_timestamp(&key).await else {
+                continue;
+            };
+            let stripped_key = match self.strip_prefix(&key.clone().into()) {
+                Ok(stripped) => stripped,
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    continue;
+                }
+            };
+            tracing::trace!("[STORAGE] Value of {} evicted", key);
+            if let Err(e) = self
+                .storage
+                .lock()
+                .await
+                .delete(stripped_key, timestamp)
+                .await
+            {
+                tracing::warn!(
+                    "Storage '{}' raised an error evicting {}: {}",
+                    self.name,
+                    key,
+                    e
+                );
+                continue;
             }
+            self.expirations.lock().await.remove(&key);
+            self.changes.lock().await.remove(&key);
+            let mut usage = usage.lock().await;
+            usage.remove(&key);
+            usage.record_eviction();
         }
     }
 
+    // Initializes the usage with the values stored before a restart
+    async fn initialize_usage(&self) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        let mut storage = self.storage.lock().await;
+        let entries = match storage.get_all_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(
+                    "Storage '{}' raised an error while retrieving keys: {}",
+                    self.name,
+                    e
+                );
+                return;
+            }
+        };
+        let mut usage = usage.lock().await;
+        for (stripped_key, timestamp) in entries {
+            let key = match &stripped_key {
+                Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.clone().into()),
+                None => self.strip_prefix.clone().unwrap(),
+            };
+            if self.is_tombstone(&key, &timestamp).await {
+                continue;
+            }
+            let bytes = match storage.get(stripped_key, "").await {
+                Ok(stored) => stored.last().map_or(0, |s| s.value.payload.len()),
+                Err(e) => {
+                    tracing::warn!(
+                        "Storage '{}' raised an error while retrieving {}: {}",
+                        self.name,
+                        key,
+                        e
+                    );
+                    continue;
+                }
+            };
+            usage.insert(key, bytes, timestamp);
+        }
+    }
+
+    // The admin status of the storage, completed with its alignment and usage
+    async fn get_status(&self) -> serde_json::Value {
+        let mut status = self.storage.lock().await.get_admin_status();
+        if let Some(status) = status.as_object_mut() {
+            if let Some(replication) = &self.replication {
+                let alignment = replication.alignment.read().await;
+                status.insert(
+                    "alignment".into(),
+                    serde_json::to_value(&*alignment).unwrap(),
+                );
+            }
+            if let Some(usage) = &self.usage {
+                status.insert("usage".into(), usage.lock().await.to_json_value());
+            }
+        }
+        status
+    }
+
+    // Exports the stored values to a snapshot file. The storage is locked during the export,
+    // and no sample is processed meanwhile, so that the snapshot is consistent.
+    // The keys listed by the storage but removed in the meantime are skipped, and the file
+    // is written on blocking tasks, as the values of each key are retrieved.
+    async fn export(&self, path: &Path) -> ZResult<usize> {
+        tracing::debug!("Exporting storage '{}' to {}", self.name, path.display());
+        // the complete history is exported if the storage keeps it
+        let parameters = if self.capability.history.eq(&History::All) {
+            "_time=[..]"
+        } else {
+            ""
+        };
+        let mut storage = self.storage.lock().await;
+        let (path, key_expr) = (path.to_path_buf(), self.key_expr.clone());
+        let mut writer =
+            task::spawn_blocking(move || SnapshotWriter::create(&path, &key_expr)).await?;
+        for (key, _ts) in storage.get_all_entries().await? {
+            let full_key = match &key {
+                Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
+                None => self.strip_prefix.clone().unwrap(),
+            };
+            let data = match storage.get(key, parameters).await {
+                Ok(data) => data,
+                Err(e) => {
+                    tracing::warn!(
+                        "Storage '{}' skipped {} in its export: {}",
+                        self.name,
+                        full_key,
+                        e
+                    );
+                    continue;
+                }
+            };
+            writer = task::spawn_blocking(move || {
+                for data in &data {
+                    writer.write(&full_key, data)?;
+                }
+                ZResult::Ok(writer)
+            })
+            .await?;
+        }
+        task::spawn_blocking(move || writer.finish()).await
+    }
+
+    // Imports the values of a snapshot file with their original timestamps, so that they are
+    // stored and propagated to the replicas as if they were received from their publishers.
+    // The snapshot is read twice: it is entirely validated first, so that an invalid snapshot
+    // is never partially imported, then imported without being loaded in memory.
+    async fn import(&self, path: &Path) -> ZResult<usize> {
+        tracing::debug!("Importing {} into storage '{}'", path.display(), self.name);
+        SnapshotReader::open(path)?.validate()?;
+        let mut imported = 0;
+        for sample in SnapshotReader::open(path)? {
+            let sample = sample?;
+            if !self.key_expr.includes(&sample.key_expr) {
+                tracing::warn!(
+                    "Storage '{}' ignored the imported value of {}: it doesn't match '{}'",
+                    self.name,
+                    sample.key_expr,
+                    self.key_expr
+                );
+                continue;
+            }
+            self.process_sample(sample).await;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
     async fn mark_tombstone(&self, key_expr: &OwnedKeyExpr, timestamp: Timestamp) {
         // @TODO: change into a better store that does incremental writes
         let mut tombstones = self.tombstones.write().await;
@@ -424,6 +919,12 @@ impl StorageService {
         weight.is_some() && weight.unwrap() > timestamp
     }
 
+    // Whether the entry of a key is its deletion, as the storages may keep the timestamps of the
+    // deleted keys in their entries
+    async fn is_tombstone(&self, key_expr: &OwnedKeyExpr, timestamp: &Timestamp) -> bool {
+        self.tombstones.read().await.weight_at(key_expr) == Some(timestamp)
+    }
+
     async fn ovderriding_wild_update(
         &self,
         key_expr: &OwnedKeyExpr,
@@ -499,95 +1000,398 @@ impl StorageService {
             }
         };
         tracing::trace!("[STORAGE] Processing query on key_expr: {}", q.key_expr());
-        if q.key_expr().is_wild() {
+        match q.parameters().get_parameters([CHANGES_KEY, WRITE_KEY]) {
+            Ok([None, None]) => (),
+            Ok([Some(cursor), None]) => {
+                self.reply_changes(&q, &cursor).await;
+                return;
+            }
+            Ok([None, Some(_)]) => {
+                self.reply_writes(&q).await;
+                return;
+            }
+            Ok(_) => {
+                let error = format!("`{CHANGES_KEY}` and `{WRITE_KEY}` can't be combined");
+                self.reply_error(&q, error.into()).await;
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("Storage '{}' received an invalid query: {}", self.name, e);
+                self.reply_error(&q, e.to_string().into()).await;
+                return;
+            }
+        }
+        let pagination = match Pagination::from_parameters(q.parameters()) {
+            Ok(pagination) => pagination,
+            Err(e) => {
+                tracing::warn!("Storage '{}' received an invalid query: {}", self.name, e);
+                self.reply_error(&q, e.to_string().into()).await;
+                return;
+            }
+        };
+        let filter = match ValueFilter::from_parameters(q.parameters()) {
+            Ok(filter) => filter,
+            Err(e) => {
+                tracing::warn!("Storage '{}' received an invalid query: {}", self.name, e);
+                self.reply_error(&q, e.to_string().into()).await;
+                return;
+            }
+        };
+        let mut keys = if q.key_expr().is_wild() {
             // resolve key expr into individual keys
-            let matching_keys = self.get_matching_keys(q.key_expr()).await;
-            let mut storage = self.storage.lock().await;
-            for key in matching_keys {
-                let stripped_key = match self.strip_prefix(&key.clone().into()) {
-                    Ok(k) => k,
+            self.get_matching_keys(q.key_expr()).await
+        } else {
+            vec![q.key_expr().clone().into()]
+        };
+        // The replies are sent by chunks, retrieved without replying so that the storage isn't locked meanwhile.
+        // Each chunk resumes after the last reply of the previous one, like the `_after` continuation token,
+        // which needs the keys to be sorted.
+        let mut pagination = pagination;
+        if pagination.order.is_none() {
+            pagination.order = Some(KeyOrder::Ascending);
+        }
+        let mut remaining = pagination.limit.unwrap_or(usize::MAX);
+        while remaining > 0 {
+            let chunk_size = remaining.min(REPLIES_CHUNK_SIZE);
+            pagination.limit = Some(chunk_size);
+            pagination.select_keys(&mut keys);
+            let mut stripped_keys = Vec::with_capacity(keys.len());
+            for key in &keys {
+                match self.strip_prefix(&key.into()) {
+                    Ok(k) => stripped_keys.push(k),
                     Err(e) => {
                         tracing::error!("{}", e);
                         // @TODO: return error when it is supported
                         return;
                     }
+                }
+            }
+            let chunk = {
+                let mut storage = self.storage.lock().await;
+                // the parts of the filter not evaluated by the backend are evaluated on the retrieved data
+                let filter = filter.residual(storage.filter_pushdown(&filter));
+                let stored_data = match storage.get_stream(stripped_keys, q.parameters()).await {
+                    Ok(stored_data) => stored_data,
+                    Err(e) => {
+                        tracing::warn!("Storage '{}' raised an error on query: {}", self.name, e);
+                        return;
+                    }
                 };
-                match storage.get(stripped_key, q.parameters()).await {
-                    Ok(stored_data) => {
-                        for entry in stored_data {
-                            let sample = Sample::new(key.clone(), entry.value)
-                                .with_timestamp(entry.timestamp);
-                            // apply outgoing interceptor on results
-                            let sample = if let Some(ref interceptor) = self.out_interceptor {
-                                interceptor(sample)
-                            } else {
-                                sample
-                            };
-                            if let Err(e) = q.reply(Ok(sample)).res().await {
+                let name = &self.name;
+                let strip_prefix = &self.strip_prefix;
+                let stored_data = stored_data
+                    .filter_map(move |entry| {
+                        future::ready(match entry {
+                            Ok((key, data)) => filter.apply(data).map(|data| {
+                                let key = match key {
+                                    Some(key) => {
+                                        StorageService::get_prefixed(strip_prefix, &key.into())
+                                    }
+                                    None => strip_prefix.clone().unwrap(),
+                                };
+                                (key, data)
+                            }),
+                            Err(e) => {
                                 tracing::warn!(
-                                    "Storage '{}' raised an error replying a query: {}",
-                                    self.name,
+                                    "Storage '{}' raised an error on query: {}",
+                                    name,
                                     e
-                                )
+                                );
+                                None
                             }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!("Storage'{}' raised an error on query: {}", self.name, e)
-                    }
+                        })
+                    })
+                    .boxed();
+                pagination.select_page(stored_data).await
+            };
+            remaining -= chunk.len();
+            let next = match chunk.last() {
+                Some((key, entry)) if chunk.len() == chunk_size => Some(PageToken {
+                    key: key.clone(),
+                    timestamp: Some(entry.timestamp),
+                }),
+                _ => None,
+            };
+            for (key, entry) in chunk {
+                if let Some(usage) = &self.usage {
+                    usage.lock().await.touch(&key);
+                }
+                let sample = Sample::new(key, entry.value).with_timestamp(entry.timestamp);
+                // apply outgoing interceptor on results
+                let sample = if let Some(ref interceptor) = self.out_interceptor {
+                    interceptor(sample)
+                } else {
+                    sample
                 };
+                if let Err(e) = q.reply(Ok(sample)).res().await {
+                    tracing::warn!(
+                        "Storage '{}' raised an error replying a query: {}",
+                        self.name,
+                        e
+                    )
+                }
             }
-            drop(storage);
+            match next {
+                Some(next) => {
+                    pagination.offset = 0;
+                    pagination.after = Some(next);
+                }
+                None => break,
+            }
+        }
+    }
+
+    // Apply a batch of writes if all their conditions are met, replying the written samples or the conflicts (see `writes`).
+    // The batch is applied while processing the query, hence without any other write in between.
+    async fn reply_writes(&self, q: &zenoh::queryable::Query) {
+        let writes = match parse_writes(q.value()) {
+            Ok(writes) => writes,
+            Err(e) => {
+                tracing::warn!("Storage '{}' received an invalid query: {}", self.name, e);
+                self.reply_error(q, e.to_string().into()).await;
+                return;
+            }
+        };
+        if let Some(write) = writes
+            .iter()
+            .find(|w| !self.key_expr.includes(&w.key) || !q.key_expr().intersects(&w.key))
+        {
+            let error = format!(
+                "Invalid write on '{}': the key must match both the storage and the query",
+                write.key
+            );
+            self.reply_error(q, error.into()).await;
+            return;
+        }
+        let mut conflicts = Vec::new();
+        let mut current_timestamps = Vec::new();
+        for write in &writes {
+            let current = self.get_current_timestamp(&write.key).await;
+            if let Some(condition) = &write.condition {
+                if !condition.is_met(current.as_ref()) {
+                    conflicts.push((write.key.clone(), current));
+                }
+            }
+            current_timestamps.extend(current);
+            // a write older than the deletion of its key would be dropped
+            current_timestamps.extend(self.tombstones.read().await.weight_at(&write.key).copied());
+        }
+        if !conflicts.is_empty() {
+            tracing::debug!(
+                "Storage '{}' rejected conflicting writes: {:?}",
+                self.name,
+                conflicts
+            );
+            let error =
+                Value::from(conflicts_to_json(&conflicts)).encoding(KnownEncoding::AppJson.into());
+            self.reply_error(q, error).await;
+            return;
+        }
+        let timestamp = self.new_timestamp(&current_timestamps);
+        let mut samples = Vec::with_capacity(writes.len());
+        let mut failure = None;
+        for write in writes {
+            let mut sample = Sample::new(KeyExpr::from(write.key.clone()), write.value)
+                .with_timestamp(timestamp);
+            sample.kind = write.kind;
+            // the key of a write isn't wild, hence a single result
+            match self.process_sample(sample.clone()).await.pop() {
+                Some(Ok(StorageInsertionResult::Outdated)) => {
+                    failure = Some((write.key, "a more recent value is stored".to_string()));
+                }
+                Some(Ok(_)) => samples.push(sample),
+                Some(Err(e)) => failure = Some((write.key, e.to_string())),
+                None => failure = Some((write.key, "the key isn't stored".to_string())),
+            }
+            if failure.is_some() {
+                break;
+            }
+        }
+        for sample in samples {
+            if let Err(e) = q.reply(Ok(sample)).res().await {
+                tracing::warn!(
+                    "Storage '{}' raised an error replying a query: {}",
+                    self.name,
+                    e
+                )
+            }
+        }
+        if let Some((key, reason)) = failure {
+            tracing::warn!(
+                "Storage '{}' failed to apply a write on '{}': {}",
+                self.name,
+                key,
+                reason
+            );
+            let error = format!(
+                "Write on '{key}' failed: {reason}. The writes before it were applied, the following ones were not"
+            );
+            self.reply_error(q, error.into()).await;
+        }
+    }
+
+    // The timestamp of the value stored for a key, if any
+    async fn get_current_timestamp(&self, key: &OwnedKeyExpr) -> Option<Timestamp> {
+        let stripped_key = match self.strip_prefix(&key.into()) {
+            Ok(stripped) => stripped,
+            Err(e) => {
+                tracing::error!("{}", e);
+                return None;
+            }
+        };
+        match self.storage.lock().await.get(stripped_key, "").await {
+            Ok(stored_data) => stored_data.into_iter().map(|data| data.timestamp).max(),
+            Err(e) => {
+                tracing::warn!("Storage '{}' raised an error on query: {}", self.name, e);
+                None
+            }
+        }
+    }
+
+    // A new timestamp, greater than the given ones so that the writes are not outdated
+    fn new_timestamp(&self, observed: &[Timestamp]) -> Timestamp {
+        match self.session.hlc() {
+            Some(hlc) => {
+                for timestamp in observed {
+                    if let Err(e) = hlc.update_with_timestamp(timestamp) {
+                        tracing::warn!("Storage '{}': {}", self.name, e);
+                    }
+                }
+                hlc.new_timestamp()
+            }
+            None => zenoh::time::new_reception_timestamp(),
+        }
+    }
+
+    async fn reply_error(&self, q: &zenoh::queryable::Query, error: Value) {
+        if let Err(e) = q.reply(Err(error)).res().await {
+            tracing::warn!(
+                "Storage '{}' raised an error replying a query: {}",
+                self.name,
+                e
+            )
+        }
+    }
+
+    // Reply the latest change of each key matching the query after the cursor, in timestamp order.
+    // The kind of each change is attached to the reply, as replies are always received as puts.
+    async fn reply_changes(&self, q: &zenoh::queryable::Query, cursor: &str) {
+        let cursor = if cursor.is_empty() {
+            None
         } else {
-            let stripped_key = match self.strip_prefix(q.key_expr()) {
-                Ok(k) => k,
+            match Timestamp::from_str(cursor) {
+                Ok(cursor) => Some(cursor),
                 Err(e) => {
-                    tracing::error!("{}", e);
-                    // @TODO: return error when it is supported
+                    tracing::warn!(
+                        "Storage '{}' received an invalid `{}` cursor: {:?}",
+                        self.name,
+                        CHANGES_KEY,
+                        e
+                    );
+                    let error = format!("Invalid `{CHANGES_KEY}` cursor '{cursor}'");
+                    self.reply_error(q, error.into()).await;
                     return;
                 }
-            };
-            let mut storage = self.storage.lock().await;
-            match storage.get(stripped_key, q.parameters()).await {
-                Ok(stored_data) => {
-                    for entry in stored_data {
-                        let sample = Sample::new(q.key_expr().clone(), entry.value)
-                            .with_timestamp(entry.timestamp);
-                        // apply outgoing interceptor on results
-                        let sample = if let Some(ref interceptor) = self.out_interceptor {
-                            interceptor(sample)
-                        } else {
-                            sample
-                        };
-                        if let Err(e) = q.reply(Ok(sample)).res().await {
-                            tracing::warn!(
-                                "Storage '{}' raised an error replying a query: {}",
-                                self.name,
-                                e
-                            )
-                        }
-                    }
+            }
+        };
+        // the deletions after an expired cursor might have been garbage collected
+        if let Some(cursor) = &cursor {
+            let horizon = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .saturating_sub(self.tombstones_lifespan);
+            if *cursor.get_time() < NTP64::from(horizon) {
+                let error = format!(
+                    "Expired `{CHANGES_KEY}` cursor '{cursor}': the changes since then are no longer known, resync with all the changes"
+                );
+                self.reply_error(q, error.into()).await;
+                return;
+            }
+        }
+        let mut changes = Vec::new();
+        for entry in self.get_changes_after(cursor.as_ref()).await {
+            if !q.key_expr().intersects(&entry.key) {
+                continue;
+            }
+            if self.is_tombstone(&entry.key, &entry.timestamp).await {
+                let mut sample = Sample::new(KeyExpr::from(entry.key), Value::empty())
+                    .with_timestamp(entry.timestamp);
+                sample.kind = SampleKind::Delete;
+                changes.push(sample);
+                continue;
+            }
+            let stripped_key = match self.strip_prefix(&entry.key.clone().into()) {
+                Ok(stripped) => stripped,
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    continue;
                 }
+            };
+            // the log might lag behind the storage: the stored value is the latest change
+            let stored_data = match self.storage.lock().await.get(stripped_key, "").await {
+                Ok(stored_data) => stored_data,
                 Err(e) => {
-                    tracing::warn!("Storage '{}' raised an error on query: {e}", self.name);
+                    tracing::warn!("Storage '{}' raised an error on query: {}", self.name, e);
+                    continue;
                 }
             };
+            for data in stored_data {
+                if cursor.map_or(true, |cursor| data.timestamp > cursor) {
+                    changes.push(
+                        Sample::new(KeyExpr::from(entry.key.clone()), data.value)
+                            .with_timestamp(data.timestamp),
+                    );
+                }
+            }
+        }
+        changes.sort_by_key(|sample| sample.timestamp);
+        for sample in changes {
+            let mut attachment = AttachmentBuilder::new();
+            attachment.insert(KIND_ATTACHMENT_KEY, sample.kind.to_string().as_str());
+            attachment.insert(COALESCED_ATTACHMENT_KEY, "true");
+            let sample = if let Some(ref interceptor) = self.out_interceptor {
+                interceptor(sample)
+            } else {
+                sample
+            };
+            if let Err(e) = q
+                .reply(Ok(sample.with_attachment(attachment.build())))
+                .res()
+                .await
+            {
+                tracing::warn!(
+                    "Storage '{}' raised an error replying a query: {}",
+                    self.name,
+                    e
+                )
+            }
         }
     }
 
+    // The latest change of each key after the given timestamp, in timestamp order.
+    // A replica relies on the log of its `Snapshotter`, other storages on the log of their changes.
+    async fn get_changes_after(&self, cursor: Option<&Timestamp>) -> Vec<LogEntry> {
+        if let Some(replication) = &self.replication {
+            return replication.snapshotter.get_log_after(cursor).await;
+        }
+        self.changes.lock().await.after(cursor)
+    }
+
     async fn get_matching_keys(&self, key_expr: &KeyExpr<'_>) -> Vec<OwnedKeyExpr> {
         let mut result = Vec::new();
         // @TODO: if cache exists, use that to get the list
         let storage = self.storage.lock().await;
         match storage.get_all_entries().await {
             Ok(entries) => {
-                for (k, _ts) in entries {
+                for (k, ts) in entries {
                     // @TODO: optimize adding back the prefix (possible inspiration from https://github.com/eclipse-zenoh/zenoh/blob/0.5.0-beta.9/backends/traits/src/utils.rs#L79)
                     let full_key = match k {
                         Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
                         None => self.strip_prefix.clone().unwrap(),
                     };
-                    if key_expr.intersects(&full_key.clone()) {
+                    if key_expr.intersects(&full_key.clone())
+                        && !self.is_tombstone(&full_key, &ts).await
+                    {
                         result.push(full_key);
                     }
                 }
@@ -670,6 +1474,14 @@ impl StorageService {
     }
 }
 
+// The expiration timestamp of a value: its lifespan after its own timestamp, with the same identifier
+fn expiration(timestamp: Timestamp, lifespan: Duration) -> Timestamp {
+    Timestamp::new(
+        *timestamp.get_time() + NTP64::from(lifespan),
+        *timestamp.get_id(),
+    )
+}
+
 fn serialize_update(update: &Update) -> String {
     let result = (
         update.kind.to_string(),
@@ -704,6 +1516,7 @@ struct GarbageCollectionEvent {
     config: GarbageCollectionConfig,
     tombstones: Arc<RwLock<KeBoxTree<Timestamp, NonWild, KeyedSetProvider>>>,
     wildcard_updates: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
+    changes: Arc<Mutex<KeyTimestamps>>,
 }
 
 #[async_trait]
@@ -717,16 +1530,20 @@ impl Timed for GarbageCollectionEvent {
         let mut tombstones = self.tombstones.write().await;
         let mut wildcard_updates = self.wildcard_updates.write().await;
 
-        let mut to_be_removed = HashSet::new();
+        let mut to_be_removed = HashMap::new();
         for (k, ts) in tombstones.key_value_pairs() {
             if ts.get_time() < &time_limit {
                 // mark key to be removed
-                to_be_removed.insert(k);
+                to_be_removed.insert(k, *ts);
             }
         }
-        for k in to_be_removed {
+        // the deletions are forgotten with their tombstones
+        let mut changes = self.changes.lock().await;
+        for (k, ts) in to_be_removed {
             tombstones.remove(&k);
+            changes.remove_if(&k, &ts);
         }
+        drop(changes);
 
         let mut to_be_removed = HashSet::new();
         for (k, update) in wildcard_updates.key_value_pairs() {
@@ -0,0 +1,265 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Snapshots of the content of a storage.
+//!
+//! A snapshot is exported or imported by querying the admin space of a storage with the
+//! `_export=<name>` or `_import=<name>` selector parameter:
+//! `@/router/<zid>/status/plugins/storage_manager/storages/<storage>?_export=demo`
+//!
+//! The snapshot of each storage is a file named `<storage>.snapshot` in the `<name>` directory of
+//! the `snapshots_dir` of the plugin (`$ZENOH_HOME/snapshots` by default), so that the storages
+//! selected by a wildcard are exported to distinct files. `<name>` is a relative path which can't
+//! leave `snapshots_dir`. As an export writes files on the disk of the router and an import changes
+//! the content of the storages, both require the `adminspace.permissions.write` permission.
+//!
+//! A snapshot file is made of [JSON lines](https://jsonlines.org):
+//! - the first line is a header: `{"format":"zenoh-storage-snapshot","version":1,"key_expr":"<storage key expression>"}`
+//! - each following line is a stored value: `{"key":"<key>","timestamp":"<timestamp>","encoding":"<encoding>","value":"<payload>"}`,
+//!   where `key` is the complete key of the value (the storage's `strip_prefix` is not applied),
+//!   `timestamp` is the HLC timestamp of the value (`<NTP64 time>/<HLC id>`) and `value` is its payload in standard base64.
+//!
+//! Values are imported with their original timestamp, as if they were received from their publishers.
+
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::StoredData;
+use zenoh_keyexpr::key_expr::OwnedKeyExpr;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The selector parameter exporting the content of a storage to the given file.
+pub const EXPORT_KEY: &str = "_export";
+/// The selector parameter importing the content of the given file into a storage.
+pub const IMPORT_KEY: &str = "_import";
+
+const SNAPSHOT_FORMAT: &str = "zenoh-storage-snapshot";
+const SNAPSHOT_VERSION: u64 = 1;
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+
+/// Resolves the file of the snapshot `name` of a storage in the snapshots directory.
+pub(crate) fn snapshot_path(dir: &Path, name: &str, storage: &str) -> ZResult<PathBuf> {
+    fn is_relative_path(path: &str) -> bool {
+        let mut components = Path::new(path).components().peekable();
+        components.peek().is_some() && components.all(|c| matches!(c, Component::Normal(_)))
+    }
+
+    if !is_relative_path(name) {
+        bail!(
+            "Invalid snapshot name '{}': it must be a relative path without '..'",
+            name
+        );
+    }
+    if !is_relative_path(storage) || storage.contains(std::path::MAIN_SEPARATOR) {
+        bail!("Storage '{}' can't be named as a snapshot file", storage);
+    }
+    // the extension is appended, as the name of a storage may contain dots
+    Ok(dir
+        .join(name)
+        .join(format!("{storage}.{SNAPSHOT_EXTENSION}")))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u64,
+    key_expr: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    timestamp: String,
+    encoding: String,
+    value: String,
+}
+
+// Writes a snapshot to a temporary file, renamed once complete so that a snapshot file is never partially written
+pub(crate) struct SnapshotWriter {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    file: BufWriter<File>,
+    entries: usize,
+}
+
+impl SnapshotWriter {
+    pub(crate) fn create(path: &Path, key_expr: &OwnedKeyExpr) -> ZResult<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| zerror!("Unable to create directory '{}': {}", dir.display(), e))?;
+        }
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let file = File::create(&tmp_path)
+            .map_err(|e| zerror!("Unable to create snapshot '{}': {}", path.display(), e))?;
+        let mut writer = SnapshotWriter {
+            path: path.to_path_buf(),
+            tmp_path,
+            file: BufWriter::new(file),
+            entries: 0,
+        };
+        let header = Header {
+            format: SNAPSHOT_FORMAT.into(),
+            version: SNAPSHOT_VERSION,
+            key_expr: key_expr.to_string(),
+        };
+        writer.write_line(&header)?;
+        Ok(writer)
+    }
+
+    pub(crate) fn write(&mut self, key: &OwnedKeyExpr, data: &StoredData) -> ZResult<()> {
+        let entry = Entry {
+            key: key.to_string(),
+            timestamp: data.timestamp.to_string(),
+            encoding: data.value.encoding.to_string(),
+            value: b64_std_engine.encode(data.value.payload.contiguous()),
+        };
+        self.write_line(&entry)?;
+        self.entries += 1;
+        Ok(())
+    }
+
+    // Returns the number of values written to the snapshot
+    pub(crate) fn finish(mut self) -> ZResult<usize> {
+        self.file.flush()?;
+        self.file.get_ref().sync_all()?;
+        std::fs::rename(&self.tmp_path, &self.path)
+            .map_err(|e| zerror!("Unable to write snapshot '{}': {}", self.path.display(), e))?;
+        Ok(self.entries)
+    }
+
+    fn write_line<T: Serialize>(&mut self, line: &T) -> ZResult<()> {
+        serde_json::to_writer(&mut self.file, line)?;
+        self.file.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+impl Drop for SnapshotWriter {
+    fn drop(&mut self) {
+        // nothing to remove if the snapshot was renamed
+        let _ = std::fs::remove_file(&self.tmp_path);
+    }
+}
+
+/// Reads the values of a snapshot as timestamped samples, one line at a time.
+pub(crate) struct SnapshotReader {
+    path: PathBuf,
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl SnapshotReader {
+    pub(crate) fn open(path: &Path) -> ZResult<Self> {
+        let file = File::open(path)
+            .map_err(|e| zerror!("Unable to open snapshot '{}': {}", path.display(), e))?;
+        let mut lines = BufReader::new(file).lines();
+        let header: Header = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)
+                .map_err(|e| zerror!("Invalid snapshot header in '{}': {}", path.display(), e))?,
+            None => bail!("Snapshot '{}' is empty", path.display()),
+        };
+        if header.format != SNAPSHOT_FORMAT || header.version != SNAPSHOT_VERSION {
+            bail!(
+                "Unsupported snapshot format in '{}': {} version {}",
+                path.display(),
+                header.format,
+                header.version
+            );
+        }
+        Ok(SnapshotReader {
+            path: path.to_path_buf(),
+            lines,
+            // the header is line 1
+            line_number: 1,
+        })
+    }
+
+    /// Reads the whole snapshot, so that an invalid snapshot is detected before being imported.
+    pub(crate) fn validate(self) -> ZResult<()> {
+        for sample in self {
+            sample?;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for SnapshotReader {
+    type Item = ZResult<Sample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line_number += 1;
+            if line.is_empty() {
+                continue;
+            }
+            let sample = serde_json::from_str(&line)
+                .map_err(|e| e.into())
+                .and_then(entry_to_sample)
+                .map_err(|e| {
+                    zerror!(
+                        "Invalid snapshot entry at {}:{}: {}",
+                        self.path.display(),
+                        self.line_number,
+                        e
+                    )
+                    .into()
+                });
+            return Some(sample);
+        }
+    }
+}
+
+fn entry_to_sample(entry: Entry) -> ZResult<Sample> {
+    let key_expr = OwnedKeyExpr::from_str(&entry.key)?;
+    let timestamp = Timestamp::from_str(&entry.timestamp)
+        .map_err(|e| zerror!("invalid timestamp '{}': {:?}", entry.timestamp, e))?;
+    let payload = b64_std_engine
+        .decode(entry.value)
+        .map_err(|e| zerror!("invalid value: {}", e))?;
+    let value = Value::new(payload.into()).encoding(Encoding::from(entry.encoding));
+    Ok(Sample::new(KeyExpr::from(key_expr), value).with_timestamp(timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_paths() {
+        let dir = Path::new("snapshots");
+        assert_eq!(
+            snapshot_path(dir, "daily/1", "demo").unwrap(),
+            dir.join("daily/1/demo.snapshot")
+        );
+        // storages differing by the part of their name after a dot have distinct files
+        let a = snapshot_path(dir, "daily", "a.x").unwrap();
+        let b = snapshot_path(dir, "daily", "a.y").unwrap();
+        assert_eq!(a, dir.join("daily/a.x.snapshot"));
+        assert_eq!(b, dir.join("daily/a.y.snapshot"));
+        assert!(snapshot_path(dir, "../daily", "demo").is_err());
+        assert!(snapshot_path(dir, "/daily", "demo").is_err());
+        assert!(snapshot_path(dir, "daily", "..").is_err());
+    }
+}
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use async_std::sync::Arc;
+use std::path::PathBuf;
 use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
 use zenoh_result::ZResult;
@@ -21,6 +22,10 @@ pub use super::replica::{Replica, StorageService};
 pub enum StorageMessage {
     Stop,
     GetStatus(async_std::channel::Sender<serde_json::Value>),
+    // Export a snapshot of the storage to a file, replying the number of exported values
+    Export(PathBuf, async_std::channel::Sender<ZResult<usize>>),
+    // Import a snapshot from a file into the storage, replying the number of imported values
+    Import(PathBuf, async_std::channel::Sender<ZResult<usize>>),
 }
 
 pub(crate) async fn start_storage(
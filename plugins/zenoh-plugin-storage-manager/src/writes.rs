@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Conditional and batched writes to a storage.
+//!
+//! A batch of writes is sent to a storage as a query with the `_write` selector parameter,
+//! on a key expression intersecting all the written keys. The value of the query is a JSON array of writes:
+//! `[{"key":"<key>","value":"<payload>","encoding":"<encoding>","condition":<condition>}, {"key":"<key>","delete":true}]`
+//! where `value` is the payload in standard base64, and the optional `condition` is either `"absent"`
+//! (the key must not have a value) or `{"timestamp":"<timestamp>"}` (the value of the key must have this timestamp).
+//!
+//! If all the conditions are met, the writes are applied together with the same new timestamp,
+//! without any other write in between, and the storage replies a sample per write with this timestamp.
+//! Otherwise, no write is applied and the storage replies an error with the current timestamps of the conflicting keys:
+//! `{"conflicts":[{"key":"<key>","timestamp":"<timestamp>"|null}]}`.
+//! If a write can't be stored, e.g. when the limits of the storage are reached, the following writes are not applied:
+//! the storage replies the samples of the writes applied before it, then an error naming the failed write.
+//!
+//! The writes are stored and propagated to the replicas of the storage, but not published to subscribers.
+
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use std::str::FromStr;
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+pub(crate) use zenoh_ext::write_batch_wire::WRITE_KEY;
+use zenoh_ext::write_batch_wire::{WireCondition, WireConflict, WireConflicts, WireWrite};
+use zenoh_keyexpr::key_expr::OwnedKeyExpr;
+use zenoh_result::{bail, zerror, ZResult};
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Condition {
+    Absent,
+    Timestamp(Timestamp),
+}
+
+impl Condition {
+    pub(crate) fn is_met(&self, current: Option<&Timestamp>) -> bool {
+        match self {
+            Condition::Absent => current.is_none(),
+            Condition::Timestamp(expected) => current == Some(expected),
+        }
+    }
+}
+
+pub(crate) struct Write {
+    pub(crate) key: OwnedKeyExpr,
+    pub(crate) kind: SampleKind,
+    pub(crate) value: Value,
+    pub(crate) condition: Option<Condition>,
+}
+
+pub(crate) fn parse_writes(value: Option<&Value>) -> ZResult<Vec<Write>> {
+    let Some(value) = value else {
+        bail!("A `{}` query must have a value", WRITE_KEY)
+    };
+    let writes: Vec<WireWrite> = serde_json::from_slice(&value.payload.contiguous())
+        .map_err(|e| zerror!("Invalid `{}` query value: {}", WRITE_KEY, e))?;
+    if writes.is_empty() {
+        bail!("A `{}` query must contain at least one write", WRITE_KEY)
+    }
+    writes
+        .into_iter()
+        .map(|write| {
+            let key = OwnedKeyExpr::from_str(&write.key)?;
+            if key.is_wild() {
+                bail!("Invalid write on '{}': the key can't be wild", key)
+            }
+            let condition = match write.condition {
+                None => None,
+                Some(WireCondition::Absent) => Some(Condition::Absent),
+                Some(WireCondition::Timestamp(ts)) => Some(Condition::Timestamp(
+                    Timestamp::from_str(&ts)
+                        .map_err(|e| zerror!("Invalid timestamp '{}': {:?}", ts, e))?,
+                )),
+            };
+            let (kind, value) = if write.delete {
+                (SampleKind::Delete, Value::empty())
+            } else {
+                let payload = b64_std_engine
+                    .decode(&write.value)
+                    .map_err(|e| zerror!("Invalid value for '{}': {}", key, e))?;
+                let value = Value::new(payload.into()).encoding(Encoding::from(write.encoding));
+                (SampleKind::Put, value)
+            };
+            Ok(Write {
+                key,
+                kind,
+                value,
+                condition,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn conflicts_to_json(conflicts: &[(OwnedKeyExpr, Option<Timestamp>)]) -> String {
+    let conflicts = WireConflicts {
+        conflicts: conflicts
+            .iter()
+            .map(|(key, timestamp)| WireConflict {
+                key: key.to_string(),
+                timestamp: timestamp.map(|ts| ts.to_string()),
+            })
+            .collect(),
+    };
+    serde_json::to_string(&conflicts).unwrap()
+}
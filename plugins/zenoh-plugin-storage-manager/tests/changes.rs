@@ -0,0 +1,141 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the change feed of storages -
+// 1. the latest change of each key is returned in timestamp order, with its kind attached
+// 2. only the changes after the given cursor are returned
+// 3. a cursor older than the lifespan of the tombstones is replied with an error
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::{ConsolidationMode, Reply};
+use zenoh::time::{new_reception_timestamp, Timestamp, NTP64};
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_changes(session: &zenoh::Session, selector: &str) -> Vec<(String, String, Timestamp)> {
+    let replies: Vec<Reply> = session
+        .get(selector)
+        .consolidation(ConsolidationMode::None)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{selector}': '{replies:?}'...");
+    replies
+        .into_iter()
+        .filter_map(|r| r.sample.ok())
+        .map(|s| {
+            let attachment = s.attachment().unwrap();
+            // the intermediate changes of each key are coalesced
+            let coalesced = attachment.get(&"coalesced".as_bytes()).unwrap();
+            assert_eq!(coalesced.as_slice(), b"true");
+            let kind = attachment.get(&"kind".as_bytes()).unwrap();
+            (
+                s.key_expr.as_str().to_string(),
+                String::from_utf8(kind.to_vec()).unwrap(),
+                s.timestamp.unwrap(),
+            )
+        })
+        .collect()
+}
+
+async fn test_changes() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        changes_test: {
+                            key_expr: "changes/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    for (key, value) in [("changes/test/a", "1"), ("changes/test/b", "2")] {
+        session.put(key, value).res().await.unwrap();
+        sleep(std::time::Duration::from_millis(10));
+    }
+    session.delete("changes/test/a").res().await.unwrap();
+    sleep(std::time::Duration::from_millis(100));
+
+    let changes = get_changes(&session, "changes/test/**?_changes").await;
+    let summary: Vec<(&str, &str)> = changes
+        .iter()
+        .map(|(key, kind, _)| (key.as_str(), kind.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![("changes/test/b", "PUT"), ("changes/test/a", "DELETE")]
+    );
+
+    let cursor = changes.last().unwrap().2;
+    assert!(
+        get_changes(&session, &format!("changes/test/**?_changes={cursor}"))
+            .await
+            .is_empty()
+    );
+
+    session.put("changes/test/c", "3").res().await.unwrap();
+    sleep(std::time::Duration::from_millis(100));
+
+    let changes = get_changes(&session, &format!("changes/test/**?_changes={cursor}")).await;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, "changes/test/c");
+    assert_eq!(changes[0].1, "PUT");
+
+    // the deletions after this cursor might have been garbage collected
+    let expired = Timestamp::new(NTP64(0), *new_reception_timestamp().get_id());
+    let replies: Vec<Reply> = session
+        .get(format!("changes/test/**?_changes={expired}"))
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    let error = replies[0].sample.as_ref().unwrap_err().to_string();
+    assert!(error.contains("Expired"), "{error}");
+
+    drop(storage);
+}
+
+#[test]
+fn changes_test() {
+    task::block_on(async { test_changes().await });
+}
@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the expiration of stored values -
+// 1. values expire after the lifespan configured for their key
+// 2. values expire after the TTL attached by their publisher
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh::sample::AttachmentBuilder;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_keys(session: &zenoh::Session, key_expr: &str) -> Vec<String> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut keys: Vec<String> = replies
+        .into_iter()
+        .filter_map(|r| r.sample.ok())
+        .map(|s| s.key_expr.as_str().to_string())
+        .collect();
+    keys.sort();
+    keys
+}
+
+async fn test_expiration() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        expiration_test: {
+                            key_expr: "expiration/test/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            lifespans: {
+                                "expiration/test/short/**": 1,
+                            },
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    session
+        .put("expiration/test/short/a", "1")
+        .res()
+        .await
+        .unwrap();
+    session
+        .put("expiration/test/long/b", "2")
+        .res()
+        .await
+        .unwrap();
+    let mut attachment = AttachmentBuilder::new();
+    attachment.insert("ttl", "1");
+    session
+        .put("expiration/test/long/c", "3")
+        .with_attachment(attachment.build())
+        .res()
+        .await
+        .unwrap();
+    sleep(std::time::Duration::from_millis(100));
+
+    let keys = get_keys(&session, "expiration/test/**").await;
+    assert_eq!(
+        keys,
+        vec![
+            "expiration/test/long/b",
+            "expiration/test/long/c",
+            "expiration/test/short/a"
+        ]
+    );
+
+    sleep(std::time::Duration::from_secs(3));
+
+    let keys = get_keys(&session, "expiration/test/**").await;
+    assert_eq!(keys, vec!["expiration/test/long/b"]);
+
+    drop(storage);
+}
+
+#[test]
+fn expiration_test() {
+    task::block_on(async { test_expiration().await });
+}
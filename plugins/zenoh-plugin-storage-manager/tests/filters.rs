@@ -0,0 +1,164 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the filters of the replies of a storage -
+// 1. `_filter` selects the JSON values meeting conditions on their fields
+// 2. `_encoding` selects the values by encoding
+// 3. `_fields` projects the JSON values on some of their fields
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &zenoh::Session, selector: &str) -> Vec<(String, String)> {
+    let replies: Vec<Reply> = session
+        .get(selector)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{selector}': '{replies:?}'...");
+    let mut data: Vec<(String, String)> = replies
+        .into_iter()
+        .filter_map(|r| r.sample.ok())
+        .map(|s| (s.key_expr.as_str().to_string(), s.value.to_string()))
+        .collect();
+    data.sort();
+    data
+}
+
+async fn get_keys(session: &zenoh::Session, selector: &str) -> Vec<String> {
+    get_data(session, selector)
+        .await
+        .into_iter()
+        .map(|(k, _)| k)
+        .collect()
+}
+
+async fn test_filters() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        filters_test: {
+                            key_expr: "filters/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    for (key, value) in [
+        (
+            "a",
+            r#"{"temp": 18.5, "room": "kitchen", "sensor": {"id": 1}}"#,
+        ),
+        (
+            "b",
+            r#"{"temp": 21, "room": "bedroom", "sensor": {"id": 2}}"#,
+        ),
+        ("c", r#"{"temp": 25, "room": "kitchen"}"#),
+    ] {
+        session
+            .put(format!("filters/test/{key}"), value)
+            .encoding(KnownEncoding::AppJson)
+            .res()
+            .await
+            .unwrap();
+    }
+    session
+        .put("filters/test/d", "not json")
+        .res()
+        .await
+        .unwrap();
+    sleep(std::time::Duration::from_millis(100));
+
+    let keys = get_keys(&session, "filters/test/**?_filter=temp>20").await;
+    assert_eq!(keys, vec!["filters/test/b", "filters/test/c"]);
+
+    let keys = get_keys(&session, "filters/test/**?_filter=temp>20,room==kitchen").await;
+    assert_eq!(keys, vec!["filters/test/c"]);
+
+    let keys = get_keys(&session, r#"filters/test/**?_filter=room!="kitchen""#).await;
+    assert_eq!(keys, vec!["filters/test/b"]);
+
+    let keys = get_keys(&session, "filters/test/**?_filter=sensor.id").await;
+    assert_eq!(keys, vec!["filters/test/a", "filters/test/b"]);
+
+    let keys = get_keys(&session, "filters/test/**?_encoding=text").await;
+    assert_eq!(keys, vec!["filters/test/d"]);
+
+    let data = get_data(
+        &session,
+        "filters/test/**?_filter=sensor.id<=1&_fields=room,sensor.id",
+    )
+    .await;
+    assert_eq!(
+        data,
+        vec![(
+            "filters/test/a".to_string(),
+            r#"{"room":"kitchen","sensor":{"id":1}}"#.to_string()
+        )]
+    );
+
+    // the projection doesn't change the values that aren't JSON objects
+    let data = get_data(&session, "filters/test/d?_fields=room").await;
+    assert_eq!(
+        data,
+        vec![("filters/test/d".to_string(), "not json".to_string())]
+    );
+
+    // an invalid filter is replied with an error
+    let replies: Vec<Reply> = session
+        .get("filters/test/**?_filter=temp>20,")
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].sample.is_err());
+
+    drop(storage);
+}
+
+#[test]
+fn filters_test() {
+    task::block_on(async { test_filters().await });
+}
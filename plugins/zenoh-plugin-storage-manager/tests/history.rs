@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the history of the memory backend -
+// 1. all the values are kept, up to `max_samples`
+// 2. only the latest value is returned without time range
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn put_data(session: &zenoh::Session, key_expr: &str, value: &str) {
+    println!("Putting Data ('{key_expr}': '{value}')...");
+    session.put(key_expr, value).res().await.unwrap();
+}
+
+async fn get_data(session: &zenoh::Session, selector: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session
+        .get(selector)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{selector}': '{replies:?}'...");
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.sample {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{selector}': '{samples:?}'...");
+    samples
+}
+
+async fn test_history_all() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        history_test: {
+                            key_expr: "history/test/**",
+                            volume: {
+                                id: "memory",
+                                history: "all",
+                                max_samples: 3,
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    for value in ["1", "2", "3", "4"] {
+        put_data(&session, "history/test/a", value).await;
+        sleep(std::time::Duration::from_millis(10));
+    }
+
+    // expects the latest sample only
+    let data = get_data(&session, "history/test/a").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(format!("{}", data[0].value), "4");
+
+    // expects the 3 latest samples, in order
+    let data = get_data(&session, "history/test/a?_time=[..]").await;
+    let values: Vec<String> = data.iter().map(|s| format!("{}", s.value)).collect();
+    assert_eq!(values, vec!["2", "3", "4"]);
+
+    // expects no sample in the future
+    let data = get_data(&session, "history/test/a?_time=[now(1h)..]").await;
+    assert_eq!(data.len(), 0);
+
+    // expects the samples of all the matching keys
+    put_data(&session, "history/test/b", "5").await;
+    sleep(std::time::Duration::from_millis(10));
+    let data = get_data(&session, "history/test/*?_time=[now(-1h)..]").await;
+    assert_eq!(data.len(), 4);
+
+    drop(storage);
+}
+
+#[test]
+fn history_test() {
+    task::block_on(async { test_history_all().await });
+}
@@ -0,0 +1,163 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the limits of storages -
+// 1. with the LRU policy, the least recently written or queried values are evicted, within the quotas first
+// 2. with the reject-new policy, the values exceeding the limits are discarded
+// 3. the usage of the storages is reported in their admin status
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn put_data(session: &zenoh::Session, key_expr: &str, value: &str) {
+    session.put(key_expr, value).res().await.unwrap();
+    sleep(std::time::Duration::from_millis(50));
+}
+
+async fn get_data(session: &zenoh::Session, key_expr: &str) -> Vec<(String, String)> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut data: Vec<(String, String)> = replies
+        .into_iter()
+        .filter_map(|r| r.sample.ok())
+        .map(|s| (s.key_expr.as_str().to_string(), s.value.to_string()))
+        .collect();
+    data.sort();
+    data
+}
+
+fn usage(
+    storage: &zenoh::plugins::RunningPlugin,
+    runtime: &zenoh::runtime::Runtime,
+    name: &str,
+) -> serde_json::Value {
+    let status_key = format!("@/router/{}/status/plugins/storage-manager", runtime.zid());
+    let selector = Selector::try_from(format!("{status_key}/storages/{name}")).unwrap();
+    let mut responses = storage.adminspace_getter(&selector, &status_key).unwrap();
+    assert_eq!(responses.len(), 1);
+    responses.pop().unwrap().value["usage"].clone()
+}
+
+async fn test_limits() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        lru_test: {
+                            key_expr: "limits/lru/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            limits: {
+                                max_keys: 3,
+                                quotas: {
+                                    "limits/lru/quota/**": { max_keys: 1 },
+                                },
+                                eviction: "lru",
+                            },
+                        },
+                        reject_test: {
+                            key_expr: "limits/reject/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            limits: {
+                                max_bytes: 10,
+                                eviction: "reject_new",
+                            },
+                        },
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+    let session = zenoh::init(runtime.clone()).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    // the least recently used value is evicted
+    put_data(&session, "limits/lru/a", "1").await;
+    put_data(&session, "limits/lru/b", "2").await;
+    put_data(&session, "limits/lru/c", "3").await;
+    assert_eq!(get_data(&session, "limits/lru/a").await.len(), 1);
+    put_data(&session, "limits/lru/d", "4").await;
+    let data = get_data(&session, "limits/lru/**").await;
+    assert_eq!(
+        data,
+        vec![
+            ("limits/lru/a".to_string(), "1".to_string()),
+            ("limits/lru/c".to_string(), "3".to_string()),
+            ("limits/lru/d".to_string(), "4".to_string()),
+        ]
+    );
+
+    // the quota is satisfied first, then the limits of the storage
+    put_data(&session, "limits/lru/quota/x", "5").await;
+    put_data(&session, "limits/lru/quota/y", "6").await;
+    let data = get_data(&session, "limits/lru/**").await;
+    assert_eq!(data.len(), 3);
+    assert!(data.contains(&("limits/lru/quota/y".to_string(), "6".to_string())));
+    assert!(!data.iter().any(|(k, _)| k == "limits/lru/quota/x"));
+
+    let lru_usage = usage(&storage, &runtime, "lru_test");
+    assert_eq!(lru_usage["keys"], 3);
+    assert_eq!(lru_usage["evictions"], 3);
+    assert_eq!(lru_usage["quotas"]["limits/lru/quota/**"]["keys"], 1);
+
+    // the values exceeding the limits are rejected
+    put_data(&session, "limits/reject/a", "12345").await;
+    put_data(&session, "limits/reject/b", "123456").await;
+    put_data(&session, "limits/reject/a", "1234567890").await;
+    let data = get_data(&session, "limits/reject/**").await;
+    assert_eq!(
+        data,
+        vec![("limits/reject/a".to_string(), "1234567890".to_string())]
+    );
+
+    let reject_usage = usage(&storage, &runtime, "reject_test");
+    assert_eq!(reject_usage["keys"], 1);
+    assert_eq!(reject_usage["bytes"], 10);
+    assert_eq!(reject_usage["rejections"], 1);
+    assert_eq!(reject_usage["evictions"], 0);
+
+    drop(storage);
+}
+
+#[test]
+fn limits_test() {
+    task::block_on(async { test_limits().await });
+}
@@ -0,0 +1,199 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the pagination of the replies of a storage -
+// 1. `_limit`, `_offset` and `_order` select the replies
+// 2. `_after` continues from the last reply of a page, even in the middle of the history of a key
+// 3. the replies are all sent when they are retrieved from the storage in several chunks
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::{ConsolidationMode, Reply};
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_samples(session: &zenoh::Session, selector: &str) -> Vec<Sample> {
+    // replies must not be consolidated to keep their order
+    let replies: Vec<Reply> = session
+        .get(selector)
+        .consolidation(ConsolidationMode::None)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{selector}': '{replies:?}'...");
+    replies.into_iter().filter_map(|r| r.sample.ok()).collect()
+}
+
+async fn get_keys(session: &zenoh::Session, selector: &str) -> Vec<String> {
+    get_samples(session, selector)
+        .await
+        .into_iter()
+        .map(|s| s.key_expr.as_str().to_string())
+        .collect()
+}
+
+async fn get_values(session: &zenoh::Session, selector: &str) -> Vec<String> {
+    get_samples(session, selector)
+        .await
+        .into_iter()
+        .map(|s| s.value.to_string())
+        .collect()
+}
+
+async fn test_pagination() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        pagination_test: {
+                            key_expr: "pagination/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        },
+                        pagination_history_test: {
+                            key_expr: "pagination/history/**",
+                            volume: {
+                                id: "memory",
+                                history: "all",
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    for key in ["c", "a", "e", "b", "d"] {
+        session
+            .put(format!("pagination/test/{key}"), key)
+            .res()
+            .await
+            .unwrap();
+    }
+    sleep(std::time::Duration::from_millis(10));
+
+    let keys = get_keys(&session, "pagination/test/**").await;
+    assert_eq!(keys.len(), 5);
+
+    let keys = get_keys(&session, "pagination/test/**?_limit=2").await;
+    assert_eq!(keys, vec!["pagination/test/a", "pagination/test/b"]);
+
+    let keys = get_keys(
+        &session,
+        "pagination/test/**?_limit=2&_after=pagination/test/b",
+    )
+    .await;
+    assert_eq!(keys, vec!["pagination/test/c", "pagination/test/d"]);
+
+    let keys = get_keys(
+        &session,
+        "pagination/test/**?_order=desc&_offset=1&_limit=2",
+    )
+    .await;
+    assert_eq!(keys, vec!["pagination/test/d", "pagination/test/c"]);
+
+    let keys = get_keys(&session, "pagination/test/**?_offset=4").await;
+    assert_eq!(keys, vec!["pagination/test/e"]);
+
+    // a page may end in the middle of the history of a key
+    for (key, value) in [("a", "1"), ("a", "2"), ("a", "3"), ("b", "4")] {
+        session
+            .put(format!("pagination/history/{key}"), value)
+            .res()
+            .await
+            .unwrap();
+        sleep(std::time::Duration::from_millis(10));
+    }
+    let page = get_samples(&session, "pagination/history/**?_time=[..]&_limit=2").await;
+    let values: Vec<String> = page.iter().map(|s| s.value.to_string()).collect();
+    assert_eq!(values, vec!["1", "2"]);
+    let last = page.last().unwrap();
+    let values = get_values(
+        &session,
+        &format!(
+            "pagination/history/**?_time=[..]&_limit=2&_after={}@{}",
+            last.key_expr,
+            last.timestamp.unwrap()
+        ),
+    )
+    .await;
+    assert_eq!(values, vec!["3", "4"]);
+    let values = get_values(
+        &session,
+        "pagination/history/**?_time=[..]&_after=pagination/history/a",
+    )
+    .await;
+    assert_eq!(values, vec!["4"]);
+
+    // the storage is read by chunks of 256 replies
+    for i in 0..600 {
+        session
+            .put(format!("pagination/test/many/{i:03}"), i.to_string())
+            .res()
+            .await
+            .unwrap();
+    }
+    sleep(std::time::Duration::from_millis(500));
+    // no reply is lost nor repeated between the chunks
+    let mut keys = get_keys(&session, "pagination/test/many/**").await;
+    keys.sort();
+    let expected: Vec<String> = (0..600)
+        .map(|i| format!("pagination/test/many/{i:03}"))
+        .collect();
+    assert_eq!(keys, expected);
+    let keys = get_keys(&session, "pagination/test/many/**?_offset=10&_limit=300").await;
+    let expected: Vec<String> = (10..310)
+        .map(|i| format!("pagination/test/many/{i:03}"))
+        .collect();
+    assert_eq!(keys, expected);
+
+    // invalid parameters are replied with an error
+    let replies: Vec<Reply> = session
+        .get("pagination/test/**?_limit=all")
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].sample.is_err());
+
+    drop(storage);
+}
+
+#[test]
+fn pagination_test() {
+    task::block_on(async { test_pagination().await });
+}
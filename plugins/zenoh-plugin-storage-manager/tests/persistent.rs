@@ -0,0 +1,130 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the persistent backend -
+// 1. the content of a storage is recovered after a restart
+// 2. an incomplete record at the end of the log is discarded
+
+use std::io::Write;
+use std::path::Path;
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &zenoh::Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.sample {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn start(dir: &Path) -> (zenoh::plugins::RunningPlugin, zenoh::Session) {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            &format!(
+                r#"{{
+                    volumes: {{
+                        persistent: {{
+                            dir: "{}",
+                        }}
+                    }},
+                    storages: {{
+                        persistent_test: {{
+                            key_expr: "persistent/test/**",
+                            volume: "persistent",
+                        }}
+                    }}
+                }}"#,
+                dir.display()
+            ),
+        )
+        .unwrap();
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+    let session = zenoh::init(runtime).res().await.unwrap();
+    sleep(std::time::Duration::from_secs(1));
+    (storage, session)
+}
+
+async fn test_restart() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let dir = std::env::temp_dir().join(format!("zenoh_persistent_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let (storage, session) = start(&dir).await;
+    session.put("persistent/test/a", "1").res().await.unwrap();
+    session.put("persistent/test/b", "2").res().await.unwrap();
+    session.put("persistent/test/a", "3").res().await.unwrap();
+    session.delete("persistent/test/b").res().await.unwrap();
+    sleep(std::time::Duration::from_millis(100));
+    let data = get_data(&session, "persistent/test/**").await;
+    assert_eq!(data.len(), 1);
+    session.close().res().await.unwrap();
+    drop(storage);
+    sleep(std::time::Duration::from_millis(100));
+
+    // simulate a crash while writing a record
+    let mut log = std::fs::OpenOptions::new()
+        .append(true)
+        .open(dir.join("persistent_test").join("data.log"))
+        .unwrap();
+    log.write_all(&[42, 0, 0, 0, 1, 2]).unwrap();
+    drop(log);
+
+    let (storage, session) = start(&dir).await;
+    let data = get_data(&session, "persistent/test/**").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].key_expr.as_str(), "persistent/test/a");
+    assert_eq!(format!("{}", data[0].value), "3");
+
+    session.put("persistent/test/c", "4").res().await.unwrap();
+    sleep(std::time::Duration::from_millis(100));
+    let data = get_data(&session, "persistent/test/c").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(format!("{}", data[0].value), "4");
+
+    session.close().res().await.unwrap();
+    drop(storage);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn persistent_test() {
+    task::block_on(async { test_restart().await });
+}
@@ -0,0 +1,223 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the snapshots of storages -
+// 1. a storage exports its values to a snapshot file through its admin space
+// 2. another storage imports the snapshot, keeping the original timestamps of the values
+// 3. snapshots stay in the snapshots directory, one per storage, and are only exported or imported with the write permission
+
+use std::path::Path;
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh::runtime::Runtime;
+use zenoh::time::Timestamp;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &zenoh::Session, key_expr: &str) -> Vec<(String, String, Timestamp)> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut data: Vec<(String, String, Timestamp)> = replies
+        .into_iter()
+        .filter_map(|r| r.sample.ok())
+        .map(|s| {
+            (
+                s.key_expr.as_str().to_string(),
+                s.value.to_string(),
+                s.timestamp.unwrap(),
+            )
+        })
+        .collect();
+    data.sort();
+    data
+}
+
+async fn start_runtime(snapshots_dir: &Path, write_permission: bool) -> Runtime {
+    let mut config = Config::default();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .adminspace
+        .permissions
+        .set_write(write_permission)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            &format!(
+                r#"{{
+                    snapshots_dir: {:?},
+                    storages: {{
+                        snapshot_test: {{
+                            key_expr: "snapshot/test/**",
+                            volume: {{
+                                id: "memory"
+                            }}
+                        }},
+                        snapshot_other: {{
+                            key_expr: "snapshot/other/**",
+                            volume: {{
+                                id: "memory"
+                            }}
+                        }}
+                    }}
+                }}"#,
+                snapshots_dir.display()
+            ),
+        )
+        .unwrap();
+    zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap()
+}
+
+fn admin_query(
+    storage: &zenoh::plugins::RunningPlugin,
+    runtime: &Runtime,
+    storages: &str,
+    parameters: &str,
+) -> Vec<serde_json::Value> {
+    let status_key = format!("@/router/{}/status/plugins/storage-manager", runtime.zid());
+    let selector =
+        Selector::try_from(format!("{status_key}/storages/{storages}?{parameters}")).unwrap();
+    let mut responses = storage.adminspace_getter(&selector, &status_key).unwrap();
+    responses.sort_by(|a, b| a.key.cmp(&b.key));
+    responses.into_iter().map(|r| r.value).collect()
+}
+
+async fn test_snapshot() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let dir = std::env::temp_dir().join(format!("zenoh_snapshot_test_{}", std::process::id()));
+    let path = dir.join("demo").join("snapshot_test.snapshot");
+
+    let source_runtime = start_runtime(&dir, true).await;
+    let source =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &source_runtime)
+            .unwrap();
+    let source_session = zenoh::init(source_runtime.clone()).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    source_session
+        .put("snapshot/test/a", "1")
+        .res()
+        .await
+        .unwrap();
+    source_session
+        .put("snapshot/test/b", "2")
+        .res()
+        .await
+        .unwrap();
+    sleep(std::time::Duration::from_millis(100));
+
+    let exported = get_data(&source_session, "snapshot/test/**").await;
+    assert_eq!(exported.len(), 2);
+
+    // each storage is exported to its own file
+    let status = admin_query(&source, &source_runtime, "*", "_export=demo");
+    assert_eq!(status.len(), 2);
+    assert_eq!(status[0]["_export"]["entries"], 0);
+    assert_eq!(status[1]["_export"]["entries"], 2);
+    assert_eq!(status[1]["_export"]["file"], path.to_str().unwrap());
+    assert!(dir.join("demo").join("snapshot_other.snapshot").exists());
+    let snapshot = std::fs::read_to_string(&path).unwrap();
+    let mut lines = snapshot.lines();
+    let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(header["format"], "zenoh-storage-snapshot");
+    assert_eq!(header["key_expr"], "snapshot/test/**");
+    assert_eq!(lines.count(), 2);
+
+    // snapshots can't be written out of the snapshots directory
+    for name in ["../escape", "/tmp/escape", "a/../../escape", ""] {
+        let status = admin_query(
+            &source,
+            &source_runtime,
+            "snapshot_test",
+            &format!("_export={name}"),
+        );
+        assert!(status[0]["_export"]["error"].is_string());
+    }
+    assert!(!dir.parent().unwrap().join("escape").exists());
+
+    drop(source);
+    drop(source_session);
+
+    // exporting and importing require the write permission
+    let readonly_runtime = start_runtime(&dir, false).await;
+    let readonly =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &readonly_runtime)
+            .unwrap();
+    let status = admin_query(
+        &readonly,
+        &readonly_runtime,
+        "snapshot_test",
+        "_export=denied",
+    );
+    assert!(status[0]["_export"]["error"].is_string());
+    assert!(!dir.join("denied").exists());
+    let status = admin_query(
+        &readonly,
+        &readonly_runtime,
+        "snapshot_test",
+        "_import=demo",
+    );
+    assert!(status[0]["_import"]["error"].is_string());
+    drop(readonly);
+
+    let target_runtime = start_runtime(&dir, true).await;
+    let target =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &target_runtime)
+            .unwrap();
+    let target_session = zenoh::init(target_runtime.clone()).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    assert!(get_data(&target_session, "snapshot/test/**")
+        .await
+        .is_empty());
+
+    let status = admin_query(&target, &target_runtime, "snapshot_test", "_import=demo");
+    assert_eq!(status[0]["_import"]["entries"], 2);
+
+    let imported = get_data(&target_session, "snapshot/test/**").await;
+    assert_eq!(imported, exported);
+
+    let status = admin_query(
+        &target,
+        &target_runtime,
+        "snapshot_test",
+        "_import=nonexistent",
+    );
+    assert!(status[0]["_import"]["error"].is_string());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    drop(target);
+}
+
+#[test]
+fn snapshot_test() {
+    task::block_on(async { test_snapshot().await });
+}
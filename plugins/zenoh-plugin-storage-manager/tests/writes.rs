@@ -0,0 +1,204 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the conditional and batched writes to a storage -
+// 1. a write conditioned by the absence of a value is applied once only
+// 2. a write conditioned by a timestamp is applied if the stored value has this timestamp
+// 3. a batch is applied entirely with the same timestamp, or not at all on a conflict
+// 4. a write that can't be stored fails the batch, and the following writes are not applied
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh::time::Timestamp;
+use zenoh_core::zasync_executor_init;
+use zenoh_ext::{WriteBatchExt, WriteOutcome};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &zenoh::Session, key_expr: &str) -> Vec<(String, String, Timestamp)> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut data: Vec<(String, String, Timestamp)> = replies
+        .into_iter()
+        .filter_map(|r| r.sample.ok())
+        .map(|s| {
+            (
+                s.key_expr.as_str().to_string(),
+                s.value.to_string(),
+                s.timestamp.unwrap(),
+            )
+        })
+        .collect();
+    data.sort();
+    data
+}
+
+async fn test_writes() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        writes_test: {
+                            key_expr: "writes/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        },
+                        writes_limits: {
+                            key_expr: "writes/limits/**",
+                            volume: {
+                                id: "memory"
+                            },
+                            limits: {
+                                max_bytes: 4,
+                                eviction: "reject_new",
+                            },
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    // a value is written if absent, once only
+    let outcome = session
+        .write_batch("writes/test/**")
+        .put_if_absent("writes/test/a", "1")
+        .res()
+        .await
+        .unwrap();
+    let WriteOutcome::Applied(first) = outcome else {
+        panic!("Unexpected outcome {outcome:?}")
+    };
+    let outcome = session
+        .write_batch("writes/test/**")
+        .put_if_absent("writes/test/a", "2")
+        .res()
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome,
+        WriteOutcome::Conflict(vec![("writes/test/a".try_into().unwrap(), Some(first))])
+    );
+    let data = get_data(&session, "writes/test/a").await;
+    assert_eq!(
+        data,
+        vec![("writes/test/a".to_string(), "1".to_string(), first)]
+    );
+
+    // a batch with a stale timestamp is not applied at all
+    let outcome = session
+        .write_batch("writes/test/**")
+        .put_if_absent("writes/test/b", "3")
+        .put_if(
+            "writes/test/a",
+            "4",
+            Timestamp::new(Default::default(), *first.get_id()),
+        )
+        .res()
+        .await
+        .unwrap();
+    assert!(matches!(outcome, WriteOutcome::Conflict(c) if c.len() == 1));
+    assert!(get_data(&session, "writes/test/b").await.is_empty());
+
+    // a batch with the current timestamp is applied with the same timestamp
+    let outcome = session
+        .write_batch("writes/test/**")
+        .put_if_absent("writes/test/b", "3")
+        .put_if("writes/test/a", "4", first)
+        .put("writes/test/c", "5")
+        .res()
+        .await
+        .unwrap();
+    let WriteOutcome::Applied(second) = outcome else {
+        panic!("Unexpected outcome {outcome:?}")
+    };
+    assert!(second > first);
+    let data = get_data(&session, "writes/test/**").await;
+    assert_eq!(
+        data,
+        vec![
+            ("writes/test/a".to_string(), "4".to_string(), second),
+            ("writes/test/b".to_string(), "3".to_string(), second),
+            ("writes/test/c".to_string(), "5".to_string(), second),
+        ]
+    );
+
+    // a conditional deletion
+    let outcome = session
+        .write_batch("writes/test/**")
+        .delete_if("writes/test/a", second)
+        .delete("writes/test/c")
+        .res()
+        .await
+        .unwrap();
+    assert!(matches!(outcome, WriteOutcome::Applied(_)));
+    let data = get_data(&session, "writes/test/**").await;
+    assert_eq!(
+        data,
+        vec![("writes/test/b".to_string(), "3".to_string(), second)]
+    );
+
+    // writes outside of the storage are rejected
+    assert!(session
+        .write_batch("writes/test/**")
+        .put("other/a", "6")
+        .res()
+        .await
+        .is_err());
+
+    // a write rejected by the limits of the storage fails the batch
+    let result = session
+        .write_batch("writes/limits/**")
+        .put("writes/limits/a", "12")
+        .put("writes/limits/b", "345")
+        .put("writes/limits/c", "6")
+        .res()
+        .await;
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("writes/limits/b"), "{error}");
+    let data = get_data(&session, "writes/limits/**").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].0, "writes/limits/a");
+
+    drop(storage);
+}
+
+#[test]
+fn writes_test() {
+    task::block_on(async { test_writes().await });
+}
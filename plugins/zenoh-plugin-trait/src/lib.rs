@@ -27,12 +27,17 @@
 //!
 //! Plugins are loaded, started and stopped by [`PluginsManager`](crate::manager::PluginsManager). Stopping plugin is just dropping it's instance.
 //!
+//! A plugin may declare the plugins it depends on with [`Plugin::DEPENDENCIES`], by name or by one of the [`Plugin::CAPABILITIES`] they provide.
+//! The [`PluginsManager`](crate::manager::PluginsManager) starts plugins after their dependencies, and stops them before.
+//!
 //! Plugins can be static and dynamic.
 //!
 //! Static plugin is just a type which implements [`Plugin`] trait. It can be added to [`PluginsManager`](crate::manager::PluginsManager) by [`PluginsManager::add_static_plugin`](crate::manager::PluginsManager::add_static_plugin) method.
 //!
 //! Dynamic plugin is a shared library which exports set of C-repr (unmangled) functions which allows to check plugin compatibility and create plugin instance. These functiuons are defined automatically by [`declare_plugin`](crate::declare_plugin) macro.
 //!
+//! With the `wasm` feature, a plugin may also be a WebAssembly module, which runs in a sandbox and only accesses the host through the API provided by its [`WasmStarter`].
+//!
 mod compatibility;
 mod manager;
 mod plugin;
@@ -40,11 +45,15 @@ mod vtable;
 
 pub use compatibility::{Compatibility, PluginStructVersion, StructVersion};
 pub use manager::{DeclaredPlugin, LoadedPlugin, PluginsManager, StartedPlugin};
+#[cfg(feature = "wasm")]
+pub use manager::{WasmModule, WasmStarter};
 pub use plugin::{
     Plugin, PluginConditionSetter, PluginControl, PluginInstance, PluginReport, PluginStartArgs,
     PluginState, PluginStatus, PluginStatusRec,
 };
 pub use vtable::{PluginLoaderVersion, PluginVTable, PLUGIN_LOADER_VERSION};
+#[cfg(feature = "wasm")]
+pub use wasmi;
 use zenoh_util::concat_enabled_features;
 
 pub const FEATURES: &str =
@@ -12,16 +12,22 @@
 //
 mod dynamic_plugin;
 mod static_plugin;
+#[cfg(feature = "wasm")]
+mod wasm_plugin;
 
 use crate::*;
 use zenoh_keyexpr::keyexpr;
-use zenoh_result::ZResult;
+use zenoh_result::{zerror, ZResult};
 use zenoh_util::LibLoader;
 
 use self::{
     dynamic_plugin::{DynamicPlugin, DynamicPluginSource},
     static_plugin::StaticPlugin,
 };
+#[cfg(feature = "wasm")]
+use wasm_plugin::WasmPlugin;
+#[cfg(feature = "wasm")]
+pub use wasm_plugin::{WasmModule, WasmStarter};
 
 pub trait DeclaredPlugin<StartArgs, Instance>: PluginStatus {
     fn as_status(&self) -> &dyn PluginStatus;
@@ -32,6 +38,8 @@ pub trait DeclaredPlugin<StartArgs, Instance>: PluginStatus {
 pub trait LoadedPlugin<StartArgs, Instance>: PluginStatus {
     fn as_status(&self) -> &dyn PluginStatus;
     fn required(&self) -> bool;
+    /// Returns the JSON schema of the plugin's configuration, if the plugin provides one
+    fn config_schema(&self) -> Option<serde_json::Value>;
     fn start(&mut self, args: &StartArgs) -> ZResult<&mut dyn StartedPlugin<StartArgs, Instance>>;
     fn started(&self) -> Option<&dyn StartedPlugin<StartArgs, Instance>>;
     fn started_mut(&mut self) -> Option<&mut dyn StartedPlugin<StartArgs, Instance>>;
@@ -44,13 +52,27 @@ pub trait StartedPlugin<StartArgs, Instance>: PluginStatus {
     fn instance_mut(&mut self) -> &mut Instance;
 }
 
-struct PluginRecord<StartArgs: PluginStartArgs, Instance: PluginInstance>(
-    Box<dyn DeclaredPlugin<StartArgs, Instance> + Send + Sync>,
-);
+struct PluginRecord<StartArgs: PluginStartArgs, Instance: PluginInstance> {
+    plugin: Box<dyn DeclaredPlugin<StartArgs, Instance> + Send + Sync>,
+    // Errors reported by the manager, e.g. unsatisfied dependencies
+    report: PluginReport,
+}
 
 impl<StartArgs: PluginStartArgs, Instance: PluginInstance> PluginRecord<StartArgs, Instance> {
     fn new<P: DeclaredPlugin<StartArgs, Instance> + Send + Sync + 'static>(plugin: P) -> Self {
-        Self(Box::new(plugin))
+        Self {
+            plugin: Box::new(plugin),
+            report: PluginReport::new(),
+        }
+    }
+    /// Returns true if this plugin satisfies the dependency by its name, ID or one of its capabilities
+    fn provides(&self, dependency: &str) -> bool {
+        self.name() == dependency
+            || self.id() == dependency
+            || self.capabilities().contains(&dependency)
+    }
+    fn is_started(&self) -> bool {
+        self.state() == PluginState::Started
     }
 }
 
@@ -58,27 +80,37 @@ impl<StartArgs: PluginStartArgs, Instance: PluginInstance> PluginStatus
     for PluginRecord<StartArgs, Instance>
 {
     fn name(&self) -> &str {
-        self.0.name()
+        self.plugin.name()
     }
 
     fn id(&self) -> &str {
-        self.0.id()
+        self.plugin.id()
     }
 
     fn version(&self) -> Option<&str> {
-        self.0.version()
+        self.plugin.version()
     }
     fn long_version(&self) -> Option<&str> {
-        self.0.long_version()
+        self.plugin.long_version()
     }
     fn path(&self) -> &str {
-        self.0.path()
+        self.plugin.path()
     }
     fn state(&self) -> PluginState {
-        self.0.state()
+        self.plugin.state()
     }
     fn report(&self) -> PluginReport {
-        self.0.report()
+        let mut report = self.plugin.report();
+        for message in self.report.messages() {
+            report.add_error(message.clone());
+        }
+        report
+    }
+    fn dependencies(&self) -> Vec<&str> {
+        self.plugin.dependencies()
+    }
+    fn capabilities(&self) -> Vec<&str> {
+        self.plugin.capabilities()
     }
 }
 
@@ -89,13 +121,13 @@ impl<StartArgs: PluginStartArgs, Instance: PluginInstance> DeclaredPlugin<StartA
         self
     }
     fn load(&mut self) -> ZResult<&mut dyn LoadedPlugin<StartArgs, Instance>> {
-        self.0.load()
+        self.plugin.load()
     }
     fn loaded(&self) -> Option<&dyn LoadedPlugin<StartArgs, Instance>> {
-        self.0.loaded()
+        self.plugin.loaded()
     }
     fn loaded_mut(&mut self) -> Option<&mut dyn LoadedPlugin<StartArgs, Instance>> {
-        self.0.loaded_mut()
+        self.plugin.loaded_mut()
     }
 }
 
@@ -105,6 +137,8 @@ pub struct PluginsManager<StartArgs: PluginStartArgs, Instance: PluginInstance>
     default_lib_prefix: String,
     loader: Option<LibLoader>,
     plugins: Vec<PluginRecord<StartArgs, Instance>>,
+    // The removed plugins, whose libraries stay loaded as tasks spawned by their instances may still run
+    retired: Vec<PluginRecord<StartArgs, Instance>>,
 }
 
 impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static>
@@ -116,6 +150,7 @@ impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static>
             default_lib_prefix: default_lib_prefix.into(),
             loader: Some(loader),
             plugins: Default::default(),
+            retired: Default::default(),
         }
     }
     /// Constructs a new plugin manager with dynamic library loading disabled.
@@ -124,6 +159,7 @@ impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static>
             default_lib_prefix: String::new(),
             loader: None,
             plugins: Default::default(),
+            retired: Default::default(),
         }
     }
 
@@ -220,6 +256,163 @@ impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static>
         Ok(self.plugins.last_mut().unwrap())
     }
 
+    /// Add a plugin compiled to WebAssembly from the first available file of the list of paths.
+    /// The plugin is started by `starter`, which provides it the API of the host.
+    #[cfg(feature = "wasm")]
+    pub fn declare_wasm_plugin_by_paths<S: Into<String>, P: AsRef<str> + std::fmt::Debug>(
+        &mut self,
+        name: S,
+        id: S,
+        paths: &[P],
+        starter: WasmStarter<StartArgs, Instance>,
+        required: bool,
+    ) -> ZResult<&mut dyn DeclaredPlugin<StartArgs, Instance>> {
+        let name = name.into();
+        let id = id.into();
+        let paths = paths.iter().map(|p| p.as_ref().into()).collect();
+        tracing::debug!("Declared WASM plugin {} by paths {:?}", &id, &paths);
+        let loader = WasmPlugin::new(name, id.clone(), paths, starter, required);
+
+        if self.get_plugin_index(&id).is_some() {
+            tracing::warn!(
+                "Duplicate plugin with ID: {id}, only the last declared one will be loaded"
+            )
+        }
+
+        self.plugins.push(PluginRecord::new(loader));
+        Ok(self.plugins.last_mut().unwrap())
+    }
+
+    /// Removes the plugin from the manager, stopping it first if it's started.
+    /// The started plugins depending on it are stopped too.
+    /// A dynamic plugin's library isn't unloaded before the manager is dropped, as tasks spawned by its instance may still run.
+    /// Returns `false` if no plugin with this ID is declared.
+    pub fn remove_plugin(&mut self, id: &str) -> bool {
+        self.stop_plugin(id);
+        let Some(index) = self.get_plugin_index(id) else {
+            return false;
+        };
+        self.retired.push(self.plugins.remove(index));
+        tracing::debug!("Removed plugin {}", id);
+        true
+    }
+
+    /// Declares again the last plugin removed with this ID, in place of the plugin declared with this ID since, if any.
+    /// The restored plugin isn't started again.
+    /// Returns `false` if no plugin with this ID was removed.
+    pub fn restore_plugin(&mut self, id: &str) -> bool {
+        let Some(index) = self.retired.iter().rposition(|p| p.id() == id) else {
+            return false;
+        };
+        self.remove_plugin(id);
+        let record = self.retired.remove(index);
+        self.plugins.push(record);
+        tracing::debug!("Restored plugin {}", id);
+        true
+    }
+
+    /// Starts a loaded plugin, whose dependencies must be provided by started plugins.
+    /// An unsatisfied dependency is reported as an error in the plugin's report.
+    pub fn start_plugin(
+        &mut self,
+        id: &str,
+        args: &StartArgs,
+    ) -> ZResult<&mut dyn StartedPlugin<StartArgs, Instance>> {
+        let index = self
+            .get_plugin_index(id)
+            .ok_or_else(|| zerror!("Plugin `{}` is not declared", id))?;
+        if let Some(Err(e)) = self.check_dependencies(index, &[]) {
+            self.plugins[index].report.clear();
+            self.plugins[index].report.add_error(e.to_string());
+            return Err(e);
+        }
+        self.plugins[index].report.clear();
+        self.plugins[index]
+            .loaded_mut()
+            .ok_or_else(|| zerror!("Plugin `{}` is not loaded", id))?
+            .start(args)
+    }
+
+    /// Starts the loaded plugins which aren't started yet, each one after the plugins it depends on.
+    /// Returns the result of each start attempt by plugin ID, in start order.
+    /// Plugins with unsatisfied or cyclic dependencies aren't started, which is reported as an error.
+    pub fn start_plugins(&mut self, args: &StartArgs) -> Vec<(String, ZResult<()>)> {
+        let mut pending: Vec<usize> = (0..self.plugins.len())
+            .filter(|&i| self.plugins[i].loaded().is_some() && !self.plugins[i].is_started())
+            .collect();
+        let mut results = Vec::with_capacity(pending.len());
+        loop {
+            let mut progress = false;
+            let mut i = 0;
+            while i < pending.len() {
+                let index = pending[i];
+                let result = match self.check_dependencies(index, &pending) {
+                    // Wait for a pending plugin providing a dependency
+                    None => {
+                        i += 1;
+                        continue;
+                    }
+                    Some(Ok(())) => {
+                        self.plugins[index].report.clear();
+                        self.plugins[index]
+                            .loaded_mut()
+                            .unwrap()
+                            .start(args)
+                            .map(|_| ())
+                    }
+                    Some(Err(e)) => {
+                        self.plugins[index].report.clear();
+                        self.plugins[index].report.add_error(e.to_string());
+                        Err(e)
+                    }
+                };
+                results.push((self.plugins[index].id().to_string(), result));
+                pending.remove(i);
+                progress = true;
+            }
+            if !progress {
+                break;
+            }
+        }
+        for index in pending {
+            let plugin = &mut self.plugins[index];
+            let e = zerror!("Plugin `{}` has cyclic dependencies", plugin.id());
+            plugin.report.clear();
+            plugin.report.add_error(e.to_string());
+            results.push((plugin.id().to_string(), Err(e.into())));
+        }
+        results
+    }
+
+    /// Checks that the dependencies of a plugin are provided by started plugins.
+    /// Returns `None` if a dependency is only provided by one of the `pending` plugins, which may still be started.
+    fn check_dependencies(&self, index: usize, pending: &[usize]) -> Option<ZResult<()>> {
+        let plugin = &self.plugins[index];
+        let mut wait = false;
+        for dependency in plugin.dependencies() {
+            let providers = || {
+                self.plugins
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, p)| *i != index && p.provides(dependency))
+            };
+            if providers().any(|(_, p)| p.is_started()) {
+                continue;
+            }
+            if providers().any(|(i, _)| pending.contains(&i)) {
+                wait = true;
+                continue;
+            }
+            return Some(Err(zerror!(
+                "Plugin `{}` depends on `{}`, which isn't provided by any started plugin",
+                plugin.id(),
+                dependency
+            )
+            .into()));
+        }
+        (!wait).then_some(Ok(()))
+    }
+
     fn get_plugin_index(&self, id: &str) -> Option<usize> {
         self.plugins.iter().position(|p| p.id() == id)
     }
@@ -311,6 +504,70 @@ impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static>
     }
 }
 
+impl<StartArgs: PluginStartArgs, Instance: PluginInstance> PluginsManager<StartArgs, Instance> {
+    /// Stops a started plugin, after stopping the started plugins depending on it.
+    /// Returns the IDs of the stopped plugins, in stop order (empty if the plugin isn't started).
+    pub fn stop_plugin(&mut self, id: &str) -> Vec<String> {
+        let mut stopped = Vec::new();
+        if let Some(index) = self.plugins.iter().position(|p| p.id() == id) {
+            self.stop_with_dependents(index, &mut stopped);
+        }
+        stopped
+    }
+
+    /// Stops all the started plugins, each one after the plugins depending on it.
+    pub fn stop_plugins(&mut self) {
+        let mut stopped = Vec::new();
+        for index in (0..self.plugins.len()).rev() {
+            self.stop_with_dependents(index, &mut stopped);
+        }
+    }
+
+    fn stop_with_dependents(&mut self, index: usize, stopped: &mut Vec<String>) {
+        if !self.plugins[index].is_started() {
+            return;
+        }
+        // Mark the plugin before stopping its dependents, which may be cyclic
+        let id = self.plugins[index].id().to_string();
+        if stopped.contains(&id) {
+            return;
+        }
+        stopped.push(id);
+        let position = stopped.len() - 1;
+        let dependents: Vec<usize> = (0..self.plugins.len())
+            .filter(|&i| {
+                i != index
+                    && self.plugins[i].is_started()
+                    && self.plugins[i]
+                        .dependencies()
+                        .iter()
+                        .any(|dependency| self.plugins[index].provides(dependency))
+            })
+            .collect();
+        for dependent in dependents {
+            self.stop_with_dependents(dependent, stopped);
+        }
+        // The plugin is stopped after its dependents, so it's moved after them in the stop order
+        let id = stopped.remove(position);
+        if let Some(started) = self.plugins[index]
+            .loaded_mut()
+            .and_then(|p| p.started_mut())
+        {
+            started.stop();
+        }
+        stopped.push(id);
+    }
+}
+
+/// The plugins are stopped in dependency order when the manager is dropped
+impl<StartArgs: PluginStartArgs, Instance: PluginInstance> Drop
+    for PluginsManager<StartArgs, Instance>
+{
+    fn drop(&mut self) {
+        self.stop_plugins();
+    }
+}
+
 impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static> PluginControl
     for PluginsManager<StartArgs, Instance>
 {
@@ -346,3 +603,168 @@ impl<StartArgs: PluginStartArgs + 'static, Instance: PluginInstance + 'static> P
         plugins
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::PluginReportLevel;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Events(Arc<Mutex<Vec<String>>>);
+
+    impl StructVersion for Events {
+        fn struct_version() -> u64 {
+            1
+        }
+        fn struct_features() -> &'static str {
+            ""
+        }
+    }
+    impl PluginStartArgs for Events {}
+
+    struct Instance(String, Arc<Mutex<Vec<String>>>);
+
+    impl Drop for Instance {
+        fn drop(&mut self) {
+            self.1.lock().unwrap().push(format!("stop {}", self.0));
+        }
+    }
+    impl StructVersion for Instance {
+        fn struct_version() -> u64 {
+            1
+        }
+        fn struct_features() -> &'static str {
+            ""
+        }
+    }
+    impl PluginControl for Instance {}
+    impl PluginInstance for Instance {}
+
+    macro_rules! test_plugin {
+        ($ty: ident, $name: literal, $dependencies: expr, $capabilities: expr) => {
+            struct $ty;
+            impl Plugin for $ty {
+                type StartArgs = Events;
+                type Instance = Instance;
+                const DEFAULT_NAME: &'static str = $name;
+                const PLUGIN_VERSION: &'static str = "";
+                const PLUGIN_LONG_VERSION: &'static str = "";
+                const DEPENDENCIES: &'static [&'static str] = $dependencies;
+                const CAPABILITIES: &'static [&'static str] = $capabilities;
+                fn start(name: &str, args: &Events) -> ZResult<Instance> {
+                    args.0.lock().unwrap().push(format!("start {name}"));
+                    Ok(Instance(name.into(), args.0.clone()))
+                }
+            }
+        };
+    }
+    test_plugin!(A, "a", &[], &["http"]);
+    test_plugin!(B, "b", &["http"], &[]);
+    test_plugin!(C, "c", &["b"], &[]);
+    test_plugin!(D, "d", &["missing"], &[]);
+
+    #[test]
+    fn test_dependencies() {
+        let events = Events::default();
+        let mut manager = PluginsManager::<Events, Instance>::static_plugins_only()
+            .declare_static_plugin::<C, _>("c", false)
+            .declare_static_plugin::<D, _>("d", false)
+            .declare_static_plugin::<B, _>("b", false)
+            .declare_static_plugin::<A, _>("a", false);
+
+        let results = manager.start_plugins(&events);
+        let ids: Vec<_> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["d", "a", "b", "c"]);
+        assert!(results[0].1.is_err());
+        assert!(results[1..].iter().all(|(_, r)| r.is_ok()));
+        let report = manager.plugin("d").unwrap().report();
+        assert_eq!(report.get_level(), PluginReportLevel::Error);
+        assert!(manager.start_plugin("d", &events).is_err());
+
+        assert_eq!(manager.stop_plugin("a"), ["c", "b", "a"]);
+        assert!(manager.start_plugin("b", &events).is_err());
+        manager.start_plugin("a", &events).unwrap();
+        manager.start_plugin("b", &events).unwrap();
+        drop(manager);
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            [
+                "start a", "start b", "start c", "stop c", "stop b", "stop a", "start a",
+                "start b", "stop b", "stop a"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_and_restore() {
+        let events = Events::default();
+        let mut manager = PluginsManager::<Events, Instance>::static_plugins_only()
+            .declare_static_plugin::<A, _>("a", false)
+            .declare_static_plugin::<B, _>("b", false);
+        manager.start_plugins(&events);
+
+        assert!(manager.remove_plugin("a"));
+        assert!(manager.plugin("a").is_none());
+        assert_eq!(manager.plugin("b").unwrap().state(), PluginState::Loaded);
+        assert!(!manager.remove_plugin("a"));
+
+        assert!(manager.restore_plugin("a"));
+        assert_eq!(manager.plugin("a").unwrap().state(), PluginState::Loaded);
+        assert!(!manager.restore_plugin("a"));
+        manager.start_plugin("a", &events).unwrap();
+        drop(manager);
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            ["start a", "start b", "stop b", "stop a", "start a", "stop a"]
+        );
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_plugin() {
+        fn start(module: &WasmModule, id: &str, args: &Events) -> ZResult<Instance> {
+            args.0
+                .lock()
+                .unwrap()
+                .push(format!("start {id} from {}", module.path()));
+            Ok(Instance(id.into(), args.0.clone()))
+        }
+        let dir = std::env::temp_dir().join(format!("zenoh-wasm-plugin-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let invalid = dir.join("invalid.wasm").to_str().unwrap().to_string();
+        std::fs::write(&invalid, b"not a module").unwrap();
+        let valid = dir.join("valid.wasm").to_str().unwrap().to_string();
+        // The empty module
+        std::fs::write(&valid, b"\0asm\x01\0\0\0").unwrap();
+
+        let events = Events::default();
+        let mut manager = PluginsManager::<Events, Instance>::static_plugins_only();
+        let declared = manager
+            .declare_wasm_plugin_by_paths("invalid", "invalid", &[&invalid], start, false)
+            .unwrap();
+        assert!(declared.load().is_err());
+        assert_eq!(declared.state(), PluginState::Declared);
+        assert_eq!(declared.report().get_level(), PluginReportLevel::Error);
+
+        let missing = dir.join("missing.wasm").to_str().unwrap().to_string();
+        let declared = manager
+            .declare_wasm_plugin_by_paths("valid", "valid", &[&missing, &valid], start, false)
+            .unwrap();
+        assert_eq!(declared.load().unwrap().path(), valid);
+        manager.start_plugin("valid", &events).unwrap();
+        assert_eq!(
+            manager.plugin("valid").unwrap().state(),
+            PluginState::Started
+        );
+        drop(manager);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            [
+                format!("start valid from {valid}"),
+                "stop valid".to_string()
+            ]
+        );
+    }
+}
@@ -170,6 +170,16 @@ impl<StartArgs: PluginStartArgs, Instance: PluginInstance> PluginStatus
             self.report.clone()
         }
     }
+    fn dependencies(&self) -> Vec<&str> {
+        self.starter
+            .as_ref()
+            .map_or(Vec::new(), |v| v.vtable.dependencies.to_vec())
+    }
+    fn capabilities(&self) -> Vec<&str> {
+        self.starter
+            .as_ref()
+            .map_or(Vec::new(), |v| v.vtable.capabilities.to_vec())
+    }
 }
 
 impl<StartArgs: PluginStartArgs, Instance: PluginInstance> DeclaredPlugin<StartArgs, Instance>
@@ -214,6 +224,11 @@ impl<StartArgs: PluginStartArgs, Instance: PluginInstance> LoadedPlugin<StartArg
     fn required(&self) -> bool {
         self.required
     }
+    fn config_schema(&self) -> Option<serde_json::Value> {
+        self.starter
+            .as_ref()
+            .and_then(|starter| (starter.vtable.config_schema)())
+    }
     fn start(&mut self, args: &StartArgs) -> ZResult<&mut dyn StartedPlugin<StartArgs, Instance>> {
         let starter = self
             .starter
@@ -72,6 +72,12 @@ where
             PluginReport::default()
         }
     }
+    fn dependencies(&self) -> Vec<&str> {
+        P::DEPENDENCIES.to_vec()
+    }
+    fn capabilities(&self) -> Vec<&str> {
+        P::CAPABILITIES.to_vec()
+    }
 }
 
 impl<StartArgs, Instance: PluginInstance, P> DeclaredPlugin<StartArgs, Instance>
@@ -104,6 +110,9 @@ where
     fn required(&self) -> bool {
         self.required
     }
+    fn config_schema(&self) -> Option<serde_json::Value> {
+        P::config_schema()
+    }
     fn start(&mut self, args: &StartArgs) -> ZResult<&mut dyn StartedPlugin<StartArgs, Instance>> {
         if self.instance.is_none() {
             tracing::debug!("Plugin `{}` started", self.id());
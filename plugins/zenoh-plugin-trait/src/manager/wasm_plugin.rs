@@ -0,0 +1,240 @@
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::*;
+use std::path::PathBuf;
+
+use zenoh_result::{bail, ZResult};
+
+/// A WebAssembly module, compiled when its plugin is loaded.
+/// Fuel metering is enabled on its engine, so the host must add fuel to the stores running it.
+pub struct WasmModule {
+    engine: wasmi::Engine,
+    module: wasmi::Module,
+    path: PathBuf,
+}
+
+impl WasmModule {
+    /// Compiles the first available module from the list of paths
+    fn load(paths: &[String]) -> ZResult<Self> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        for path in paths {
+            let bytes = match std::fs::read(path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::debug!("Attempt to load {} failed: {}", path, e);
+                    continue;
+                }
+            };
+            let module = wasmi::Module::new(&engine, &bytes[..])
+                .map_err(|e| format!("Error compiling {}: {}", path, e))?;
+            return Ok(Self {
+                engine,
+                module,
+                path: path.into(),
+            });
+        }
+        bail!("Plugin not found in {:?}", paths)
+    }
+    pub fn engine(&self) -> &wasmi::Engine {
+        &self.engine
+    }
+    pub fn module(&self) -> &wasmi::Module {
+        &self.module
+    }
+    pub fn path(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+/// Starts an instance of a WebAssembly module with the API provided by the host, given the ID of the plugin.
+pub type WasmStarter<StartArgs, Instance> = fn(&WasmModule, &str, &StartArgs) -> ZResult<Instance>;
+
+/// A plugin compiled to WebAssembly, which only accesses the host through the API of its [`WasmStarter`].
+/// Unlike a dynamic plugin, it doesn't need to be built with the same compiler and zenoh version as the host.
+pub struct WasmPlugin<StartArgs, Instance> {
+    name: String,
+    id: String,
+    required: bool,
+    report: PluginReport,
+    paths: Vec<String>,
+    starter: WasmStarter<StartArgs, Instance>,
+    module: Option<WasmModule>,
+    instance: Option<Instance>,
+}
+
+impl<StartArgs, Instance> WasmPlugin<StartArgs, Instance> {
+    pub fn new(
+        name: String,
+        id: String,
+        paths: Vec<String>,
+        starter: WasmStarter<StartArgs, Instance>,
+        required: bool,
+    ) -> Self {
+        Self {
+            name,
+            id,
+            required,
+            report: PluginReport::new(),
+            paths,
+            starter,
+            module: None,
+            instance: None,
+        }
+    }
+}
+
+impl<StartArgs: PluginStartArgs, Instance: PluginInstance> PluginStatus
+    for WasmPlugin<StartArgs, Instance>
+{
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn version(&self) -> Option<&str> {
+        None
+    }
+    fn long_version(&self) -> Option<&str> {
+        None
+    }
+    fn path(&self) -> &str {
+        if let Some(module) = &self.module {
+            module.path()
+        } else {
+            "__not_loaded__"
+        }
+    }
+    fn state(&self) -> PluginState {
+        if self.module.is_some() {
+            if self.instance.is_some() {
+                PluginState::Started
+            } else {
+                PluginState::Loaded
+            }
+        } else {
+            PluginState::Declared
+        }
+    }
+    fn report(&self) -> PluginReport {
+        if let Some(instance) = &self.instance {
+            instance.report()
+        } else {
+            self.report.clone()
+        }
+    }
+    fn dependencies(&self) -> Vec<&str> {
+        Vec::new()
+    }
+    fn capabilities(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+impl<StartArgs: PluginStartArgs, Instance: PluginInstance> DeclaredPlugin<StartArgs, Instance>
+    for WasmPlugin<StartArgs, Instance>
+{
+    fn as_status(&self) -> &dyn PluginStatus {
+        self
+    }
+    fn load(&mut self) -> ZResult<&mut dyn LoadedPlugin<StartArgs, Instance>> {
+        if self.module.is_none() {
+            let module = WasmModule::load(&self.paths).add_error(&mut self.report)?;
+            tracing::debug!("Plugin {} loaded from {}", self.name, module.path());
+            self.module = Some(module);
+        } else {
+            tracing::warn!("Plugin `{}` already loaded", self.name);
+        }
+        Ok(self)
+    }
+    fn loaded(&self) -> Option<&dyn LoadedPlugin<StartArgs, Instance>> {
+        if self.module.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+    fn loaded_mut(&mut self) -> Option<&mut dyn LoadedPlugin<StartArgs, Instance>> {
+        if self.module.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<StartArgs: PluginStartArgs, Instance: PluginInstance> LoadedPlugin<StartArgs, Instance>
+    for WasmPlugin<StartArgs, Instance>
+{
+    fn as_status(&self) -> &dyn PluginStatus {
+        self
+    }
+    fn required(&self) -> bool {
+        self.required
+    }
+    fn config_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+    fn start(&mut self, args: &StartArgs) -> ZResult<&mut dyn StartedPlugin<StartArgs, Instance>> {
+        let module = self
+            .module
+            .as_ref()
+            .ok_or_else(|| format!("Plugin `{}` not loaded", self.name))
+            .add_error(&mut self.report)?;
+        if self.instance.is_none() {
+            let instance = (self.starter)(module, &self.id, args).add_error(&mut self.report)?;
+            tracing::debug!("Plugin `{}` started", self.name);
+            self.instance = Some(instance);
+        } else {
+            tracing::warn!("Plugin `{}` already started", self.name);
+        }
+        Ok(self)
+    }
+    fn started(&self) -> Option<&dyn StartedPlugin<StartArgs, Instance>> {
+        if self.instance.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+    fn started_mut(&mut self) -> Option<&mut dyn StartedPlugin<StartArgs, Instance>> {
+        if self.instance.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<StartArgs: PluginStartArgs, Instance: PluginInstance> StartedPlugin<StartArgs, Instance>
+    for WasmPlugin<StartArgs, Instance>
+{
+    fn as_status(&self) -> &dyn PluginStatus {
+        self
+    }
+    fn stop(&mut self) {
+        tracing::debug!("Plugin `{}` stopped", self.name);
+        self.report.clear();
+        self.instance = None;
+    }
+    fn instance(&self) -> &Instance {
+        self.instance.as_ref().unwrap()
+    }
+    fn instance_mut(&mut self) -> &mut Instance {
+        self.instance.as_mut().unwrap()
+    }
+}
@@ -75,6 +75,10 @@ pub trait PluginStatus {
     /// Returns the plugin's current report: a list of messages and the severity level
     /// When the status is changed, the report is cleared
     fn report(&self) -> PluginReport;
+    /// Returns the names or capabilities of the plugins which must be started before this plugin (empty if the plugin is not loaded)
+    fn dependencies(&self) -> Vec<&str>;
+    /// Returns the capabilities provided by this plugin in addition to its name (empty if the plugin is not loaded)
+    fn capabilities(&self) -> Vec<&str>;
 }
 
 /// The structure which contains all information about the plugin status in a single cloneable structure
@@ -88,6 +92,10 @@ pub struct PluginStatusRec<'a> {
     pub path: Cow<'a, str>,
     pub state: PluginState,
     pub report: PluginReport,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<Cow<'a, str>>,
 }
 
 impl PluginStatus for PluginStatusRec<'_> {
@@ -114,6 +122,12 @@ impl PluginStatus for PluginStatusRec<'_> {
     fn report(&self) -> PluginReport {
         self.report.clone()
     }
+    fn dependencies(&self) -> Vec<&str> {
+        self.dependencies.iter().map(|d| d.as_ref()).collect()
+    }
+    fn capabilities(&self) -> Vec<&str> {
+        self.capabilities.iter().map(|c| c.as_ref()).collect()
+    }
 }
 
 impl<'a> PluginStatusRec<'a> {
@@ -127,6 +141,16 @@ impl<'a> PluginStatusRec<'a> {
             path: Cow::Borrowed(plugin.path()),
             state: plugin.state(),
             report: plugin.report(),
+            dependencies: plugin
+                .dependencies()
+                .into_iter()
+                .map(Cow::Borrowed)
+                .collect(),
+            capabilities: plugin
+                .capabilities()
+                .into_iter()
+                .map(Cow::Borrowed)
+                .collect(),
         }
     }
     /// Convert the status structure to the owned version
@@ -139,6 +163,16 @@ impl<'a> PluginStatusRec<'a> {
             path: Cow::Owned(self.path.into_owned()),
             state: self.state,
             report: self.report,
+            dependencies: self
+                .dependencies
+                .into_iter()
+                .map(|d| Cow::Owned(d.into_owned()))
+                .collect(),
+            capabilities: self
+                .capabilities
+                .into_iter()
+                .map(|c| Cow::Owned(c.into_owned()))
+                .collect(),
         }
     }
     pub(crate) fn prepend_name(self, prefix: &str) -> Self {
@@ -177,6 +211,16 @@ pub trait Plugin: Sized + 'static {
     const PLUGIN_VERSION: &'static str;
     /// Plugin's long version (with git commit hash). Used only for information purposes. It's recommended to use [plugin_long_version!] macro to generate this string.
     const PLUGIN_LONG_VERSION: &'static str;
+    /// Names or capabilities of the plugins which must be started before this plugin, and stopped after it.
+    /// A dependency is satisfied by a plugin with this name or ID, or providing this capability.
+    const DEPENDENCIES: &'static [&'static str] = &[];
+    /// Capabilities provided by this plugin, which other plugins may depend on in addition to its name.
+    const CAPABILITIES: &'static [&'static str] = &[];
+    /// Returns the JSON schema of the plugin's configuration, used to validate it without starting the plugin.
+    /// Returns `None` by default, in which case the configuration is only validated when the plugin starts.
+    fn config_schema() -> Option<serde_json::Value> {
+        None
+    }
     /// Starts your plugin. Use `Ok` to return your plugin's control structure
     fn start(name: &str, args: &Self::StartArgs) -> ZResult<Self::Instance>;
 }
@@ -19,16 +19,20 @@ pub type PluginLoaderVersion = u64;
 pub const PLUGIN_LOADER_VERSION: PluginLoaderVersion = 1;
 
 type StartFn<StartArgs, Instance> = fn(&str, &StartArgs) -> ZResult<Instance>;
+type ConfigSchemaFn = fn() -> Option<serde_json::Value>;
 
 #[repr(C)]
 pub struct PluginVTable<StartArgs, Instance> {
     pub plugin_version: &'static str,
     pub plugin_long_version: &'static str,
+    pub dependencies: &'static [&'static str],
+    pub capabilities: &'static [&'static str],
+    pub config_schema: ConfigSchemaFn,
     pub start: StartFn<StartArgs, Instance>,
 }
 impl<StartArgs, Instance> StructVersion for PluginVTable<StartArgs, Instance> {
     fn struct_version() -> u64 {
-        1
+        3
     }
     fn struct_features() -> &'static str {
         FEATURES
@@ -40,6 +44,9 @@ impl<StartArgs, Instance> PluginVTable<StartArgs, Instance> {
         Self {
             plugin_version: ConcretePlugin::PLUGIN_VERSION,
             plugin_long_version: ConcretePlugin::PLUGIN_LONG_VERSION,
+            dependencies: ConcretePlugin::DEPENDENCIES,
+            capabilities: ConcretePlugin::CAPABILITIES,
+            config_schema: ConcretePlugin::config_schema,
             start: ConcretePlugin::start,
         }
     }
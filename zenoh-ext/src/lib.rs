@@ -16,6 +16,7 @@ mod publication_cache;
 mod querying_subscriber;
 mod session_ext;
 mod subscriber_ext;
+mod write_batch;
 pub use publication_cache::{PublicationCache, PublicationCacheBuilder};
 pub use querying_subscriber::{
     FetchingSubscriber, FetchingSubscriberBuilder, QueryingSubscriberBuilder,
@@ -23,6 +24,7 @@ pub use querying_subscriber::{
 pub use session_ext::SessionExt;
 pub use subscriber_ext::SubscriberBuilderExt;
 pub use subscriber_ext::SubscriberForward;
+pub use write_batch::{wire as write_batch_wire, WriteBatchBuilder, WriteBatchExt, WriteOutcome};
 
 /// The space of keys to use in a [`FetchingSubscriber`].
 pub enum KeySpace {
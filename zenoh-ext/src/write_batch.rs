@@ -0,0 +1,387 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use std::convert::TryInto;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use wire::{WireCondition, WireConflicts, WireWrite, WRITE_KEY};
+use zenoh::prelude::r#async::*;
+use zenoh::query::{ConsolidationMode, Reply};
+use zenoh::time::Timestamp;
+use zenoh::{Session, SessionRef};
+use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::{bail, zerror, ZResult};
+
+pub mod wire {
+    //! The wire format of the batches of writes, shared with the storage manager.
+    //!
+    //! A batch of writes is sent to a storage as a query with the [`WRITE_KEY`] selector parameter,
+    //! and a JSON array of [`WireWrite`] as value. On a conflict, the storage replies a [`WireConflicts`] error.
+    use serde::{Deserialize, Serialize};
+
+    /// The selector parameter sending a batch of writes to a storage.
+    pub const WRITE_KEY: &str = "_write";
+
+    /// The condition of a write on the value currently stored for its key.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum WireCondition {
+        /// The key must not have a value.
+        Absent,
+        /// The value of the key must have this timestamp.
+        Timestamp(String),
+    }
+
+    /// A write of a batch: a put of a base64 encoded value, or a deletion.
+    #[derive(Serialize, Deserialize)]
+    pub struct WireWrite {
+        pub key: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub value: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub encoding: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        pub delete: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub condition: Option<WireCondition>,
+    }
+
+    /// The current timestamp of a conflicting key, `None` if the key has no value.
+    #[derive(Serialize, Deserialize)]
+    pub struct WireConflict {
+        pub key: String,
+        pub timestamp: Option<String>,
+    }
+
+    /// The error replied by a storage when conditions of a batch are not met.
+    #[derive(Serialize, Deserialize)]
+    pub struct WireConflicts {
+        pub conflicts: Vec<WireConflict>,
+    }
+}
+
+/// The outcome of a [`WriteBatchBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteOutcome {
+    /// All the writes were applied, with the given timestamp.
+    Applied(Timestamp),
+    /// No write was applied, as the values of these keys didn't meet the conditions.
+    /// The current timestamp of each key is given, `None` if the key has no value.
+    Conflict(Vec<(OwnedKeyExpr, Option<Timestamp>)>),
+}
+
+/// The builder of a batch of writes applied together by a storage of the storage manager.
+///
+/// Each write may be conditioned by the value currently stored for its key: it is either expected to have no value,
+/// or a value with a given timestamp. The writes are applied only if all their conditions are met,
+/// allowing optimistic concurrency (compare-and-set) on the stored values.
+///
+/// The written keys must all match the key expression of the builder, which must be served by a single storage.
+/// The writes are stored, but not published to the subscribers of their keys.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh::prelude::r#async::*;
+/// use zenoh_ext::{WriteBatchExt, WriteOutcome};
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let current = session.get("config/a").res().await.unwrap().recv_async().await.unwrap();
+/// let timestamp = current.sample.unwrap().timestamp.unwrap();
+/// match session
+///     .write_batch("config/**")
+///     .put_if("config/a", "new value", timestamp)
+///     .put_if_absent("config/b", "other value")
+///     .res()
+///     .await
+///     .unwrap()
+/// {
+///     WriteOutcome::Applied(timestamp) => println!("Written at {timestamp}"),
+///     WriteOutcome::Conflict(conflicts) => println!("Conflicting keys: {conflicts:?}"),
+/// }
+/// # }
+/// ```
+#[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
+pub struct WriteBatchBuilder<'a, 'b> {
+    session: SessionRef<'a>,
+    key_expr: ZResult<KeyExpr<'b>>,
+    writes: Vec<ZResult<WireWrite>>,
+    timeout: Option<Duration>,
+}
+
+impl<'a, 'b> WriteBatchBuilder<'a, 'b> {
+    pub(crate) fn new(
+        session: SessionRef<'a>,
+        key_expr: ZResult<KeyExpr<'b>>,
+    ) -> WriteBatchBuilder<'a, 'b> {
+        WriteBatchBuilder {
+            session,
+            key_expr,
+            writes: Vec::new(),
+            timeout: None,
+        }
+    }
+
+    /// Put a value, whatever the currently stored value.
+    pub fn put<TryIntoKeyExpr, IntoValue>(self, key: TryIntoKeyExpr, value: IntoValue) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        self.write(key, Some(value.into()), None)
+    }
+
+    /// Put a value if the currently stored value has the expected timestamp.
+    pub fn put_if<TryIntoKeyExpr, IntoValue>(
+        self,
+        key: TryIntoKeyExpr,
+        value: IntoValue,
+        expected: Timestamp,
+    ) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        let condition = WireCondition::Timestamp(expected.to_string());
+        self.write(key, Some(value.into()), Some(condition))
+    }
+
+    /// Put a value if no value is currently stored.
+    pub fn put_if_absent<TryIntoKeyExpr, IntoValue>(
+        self,
+        key: TryIntoKeyExpr,
+        value: IntoValue,
+    ) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        self.write(key, Some(value.into()), Some(WireCondition::Absent))
+    }
+
+    /// Delete a value, whatever the currently stored value.
+    pub fn delete<TryIntoKeyExpr>(self, key: TryIntoKeyExpr) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+    {
+        self.write(key, None, None)
+    }
+
+    /// Delete a value if the currently stored value has the expected timestamp.
+    pub fn delete_if<TryIntoKeyExpr>(self, key: TryIntoKeyExpr, expected: Timestamp) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+    {
+        let condition = WireCondition::Timestamp(expected.to_string());
+        self.write(key, None, Some(condition))
+    }
+
+    /// Change the timeout of the query sending the writes to the storage.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn write<TryIntoKeyExpr>(
+        mut self,
+        key: TryIntoKeyExpr,
+        value: Option<Value>,
+        condition: Option<WireCondition>,
+    ) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+    {
+        let write = key.try_into().map_err(Into::into).map(|key| match value {
+            Some(value) => WireWrite {
+                key: key.to_string(),
+                value: b64_std_engine.encode(value.payload.contiguous()),
+                encoding: value.encoding.to_string(),
+                delete: false,
+                condition,
+            },
+            None => WireWrite {
+                key: key.to_string(),
+                value: String::new(),
+                encoding: String::new(),
+                delete: true,
+                condition,
+            },
+        });
+        self.writes.push(write);
+        self
+    }
+
+    // The selector and value of the query sending the writes
+    fn query(self) -> ZResult<(SessionRef<'a>, Selector<'b>, Value, Option<Duration>)> {
+        let key_expr = self.key_expr?;
+        let writes = self
+            .writes
+            .into_iter()
+            .collect::<ZResult<Vec<WireWrite>>>()?;
+        if writes.is_empty() {
+            bail!("No write to send on '{}'", key_expr)
+        }
+        let value =
+            Value::from(serde_json::to_string(&writes)?).encoding(KnownEncoding::AppJson.into());
+        let selector = key_expr.with_parameters(WRITE_KEY);
+        Ok((self.session, selector, value, self.timeout))
+    }
+}
+
+// Interpret the replies of the storage: the written samples, or an error with the conflicts
+fn outcome(selector: &Selector, replies: Vec<Reply>) -> ZResult<WriteOutcome> {
+    let mut timestamp = None;
+    for reply in replies {
+        match reply.sample {
+            Ok(sample) => timestamp = timestamp.or(sample.timestamp),
+            Err(value) => {
+                let payload = value.payload.contiguous();
+                return match serde_json::from_slice::<WireConflicts>(&payload) {
+                    Ok(conflicts) => {
+                        let conflicts = conflicts
+                            .conflicts
+                            .into_iter()
+                            .map(|c| {
+                                let key = OwnedKeyExpr::from_str(&c.key)?;
+                                let timestamp = match c.timestamp {
+                                    Some(ts) => Some(Timestamp::from_str(&ts).map_err(|e| {
+                                        zerror!("Invalid timestamp '{}': {:?}", ts, e)
+                                    })?),
+                                    None => None,
+                                };
+                                Ok((key, timestamp))
+                            })
+                            .collect::<ZResult<_>>()?;
+                        Ok(WriteOutcome::Conflict(conflicts))
+                    }
+                    Err(_) => bail!(
+                        "Writes on '{}' failed: {}",
+                        selector,
+                        String::from_utf8_lossy(&payload)
+                    ),
+                };
+            }
+        }
+    }
+    match timestamp {
+        Some(timestamp) => Ok(WriteOutcome::Applied(timestamp)),
+        None => bail!("No storage applied the writes on '{}'", selector),
+    }
+}
+
+impl Resolvable for WriteBatchBuilder<'_, '_> {
+    type To = ZResult<WriteOutcome>;
+}
+
+impl SyncResolve for WriteBatchBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let (session, selector, value, timeout) = self.query()?;
+        let mut get = session
+            .get(&selector)
+            .with_value(value)
+            .consolidation(ConsolidationMode::None);
+        if let Some(timeout) = timeout {
+            get = get.timeout(timeout);
+        }
+        let replies = get.res_sync()?;
+        outcome(&selector, replies.into_iter().collect())
+    }
+}
+
+impl<'a> AsyncResolve for WriteBatchBuilder<'a, '_> {
+    type Future = Pin<Box<dyn Future<Output = Self::To> + Send + 'a>>;
+
+    fn res_async(self) -> Self::Future {
+        let query = self.query().map(|(session, selector, value, timeout)| {
+            (session, selector.into_owned(), value, timeout)
+        });
+        Box::pin(async move {
+            let (session, selector, value, timeout) = query?;
+            let mut get = session
+                .get(&selector)
+                .with_value(value)
+                .consolidation(ConsolidationMode::None);
+            if let Some(timeout) = timeout {
+                get = get.timeout(timeout);
+            }
+            let replies = get.res_async().await?;
+            let mut received = Vec::new();
+            while let Ok(reply) = replies.recv_async().await {
+                received.push(reply);
+            }
+            outcome(&selector, received)
+        })
+    }
+}
+
+/// An extension to the [`zenoh::Session`](zenoh::Session) sending batches of writes to the storages.
+pub trait WriteBatchExt<'s, 'a> {
+    /// Build a batch of conditional writes, applied together by the storage serving the given key expression.
+    fn write_batch<'b, TryIntoKeyExpr>(
+        &'s self,
+        key_expr: TryIntoKeyExpr,
+    ) -> WriteBatchBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
+}
+
+impl<'s, 'a> WriteBatchExt<'s, 'a> for SessionRef<'a> {
+    fn write_batch<'b, TryIntoKeyExpr>(
+        &'s self,
+        key_expr: TryIntoKeyExpr,
+    ) -> WriteBatchBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        WriteBatchBuilder::new(self.clone(), key_expr.try_into().map_err(Into::into))
+    }
+}
+
+impl<'a> WriteBatchExt<'a, 'a> for Session {
+    fn write_batch<'b, TryIntoKeyExpr>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+    ) -> WriteBatchBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        SessionRef::Borrow(self).write_batch(key_expr)
+    }
+}
+
+impl<'s> WriteBatchExt<'s, 'static> for Arc<Session> {
+    fn write_batch<'b, TryIntoKeyExpr>(
+        &'s self,
+        key_expr: TryIntoKeyExpr,
+    ) -> WriteBatchBuilder<'static, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        SessionRef::Shared(self.clone()).write_batch(key_expr)
+    }
+}
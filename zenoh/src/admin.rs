@@ -158,6 +158,8 @@ impl TransportMulticastEventHandler for Handler {
                     serde_json::to_vec(&peer).unwrap().into(),
                     #[cfg(feature = "unstable")]
                     None,
+                    #[cfg(feature = "unstable")]
+                    None,
                 );
                 Ok(Arc::new(PeerHandler {
                     expr,
@@ -207,6 +209,8 @@ impl TransportPeerEventHandler for PeerHandler {
             serde_json::to_vec(&link).unwrap().into(),
             #[cfg(feature = "unstable")]
             None,
+            #[cfg(feature = "unstable")]
+            None,
         );
     }
 
@@ -227,6 +231,8 @@ impl TransportPeerEventHandler for PeerHandler {
             vec![0u8; 0].into(),
             #[cfg(feature = "unstable")]
             None,
+            #[cfg(feature = "unstable")]
+            None,
         );
     }
 
@@ -244,6 +250,8 @@ impl TransportPeerEventHandler for PeerHandler {
             vec![0u8; 0].into(),
             #[cfg(feature = "unstable")]
             None,
+            #[cfg(feature = "unstable")]
+            None,
         );
     }
 
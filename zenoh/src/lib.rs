@@ -151,6 +151,8 @@ pub mod query;
 pub mod queryable;
 pub mod sample;
 pub mod subscriber;
+#[cfg(feature = "unstable")]
+pub mod trace;
 pub mod value;
 #[cfg(feature = "shared-memory")]
 pub use zenoh_shm as shm;
@@ -770,6 +770,8 @@ where
                 None,
                 #[cfg(feature = "unstable")]
                 None,
+                #[cfg(feature = "unstable")]
+                None,
                 callback,
             )
             .map(|_| receiver)
@@ -15,6 +15,7 @@ use super::face::FaceState;
 use super::resource::{DataRoutes, Direction, PullCaches, Resource};
 use super::tables::{NodeId, Route, RoutingExpr, Tables, TablesLock};
 use crate::net::routing::hat::HatTrait;
+use crate::net::routing::route_span;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -459,6 +460,23 @@ pub fn full_reentrant_route_data(
 
                 let matching_pulls = get_matching_pulls(&tables, &res, &mut expr);
 
+                let ext_trace = match &payload {
+                    PushBody::Put(m) => m.ext_trace.map(|t| (t.trace_id, t.span_id)),
+                    PushBody::Del(m) => m.ext_trace.map(|t| (t.trace_id, t.span_id)),
+                };
+                let _span = ext_trace
+                    .filter(|_| tables.trace_spans)
+                    .map(|(trace_id, span_id)| {
+                        route_span(
+                            &trace_id,
+                            &span_id,
+                            expr.full_expr(),
+                            face.id,
+                            route.len() + matching_pulls.len(),
+                        )
+                        .entered()
+                    });
+
                 if !(route.is_empty() && matching_pulls.is_empty()) {
                     treat_timestamp!(&tables.hlc, payload, tables.drop_future_timestamp);
 
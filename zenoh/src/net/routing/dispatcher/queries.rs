@@ -16,11 +16,13 @@ use super::resource::{QueryRoute, QueryRoutes, QueryTargetQablSet, Resource};
 use super::tables::NodeId;
 use super::tables::{RoutingExpr, Tables, TablesLock};
 use crate::net::routing::hat::HatTrait;
+use crate::net::routing::metrics::RoutingMetrics;
+use crate::net::routing::route_span;
 use crate::net::routing::RoutingContext;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::{Arc, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 use zenoh_buffers::ZBuf;
 use zenoh_config::WhatAmI;
@@ -47,6 +49,8 @@ use zenoh_util::Timed;
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: RequestId,
+    start: Instant,
+    metrics: Arc<RoutingMetrics>,
 }
 
 pub(crate) fn declare_queryable(
@@ -589,6 +593,8 @@ pub fn route_query(
                 let query = Arc::new(Query {
                     src_face: face.clone(),
                     src_qid: qid,
+                    start: Instant::now(),
+                    metrics: rtables.metrics.clone(),
                 });
 
                 let queries_lock = zwrite!(tables_ref.queries_lock);
@@ -600,6 +606,25 @@ pub fn route_query(
                         .compute_local_replies(&rtables, &prefix, expr.suffix, face);
                 let zid = rtables.zid;
 
+                let ext_trace = match &body {
+                    RequestBody::Query(m) => m.ext_trace.map(|t| (t.trace_id, t.span_id)),
+                    RequestBody::Put(m) => m.ext_trace.map(|t| (t.trace_id, t.span_id)),
+                    RequestBody::Del(m) => m.ext_trace.map(|t| (t.trace_id, t.span_id)),
+                    RequestBody::Pull(_) => None,
+                };
+                let _span = ext_trace
+                    .filter(|_| rtables.trace_spans)
+                    .map(|(trace_id, span_id)| {
+                        route_span(
+                            &trace_id,
+                            &span_id,
+                            expr.full_expr(),
+                            face.id,
+                            route.len() + local_replies.len(),
+                        )
+                        .entered()
+                    });
+
                 let timeout = ext_timeout.unwrap_or(rtables.queries_default_timeout);
 
                 drop(queries_lock);
@@ -614,6 +639,7 @@ pub fn route_query(
                         #[cfg(feature = "shared-memory")]
                         ext_shm: None,
                         ext_attachment: None, // @TODO: expose it in the API
+                        ext_trace: None,
                         ext_unknown: vec![],
                         payload,
                     });
@@ -849,6 +875,7 @@ pub(crate) fn finalize_pending_query(query: (Arc<Query>, CancellationToken)) {
     cancellation_token.cancel();
     if let Some(query) = Arc::into_inner(query) {
         tracing::debug!("Propagate final reply {}:{}", query.src_face, query.src_qid);
+        query.metrics.observe_query_latency(query.start.elapsed());
         query
             .src_face
             .primitives
@@ -19,6 +19,7 @@ use crate::net::routing::hat;
 use crate::net::routing::hat::HatTrait;
 use crate::net::routing::interceptor::interceptor_factories;
 use crate::net::routing::interceptor::InterceptorFactory;
+use crate::net::routing::metrics::RoutingMetrics;
 use std::any::Any;
 use std::collections::HashMap;
 use std::sync::{Arc, Weak};
@@ -65,11 +66,13 @@ pub struct Tables {
     pub(crate) hlc: Option<Arc<HLC>>,
     pub(crate) drop_future_timestamp: bool,
     pub(crate) queries_default_timeout: Duration,
+    pub(crate) trace_spans: bool,
     pub(crate) root_res: Arc<Resource>,
     pub(crate) faces: HashMap<usize, Arc<FaceState>>,
     pub(crate) mcast_groups: Vec<Arc<FaceState>>,
     pub(crate) mcast_faces: Vec<Arc<FaceState>>,
     pub(crate) interceptors: Vec<InterceptorFactory>,
+    pub(crate) metrics: Arc<RoutingMetrics>,
     pub(crate) pull_caches_lock: Mutex<()>,
     pub(crate) hat: Box<dyn Any + Send + Sync>,
     pub(crate) hat_code: Arc<dyn HatTrait + Send + Sync>, // @TODO make this a Box
@@ -88,6 +91,7 @@ impl Tables {
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
         let queries_default_timeout =
             Duration::from_millis(unwrap_or_default!(config.queries_default_timeout()));
+        let trace_spans = unwrap_or_default!(config.routing().trace_spans());
         let hat_code = hat::new_hat(whatami, config);
         Ok(Tables {
             zid,
@@ -96,11 +100,13 @@ impl Tables {
             hlc,
             drop_future_timestamp,
             queries_default_timeout,
+            trace_spans,
             root_res: Resource::root(),
             faces: HashMap::new(),
             mcast_groups: vec![],
             mcast_faces: vec![],
             interceptors: interceptor_factories(config)?,
+            metrics: Arc::new(RoutingMetrics::default()),
             pull_caches_lock: Mutex::new(()),
             hat: hat_code.new_tables(router_peers_failover_brokering),
             hat_code: hat_code.into(),
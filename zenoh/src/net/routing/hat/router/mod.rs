@@ -57,6 +57,7 @@ use std::{
 use zenoh_config::{unwrap_or_default, ModeDependent, WhatAmI, WhatAmIMatcher, ZenohId};
 use zenoh_protocol::{
     common::ZExtBody,
+    core::key_expr::OwnedKeyExpr,
     network::{declare::queryable::ext::QueryableInfo, oam::id::OAM_LINKSTATE, Oam},
 };
 use zenoh_result::ZResult;
@@ -125,6 +126,9 @@ struct HatTables {
     routers_trees_task: Option<TerminatableTask>,
     peers_trees_task: Option<TerminatableTask>,
     router_peers_failover_brokering: bool,
+    aggregated_subs: Vec<OwnedKeyExpr>,
+    // the aggregates declared to the other routers for the subscriptions of this router
+    aggregated_router_subs: HashSet<Arc<Resource>>,
 }
 
 impl Drop for HatTables {
@@ -153,6 +157,8 @@ impl HatTables {
             routers_trees_task: None,
             peers_trees_task: None,
             router_peers_failover_brokering,
+            aggregated_subs: vec![],
+            aggregated_router_subs: HashSet::new(),
         }
     }
 
@@ -315,8 +321,11 @@ impl HatBaseTrait for HatCode {
             && unwrap_or_default!(config.routing().peer().mode()) == *"linkstate";
         let router_peers_failover_brokering =
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
+        let aggregated_subs = config.aggregation().subscribers().clone();
         drop(config);
 
+        hat_mut!(tables).aggregated_subs = aggregated_subs;
+
         if router_full_linkstate | gossip {
             hat_mut!(tables).routers_net = Some(Network::new(
                 "[Routers network]".to_string(),
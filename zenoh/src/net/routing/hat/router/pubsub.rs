@@ -26,7 +26,7 @@ use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use zenoh_protocol::core::key_expr::OwnedKeyExpr;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
 use zenoh_protocol::{
     core::{Reliability, WhatAmI, ZenohId},
     network::declare::{
@@ -199,7 +199,18 @@ fn register_router_subscription(
         }
 
         // Propagate subscription to routers
-        propagate_sourced_subscription(tables, res, sub_info, Some(face), &router, WhatAmI::Router);
+        if router == tables.zid {
+            propagate_local_router_subscription(tables, res, sub_info, face);
+        } else {
+            propagate_sourced_subscription(
+                tables,
+                res,
+                sub_info,
+                Some(face),
+                &router,
+                WhatAmI::Router,
+            );
+        }
     }
     // Propagate subscription to peers
     if hat!(tables).full_net(WhatAmI::Peer) && face.whatami != WhatAmI::Peer {
@@ -326,6 +337,108 @@ fn remote_peer_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
             .any(|peer| peer != &tables.zid)
 }
 
+/// Returns the configured aggregation key expression that includes the given resource, if any.
+#[inline]
+fn aggregated_sub(tables: &Tables, res: &Arc<Resource>) -> Option<OwnedKeyExpr> {
+    let expr = res.expr();
+    let key_expr = keyexpr::new(expr.as_str()).ok()?;
+    hat!(tables)
+        .aggregated_subs
+        .iter()
+        .find(|agg| agg.includes(key_expr))
+        .cloned()
+}
+
+/// Returns the resource of the aggregation key expression `agg`, declared to the other routers
+/// for the subscriptions of this router that it includes.
+fn aggregate_resource(tables: &mut Tables, agg: &OwnedKeyExpr) -> Arc<Resource> {
+    match Resource::get_resource(&tables.root_res, agg) {
+        Some(agg_res) if agg_res.context.is_some() => agg_res,
+        _ => {
+            let mut matches = Resource::get_matches(tables, agg);
+            let mut root_res = tables.root_res.clone();
+            let mut agg_res = Resource::make_resource(tables, &mut root_res, agg);
+            matches.push(Arc::downgrade(&agg_res));
+            Resource::match_resource(tables, &mut agg_res, matches);
+            agg_res
+        }
+    }
+}
+
+/// Propagates a subscription of this router to the other routers. A subscription included in an
+/// aggregation key expression is propagated as the aggregate, once for all the subscriptions it includes.
+/// The peers and the clients are still sent the precise subscriptions.
+fn propagate_local_router_subscription(
+    tables: &mut Tables,
+    res: &Arc<Resource>,
+    sub_info: &SubscriberInfo,
+    face: &Arc<FaceState>,
+) {
+    let zid = tables.zid;
+    match aggregated_sub(tables, res) {
+        Some(agg) => {
+            let agg_res = aggregate_resource(tables, &agg);
+            if hat_mut!(tables)
+                .aggregated_router_subs
+                .insert(agg_res.clone())
+            {
+                tracing::debug!("Aggregate subscription {} into {}", res.expr(), agg);
+                propagate_sourced_subscription(
+                    tables,
+                    &agg_res,
+                    sub_info,
+                    Some(face),
+                    &zid,
+                    WhatAmI::Router,
+                );
+            }
+        }
+        None => {
+            propagate_sourced_subscription(tables, res, sub_info, Some(face), &zid, WhatAmI::Router)
+        }
+    }
+}
+
+/// Propagates the undeclaration of a subscription of this router to the other routers.
+/// An aggregate is only undeclared once it includes no other subscription of this router.
+fn propagate_forget_local_router_subscription(tables: &mut Tables, res: &Arc<Resource>) {
+    let zid = tables.zid;
+    match aggregated_sub(tables, res) {
+        Some(agg) => {
+            let Some(mut agg_res) = Resource::get_resource(&tables.root_res, &agg) else {
+                return;
+            };
+            if !hat!(tables).aggregated_router_subs.contains(&agg_res) {
+                return;
+            }
+            let remaining = agg_res
+                .context()
+                .matches
+                .iter()
+                .filter_map(|m| m.upgrade())
+                .any(|m| {
+                    m.context.is_some()
+                        && res_hat!(m).router_subs.contains(&zid)
+                        && aggregated_sub(tables, &m).as_ref() == Some(&agg)
+                });
+            if !remaining {
+                hat_mut!(tables).aggregated_router_subs.remove(&agg_res);
+                propagate_forget_sourced_subscription(
+                    tables,
+                    &agg_res,
+                    None,
+                    &zid,
+                    WhatAmI::Router,
+                );
+                if !Arc::ptr_eq(&agg_res, res) {
+                    Resource::clean(&mut agg_res);
+                }
+            }
+        }
+        None => propagate_forget_sourced_subscription(tables, res, None, &zid, WhatAmI::Router),
+    }
+}
+
 #[inline]
 fn client_subs(res: &Arc<Resource>) -> Vec<Arc<FaceState>> {
     res.session_ctxs
@@ -508,7 +621,11 @@ fn undeclare_router_subscription(
 ) {
     if res_hat!(res).router_subs.contains(router) {
         unregister_router_subscription(tables, res, router);
-        propagate_forget_sourced_subscription(tables, res, face, router, WhatAmI::Router);
+        if *router == tables.zid {
+            propagate_forget_local_router_subscription(tables, res);
+        } else {
+            propagate_forget_sourced_subscription(tables, res, face, router, WhatAmI::Router);
+        }
     }
 }
 
@@ -732,7 +849,12 @@ pub(super) fn pubsub_tree_change(
                         _ => &res_hat!(res).peer_subs,
                     };
                     for sub in subs {
-                        if *sub == tree_id {
+                        // the aggregated subscriptions of this router are propagated as their aggregate
+                        if *sub == tree_id
+                            && !(net_type == WhatAmI::Router
+                                && tree_id == tables.zid
+                                && aggregated_sub(tables, res).is_some())
+                        {
                             let sub_info = SubscriberInfo {
                                 reliability: Reliability::Reliable, // @TODO compute proper reliability to propagate from reliability of known subscribers
                                 mode: Mode::Push,
@@ -749,6 +871,23 @@ pub(super) fn pubsub_tree_change(
                         }
                     }
                 }
+                if net_type == WhatAmI::Router && tree_id == tables.zid {
+                    let sub_info = SubscriberInfo {
+                        reliability: Reliability::Reliable, // @TODO compute proper reliability to propagate from reliability of known subscribers
+                        mode: Mode::Push,
+                    };
+                    for agg_res in &hat!(tables).aggregated_router_subs {
+                        send_sourced_subscription_to_net_children(
+                            tables,
+                            net,
+                            tree_children,
+                            agg_res,
+                            None,
+                            &sub_info,
+                            tree_sid as NodeId,
+                        );
+                    }
+                }
             }
         }
     }
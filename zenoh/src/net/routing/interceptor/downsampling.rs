@@ -120,6 +120,10 @@ pub(crate) struct DownsamplingInterceptor {
 }
 
 impl InterceptorTrait for DownsamplingInterceptor {
+    fn name(&self) -> &'static str {
+        "downsampling"
+    }
+
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
         let ke_id = zlock!(self.ke_id);
         if let Some(id) = ke_id.weight_at(&key_expr.clone()) {
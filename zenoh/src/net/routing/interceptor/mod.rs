@@ -22,9 +22,10 @@ mod access_control;
 use access_control::acl_interceptor_factories;
 
 mod authorization;
-use super::RoutingContext;
+use super::{metrics::RoutingMetrics, RoutingContext};
 use crate::KeyExpr;
 use std::any::Any;
+use std::sync::Arc;
 
 use zenoh_config::Config;
 use zenoh_protocol::network::NetworkMessage;
@@ -35,6 +36,9 @@ pub mod downsampling;
 use crate::net::routing::interceptor::downsampling::downsampling_interceptor_factories;
 
 pub(crate) trait InterceptorTrait {
+    /// The name under which the messages dropped by this interceptor are counted.
+    fn name(&self) -> &'static str;
+
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>>;
 
     fn intercept(
@@ -68,8 +72,24 @@ pub(crate) fn interceptor_factories(config: &Config) -> ZResult<Vec<InterceptorF
     Ok(res)
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum InterceptorFlow {
+    Ingress,
+    Egress,
+}
+
+impl InterceptorFlow {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            InterceptorFlow::Ingress => "ingress",
+            InterceptorFlow::Egress => "egress",
+        }
+    }
+}
+
 pub(crate) struct InterceptorsChain {
     pub(crate) interceptors: Vec<Interceptor>,
+    metrics: Option<(Arc<RoutingMetrics>, InterceptorFlow)>,
 }
 
 impl InterceptorsChain {
@@ -77,17 +97,35 @@ impl InterceptorsChain {
     pub(crate) fn empty() -> Self {
         Self {
             interceptors: vec![],
+            metrics: None,
         }
     }
+
+    /// Count the messages dropped by the interceptors of this chain in `metrics`.
+    pub(crate) fn with_metrics(
+        mut self,
+        metrics: Arc<RoutingMetrics>,
+        flow: InterceptorFlow,
+    ) -> Self {
+        self.metrics = Some((metrics, flow));
+        self
+    }
 }
 
 impl From<Vec<Interceptor>> for InterceptorsChain {
     fn from(interceptors: Vec<Interceptor>) -> Self {
-        InterceptorsChain { interceptors }
+        InterceptorsChain {
+            interceptors,
+            metrics: None,
+        }
     }
 }
 
 impl InterceptorTrait for InterceptorsChain {
+    fn name(&self) -> &'static str {
+        "chain"
+    }
+
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
         Some(Box::new(
             self.interceptors
@@ -112,6 +150,9 @@ impl InterceptorTrait for InterceptorsChain {
                 Some(newctx) => ctx = newctx,
                 None => {
                     tracing::trace!("Msg intercepted!");
+                    if let Some((metrics, flow)) = &self.metrics {
+                        metrics.inc_interceptor_drops(interceptor.name(), *flow);
+                    }
                     return None;
                 }
             }
@@ -132,6 +173,11 @@ impl<T: InterceptorTrait> ComputeOnMiss<T> {
 }
 
 impl<T: InterceptorTrait> InterceptorTrait for ComputeOnMiss<T> {
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.interceptor.name()
+    }
+
     #[inline]
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
         self.interceptor.compute_keyexpr_cache(key_expr)
@@ -161,6 +207,10 @@ impl<T: InterceptorTrait> InterceptorTrait for ComputeOnMiss<T> {
 pub(crate) struct IngressMsgLogger {}
 
 impl InterceptorTrait for IngressMsgLogger {
+    fn name(&self) -> &'static str {
+        "logger"
+    }
+
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
         Some(Box::new(key_expr.to_string()))
     }
@@ -188,6 +238,10 @@ impl InterceptorTrait for IngressMsgLogger {
 pub(crate) struct EgressMsgLogger {}
 
 impl InterceptorTrait for EgressMsgLogger {
+    fn name(&self) -> &'static str {
+        "logger"
+    }
+
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
         Some(Box::new(key_expr.to_string()))
     }
@@ -0,0 +1,136 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+use super::interceptor::InterceptorFlow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (in seconds) of the buckets of the query latency histogram.
+const QUERY_LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0,
+];
+
+/// Metrics maintained by the routing layer that are not bound to a transport.
+#[derive(Default)]
+pub(crate) struct RoutingMetrics {
+    interceptor_drops: Mutex<HashMap<(&'static str, InterceptorFlow), u64>>,
+    query_latency: Histogram,
+}
+
+impl RoutingMetrics {
+    pub(crate) fn inc_interceptor_drops(&self, interceptor: &'static str, flow: InterceptorFlow) {
+        *zlock!(self.interceptor_drops)
+            .entry((interceptor, flow))
+            .or_default() += 1;
+    }
+
+    pub(crate) fn observe_query_latency(&self, latency: Duration) {
+        self.query_latency.observe(latency);
+    }
+
+    pub(crate) fn openmetrics_text(&self) -> String {
+        let mut s = String::new();
+        s.push_str("# HELP zenoh_interceptor_dropped_messages Counter of network messages dropped by an interceptor.\n");
+        s.push_str("# TYPE zenoh_interceptor_dropped_messages counter\n");
+        let mut drops = zlock!(self.interceptor_drops)
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<_>>();
+        drops.sort_unstable();
+        for ((interceptor, flow), count) in drops {
+            let _ = writeln!(
+                s,
+                "zenoh_interceptor_dropped_messages_total{{interceptor=\"{interceptor}\",flow=\"{}\"}} {count}",
+                flow.as_str()
+            );
+        }
+        s.push_str(&self.query_latency.openmetrics_text(
+            "zenoh_query_duration_seconds",
+            "Time between the routing of a query and its final reply.",
+        ));
+        s
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    // Non-cumulative counts, the last slot holding the observations above the largest bound.
+    buckets: [AtomicU64; QUERY_LATENCY_BUCKETS.len() + 1],
+    sum_us: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, value: Duration) {
+        let secs = value.as_secs_f64();
+        let idx = QUERY_LATENCY_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(QUERY_LATENCY_BUCKETS.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum_us
+            .fetch_add(value.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn openmetrics_text(&self, name: &str, help: &str) -> String {
+        let mut s =
+            format!("# HELP {name} {help}\n# TYPE {name} histogram\n# UNIT {name} seconds\n");
+        let mut count = 0;
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            count += bucket.load(Ordering::Relaxed);
+            match QUERY_LATENCY_BUCKETS.get(idx) {
+                Some(bound) => writeln!(s, "{name}_bucket{{le=\"{bound:?}\"}} {count}"),
+                None => writeln!(s, "{name}_bucket{{le=\"+Inf\"}} {count}"),
+            }
+            .unwrap();
+        }
+        let sum = self.sum_us.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        writeln!(s, "{name}_sum {sum}\n{name}_count {count}").unwrap();
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_openmetrics_text() {
+        let metrics = RoutingMetrics::default();
+        metrics.observe_query_latency(Duration::from_micros(500));
+        metrics.observe_query_latency(Duration::from_millis(30));
+        metrics.observe_query_latency(Duration::from_secs(20));
+        metrics.inc_interceptor_drops("downsampling", InterceptorFlow::Egress);
+        metrics.inc_interceptor_drops("downsampling", InterceptorFlow::Egress);
+
+        let text = metrics.openmetrics_text();
+        assert!(text.contains(
+            "zenoh_interceptor_dropped_messages_total{interceptor=\"downsampling\",flow=\"egress\"} 2\n"
+        ));
+        assert!(text.contains("zenoh_query_duration_seconds_bucket{le=\"0.001\"} 1\n"));
+        assert!(text.contains("zenoh_query_duration_seconds_bucket{le=\"0.025\"} 1\n"));
+        assert!(text.contains("zenoh_query_duration_seconds_bucket{le=\"0.05\"} 2\n"));
+        assert!(text.contains("zenoh_query_duration_seconds_bucket{le=\"10.0\"} 2\n"));
+        assert!(text.contains("zenoh_query_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("zenoh_query_duration_seconds_sum 20.0305\n"));
+        assert!(text.contains("zenoh_query_duration_seconds_count 3\n"));
+    }
+}
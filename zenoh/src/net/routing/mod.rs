@@ -20,6 +20,7 @@
 pub mod dispatcher;
 pub mod hat;
 pub mod interceptor;
+pub(crate) mod metrics;
 pub mod router;
 
 use std::{cell::OnceCell, sync::Arc};
@@ -33,6 +34,36 @@ use super::runtime;
 
 pub(crate) static PREFIX_LIVELINESS: &str = "@/liveliness";
 
+/// Formats a trace or span id as lowercase hexadecimal.
+pub(crate) struct HexId<'a>(pub(crate) &'a [u8]);
+
+impl std::fmt::Display for HexId<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for b in self.0 {
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates the span recording the routing decision taken on a message carrying a trace context.
+pub(crate) fn route_span(
+    trace_id: &[u8],
+    span_id: &[u8],
+    key_expr: &str,
+    face: usize,
+    routes: usize,
+) -> tracing::Span {
+    tracing::debug_span!(
+        "zenoh_route",
+        trace_id = %HexId(trace_id),
+        parent_span_id = %HexId(span_id),
+        key_expr,
+        face,
+        routes
+    )
+}
+
 pub(crate) struct RoutingContext<Msg> {
     pub(crate) msg: Msg,
     pub(crate) inface: OnceCell<Face>,
@@ -19,7 +19,7 @@ use super::dispatcher::tables::Tables;
 use super::dispatcher::tables::TablesLock;
 use super::hat;
 use super::interceptor::EgressInterceptor;
-use super::interceptor::InterceptorsChain;
+use super::interceptor::{InterceptorFlow, InterceptorsChain};
 use super::runtime::Runtime;
 use crate::net::primitives::DeMux;
 use crate::net::primitives::DummyPrimitives;
@@ -125,10 +125,12 @@ impl Router {
             .map(|itor| itor.new_transport_unicast(&transport))
             .unzip();
         let (ingress, egress) = (
-            Arc::new(InterceptorsChain::from(
-                ingress.into_iter().flatten().collect::<Vec<_>>(),
-            )),
-            InterceptorsChain::from(egress.into_iter().flatten().collect::<Vec<_>>()),
+            Arc::new(
+                InterceptorsChain::from(ingress.into_iter().flatten().collect::<Vec<_>>())
+                    .with_metrics(tables.metrics.clone(), InterceptorFlow::Ingress),
+            ),
+            InterceptorsChain::from(egress.into_iter().flatten().collect::<Vec<_>>())
+                .with_metrics(tables.metrics.clone(), InterceptorFlow::Egress),
         );
         let mux = Arc::new(Mux::new(transport.clone(), egress));
         let newface = tables
@@ -173,7 +175,8 @@ impl Router {
                 .iter()
                 .filter_map(|itor| itor.new_transport_multicast(&transport))
                 .collect::<Vec<EgressInterceptor>>(),
-        );
+        )
+        .with_metrics(tables.metrics.clone(), InterceptorFlow::Egress);
         let mux = Arc::new(McastMux::new(transport.clone(), interceptor));
         let face = FaceState::new(
             fid,
@@ -207,13 +210,16 @@ impl Router {
         let mut tables = zwrite!(self.tables.tables);
         let fid = tables.face_counter;
         tables.face_counter += 1;
-        let interceptor = Arc::new(InterceptorsChain::from(
-            tables
-                .interceptors
-                .iter()
-                .filter_map(|itor| itor.new_peer_multicast(&transport))
-                .collect::<Vec<IngressInterceptor>>(),
-        ));
+        let interceptor = Arc::new(
+            InterceptorsChain::from(
+                tables
+                    .interceptors
+                    .iter()
+                    .filter_map(|itor| itor.new_peer_multicast(&transport))
+                    .collect::<Vec<IngressInterceptor>>(),
+            )
+            .with_metrics(tables.metrics.clone(), InterceptorFlow::Ingress),
+        );
         let face_state = FaceState::new(
             fid,
             peer.zid,
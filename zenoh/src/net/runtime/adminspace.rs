@@ -30,7 +30,7 @@ use tracing::{error, trace};
 use zenoh_buffers::buffer::SplitBuffer;
 use zenoh_config::{unwrap_or_default, ConfigValidator, ValidatedMap, WhatAmI};
 #[cfg(all(feature = "unstable", feature = "plugins"))]
-use zenoh_plugin_trait::{PluginControl, PluginStatus};
+use zenoh_plugin_trait::{DeclaredPlugin, PluginControl, PluginStatus, PluginStatusRec};
 #[cfg(all(feature = "unstable", feature = "plugins"))]
 use zenoh_protocol::core::key_expr::keyexpr;
 use zenoh_protocol::{
@@ -97,22 +97,31 @@ impl ConfigValidator for AdminSpace {
 
 impl AdminSpace {
     #[cfg(all(feature = "unstable", feature = "plugins"))]
-    fn start_plugin(
-        plugin_mgr: &mut plugins::PluginsManager,
+    fn declare_plugin<'a>(
+        plugin_mgr: &'a mut plugins::PluginsManager,
         config: &crate::config::PluginLoad,
-        start_args: &Runtime,
         required: bool,
-    ) -> ZResult<()> {
+    ) -> ZResult<&'a mut dyn DeclaredPlugin<Runtime, plugins::RunningPlugin>> {
         let id = &config.id;
         let name = &config.name;
-        let declared = if let Some(declared) = plugin_mgr.plugin_mut(id) {
-            tracing::warn!("Plugin `{}` was already declared", declared.id());
-            declared
+        if plugin_mgr.plugin(id).is_some() {
+            tracing::warn!("Plugin `{}` was already declared", id);
+            Ok(plugin_mgr.plugin_mut(id).unwrap())
         } else if let Some(paths) = &config.paths {
-            plugin_mgr.declare_dynamic_plugin_by_paths(id, name, paths, required)?
+            crate::plugins::loader::declare_plugin_by_paths(plugin_mgr, name, id, paths, required)
         } else {
-            plugin_mgr.declare_dynamic_plugin_by_name(id, name, required)?
-        };
+            plugin_mgr.declare_dynamic_plugin_by_name(id, name, required)
+        }
+    }
+
+    #[cfg(all(feature = "unstable", feature = "plugins"))]
+    fn start_plugin(
+        plugin_mgr: &mut plugins::PluginsManager,
+        config: &crate::config::PluginLoad,
+        start_args: &Runtime,
+        required: bool,
+    ) -> ZResult<()> {
+        let declared = Self::declare_plugin(plugin_mgr, config, required)?;
 
         let loaded = if let Some(loaded) = declared.loaded_mut() {
             tracing::warn!(
@@ -128,7 +137,7 @@ impl AdminSpace {
         if let Some(started) = loaded.started_mut() {
             tracing::warn!("Plugin `{}` was already started", started.id());
         } else {
-            let started = loaded.start(start_args)?;
+            let started = plugin_mgr.start_plugin(&config.id, start_args)?;
             tracing::info!(
                 "Successfully started plugin `{}` from {}",
                 started.id(),
@@ -265,9 +274,7 @@ impl AdminSpace {
                             match diff {
                                 PluginDiff::Delete(id) => {
                                     active_plugins.remove(id.as_str());
-                                    if let Some(running) = plugins_mgr.started_plugin_mut(&id) {
-                                        running.stop()
-                                    }
+                                    plugins_mgr.stop_plugin(&id);
                                 }
                                 PluginDiff::Start(plugin) => {
                                     if let Err(e) = Self::start_plugin(
@@ -460,6 +467,8 @@ impl Primitives for AdminSpace {
                     primitives,
                     #[cfg(feature = "unstable")]
                     attachment: query.ext_attachment.map(Into::into),
+                    #[cfg(feature = "unstable")]
+                    trace_context: crate::trace::extract(query.ext_trace),
                 }),
             };
 
@@ -618,24 +627,7 @@ fn metrics(context: &AdminContext, query: Query) {
     )
     .try_into()
     .unwrap();
-    #[allow(unused_mut)]
-    let mut metrics = format!(
-        r#"# HELP zenoh_build Information about zenoh.
-# TYPE zenoh_build gauge
-zenoh_build{{version="{}"}} 1
-"#,
-        context.version
-    );
-
-    #[cfg(feature = "stats")]
-    metrics.push_str(
-        &context
-            .runtime
-            .manager()
-            .get_stats()
-            .report()
-            .openmetrics_text(),
-    );
+    let metrics = super::metrics::openmetrics_text(&context.runtime, &context.version, false);
 
     if let Err(e) = query
         .reply(Ok(Sample::new(
@@ -756,14 +748,196 @@ fn queryables_data(context: &AdminContext, query: Query) {
     }
 }
 
+/// The selector parameter requesting an action on a plugin.
+#[cfg(all(feature = "unstable", feature = "plugins"))]
+const PLUGIN_ACTION_PARAMETER: &str = "_action";
+
+/// The actions on a plugin which may be requested by a query on `@/<whatami>/<zid>/plugins/<id>?_action=<action>`.
+/// The value of the query, if any, is the new configuration of the plugin.
+#[cfg(all(feature = "unstable", feature = "plugins"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PluginAction {
+    /// (Re)declares the plugin from its configuration and loads it, keeping the previous declaration if it fails.
+    /// The plugin must not be started.
+    Load,
+    /// Declares, loads and starts the plugin if needed.
+    Start,
+    /// Stops the plugin, which stays loaded.
+    Stop,
+    /// Stops the plugin if it's started and starts it again.
+    Restart,
+}
+
+#[cfg(all(feature = "unstable", feature = "plugins"))]
+impl std::str::FromStr for PluginAction {
+    type Err = zenoh_result::Error;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        match s {
+            "load" => Ok(PluginAction::Load),
+            "start" => Ok(PluginAction::Start),
+            "stop" => Ok(PluginAction::Stop),
+            "restart" => Ok(PluginAction::Restart),
+            _ => bail!(
+                "Unknown plugin action '{}' (expected load, start, stop or restart)",
+                s
+            ),
+        }
+    }
+}
+
+/// Sets the configuration of a plugin, keeping the `__path__`, `__required__` and `__plugin__` properties of
+/// its current configuration unless they are redefined.
+#[cfg(all(feature = "unstable", feature = "plugins"))]
+fn set_plugin_config(runtime: &Runtime, id: &str, config: &Value) -> ZResult<()> {
+    let config: serde_json::Value = serde_json::from_slice(&config.payload.contiguous())
+        .map_err(|e| zerror!("Invalid configuration for plugin `{}`: {}", id, e))?;
+    let serde_json::Value::Object(mut config) = config else {
+        bail!("The configuration of plugin `{}` must be an object", id)
+    };
+    // The config watcher isn't notified of the change: the plugin is handled by the action itself
+    let mut guard = runtime.state.config.lock();
+    if let Some(serde_json::Value::Object(current)) = guard.plugin(id) {
+        for property in ["__path__", "__required__", "__plugin__"] {
+            if let (Some(value), false) = (current.get(property), config.contains_key(property)) {
+                config.insert(property.into(), value.clone());
+            }
+        }
+    }
+    guard
+        .insert_json5(
+            &format!("plugins/{id}"),
+            &serde_json::Value::Object(config).to_string(),
+        )
+        .map_err(|e| zerror!("Invalid configuration for plugin `{}`: {}", id, e).into())
+}
+
+#[cfg(all(feature = "unstable", feature = "plugins"))]
+fn plugin_action(
+    runtime: &Runtime,
+    id: &str,
+    action: PluginAction,
+    config: Option<&Value>,
+) -> ZResult<PluginStatusRec<'static>> {
+    // The plugins depending on a stopped plugin are stopped too
+    let stopped = {
+        let mut plugins_mgr = runtime.plugins_manager();
+        match (action, plugins_mgr.started_plugin(id)) {
+            (PluginAction::Stop | PluginAction::Restart, Some(_)) => {
+                let stopped = plugins_mgr.stop_plugin(id);
+                tracing::info!("Stopped plugins {:?} through the admin space", stopped);
+                stopped
+            }
+            (PluginAction::Stop, None) => bail!("Plugin `{}` is not started", id),
+            (PluginAction::Load | PluginAction::Start, Some(_)) => {
+                bail!("Plugin `{}` is already started", id)
+            }
+            _ => Vec::new(),
+        }
+    };
+    // The plugins manager mustn't be locked while the configuration is validated
+    if let Some(config) = config {
+        set_plugin_config(runtime, id, config)?;
+    }
+    let request = runtime
+        .state
+        .config
+        .lock()
+        .plugins()
+        .load_requests()
+        .find(|request| request.id == id);
+    let mut plugins_mgr = runtime.plugins_manager();
+    match (action, request) {
+        (PluginAction::Stop, _) => {}
+        (_, None) => bail!("Plugin `{}` isn't configured", id),
+        (PluginAction::Load, Some(request)) => {
+            // Declaring the plugin again allows to load it from a new library.
+            // The previous declaration is restored if the new one fails to load.
+            plugins_mgr.remove_plugin(id);
+            let loaded = AdminSpace::declare_plugin(&mut plugins_mgr, &request, request.required)
+                .and_then(|declared| declared.load().map(|loaded| loaded.path().to_string()));
+            match loaded {
+                Ok(path) => {
+                    tracing::info!(
+                        "Loaded plugin `{}` from {} through the admin space",
+                        id,
+                        path
+                    )
+                }
+                Err(e) => {
+                    plugins_mgr.restore_plugin(id);
+                    return Err(e);
+                }
+            }
+        }
+        (PluginAction::Start, Some(request)) => {
+            AdminSpace::start_plugin(&mut plugins_mgr, &request, runtime, request.required)?
+        }
+        (PluginAction::Restart, Some(request)) => {
+            AdminSpace::start_plugin(&mut plugins_mgr, &request, runtime, request.required)?;
+            // The dependents were stopped before the plugin, so they're started again after it
+            for dependent in stopped.iter().rev().filter(|dependent| *dependent != id) {
+                if let Err(e) = plugins_mgr.start_plugin(dependent, runtime) {
+                    tracing::error!("Failed to restart plugin `{}`: {}", dependent, e);
+                }
+            }
+        }
+    }
+    let plugin = plugins_mgr
+        .plugin(id)
+        .ok_or_else(|| zerror!("Plugin `{}` is not declared", id))?;
+    Ok(PluginStatusRec::new(plugin.as_status()).into_owned())
+}
+
 #[cfg(all(feature = "unstable", feature = "plugins"))]
 fn plugins_data(context: &AdminContext, query: Query) {
-    let guard = context.runtime.plugins_manager();
     let root_key = format!(
         "@/{}/{}/plugins",
         context.runtime.state.whatami, &context.runtime.state.zid
     );
     let root_key = unsafe { keyexpr::from_str_unchecked(&root_key) };
+    let action = query
+        .selector()
+        .parameters_stringmap()
+        .ok()
+        .and_then(|mut parameters| parameters.remove(PLUGIN_ACTION_PARAMETER));
+    if let Some(action) = action {
+        // Actions are only performed on a single plugin, which isn't a subplugin
+        let id = match query.key_expr().strip_prefix(root_key)[..] {
+            [id] if !id.is_wild() && !id.as_str().contains('/') => id.to_string(),
+            _ => return,
+        };
+        let key = root_key.join(&id).unwrap();
+        let runtime = context.runtime.clone();
+        runtime.clone().spawn(async move {
+            let result = if !runtime.state.config.lock().adminspace.permissions().write {
+                Err(zerror!(
+                    "Received action on plugin `{}` but adminspace.permissions.write=false in configuration",
+                    id
+                )
+                .into())
+            } else {
+                action
+                    .parse()
+                    .and_then(|action| plugin_action(&runtime, &id, action, query.value()))
+            };
+            let reply = match result {
+                Ok(status) => Ok(Sample::new(
+                    key,
+                    Value::from(serde_json::to_value(status).unwrap()),
+                )),
+                Err(e) => {
+                    tracing::error!("Error on plugin `{}`: {}", id, e);
+                    Err(Value::from(e.to_string()))
+                }
+            };
+            if let Err(e) = query.reply(reply).res() {
+                tracing::error!("Error sending AdminSpace reply: {:?}", e);
+            }
+        });
+        return;
+    }
+    let guard = context.runtime.plugins_manager();
     tracing::debug!("requested plugins status {:?}", query.key_expr());
     if let [names, ..] = query.key_expr().strip_prefix(root_key)[..] {
         let statuses = guard.plugins_status(names);
@@ -787,26 +961,34 @@ fn plugins_status(context: &AdminContext, query: Query) {
         context.runtime.state.whatami, &context.runtime.state.zid
     );
 
-    for plugin in guard.started_plugins_iter() {
+    for plugin in guard.declared_plugins_iter() {
         with_extended_string(&mut root_key, &[plugin.id()], |plugin_key| {
-            // @TODO: response to "__version__", this need not to be implemented by each plugin
-            with_extended_string(plugin_key, &["/__path__"], |plugin_path_key| {
-                if let Ok(key_expr) = KeyExpr::try_from(plugin_path_key.clone()) {
-                    if query.key_expr().intersects(&key_expr) {
-                        if let Err(e) = query
-                            .reply(Ok(Sample::new(
-                                key_expr,
-                                serde_json::Value::String(plugin.path().into()),
-                            )))
-                            .res()
-                        {
-                            tracing::error!("Error sending AdminSpace reply: {:?}", e);
+            let reply = |plugin_key: &mut String, suffix: &str, value: serde_json::Value| {
+                with_extended_string(plugin_key, &[suffix], |plugin_status_key| {
+                    if let Ok(key_expr) = KeyExpr::try_from(plugin_status_key.clone()) {
+                        if query.key_expr().intersects(&key_expr) {
+                            if let Err(e) =
+                                query.reply(Ok(Sample::new(key_expr, value.clone()))).res()
+                            {
+                                tracing::error!("Error sending AdminSpace reply: {:?}", e);
+                            }
                         }
+                    } else {
+                        tracing::error!("Error: invalid plugin status key {}", plugin_status_key);
                     }
-                } else {
-                    tracing::error!("Error: invalid plugin path key {}", plugin_path_key);
-                }
-            });
+                })
+            };
+            // The state of every declared plugin is reported, as it changes through the plugins actions
+            reply(plugin_key, "/__state__", json!(plugin.state()));
+            reply(plugin_key, "/__report__", json!(plugin.report()));
+            let Some(plugin) = plugin.loaded() else {
+                return;
+            };
+            // @TODO: response to "__version__", this need not to be implemented by each plugin
+            reply(plugin_key, "/__path__", json!(plugin.path()));
+            let Some(plugin) = plugin.started() else {
+                return;
+            };
             let matches_plugin = |plugin_status_space: &mut String| {
                 query
                     .key_expr()
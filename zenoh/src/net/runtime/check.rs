@@ -0,0 +1,160 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::RuntimeBuilder;
+use crate::config::Config;
+use crate::net::routing::interceptor::interceptor_factories;
+use std::path::Path;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::key_expr::keyexpr;
+use zenoh_result::{zerror, Error};
+use zenoh_transport::TransportManager;
+
+impl RuntimeBuilder {
+    /// Validates the configuration of the runtime without building it: no link is opened and no plugin is started.
+    ///
+    /// Returns all the problems found, an empty vector meaning that the configuration is valid.
+    pub async fn check(&self) -> Vec<Error> {
+        let config = &self.config;
+        let mut errors = vec![];
+        for (section, endpoints) in [
+            ("listen", config.listen().endpoints()),
+            ("connect", config.connect().endpoints()),
+        ] {
+            for endpoint in endpoints {
+                check_endpoint(section, endpoint, &mut errors);
+            }
+        }
+        check_tls_files(config, &mut errors);
+        check_acl_key_exprs(config, &mut errors);
+        if let Err(e) = interceptor_factories(config) {
+            errors.push(e);
+        }
+        if let Err(e) = TransportManager::builder().from_config(config).await {
+            errors.push(e);
+        }
+        errors
+    }
+}
+
+fn check_endpoint(section: &str, endpoint: &EndPoint, errors: &mut Vec<Error>) {
+    let protocol = endpoint.protocol();
+    if !zenoh_link::PROTOCOLS.contains(&protocol.as_str()) {
+        errors.push(
+            zerror!(
+                "{}/endpoints: unsupported protocol `{}` in `{}` (supported: {})",
+                section,
+                protocol,
+                endpoint,
+                zenoh_link::PROTOCOLS.join(", ")
+            )
+            .into(),
+        );
+    }
+    for (key, value) in endpoint.config().iter() {
+        if key.ends_with("_file") && !Path::new(value).is_file() {
+            errors.push(
+                zerror!(
+                    "{}/endpoints: file `{}` given as `{}` of `{}` does not exist",
+                    section,
+                    value,
+                    key,
+                    endpoint
+                )
+                .into(),
+            );
+        }
+    }
+}
+
+fn check_tls_files(config: &Config, errors: &mut Vec<Error>) {
+    let tls = config.transport().link().tls();
+    for (key, path) in [
+        ("root_ca_certificate", tls.root_ca_certificate()),
+        ("server_private_key", tls.server_private_key()),
+        ("server_certificate", tls.server_certificate()),
+        ("client_private_key", tls.client_private_key()),
+        ("client_certificate", tls.client_certificate()),
+    ] {
+        if let Some(path) = path {
+            if !Path::new(path).is_file() {
+                errors.push(
+                    zerror!("transport/link/tls/{}: file `{}` does not exist", key, path).into(),
+                );
+            }
+        }
+    }
+}
+
+// The ACL rules are only parsed when access control is enabled: check them anyway so that enabling it later
+// doesn't reveal errors.
+fn check_acl_key_exprs(config: &Config, errors: &mut Vec<Error>) {
+    let acl = config.access_control();
+    if *acl.enabled() {
+        return;
+    }
+    for rule in acl.rules().iter().flatten() {
+        for key_expr in &rule.key_exprs {
+            if let Err(e) = keyexpr::new(key_expr) {
+                errors.push(zerror!("access_control/rules: {}", e).into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_config::ValidatedMap;
+
+    #[tokio::test]
+    async fn check_config() {
+        let config = Config::default();
+        assert!(RuntimeBuilder::new(config).check().await.is_empty());
+
+        let mut config = Config::default();
+        config
+            .insert_json5("listen/endpoints", r#"["foo/127.0.0.1:7447"]"#)
+            .unwrap();
+        config
+            .insert_json5(
+                "connect/endpoints",
+                r#"["tcp/127.0.0.1:7447#root_ca_certificate_file=/nonexistent/ca.pem"]"#,
+            )
+            .unwrap();
+        config
+            .insert_json5(
+                "transport/link/tls/server_certificate",
+                r#""/nonexistent/cert.pem""#,
+            )
+            .unwrap();
+        config
+            .insert_json5(
+                "access_control/rules",
+                r#"[{ "actions": ["put"], "flows": ["egress"], "permission": "allow", "key_exprs": ["test/**/**/"] }]"#,
+            )
+            .unwrap();
+        let errors = RuntimeBuilder::new(config)
+            .check()
+            .await
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("unsupported protocol `foo`")));
+        assert!(errors.iter().any(|e| e.contains("/nonexistent/ca.pem")));
+        assert!(errors.iter().any(|e| e.contains("/nonexistent/cert.pem")));
+        assert!(errors.iter().any(|e| e.contains("access_control/rules")));
+    }
+}
@@ -0,0 +1,316 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Runtime;
+use crate::net::routing::dispatcher::resource::Resource;
+use std::fmt::Write;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use zenoh_result::ZResult;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Builds the metrics of `runtime` in OpenMetrics text format.
+///
+/// The samples of the transport counters only carry the `_total` suffix with `totals`,
+/// as served on `/metrics`: the admin space keeps their historical names.
+#[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+pub(crate) fn openmetrics_text(runtime: &Runtime, version: &str, totals: bool) -> String {
+    let mut s = format!(
+        r#"# HELP zenoh_build Information about zenoh.
+# TYPE zenoh_build gauge
+zenoh_build{{version="{}"}} 1
+"#,
+        escape(version)
+    );
+
+    #[cfg(feature = "stats")]
+    {
+        let stats = runtime.manager().get_stats().report();
+        s.push_str(&if totals {
+            stats.openmetrics_text_with_totals()
+        } else {
+            stats.openmetrics_text()
+        });
+    }
+
+    routing_openmetrics_text(runtime, &mut s);
+
+    #[cfg(feature = "shared-memory")]
+    shm_openmetrics_text(&mut s);
+
+    #[cfg(all(feature = "unstable", feature = "plugins"))]
+    plugins_openmetrics_text(runtime, &mut s);
+
+    s.push_str("# EOF\n");
+    s
+}
+
+fn routing_openmetrics_text(runtime: &Runtime, s: &mut String) {
+    let router = runtime.router();
+    let tables = zread!(router.tables.tables);
+
+    s.push_str("# HELP zenoh_face Faces of the routing tables.\n");
+    s.push_str("# TYPE zenoh_face info\n");
+    let mut faces = tables.faces.values().collect::<Vec<_>>();
+    faces.sort_unstable_by_key(|face| face.id);
+    for face in &faces {
+        let _ = writeln!(
+            s,
+            "zenoh_face_info{{face=\"{}\",zid=\"{}\",whatami=\"{}\"}} 1",
+            face.id, face.zid, face.whatami
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    {
+        type Getter = fn(&zenoh_transport::stats::TransportStatsReport) -> usize;
+        let counters: [(&str, &str, Getter); 6] = [
+            ("tx_bytes", "Counter of bytes sent on the face.", |r| {
+                r.tx_bytes
+            }),
+            ("rx_bytes", "Counter of bytes received on the face.", |r| {
+                r.rx_bytes
+            }),
+            (
+                "tx_n_msgs",
+                "Counter of network messages sent on the face.",
+                |r| r.tx_n_msgs,
+            ),
+            (
+                "rx_n_msgs",
+                "Counter of network messages received on the face.",
+                |r| r.rx_n_msgs,
+            ),
+            (
+                "tx_n_dropped",
+                "Counter of network messages dropped when sending on the face.",
+                |r| r.tx_n_dropped,
+            ),
+            (
+                "rx_n_dropped",
+                "Counter of network messages dropped when receiving on the face.",
+                |r| r.rx_n_dropped,
+            ),
+        ];
+        let reports = faces
+            .iter()
+            .filter_map(|face| face.stats.as_ref().map(|stats| (face.id, stats.report())))
+            .collect::<Vec<_>>();
+        for (name, help, get) in counters {
+            let _ = writeln!(s, "# HELP zenoh_face_{name} {help}");
+            let _ = writeln!(s, "# TYPE zenoh_face_{name} counter");
+            for (id, report) in &reports {
+                let _ = writeln!(
+                    s,
+                    "zenoh_face_{name}_total{{face=\"{id}\"}} {}",
+                    get(report)
+                );
+            }
+        }
+    }
+
+    s.push_str("# HELP zenoh_routing_faces Number of faces in the routing tables.\n");
+    s.push_str("# TYPE zenoh_routing_faces gauge\n");
+    let _ = writeln!(s, "zenoh_routing_faces {}", tables.faces.len());
+    s.push_str("# HELP zenoh_routing_resources Number of resources in the routing tables.\n");
+    s.push_str("# TYPE zenoh_routing_resources gauge\n");
+    let _ = writeln!(
+        s,
+        "zenoh_routing_resources {}",
+        count_resources(&tables.root_res) - 1
+    );
+    s.push_str(
+        "# HELP zenoh_routing_subscriptions Number of subscriptions in the routing tables.\n",
+    );
+    s.push_str("# TYPE zenoh_routing_subscriptions gauge\n");
+    let _ = writeln!(
+        s,
+        "zenoh_routing_subscriptions {}",
+        tables.hat_code.get_subscriptions(&tables).len()
+    );
+    s.push_str("# HELP zenoh_routing_queryables Number of queryables in the routing tables.\n");
+    s.push_str("# TYPE zenoh_routing_queryables gauge\n");
+    let _ = writeln!(
+        s,
+        "zenoh_routing_queryables {}",
+        tables.hat_code.get_queryables(&tables).len()
+    );
+
+    s.push_str(&tables.metrics.openmetrics_text());
+}
+
+#[cfg(feature = "shared-memory")]
+fn shm_openmetrics_text(s: &mut String) {
+    let cleanup = zenoh_shm::metrics::cleanup();
+    let counters = [
+        (
+            "reclaimed_segments",
+            "Counter of orphaned shared memory segments removed at startup.",
+            cleanup.reclaimed_segments,
+        ),
+        (
+            "reclaimed_bytes",
+            "Counter of bytes of the orphaned shared memory segments removed at startup.",
+            cleanup.reclaimed_bytes,
+        ),
+        (
+            "unmapped_segments",
+            "Counter of shared memory segments of dead processes unmapped.",
+            cleanup.unmapped_segments,
+        ),
+        (
+            "unmapped_bytes",
+            "Counter of bytes of the shared memory segments of dead processes unmapped.",
+            cleanup.unmapped_bytes,
+        ),
+    ];
+    for (name, help, value) in counters {
+        let _ = writeln!(s, "# HELP zenoh_shm_{name} {help}");
+        let _ = writeln!(s, "# TYPE zenoh_shm_{name} counter");
+        let _ = writeln!(s, "zenoh_shm_{name}_total {value}");
+    }
+}
+
+fn count_resources(res: &Resource) -> usize {
+    1 + res
+        .children
+        .values()
+        .map(|child| count_resources(child))
+        .sum::<usize>()
+}
+
+#[cfg(all(feature = "unstable", feature = "plugins"))]
+fn plugins_openmetrics_text(runtime: &Runtime, s: &mut String) {
+    use zenoh_plugin_trait::PluginState;
+
+    let guard = runtime.plugins_manager();
+    s.push_str("# HELP zenoh_plugin_state State of the plugins.\n");
+    s.push_str("# TYPE zenoh_plugin_state stateset\n");
+    for plugin in guard.declared_plugins_iter() {
+        let state = plugin.state();
+        for (value, name) in [
+            (PluginState::Declared, "declared"),
+            (PluginState::Loaded, "loaded"),
+            (PluginState::Started, "started"),
+        ] {
+            let _ = writeln!(
+                s,
+                "zenoh_plugin_state{{plugin=\"{}\",zenoh_plugin_state=\"{name}\"}} {}",
+                escape(plugin.id()),
+                u8::from(state == value)
+            );
+        }
+    }
+    for plugin in guard.started_plugins_iter() {
+        let text = plugin.instance().openmetrics_text();
+        s.push_str(&text);
+        if !text.is_empty() && !text.ends_with('\n') {
+            s.push('\n');
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves the metrics of `runtime` over HTTP on `/metrics` at the `listen` address.
+pub(crate) fn start_exporter(runtime: &Runtime, listen: &str, version: String) -> ZResult<()> {
+    let listener = std::net::TcpListener::bind(listen)
+        .map_err(|e| zerror!("Unable to bind metrics exporter on {}: {}", listen, e))?;
+    listener.set_nonblocking(true)?;
+    tracing::info!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+    let weak_runtime = Runtime::downgrade(runtime);
+    runtime.spawn_abortable(async move {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("Unable to start metrics exporter: {}", e);
+                return;
+            }
+        };
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::debug!("Metrics exporter failed to accept a connection: {}", e);
+                    continue;
+                }
+            };
+            let Some(runtime) = weak_runtime.upgrade() else {
+                break;
+            };
+            // Each connection is served by its own task, so a slow client doesn't delay the others
+            let version = version.clone();
+            runtime.clone().spawn_abortable(async move {
+                match tokio::time::timeout(REQUEST_TIMEOUT, serve(stream, &runtime, &version)).await
+                {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => tracing::debug!("Metrics exporter request failed: {}", e),
+                    Err(_) => tracing::debug!("Metrics exporter request timed out"),
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+async fn serve(mut stream: TcpStream, runtime: &Runtime, version: &str) -> ZResult<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_SIZE {
+            return respond(&mut stream, "431 Request Header Fields Too Large", None).await;
+        }
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            bail!("Connection closed before the end of the request");
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let (method, target) = (request_line.next(), request_line.next());
+    let path = target.map(|t| t.split('?').next().unwrap_or_default());
+    match (method, path) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = openmetrics_text(runtime, version, true);
+            respond(&mut stream, "200 OK", Some(&body)).await
+        }
+        (Some(_), Some("/metrics")) => respond(&mut stream, "405 Method Not Allowed", None).await,
+        _ => respond(&mut stream, "404 Not Found", None).await,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: Option<&str>) -> ZResult<()> {
+    let response = match body {
+        Some(body) => format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        ),
+        None => format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -18,6 +18,8 @@
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 mod adminspace;
+mod check;
+mod metrics;
 pub mod orchestrator;
 
 use super::primitives::DeMux;
@@ -132,6 +134,7 @@ impl RuntimeBuilder {
             .unwrap_or_else(|| crate::plugins::loader::load_plugins(&config));
         // Admin space creation flag
         let start_admin_space = *config.adminspace.enabled();
+        let metrics_listen = config.metrics().listen().clone();
 
         let config = Notifier::new(config);
         let runtime = Runtime {
@@ -158,6 +161,11 @@ impl RuntimeBuilder {
             AdminSpace::start(&runtime, LONG_VERSION.clone()).await;
         }
 
+        // Metrics exporter
+        if let Some(listen) = metrics_listen {
+            metrics::start_exporter(&runtime, &listen, LONG_VERSION.clone())?;
+        }
+
         // Start plugins
         #[cfg(all(feature = "unstable", feature = "plugins"))]
         crate::plugins::loader::start_plugins(&runtime);
@@ -634,6 +634,7 @@ fn client_test() {
             ext_unknown: vec![],
             payload: ZBuf::empty(),
             ext_attachment: None,
+            ext_trace: None,
         }),
         0,
     );
@@ -668,6 +669,7 @@ fn client_test() {
             ext_unknown: vec![],
             payload: ZBuf::empty(),
             ext_attachment: None,
+            ext_trace: None,
         }),
         0,
     );
@@ -702,6 +704,7 @@ fn client_test() {
             ext_unknown: vec![],
             payload: ZBuf::empty(),
             ext_attachment: None,
+            ext_trace: None,
         }),
         0,
     );
@@ -736,6 +739,7 @@ fn client_test() {
             ext_unknown: vec![],
             payload: ZBuf::empty(),
             ext_attachment: None,
+            ext_trace: None,
         }),
         0,
     );
@@ -770,6 +774,7 @@ fn client_test() {
             ext_unknown: vec![],
             payload: ZBuf::empty(),
             ext_attachment: None,
+            ext_trace: None,
         }),
         0,
     );
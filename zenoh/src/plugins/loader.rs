@@ -11,11 +11,33 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::sealed::{PluginsManager, PLUGIN_PREFIX};
+use super::sealed::{PluginsManager, RunningPlugin, PLUGIN_PREFIX};
 use crate::runtime::Runtime;
 use zenoh_config::{Config, PluginLoad};
+use zenoh_plugin_trait::DeclaredPlugin;
 use zenoh_result::ZResult;
 
+/// Declares a plugin from the first available file of the list of paths, which may be compiled to WebAssembly
+pub(crate) fn declare_plugin_by_paths<'a>(
+    plugin_mgr: &'a mut PluginsManager,
+    name: &str,
+    id: &str,
+    paths: &[String],
+    required: bool,
+) -> ZResult<&'a mut dyn DeclaredPlugin<Runtime, RunningPlugin>> {
+    #[cfg(feature = "wasm_plugins")]
+    if super::wasm::is_wasm_plugin(paths) {
+        return plugin_mgr.declare_wasm_plugin_by_paths(
+            name,
+            id,
+            paths,
+            super::wasm::start,
+            required,
+        );
+    }
+    plugin_mgr.declare_dynamic_plugin_by_paths(name, id, paths, required)
+}
+
 pub(crate) fn load_plugin(
     plugin_mgr: &mut PluginsManager,
     name: &str,
@@ -27,7 +49,7 @@ pub(crate) fn load_plugin(
         tracing::warn!("Plugin `{}` was already declared", declared.id());
         declared
     } else if let Some(paths) = paths {
-        plugin_mgr.declare_dynamic_plugin_by_paths(name, id, paths, required)?
+        declare_plugin_by_paths(plugin_mgr, name, id, paths, required)?
     } else {
         plugin_mgr.declare_dynamic_plugin_by_name(id, name, required)?
     };
@@ -71,14 +93,13 @@ pub(crate) fn load_plugins(config: &Config) -> PluginsManager {
 
 pub(crate) fn start_plugins(runtime: &Runtime) {
     let mut manager = runtime.plugins_manager();
-    for plugin in manager.loaded_plugins_iter_mut() {
+    // The plugins are started after the plugins they depend on
+    for (id, result) in manager.start_plugins(runtime) {
+        let Some(plugin) = manager.loaded_plugin(&id) else {
+            continue;
+        };
         let required = plugin.required();
-        tracing::info!(
-            "Starting {req} plugin \"{name}\"",
-            req = if required { "required" } else { "" },
-            name = plugin.id()
-        );
-        match plugin.start(runtime) {
+        match result {
             Ok(_) => {
                 tracing::info!(
                     "Successfully started plugin {} from {:?}",
@@ -114,6 +135,6 @@ pub(crate) fn start_plugins(runtime: &Runtime) {
                 }
             }
         }
-        tracing::info!("Finished loading plugins");
     }
+    tracing::info!("Finished loading plugins");
 }
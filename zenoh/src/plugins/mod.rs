@@ -19,6 +19,8 @@
 //! [Click here for Zenoh's documentation](../../zenoh/index.html)
 pub(crate) mod loader;
 pub(crate) mod sealed;
+#[cfg(feature = "wasm_plugins")]
+pub(crate) mod wasm;
 
 #[zenoh_macros::unstable]
 pub use sealed::*;
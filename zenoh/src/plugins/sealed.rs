@@ -34,7 +34,7 @@ pub trait ZenohPlugin: Plugin<StartArgs = Runtime, Instance = RunningPlugin> {}
 
 impl StructVersion for RunningPlugin {
     fn struct_version() -> u64 {
-        1
+        2
     }
     fn struct_features() -> &'static str {
         crate::FEATURES
@@ -117,6 +117,13 @@ pub trait RunningPluginTrait: Send + Sync + PluginControl {
     ) -> ZResult<Vec<Response>> {
         Ok(Vec::new())
     }
+    /// Used to collect the plugin's own metrics, which are exposed along with the metrics of the zenoh instance
+    /// (see the `metrics` section of the configuration).
+    /// The returned text must be in OpenMetrics text format, without the final `# EOF` line. Metric names should be
+    /// prefixed with the plugin's name to avoid clashes with the other plugins.
+    fn openmetrics_text(&self) -> String {
+        String::new()
+    }
 }
 
 /// The zenoh plugins manager. It handles the full lifetime of plugins, from loading to destruction.
@@ -0,0 +1,523 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The host of the plugins compiled to WebAssembly.
+//!
+//! A WebAssembly plugin is configured like a native plugin, with a `__path__` to a `.wasm` file.
+//! It runs in a sandbox, and only accesses zenoh through the functions it imports from the `zenoh` module,
+//! on the key expressions allowed by its configuration:
+//! ```json5
+//! transform: {
+//!   __path__: "/usr/lib/zenoh/transform.wasm",
+//!   allow: { publish: ["demo/out/**"], subscribe: ["demo/in/**"], queryable: ["demo/query/**"] },
+//!   max_memory_bytes: 16777216, // the maximum size of the plugin's memory
+//!   fuel_per_call: 100000000, // the maximum number of instructions run by each call to the plugin
+//!   max_pending_events: 1024, // the maximum number of samples and queries waiting to be handled
+//! }
+//! ```
+//!
+//! The strings and payloads are passed as a pointer and a length in the memory of the plugin.
+//! The functions imported from the `zenoh` module return a negative value on failure, whose reason is logged:
+//! - `put(key_ptr, key_len, payload_ptr, payload_len) -> i32` publishes a payload;
+//! - `subscribe(key_ptr, key_len) -> i32` declares a subscriber, and returns its ID;
+//! - `declare_queryable(key_ptr, key_len) -> i32` declares a queryable, and returns its ID;
+//! - `reply(query, key_ptr, key_len, payload_ptr, payload_len) -> i32` replies to the query being handled;
+//! - `log(ptr, len)` logs a message.
+//!
+//! The plugin exports:
+//! - `memory`;
+//! - `alloc(len) -> ptr`, which allocates the buffers in which the host writes the samples and queries;
+//! - `dealloc(ptr, len)`, optional, called to free these buffers once the call they were passed to returns.
+//!   Without it, the plugin owns the buffers and must free them itself;
+//! - `start() -> i32`, optional, called when the plugin starts, which fails if it returns a non-zero value;
+//! - `on_sample(subscriber, key_ptr, key_len, payload_ptr, payload_len)`, called with the samples of the subscribers;
+//! - `on_query(queryable, query, key_ptr, key_len, parameters_ptr, parameters_len)`, called with the queries of the
+//!   queryables, which are replied to before it returns.
+//!
+//! The samples and queries are handled one at a time by a thread of the plugin, which is joined when it stops.
+//! Those received while `max_pending_events` are already waiting are dropped, the queries being replied to
+//! with an error, and their number is reported in the status of the plugin.
+use super::sealed::{RunningPlugin, RunningPluginTrait};
+use crate::prelude::sync::*;
+use crate::queryable::{Query, Queryable};
+use crate::runtime::Runtime;
+use crate::subscriber::Subscriber;
+use crate::{Session, SessionDeclarations};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use zenoh_plugin_trait::wasmi::{
+    Caller, Extern, Instance, Linker, Memory, Store, StoreLimits, StoreLimitsBuilder,
+};
+use zenoh_plugin_trait::{PluginControl, PluginReport, WasmModule};
+use zenoh_result::{bail, zerror, ZResult};
+
+const DEFAULT_MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+const DEFAULT_FUEL_PER_CALL: u64 = 100_000_000;
+const DEFAULT_MAX_PENDING_EVENTS: usize = 1024;
+/// The result of the imported functions on failure
+const ERROR: i32 = -1;
+
+/// Returns true if the plugin in one of these files is compiled to WebAssembly
+pub(crate) fn is_wasm_plugin<P: AsRef<str>>(paths: &[P]) -> bool {
+    paths.iter().any(|path| path.as_ref().ends_with(".wasm"))
+}
+
+#[derive(Deserialize)]
+struct WasmPluginConfig {
+    #[serde(default)]
+    allow: Allowed,
+    #[serde(default = "default_max_memory_bytes")]
+    max_memory_bytes: usize,
+    #[serde(default = "default_fuel_per_call")]
+    fuel_per_call: u64,
+    #[serde(default = "default_max_pending_events")]
+    max_pending_events: usize,
+}
+
+fn default_max_memory_bytes() -> usize {
+    DEFAULT_MAX_MEMORY_BYTES
+}
+
+fn default_fuel_per_call() -> u64 {
+    DEFAULT_FUEL_PER_CALL
+}
+
+fn default_max_pending_events() -> usize {
+    DEFAULT_MAX_PENDING_EVENTS
+}
+
+/// The key expressions on which the plugin may publish, subscribe and declare queryables
+#[derive(Default, Deserialize)]
+struct Allowed {
+    #[serde(default)]
+    publish: Vec<OwnedKeyExpr>,
+    #[serde(default)]
+    subscribe: Vec<OwnedKeyExpr>,
+    #[serde(default)]
+    queryable: Vec<OwnedKeyExpr>,
+}
+
+fn check_allowed(allowed: &[OwnedKeyExpr], key_expr: &keyexpr, operation: &str) -> ZResult<()> {
+    if !allowed.iter().any(|allowed| allowed.includes(key_expr)) {
+        bail!("{} on `{}` is not allowed", operation, key_expr)
+    }
+    Ok(())
+}
+
+enum Event {
+    Sample(i32, Box<Sample>),
+    Query(i32, Query),
+    Stop,
+}
+
+/// The queue of the samples and queries waiting to be handled by the plugin, which drops them when it's full
+#[derive(Clone)]
+struct Events {
+    id: Arc<str>,
+    sender: flume::Sender<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Events {
+    fn push(&self, event: Event) {
+        let event = match self.sender.try_send(event) {
+            Ok(()) => return,
+            Err(flume::TrySendError::Full(event)) => event,
+            // The plugin is stopping
+            Err(flume::TrySendError::Disconnected(_)) => return,
+        };
+        if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+            tracing::warn!(
+                "Plugin `{}` doesn't keep up with its samples and queries, which are dropped",
+                self.id
+            );
+        }
+        if let Event::Query(_, query) = event {
+            let _ = query
+                .reply(Err("The plugin is overloaded".into()))
+                .res_sync();
+        }
+    }
+}
+
+struct HostState {
+    id: String,
+    session: Arc<Session>,
+    allow: Allowed,
+    limits: StoreLimits,
+    events: Events,
+    subscribers: Vec<Subscriber<'static, ()>>,
+    queryables: Vec<Queryable<'static, ()>>,
+    // The query being handled, with its ID
+    query: Option<(i32, Query)>,
+}
+
+fn memory(caller: &Caller<'_, HostState>) -> ZResult<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| zerror!("The plugin doesn't export its memory").into())
+}
+
+fn read_bytes(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> ZResult<Vec<u8>> {
+    let data = memory(caller)?.data(caller);
+    usize::try_from(ptr)
+        .ok()
+        .zip(usize::try_from(len).ok())
+        .and_then(|(ptr, len)| data.get(ptr..ptr.checked_add(len)?))
+        .map(|bytes| bytes.to_vec())
+        .ok_or_else(|| zerror!("Invalid buffer ({}, {}) in the plugin's memory", ptr, len).into())
+}
+
+fn read_key_expr(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> ZResult<KeyExpr<'static>> {
+    let key_expr = String::from_utf8(read_bytes(caller, ptr, len)?)?;
+    KeyExpr::try_from(key_expr)
+}
+
+/// Runs an imported function, logging the reason of its failure
+fn host_call(
+    caller: &mut Caller<'_, HostState>,
+    function: &str,
+    f: impl FnOnce(&mut Caller<'_, HostState>) -> ZResult<i32>,
+) -> i32 {
+    f(caller).unwrap_or_else(|e| {
+        tracing::warn!(
+            "Call to `{}` by plugin `{}` failed: {}",
+            function,
+            caller.data().id,
+            e
+        );
+        ERROR
+    })
+}
+
+/// The functions imported by the plugins, checking that the key expressions they use are allowed
+fn linker(module: &WasmModule) -> ZResult<Linker<HostState>> {
+    let mut linker = Linker::new(module.engine());
+    linker
+        .func_wrap(
+            "zenoh",
+            "put",
+            |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, ptr: i32, len: i32| {
+                host_call(&mut caller, "put", |caller| {
+                    let key_expr = read_key_expr(caller, key_ptr, key_len)?;
+                    check_allowed(&caller.data().allow.publish, &key_expr, "Publication")?;
+                    let payload = read_bytes(caller, ptr, len)?;
+                    caller.data().session.put(key_expr, payload).res_sync()?;
+                    Ok(0)
+                })
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?
+        .func_wrap(
+            "zenoh",
+            "subscribe",
+            |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| {
+                host_call(&mut caller, "subscribe", |caller| {
+                    let key_expr = read_key_expr(caller, key_ptr, key_len)?;
+                    check_allowed(&caller.data().allow.subscribe, &key_expr, "Subscription")?;
+                    if caller.get_export("on_sample").is_none() {
+                        bail!("The plugin doesn't export `on_sample`")
+                    }
+                    let state = caller.data_mut();
+                    let id = i32::try_from(state.subscribers.len())?;
+                    let events = state.events.clone();
+                    let subscriber = state
+                        .session
+                        .declare_subscriber(key_expr)
+                        .callback(move |sample| events.push(Event::Sample(id, Box::new(sample))))
+                        .res_sync()?;
+                    state.subscribers.push(subscriber);
+                    Ok(id)
+                })
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?
+        .func_wrap(
+            "zenoh",
+            "declare_queryable",
+            |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| {
+                host_call(&mut caller, "declare_queryable", |caller| {
+                    let key_expr = read_key_expr(caller, key_ptr, key_len)?;
+                    check_allowed(&caller.data().allow.queryable, &key_expr, "Queryable")?;
+                    if caller.get_export("on_query").is_none() {
+                        bail!("The plugin doesn't export `on_query`")
+                    }
+                    let state = caller.data_mut();
+                    let id = i32::try_from(state.queryables.len())?;
+                    let events = state.events.clone();
+                    let queryable = state
+                        .session
+                        .declare_queryable(key_expr)
+                        .callback(move |query| events.push(Event::Query(id, query)))
+                        .res_sync()?;
+                    state.queryables.push(queryable);
+                    Ok(id)
+                })
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?
+        .func_wrap(
+            "zenoh",
+            "reply",
+            |mut caller: Caller<'_, HostState>,
+             query: i32,
+             key_ptr: i32,
+             key_len: i32,
+             ptr: i32,
+             len: i32| {
+                host_call(&mut caller, "reply", |caller| {
+                    let key_expr = read_key_expr(caller, key_ptr, key_len)?;
+                    check_allowed(&caller.data().allow.queryable, &key_expr, "Reply")?;
+                    let payload = read_bytes(caller, ptr, len)?;
+                    match &caller.data().query {
+                        Some((id, handled)) if *id == query => {
+                            handled
+                                .reply(Ok(Sample::new(key_expr, payload)))
+                                .res_sync()?;
+                            Ok(0)
+                        }
+                        _ => bail!("Query {} isn't being handled", query),
+                    }
+                })
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?
+        .func_wrap(
+            "zenoh",
+            "log",
+            |caller: Caller<'_, HostState>, ptr: i32, len: i32| match read_bytes(&caller, ptr, len)
+            {
+                Ok(message) => tracing::info!(
+                    "Plugin `{}`: {}",
+                    caller.data().id,
+                    String::from_utf8_lossy(&message)
+                ),
+                Err(e) => tracing::warn!(
+                    "Call to `log` by plugin `{}` failed: {}",
+                    caller.data().id,
+                    e
+                ),
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    Ok(linker)
+}
+
+/// An instance of a plugin, with the fuel it may consume in each call
+struct Guest {
+    store: Store<HostState>,
+    instance: Instance,
+    fuel_per_call: u64,
+    queries: i32,
+}
+
+impl Guest {
+    fn refuel(&mut self) -> ZResult<()> {
+        let remaining = self.store.consume_fuel(0).map_err(|e| zerror!("{}", e))?;
+        self.store
+            .add_fuel(self.fuel_per_call.saturating_sub(remaining))
+            .map_err(|e| zerror!("{}", e))?;
+        Ok(())
+    }
+
+    /// Copies the bytes in a buffer allocated by the plugin
+    fn write(&mut self, bytes: &[u8]) -> ZResult<(i32, i32)> {
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "alloc")?;
+        let len = i32::try_from(bytes.len())?;
+        let ptr = alloc.call(&mut self.store, len)?;
+        let memory = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .ok_or_else(|| zerror!("The plugin doesn't export its memory"))?;
+        memory
+            .write(&mut self.store, usize::try_from(ptr)?, bytes)
+            .map_err(|e| zerror!("{}", e))?;
+        Ok((ptr, len))
+    }
+
+    /// Frees the buffers written by the host, if the plugin exports `dealloc`
+    fn free(&mut self, buffers: &[(i32, i32)]) -> ZResult<()> {
+        let Ok(dealloc) = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&self.store, "dealloc")
+        else {
+            return Ok(());
+        };
+        // In the reverse order of their allocation
+        for buffer in buffers.iter().rev() {
+            dealloc.call(&mut self.store, *buffer)?;
+        }
+        Ok(())
+    }
+
+    fn start(&mut self) -> ZResult<()> {
+        let Ok(start) = self
+            .instance
+            .get_typed_func::<(), i32>(&self.store, "start")
+        else {
+            return Ok(());
+        };
+        self.refuel()?;
+        match start.call(&mut self.store, ())? {
+            0 => Ok(()),
+            result => bail!("`start` returned {}", result),
+        }
+    }
+
+    fn on_sample(&mut self, subscriber: i32, sample: Sample) -> ZResult<()> {
+        self.refuel()?;
+        let (key_ptr, key_len) = self.write(sample.key_expr.as_str().as_bytes())?;
+        let (ptr, len) = self.write(&sample.value.payload.contiguous())?;
+        let on_sample = self
+            .instance
+            .get_typed_func::<(i32, i32, i32, i32, i32), ()>(&self.store, "on_sample")?;
+        let result = on_sample.call(&mut self.store, (subscriber, key_ptr, key_len, ptr, len));
+        self.free(&[(key_ptr, key_len), (ptr, len)])?;
+        result?;
+        Ok(())
+    }
+
+    fn on_query(&mut self, queryable: i32, query: Query) -> ZResult<()> {
+        self.refuel()?;
+        let (key_ptr, key_len) = self.write(query.key_expr().as_str().as_bytes())?;
+        let (ptr, len) = self.write(query.parameters().as_bytes())?;
+        let on_query = self
+            .instance
+            .get_typed_func::<(i32, i32, i32, i32, i32, i32), ()>(&self.store, "on_query")?;
+        let id = self.queries;
+        self.queries = self.queries.checked_add(1).unwrap_or(0);
+        self.store.data_mut().query = Some((id, query));
+        let result = on_query.call(&mut self.store, (queryable, id, key_ptr, key_len, ptr, len));
+        // The query is finalized once handled
+        let (_, query) = self.store.data_mut().query.take().unwrap();
+        let freed = self.free(&[(key_ptr, key_len), (ptr, len)]);
+        if let Err(e) = result {
+            let _ = query.reply(Err(e.to_string().into())).res_sync();
+            return Err(e.into());
+        }
+        freed
+    }
+
+    fn run(mut self, events: flume::Receiver<Event>, report: &Mutex<PluginReport>) {
+        while let Ok(event) = events.recv() {
+            let result = match event {
+                Event::Sample(subscriber, sample) => self.on_sample(subscriber, *sample),
+                Event::Query(queryable, query) => self.on_query(queryable, query),
+                Event::Stop => break,
+            };
+            if let Err(e) = result {
+                tracing::error!("Plugin `{}` failed: {}", self.store.data().id, e);
+                // Only the last failure is reported
+                let mut report = report.lock().unwrap();
+                report.clear();
+                report.add_error(e.to_string());
+            }
+        }
+    }
+}
+
+/// Starts a plugin compiled to WebAssembly, whose samples and queries are handled by a thread of its own
+pub(crate) fn start(module: &WasmModule, id: &str, runtime: &Runtime) -> ZResult<RunningPlugin> {
+    let config = runtime
+        .config()
+        .lock()
+        .plugin(id)
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({}));
+    let config: WasmPluginConfig = serde_json::from_value(config)
+        .map_err(|e| zerror!("Plugin `{}` configuration error: {}", id, e))?;
+    let session = Arc::new(crate::init(runtime.clone()).res_sync()?);
+    let (sender, receiver) = flume::bounded(config.max_pending_events.max(1));
+    let events = Events {
+        id: id.into(),
+        sender,
+        dropped: Arc::new(AtomicU64::new(0)),
+    };
+    let state = HostState {
+        id: id.to_string(),
+        session,
+        allow: config.allow,
+        limits: StoreLimitsBuilder::new()
+            .memory_size(config.max_memory_bytes)
+            .build(),
+        events: events.clone(),
+        subscribers: Vec::new(),
+        queryables: Vec::new(),
+        query: None,
+    };
+    let mut store = Store::new(module.engine(), state);
+    store.limiter(|state| &mut state.limits);
+    store
+        .add_fuel(config.fuel_per_call)
+        .map_err(|e| zerror!("{}", e))?;
+    let instance = linker(module)?
+        .instantiate(&mut store, module.module())?
+        .start(&mut store)?;
+    let mut guest = Guest {
+        store,
+        instance,
+        fuel_per_call: config.fuel_per_call,
+        queries: 0,
+    };
+    guest.start()?;
+    let report = Arc::new(Mutex::new(PluginReport::new()));
+    let thread = std::thread::Builder::new()
+        .name(format!("plugin-{id}"))
+        .spawn({
+            let report = report.clone();
+            move || guest.run(receiver, &report)
+        })?;
+    Ok(Box::new(RunningWasmPlugin {
+        events,
+        thread: Some(thread),
+        report,
+    }))
+}
+
+struct RunningWasmPlugin {
+    events: Events,
+    thread: Option<JoinHandle<()>>,
+    report: Arc<Mutex<PluginReport>>,
+}
+
+impl PluginControl for RunningWasmPlugin {
+    fn report(&self) -> PluginReport {
+        let mut report = self.report.lock().unwrap().clone();
+        let dropped = self.events.dropped.load(Ordering::Relaxed);
+        if dropped > 0 {
+            report.add_warning(format!(
+                "{} samples and queries were dropped because the plugin didn't keep up",
+                dropped
+            ));
+        }
+        report
+    }
+}
+
+impl RunningPluginTrait for RunningWasmPlugin {}
+
+/// The thread of the plugin is joined when it's stopped, which undeclares its subscribers and queryables
+impl Drop for RunningWasmPlugin {
+    fn drop(&mut self) {
+        let _ = self.events.sender.send(Event::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -23,6 +23,8 @@ use crate::prelude::*;
 use crate::sample::Attachment;
 use crate::sample::DataInfo;
 use crate::sample::QoS;
+#[zenoh_macros::unstable]
+use crate::trace::TraceContext;
 use crate::Encoding;
 use crate::SessionRef;
 use crate::Undeclarable;
@@ -85,6 +87,8 @@ pub struct PutBuilder<'a, 'b> {
     pub(crate) kind: SampleKind,
     #[cfg(feature = "unstable")]
     pub(crate) attachment: Option<Attachment>,
+    #[cfg(feature = "unstable")]
+    pub(crate) trace_context: Option<TraceContext>,
 }
 
 impl PutBuilder<'_, '_> {
@@ -130,6 +134,14 @@ impl PutBuilder<'_, '_> {
         self.attachment = Some(attachment);
         self
     }
+
+    /// Sends the data with the given trace context instead of a child of the
+    /// [current](TraceContext::current) one.
+    #[zenoh_macros::unstable]
+    pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
+        self.trace_context = Some(trace_context);
+        self
+    }
 }
 
 impl Resolvable for PutBuilder<'_, '_> {
@@ -161,6 +173,8 @@ impl SyncResolve for PutBuilder<'_, '_> {
             self.kind,
             #[cfg(feature = "unstable")]
             self.attachment,
+            #[cfg(feature = "unstable")]
+            self.trace_context,
         )
     }
 }
@@ -320,6 +334,8 @@ impl<'a> Publisher<'a> {
             kind,
             #[cfg(feature = "unstable")]
             attachment: None,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
         }
     }
 
@@ -634,6 +650,8 @@ pub struct Publication<'a> {
     kind: SampleKind,
     #[cfg(feature = "unstable")]
     pub(crate) attachment: Option<Attachment>,
+    #[cfg(feature = "unstable")]
+    pub(crate) trace_context: Option<TraceContext>,
 }
 
 impl<'a> Publication<'a> {
@@ -642,6 +660,14 @@ impl<'a> Publication<'a> {
         self.attachment = Some(attachment);
         self
     }
+
+    /// Sends the data with the given trace context instead of a child of the
+    /// [current](TraceContext::current) one.
+    #[zenoh_macros::unstable]
+    pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
+        self.trace_context = Some(trace_context);
+        self
+    }
 }
 
 impl Resolvable for Publication<'_> {
@@ -656,6 +682,8 @@ impl SyncResolve for Publication<'_> {
             self.kind,
             #[cfg(feature = "unstable")]
             self.attachment,
+            #[cfg(feature = "unstable")]
+            self.trace_context,
         )
     }
 }
@@ -828,8 +856,11 @@ fn resolve_put(
     value: Value,
     kind: SampleKind,
     #[cfg(feature = "unstable")] attachment: Option<Attachment>,
+    #[cfg(feature = "unstable")] trace_context: Option<TraceContext>,
 ) -> ZResult<()> {
     tracing::trace!("write({:?}, [...])", &publisher.key_expr);
+    #[cfg(feature = "unstable")]
+    let trace_context = crate::trace::inject(trace_context);
     let primitives = zread!(publisher.session.state)
         .primitives
         .as_ref()
@@ -857,6 +888,10 @@ fn resolve_put(
                             ext_attachment = Some(attachment.into());
                         }
                     }
+                    #[cfg(feature = "unstable")]
+                    let ext_trace = trace_context.map(Into::into);
+                    #[cfg(not(feature = "unstable"))]
+                    let ext_trace = None;
                     PushBody::Put(Put {
                         timestamp,
                         encoding: value.encoding.clone(),
@@ -864,6 +899,7 @@ fn resolve_put(
                         #[cfg(feature = "shared-memory")]
                         ext_shm: None,
                         ext_attachment,
+                        ext_trace,
                         ext_unknown: vec![],
                         payload: value.payload.clone(),
                     })
@@ -877,10 +913,15 @@ fn resolve_put(
                             ext_attachment = Some(attachment.into());
                         }
                     }
+                    #[cfg(feature = "unstable")]
+                    let ext_trace = trace_context.map(Into::into);
+                    #[cfg(not(feature = "unstable"))]
+                    let ext_trace = None;
                     PushBody::Del(Del {
                         timestamp,
                         ext_sinfo: None,
                         ext_attachment,
+                        ext_trace,
                         ext_unknown: vec![],
                     })
                 }
@@ -908,6 +949,8 @@ fn resolve_put(
             value.payload,
             #[cfg(feature = "unstable")]
             attachment,
+            #[cfg(feature = "unstable")]
+            trace_context,
         );
     }
     Ok(())
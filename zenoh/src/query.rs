@@ -18,6 +18,8 @@ use crate::handlers::{locked, Callback, DefaultHandler};
 use crate::prelude::*;
 #[zenoh_macros::unstable]
 use crate::sample::Attachment;
+#[zenoh_macros::unstable]
+use crate::trace::TraceContext;
 use crate::Session;
 use std::collections::HashMap;
 use std::future::Ready;
@@ -131,6 +133,8 @@ pub struct GetBuilder<'a, 'b, Handler> {
     pub(crate) value: Option<Value>,
     #[cfg(feature = "unstable")]
     pub(crate) attachment: Option<Attachment>,
+    #[cfg(feature = "unstable")]
+    pub(crate) trace_context: Option<TraceContext>,
 }
 
 impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
@@ -167,6 +171,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             value,
             #[cfg(feature = "unstable")]
             attachment,
+            #[cfg(feature = "unstable")]
+            trace_context,
             handler: _,
         } = self;
         GetBuilder {
@@ -180,6 +186,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             value,
             #[cfg(feature = "unstable")]
             attachment,
+            #[cfg(feature = "unstable")]
+            trace_context,
             handler: callback,
         }
     }
@@ -252,6 +260,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             value,
             #[cfg(feature = "unstable")]
             attachment,
+            #[cfg(feature = "unstable")]
+            trace_context,
             handler: _,
         } = self;
         GetBuilder {
@@ -265,6 +275,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             value,
             #[cfg(feature = "unstable")]
             attachment,
+            #[cfg(feature = "unstable")]
+            trace_context,
             handler,
         }
     }
@@ -316,6 +328,14 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
         self
     }
 
+    /// Sends the query with the given trace context instead of a child of the
+    /// [current](TraceContext::current) one.
+    #[zenoh_macros::unstable]
+    pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
+        self.trace_context = Some(trace_context);
+        self
+    }
+
     /// By default, `get` guarantees that it will only receive replies whose key expressions intersect
     /// with the queried key expression.
     ///
@@ -333,6 +353,7 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             timeout,
             value,
             attachment,
+            trace_context,
             handler,
         } = self;
         Self {
@@ -345,6 +366,7 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             timeout,
             value,
             attachment,
+            trace_context,
             handler,
         }
     }
@@ -389,6 +411,8 @@ where
                 self.value,
                 #[cfg(feature = "unstable")]
                 self.attachment,
+                #[cfg(feature = "unstable")]
+                self.trace_context,
                 callback,
             )
             .map(|_| receiver)
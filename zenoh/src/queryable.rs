@@ -22,6 +22,8 @@ use crate::query::ReplyKeyExpr;
 #[zenoh_macros::unstable]
 use crate::sample::Attachment;
 use crate::sample::DataInfo;
+#[zenoh_macros::unstable]
+use crate::trace::TraceContext;
 use crate::SessionRef;
 use crate::Undeclarable;
 
@@ -50,6 +52,8 @@ pub(crate) struct QueryInner {
     pub(crate) primitives: Arc<dyn Primitives>,
     #[cfg(feature = "unstable")]
     pub(crate) attachment: Option<Attachment>,
+    #[cfg(feature = "unstable")]
+    pub(crate) trace_context: Option<TraceContext>,
 }
 
 impl Drop for QueryInner {
@@ -101,6 +105,12 @@ impl Query {
         self.inner.attachment.as_ref()
     }
 
+    /// The trace context this Query was sent with.
+    #[zenoh_macros::unstable]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.inner.trace_context.as_ref()
+    }
+
     /// Sends a reply to this Query.
     ///
     /// By default, queries only accept replies whose key expression intersects with the query's.
@@ -111,6 +121,8 @@ impl Query {
         ReplyBuilder {
             query: self,
             result,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
         }
     }
 
@@ -159,6 +171,8 @@ impl fmt::Display for Query {
 pub struct ReplyBuilder<'a> {
     query: &'a Query,
     result: Result<Sample, Value>,
+    #[cfg(feature = "unstable")]
+    trace_context: Option<TraceContext>,
 }
 
 impl<'a> ReplyBuilder<'a> {
@@ -173,6 +187,14 @@ impl<'a> ReplyBuilder<'a> {
             Err(_) => Err((self, attachment)),
         }
     }
+
+    /// Sends the reply with the given trace context instead of a child of the
+    /// [current](TraceContext::current) one, or else of the query's one.
+    #[zenoh_macros::unstable]
+    pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
+        self.trace_context = Some(trace_context);
+        self
+    }
 }
 
 impl<'a> Resolvable for ReplyBuilder<'a> {
@@ -198,6 +220,8 @@ impl SyncResolve for ReplyBuilder<'_> {
                     source_info,
                     #[cfg(feature = "unstable")]
                     attachment,
+                    #[cfg(feature = "unstable")]
+                        trace_context: _,
                 } = sample;
                 #[allow(unused_mut)]
                 let mut data_info = DataInfo {
@@ -218,6 +242,12 @@ impl SyncResolve for ReplyBuilder<'_> {
                         ext_attachment = Some(attachment.into());
                     }
                 }
+                #[cfg(feature = "unstable")]
+                let ext_trace = crate::trace::inject(self.trace_context)
+                    .or_else(|| self.query.inner.trace_context.map(|ctx| ctx.child()))
+                    .map(Into::into);
+                #[cfg(not(feature = "unstable"))]
+                let ext_trace = None;
                 self.query.inner.primitives.send_response(Response {
                     rid: self.query.inner.qid,
                     wire_expr: WireExpr {
@@ -242,6 +272,7 @@ impl SyncResolve for ReplyBuilder<'_> {
                         #[cfg(feature = "shared-memory")]
                         ext_shm: None,
                         ext_attachment,
+                        ext_trace,
                         ext_unknown: vec![],
                         payload,
                     }),
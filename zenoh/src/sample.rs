@@ -18,6 +18,8 @@ use crate::prelude::ZenohId;
 use crate::prelude::{KeyExpr, SampleKind, Value};
 use crate::query::Reply;
 use crate::time::{new_reception_timestamp, Timestamp};
+#[zenoh_macros::unstable]
+use crate::trace::TraceContext;
 use crate::Priority;
 #[zenoh_macros::unstable]
 use serde::Serialize;
@@ -354,6 +356,16 @@ pub struct Sample {
     ///
     /// A map of key-value pairs, where each key and value are byte-slices.
     pub attachment: Option<Attachment>,
+
+    #[cfg(feature = "unstable")]
+    /// <div class="stab unstable">
+    ///   <span class="emoji">🔬</span>
+    ///   This API has been marked as unstable: it works as advertised, but we may change it in a future release.
+    ///   To use it, you must enable zenoh's <code>unstable</code> feature flag.
+    /// </div>
+    ///
+    /// The trace context this Sample was sent with.
+    pub trace_context: Option<TraceContext>,
 }
 
 impl Sample {
@@ -374,6 +386,8 @@ impl Sample {
             source_info: SourceInfo::empty(),
             #[cfg(feature = "unstable")]
             attachment: None,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
         }
     }
     /// Creates a new Sample.
@@ -397,6 +411,8 @@ impl Sample {
             source_info: SourceInfo::empty(),
             #[cfg(feature = "unstable")]
             attachment: None,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
         })
     }
 
@@ -422,6 +438,8 @@ impl Sample {
                 source_info: data_info.into(),
                 #[cfg(feature = "unstable")]
                 attachment: None,
+                #[cfg(feature = "unstable")]
+                trace_context: None,
             }
         } else {
             Sample {
@@ -434,6 +452,8 @@ impl Sample {
                 source_info: SourceInfo::empty(),
                 #[cfg(feature = "unstable")]
                 attachment: None,
+                #[cfg(feature = "unstable")]
+                trace_context: None,
             }
         }
     }
@@ -489,6 +509,12 @@ impl Sample {
         self.attachment = Some(attachment);
         self
     }
+
+    /// Gets the trace context this Sample was sent with.
+    #[zenoh_macros::unstable]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.trace_context.as_ref()
+    }
 }
 
 impl std::ops::Deref for Sample {
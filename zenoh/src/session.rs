@@ -35,6 +35,8 @@ use crate::sample::DataInfo;
 use crate::sample::QoS;
 use crate::selector::TIME_RANGE_KEY;
 use crate::subscriber::*;
+#[cfg(feature = "unstable")]
+use crate::trace::TraceContext;
 use crate::Id;
 use crate::Priority;
 use crate::Sample;
@@ -721,6 +723,8 @@ impl Session {
             kind: SampleKind::Put,
             #[cfg(feature = "unstable")]
             attachment: None,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
         }
     }
 
@@ -755,6 +759,8 @@ impl Session {
             kind: SampleKind::Delete,
             #[cfg(feature = "unstable")]
             attachment: None,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
         }
     }
     /// Query data from the matching queryables in the system.
@@ -803,6 +809,8 @@ impl Session {
             value: None,
             #[cfg(feature = "unstable")]
             attachment: None,
+            #[cfg(feature = "unstable")]
+            trace_context: None,
             handler: DefaultHandler,
         }
     }
@@ -1594,6 +1602,7 @@ impl Session {
         info: Option<DataInfo>,
         payload: ZBuf,
         #[cfg(feature = "unstable")] attachment: Option<Attachment>,
+        #[cfg(feature = "unstable")] trace_context: Option<TraceContext>,
     ) {
         let mut callbacks = SingleOrVec::default();
         let state = zread!(self.state);
@@ -1699,7 +1708,10 @@ impl Session {
             #[cfg(feature = "unstable")]
             {
                 sample.attachment.clone_from(&attachment);
+                sample.trace_context = trace_context;
+                crate::trace::in_context(trace_context, "zenoh_sample", || cb(sample));
             }
+            #[cfg(not(feature = "unstable"))]
             cb(sample);
         }
         if let Some((cb, key_expr)) = last {
@@ -1708,7 +1720,10 @@ impl Session {
             #[cfg(feature = "unstable")]
             {
                 sample.attachment = attachment;
+                sample.trace_context = trace_context;
+                crate::trace::in_context(trace_context, "zenoh_sample", || cb(sample));
             }
+            #[cfg(not(feature = "unstable"))]
             cb(sample);
         }
     }
@@ -1747,9 +1762,12 @@ impl Session {
         timeout: Duration,
         value: Option<Value>,
         #[cfg(feature = "unstable")] attachment: Option<Attachment>,
+        #[cfg(feature = "unstable")] trace_context: Option<TraceContext>,
         callback: Callback<'static, Reply>,
     ) -> ZResult<()> {
         tracing::trace!("get({}, {:?}, {:?})", selector, target, consolidation);
+        #[cfg(feature = "unstable")]
+        let trace_context = crate::trace::inject(trace_context);
         let mut state = zwrite!(self.state);
         let consolidation = match consolidation.mode {
             Mode::Auto => {
@@ -1829,6 +1847,10 @@ impl Session {
                     ext_attachment = Some(attachment.into());
                 }
             }
+            #[cfg(feature = "unstable")]
+            let ext_trace = trace_context.map(Into::into);
+            #[cfg(not(feature = "unstable"))]
+            let ext_trace = None;
             primitives.send_request(Request {
                 id: qid,
                 wire_expr: wexpr.clone(),
@@ -1849,6 +1871,7 @@ impl Session {
                         payload: v.payload.clone(),
                     }),
                     ext_attachment,
+                    ext_trace,
                     ext_unknown: vec![],
                 }),
             });
@@ -1869,6 +1892,8 @@ impl Session {
                 }),
                 #[cfg(feature = "unstable")]
                 attachment,
+                #[cfg(feature = "unstable")]
+                trace_context,
             );
         }
         Ok(())
@@ -1885,6 +1910,7 @@ impl Session {
         _consolidation: ConsolidationType,
         body: Option<QueryBodyType>,
         #[cfg(feature = "unstable")] attachment: Option<Attachment>,
+        #[cfg(feature = "unstable")] trace_context: Option<TraceContext>,
     ) {
         let (primitives, key_expr, callbacks) = {
             let state = zread!(self.state);
@@ -1947,9 +1973,14 @@ impl Session {
                 },
                 #[cfg(feature = "unstable")]
                 attachment,
+                #[cfg(feature = "unstable")]
+                trace_context,
             }),
         };
         for callback in callbacks.iter() {
+            #[cfg(feature = "unstable")]
+            crate::trace::in_context(trace_context, "zenoh_query", || callback(query.clone()));
+            #[cfg(not(feature = "unstable"))]
             callback(query.clone());
         }
     }
@@ -2162,6 +2193,8 @@ impl Primitives for Session {
                                     ZBuf::default(),
                                     #[cfg(feature = "unstable")]
                                     None,
+                                    #[cfg(feature = "unstable")]
+                                    None,
                                 );
                             }
                         }
@@ -2199,6 +2232,8 @@ impl Primitives for Session {
                                     ZBuf::default(),
                                     #[cfg(feature = "unstable")]
                                     None,
+                                    #[cfg(feature = "unstable")]
+                                    None,
                                 );
                             }
                         }
@@ -2244,6 +2279,8 @@ impl Primitives for Session {
                     m.payload,
                     #[cfg(feature = "unstable")]
                     m.ext_attachment.map(Into::into),
+                    #[cfg(feature = "unstable")]
+                    crate::trace::extract(m.ext_trace),
                 )
             }
             PushBody::Del(m) => {
@@ -2262,6 +2299,8 @@ impl Primitives for Session {
                     ZBuf::empty(),
                     #[cfg(feature = "unstable")]
                     m.ext_attachment.map(Into::into),
+                    #[cfg(feature = "unstable")]
+                    crate::trace::extract(m.ext_trace),
                 )
             }
         }
@@ -2280,6 +2319,8 @@ impl Primitives for Session {
                 m.ext_body,
                 #[cfg(feature = "unstable")]
                 m.ext_attachment.map(Into::into),
+                #[cfg(feature = "unstable")]
+                crate::trace::extract(m.ext_trace),
             ),
             RequestBody::Put(_) => (),
             RequestBody::Del(_) => (),
@@ -2398,6 +2439,7 @@ impl Primitives for Session {
                         #[cfg(feature = "unstable")]
                         {
                             sample.attachment = m.ext_attachment.map(Into::into);
+                            sample.trace_context = crate::trace::extract(m.ext_trace);
                         }
                         let new_reply = Reply {
                             sample: Ok(sample),
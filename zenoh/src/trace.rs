@@ -0,0 +1,344 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Trace context propagation.
+//!
+//! A [`TraceContext`] follows the [W3C Trace Context](https://www.w3.org/TR/trace-context/) model and
+//! is carried by zenoh messages, so that an operation can be traced end to end across zenoh hops.
+//!
+//! The trace context is injected automatically in the messages sent by [`Session::put`](crate::Session::put),
+//! [`Publisher::put`](crate::publication::Publisher::put), [`Session::get`](crate::Session::get) and
+//! [`Query::reply`](crate::queryable::Query::reply) if one was explicitly given to the operation or if a
+//! [current](TraceContext::current) trace context is set on the calling thread, in which case a child of it
+//! is sent. Replies fall back to a child of the trace context of the query they answer.
+//!
+//! On reception, the trace context is available through [`Sample::trace_context`](crate::sample::Sample::trace_context)
+//! and [`Query::trace_context`](crate::queryable::Query::trace_context). Subscriber and queryable callbacks are
+//! run with the received trace context set as the current one, so that the messages sent from these callbacks
+//! are part of the same trace, and within a `tracing` span named `zenoh_sample` or `zenoh_query` whose
+//! `trace_id` and `parent_span_id` fields hold the received trace context.
+//!
+//! # Examples
+//! ```
+//! # #[tokio::main]
+//! # async fn main() {
+//! use zenoh::prelude::r#async::*;
+//! use zenoh::trace::TraceContext;
+//!
+//! let session = zenoh::open(config::peer()).res().await.unwrap();
+//! let context: TraceContext = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".parse().unwrap();
+//! session
+//!     .put("key/expression", "value")
+//!     .with_trace_context(context)
+//!     .res()
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+use crate::net::routing::HexId;
+use rand::Rng;
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use zenoh_protocol::zenoh::ext::TraceContextType;
+use zenoh_result::{Error, ZResult};
+
+/// The only version of the `traceparent` format defined by the W3C Trace Context recommendation.
+const TRACEPARENT_VERSION: u8 = 0x00;
+
+thread_local! {
+    static CURRENT: Cell<Option<TraceContext>> = Cell::new(None);
+}
+
+/// A W3C trace context, identifying a trace and the span of the operation that sent a message in it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    flags: u8,
+}
+
+impl TraceContext {
+    /// The `sampled` flag, set when the caller may have recorded trace data.
+    pub const FLAG_SAMPLED: u8 = 0x01;
+
+    /// Creates a sampled trace context starting a new trace.
+    pub fn new_root() -> Self {
+        let mut rng = rand::thread_rng();
+        TraceContext {
+            trace_id: random_id(&mut rng),
+            span_id: random_id(&mut rng),
+            flags: Self::FLAG_SAMPLED,
+        }
+    }
+
+    /// Creates a trace context from its parts, failing if the trace or span id is invalid (all zeros).
+    pub fn from_parts(trace_id: [u8; 16], span_id: [u8; 8], flags: u8) -> ZResult<Self> {
+        if trace_id == [0; 16] {
+            bail!("Invalid trace id: all zeros");
+        }
+        if span_id == [0; 8] {
+            bail!("Invalid span id: all zeros");
+        }
+        Ok(TraceContext {
+            trace_id,
+            span_id,
+            flags,
+        })
+    }
+
+    /// The id of the trace.
+    pub fn trace_id(&self) -> [u8; 16] {
+        self.trace_id
+    }
+
+    /// The id of the span of the operation that sent this context.
+    pub fn span_id(&self) -> [u8; 8] {
+        self.span_id
+    }
+
+    /// The trace flags.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Whether the [`FLAG_SAMPLED`](Self::FLAG_SAMPLED) flag is set.
+    pub fn is_sampled(&self) -> bool {
+        self.flags & Self::FLAG_SAMPLED != 0
+    }
+
+    /// Creates a trace context for a new span in the same trace.
+    pub fn child(&self) -> Self {
+        TraceContext {
+            trace_id: self.trace_id,
+            span_id: random_id(&mut rand::thread_rng()),
+            flags: self.flags,
+        }
+    }
+
+    /// The trace context set on the current thread, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(Cell::get)
+    }
+
+    /// Sets this trace context as the current one on this thread until the returned guard is dropped.
+    pub fn enter(self) -> TraceContextGuard {
+        TraceContextGuard {
+            previous: CURRENT.with(|c| c.replace(Some(self))),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+fn random_id<const N: usize, R: Rng>(rng: &mut R) -> [u8; N] {
+    loop {
+        let id: [u8; N] = std::array::from_fn(|_| rng.gen());
+        if id != [0; N] {
+            return id;
+        }
+    }
+}
+
+/// Restores the previous [current](TraceContext::current) trace context when dropped.
+#[must_use = "The trace context is only current until the guard is dropped"]
+pub struct TraceContextGuard {
+    previous: Option<TraceContext>,
+    // The guard must be dropped on the thread it was created on
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for TraceContextGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|c| c.set(self.previous));
+    }
+}
+
+fn parse_hex<const N: usize>(s: &str) -> ZResult<[u8; N]> {
+    if s.len() != 2 * N || !s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        bail!(
+            "Expected {} lowercase hexadecimal digits, got '{}'",
+            2 * N,
+            s
+        );
+    }
+    let mut bytes = [0u8; N];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    Ok(bytes)
+}
+
+/// Formats this trace context as a `traceparent` header value.
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{TRACEPARENT_VERSION:02x}-{}-{}-{:02x}",
+            HexId(&self.trace_id),
+            HexId(&self.span_id),
+            self.flags
+        )
+    }
+}
+
+impl fmt::Debug for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TraceContext({self})")
+    }
+}
+
+/// Parses a `traceparent` header value.
+impl FromStr for TraceContext {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('-');
+        let (Some(version), Some(trace_id), Some(span_id), Some(flags)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("Invalid traceparent '{}': expected 4 fields", s);
+        };
+        let [version] = parse_hex::<1>(version)?;
+        if version == 0xff {
+            bail!("Invalid traceparent '{}': version ff is forbidden", s);
+        }
+        // Future versions may append fields, but version 00 has exactly 4 of them
+        if version == TRACEPARENT_VERSION && parts.next().is_some() {
+            bail!("Invalid traceparent '{}': too many fields", s);
+        }
+        let [flags] = parse_hex::<1>(flags)?;
+        TraceContext::from_parts(parse_hex(trace_id)?, parse_hex(span_id)?, flags)
+    }
+}
+
+impl<const ID: u8> From<TraceContext> for TraceContextType<{ ID }> {
+    fn from(ctx: TraceContext) -> Self {
+        TraceContextType {
+            trace_id: ctx.trace_id,
+            span_id: ctx.span_id,
+            flags: ctx.flags,
+        }
+    }
+}
+
+impl<const ID: u8> TryFrom<TraceContextType<{ ID }>> for TraceContext {
+    type Error = Error;
+
+    fn try_from(ext: TraceContextType<{ ID }>) -> Result<Self, Self::Error> {
+        TraceContext::from_parts(ext.trace_id, ext.span_id, ext.flags)
+    }
+}
+
+/// Extracts the trace context carried by a received message, dropping it if invalid.
+pub(crate) fn extract<const ID: u8>(ext: Option<TraceContextType<{ ID }>>) -> Option<TraceContext> {
+    ext.and_then(|ext| match TraceContext::try_from(ext) {
+        Ok(ctx) => Some(ctx),
+        Err(e) => {
+            tracing::debug!("Ignoring received trace context: {}", e);
+            None
+        }
+    })
+}
+
+/// The trace context to inject in a message sent by an operation, given the one explicitly set on it.
+pub(crate) fn inject(explicit: Option<TraceContext>) -> Option<TraceContext> {
+    explicit.or_else(|| TraceContext::current().map(|ctx| ctx.child()))
+}
+
+/// Runs a callback with `ctx` as the current trace context, in a `tracing` span recording it.
+pub(crate) fn in_context<R>(
+    ctx: Option<TraceContext>,
+    span_name: &'static str,
+    f: impl FnOnce() -> R,
+) -> R {
+    match ctx {
+        Some(ctx) => {
+            let span = match span_name {
+                "zenoh_query" => tracing::debug_span!(
+                    "zenoh_query",
+                    trace_id = %HexId(&ctx.trace_id),
+                    parent_span_id = %HexId(&ctx.span_id)
+                ),
+                _ => tracing::debug_span!(
+                    "zenoh_sample",
+                    trace_id = %HexId(&ctx.trace_id),
+                    parent_span_id = %HexId(&ctx.span_id)
+                ),
+            };
+            let _span = span.enter();
+            let _guard = ctx.enter();
+            f()
+        }
+        None => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traceparent() {
+        let s = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let ctx: TraceContext = s.parse().unwrap();
+        assert_eq!(ctx.trace_id()[0], 0x4b);
+        assert_eq!(ctx.span_id()[7], 0xb7);
+        assert!(ctx.is_sampled());
+        assert_eq!(ctx.to_string(), s);
+
+        // Future versions may carry more fields
+        assert!(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra"
+                .parse::<TraceContext>()
+                .is_ok()
+        );
+        for invalid in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e47-00f067aa0ba902b7-01",
+        ] {
+            assert!(invalid.parse::<TraceContext>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn current() {
+        assert!(TraceContext::current().is_none());
+        let root = TraceContext::new_root();
+        let child = root.child();
+        assert_eq!(child.trace_id(), root.trace_id());
+        assert_ne!(child.span_id(), root.span_id());
+        {
+            let _guard = root.enter();
+            assert_eq!(TraceContext::current(), Some(root));
+            {
+                let _guard = child.enter();
+                assert_eq!(TraceContext::current(), Some(child));
+            }
+            assert_eq!(TraceContext::current(), Some(root));
+            let injected = inject(None).unwrap();
+            assert_eq!(injected.trace_id(), root.trace_id());
+            assert_ne!(injected.span_id(), root.span_id());
+            assert_eq!(inject(Some(child)), Some(child));
+        }
+        assert!(TraceContext::current().is_none());
+        assert!(inject(None).is_none());
+    }
+}
@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the metrics exporter configured with `metrics/listen` -
+// 1. `/metrics` is served in OpenMetrics text format
+// 2. a client which doesn't send its request doesn't delay the others
+// 3. other methods and paths are rejected
+// 4. with `stats`, the transport counters carry the `_total` suffix on `/metrics` only, not in the admin space
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use zenoh::prelude::r#async::*;
+use zenoh_core::ztimeout;
+
+// Shorter than the time the exporter waits for a request
+const TIMEOUT: Duration = Duration::from_secs(2);
+const ADDRESS: &str = "127.0.0.1:19464";
+
+async fn scrape(request: &str) -> String {
+    let mut stream = TcpStream::connect(ADDRESS).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_metrics_exporter() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.adminspace.set_enabled(true).unwrap();
+    config
+        .insert_json5("metrics", &format!(r#"{{ listen: "{ADDRESS}" }}"#))
+        .unwrap();
+    let session = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+    // stays connected without sending its request until the end of the test
+    let _idle = ztimeout!(TcpStream::connect(ADDRESS)).unwrap();
+
+    let response = ztimeout!(scrape("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n"));
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    assert!(
+        response.contains("Content-Type: application/openmetrics-text"),
+        "{response}"
+    );
+    assert!(
+        response.contains("\r\n\r\n# HELP zenoh_build "),
+        "{response}"
+    );
+    assert!(response.ends_with("# EOF\n"), "{response}");
+    #[cfg(feature = "stats")]
+    {
+        assert!(response.contains("\ntx_bytes_total "), "{response}");
+        let replies = ztimeout!(session
+            .get(format!("@/peer/{}/metrics", session.zid()))
+            .res_async())
+        .unwrap();
+        let admin = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+        let admin = admin.value.to_string();
+        assert!(admin.contains("\ntx_bytes "), "{admin}");
+        assert!(!admin.contains("tx_bytes_total"), "{admin}");
+    }
+
+    let response = ztimeout!(scrape("POST /metrics HTTP/1.1\r\n\r\n"));
+    assert!(
+        response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"),
+        "{response}"
+    );
+    let response = ztimeout!(scrape("GET /other HTTP/1.1\r\n\r\n"));
+    assert!(
+        response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+        "{response}"
+    );
+
+    ztimeout!(session.close().res_async()).unwrap();
+}
@@ -0,0 +1,127 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the actions on a static plugin through the admin space -
+// 1. stop, start and restart, and the `__state__` of the plugin after each one
+// 2. a plugin failing to load again keeps its previous declaration
+#![cfg(all(feature = "unstable", feature = "plugins"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use zenoh::plugins::{PluginsManager, RunningPlugin, RunningPluginTrait};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{Runtime, RuntimeBuilder};
+use zenoh_core::ztimeout;
+use zenoh_plugin_trait::{Plugin, PluginControl};
+use zenoh_result::ZResult;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+static STARTED: AtomicUsize = AtomicUsize::new(0);
+static STOPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct TestPlugin;
+
+impl Plugin for TestPlugin {
+    type StartArgs = Runtime;
+    type Instance = RunningPlugin;
+    const DEFAULT_NAME: &'static str = "test";
+    const PLUGIN_VERSION: &'static str = "";
+    const PLUGIN_LONG_VERSION: &'static str = "";
+
+    fn start(_name: &str, _runtime: &Runtime) -> ZResult<RunningPlugin> {
+        STARTED.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(RunningTestPlugin))
+    }
+}
+
+struct RunningTestPlugin;
+
+impl Drop for RunningTestPlugin {
+    fn drop(&mut self) {
+        STOPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl PluginControl for RunningTestPlugin {}
+impl RunningPluginTrait for RunningTestPlugin {}
+
+// The value of the single reply to a query, or its error
+async fn get(session: &Session, selector: &str) -> Result<serde_json::Value, String> {
+    let replies = ztimeout!(session.get(selector).res_async()).unwrap();
+    let reply = ztimeout!(replies.recv_async()).unwrap();
+    match reply.sample {
+        Ok(sample) => Ok(serde_json::from_str(&sample.value.to_string()).unwrap()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_plugins_actions() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .insert_json5(
+            "adminspace",
+            r#"{ enabled: true, permissions: { read: true, write: true } }"#,
+        )
+        .unwrap();
+    config.insert_json5("plugins/test", "{}").unwrap();
+    let plugins_manager =
+        PluginsManager::static_plugins_only().declare_static_plugin::<TestPlugin, _>("test", false);
+    let runtime = ztimeout!(RuntimeBuilder::new(config)
+        .plugins_manager(plugins_manager)
+        .build())
+    .unwrap();
+    let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+    let zid = session.zid();
+    let plugin = format!("@/peer/{zid}/plugins/test");
+    let state = format!("@/peer/{zid}/status/plugins/test/__state__");
+
+    assert_eq!(get(&session, &state).await.unwrap(), "Started");
+    assert_eq!(STARTED.load(Ordering::SeqCst), 1);
+    let error = get(&session, &format!("{plugin}?_action=load"))
+        .await
+        .unwrap_err();
+    assert!(error.contains("already started"), "{error}");
+
+    let status = get(&session, &format!("{plugin}?_action=stop"))
+        .await
+        .unwrap();
+    assert_eq!(status["state"], "Loaded");
+    assert_eq!(get(&session, &state).await.unwrap(), "Loaded");
+    assert_eq!(STOPPED.load(Ordering::SeqCst), 1);
+
+    // no library provides the plugin, which stays declared
+    assert!(get(&session, &format!("{plugin}?_action=load"))
+        .await
+        .is_err());
+    assert_eq!(get(&session, &state).await.unwrap(), "Loaded");
+
+    let status = get(&session, &format!("{plugin}?_action=start"))
+        .await
+        .unwrap();
+    assert_eq!(status["state"], "Started");
+    assert_eq!(get(&session, &state).await.unwrap(), "Started");
+    assert_eq!(STARTED.load(Ordering::SeqCst), 2);
+
+    let status = get(&session, &format!("{plugin}?_action=restart"))
+        .await
+        .unwrap();
+    assert_eq!(status["state"], "Started");
+    assert_eq!(STOPPED.load(Ordering::SeqCst), 2);
+    assert_eq!(STARTED.load(Ordering::SeqCst), 3);
+    assert_eq!(get(&session, &state).await.unwrap(), "Started");
+
+    ztimeout!(session.close().res_async()).unwrap();
+}
@@ -486,6 +486,140 @@ async fn static_failover_brokering() -> Result<()> {
     Result::Ok(())
 }
 
+// Simulate a router aggregating the subscriptions of its clients into a single subscription
+// announced to another router. The upstream router must only know the aggregated subscription,
+// the local peers the precise subscriptions, and publications must still reach the precise subscribers.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_subscription_aggregation() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let locator1 = String::from("tcp/127.0.0.1:17442");
+    let locator2 = String::from("tcp/127.0.0.1:17443");
+    let ke_aggregated = "testKeyExprAggregation/**";
+    let kes = [
+        "testKeyExprAggregation/sensor/1",
+        "testKeyExprAggregation/sensor/2",
+        "testKeyExprAggregation/sensor/3",
+    ];
+
+    let node_config = |mode: WhatAmI, listen: &[&String], connect: &[&String]| {
+        let mut config = Config::default();
+        config.set_mode(Some(mode)).unwrap();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .listen
+            .set_endpoints(listen.iter().map(|x| x.parse().unwrap()).collect())
+            .unwrap();
+        config
+            .connect
+            .set_endpoints(connect.iter().map(|x| x.parse().unwrap()).collect())
+            .unwrap();
+        config
+    };
+
+    let mut aggregating_config = node_config(WhatAmI::Router, &[&locator1], &[]);
+    aggregating_config
+        .aggregation
+        .set_subscribers(vec![OwnedKeyExpr::from_str(ke_aggregated).unwrap()])
+        .unwrap();
+    let aggregating = ztimeout!(zenoh::open(aggregating_config).res_async())?;
+    let mut upstream_config = node_config(WhatAmI::Router, &[&locator2], &[&locator1]);
+    upstream_config.adminspace.set_enabled(true).unwrap();
+    let upstream = ztimeout!(zenoh::open(upstream_config).res_async())?;
+    let sub_client =
+        ztimeout!(zenoh::open(node_config(WhatAmI::Client, &[], &[&locator1])).res_async())?;
+    let pub_client =
+        ztimeout!(zenoh::open(node_config(WhatAmI::Client, &[], &[&locator2])).res_async())?;
+    let local_peer_config = || {
+        let mut config = node_config(WhatAmI::Peer, &[], &[&locator1]);
+        config.adminspace.set_enabled(true).unwrap();
+        config
+            .scouting
+            .gossip
+            .set_autoconnect(Some(ModeDependentValue::Unique(
+                WhatAmIMatcher::from_str("").unwrap(),
+            )))
+            .unwrap();
+        config
+    };
+    // A peer connected before the subscriptions are declared, and one connected after
+    let peer_before = ztimeout!(zenoh::open(local_peer_config()).res_async())?;
+
+    let mut subs = vec![];
+    for ke in kes {
+        subs.push(ztimeout!(sub_client.declare_subscriber(ke).res_async())?);
+    }
+    let peer_after = ztimeout!(zenoh::open(local_peer_config()).res_async())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // The upstream router only knows the aggregated subscription, sourced by the aggregating router
+    let selector = format!(
+        "@/router/{}/subscriber/testKeyExprAggregation/**",
+        upstream.zid()
+    );
+    let replies = ztimeout!(upstream.get(&selector).res_async())?;
+    let mut keys = vec![];
+    while let Ok(reply) = ztimeout!(replies.recv_async()) {
+        let sample = reply.sample?;
+        let sources: serde_json::Value =
+            serde_json::from_slice(&sample.value.payload.contiguous())?;
+        assert_eq!(
+            sources["routers"],
+            serde_json::json!([aggregating.zid().to_string()])
+        );
+        keys.push(sample.key_expr.to_string());
+    }
+    assert_eq!(
+        keys,
+        vec![format!(
+            "@/router/{}/subscriber/{}",
+            upstream.zid(),
+            ke_aggregated
+        )]
+    );
+
+    // The local peers know the precise subscriptions, sourced by the aggregating router
+    for peer in [&peer_before, &peer_after] {
+        let selector = format!("@/peer/{}/subscriber/testKeyExprAggregation/**", peer.zid());
+        let replies = ztimeout!(peer.get(&selector).res_async())?;
+        let mut keys = vec![];
+        while let Ok(reply) = ztimeout!(replies.recv_async()) {
+            let sample = reply.sample?;
+            let sources: serde_json::Value =
+                serde_json::from_slice(&sample.value.payload.contiguous())?;
+            assert_eq!(
+                sources["routers"],
+                serde_json::json!([aggregating.zid().to_string()])
+            );
+            keys.push(sample.key_expr.to_string());
+        }
+        keys.sort();
+        let expected: Vec<String> = kes
+            .iter()
+            .map(|ke| format!("@/peer/{}/subscriber/{}", peer.zid(), ke))
+            .collect();
+        assert_eq!(keys, expected);
+    }
+
+    // Publications through the upstream router still reach the precise subscribers
+    for (ke, sub) in kes.iter().zip(subs.iter()) {
+        ztimeout!(pub_client.put(*ke, *ke).res_async())?;
+        let sample = ztimeout!(sub.recv_async())?;
+        assert_eq!(sample.key_expr.as_str(), *ke);
+        assert!(sub.try_recv().is_err());
+    }
+
+    // Undeclaring all the aggregated subscriptions undeclares the aggregate upstream
+    for sub in subs {
+        ztimeout!(sub.undeclare().res_async())?;
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let replies = ztimeout!(upstream.get(&selector).res_async())?;
+    assert!(ztimeout!(replies.recv_async()).is_err());
+
+    println!("Router subscription aggregation test passed.");
+    Result::Ok(())
+}
+
 // All test cases varying in
 // 1. Message size: 2 (sizes)
 // 2. Mode: {Client, Peer} x {Client x Peer} x {Router} = 2 x 2 x 1 = 4 (cases)
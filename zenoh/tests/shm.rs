@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "shared-memory")]
+mod tests {
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh::shm::{SharedMemoryBuf, SharedMemoryManager};
+    use zenoh_core::ztimeout;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+    const MSG_SIZE: usize = 1_024;
+
+    fn config(mode: WhatAmI, endpoint: &str, shm: bool) -> Config {
+        let mut config = Config::default();
+        config.set_mode(Some(mode)).unwrap();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        if mode == WhatAmI::Router {
+            config
+                .listen
+                .set_endpoints(vec![endpoint.parse().unwrap()])
+                .unwrap();
+        } else {
+            config
+                .connect
+                .set_endpoints(vec![endpoint.parse().unwrap()])
+                .unwrap();
+        }
+        config.transport.shared_memory.set_enabled(shm).unwrap();
+        config
+    }
+
+    fn is_shm(sample: &Sample) -> bool {
+        sample
+            .value
+            .payload
+            .zslices()
+            .any(|s| s.downcast_ref::<SharedMemoryBuf>().is_some())
+    }
+
+    // A publisher and a subscriber connected to the same router exchange shared memory
+    // references through it, while a subscriber without shared memory receives the payload inlined.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn shm_through_router() {
+        zenoh_util::try_init_log_from_env();
+        let endpoint = "tcp/127.0.0.1:17444";
+        let ke = "test/shm/router";
+
+        let router =
+            ztimeout!(zenoh::open(config(WhatAmI::Router, endpoint, true)).res_async()).unwrap();
+        let publisher =
+            ztimeout!(zenoh::open(config(WhatAmI::Client, endpoint, true)).res_async()).unwrap();
+        let shm_subscriber =
+            ztimeout!(zenoh::open(config(WhatAmI::Client, endpoint, true)).res_async()).unwrap();
+        let net_subscriber =
+            ztimeout!(zenoh::open(config(WhatAmI::Client, endpoint, false)).res_async()).unwrap();
+
+        let shm_sub = ztimeout!(shm_subscriber.declare_subscriber(ke).res_async()).unwrap();
+        let net_sub = ztimeout!(net_subscriber.declare_subscriber(ke).res_async()).unwrap();
+        tokio::time::sleep(SLEEP).await;
+
+        let mut shm = SharedMemoryManager::make(
+            format!("shm_through_router_{}", publisher.zid()),
+            2 * MSG_SIZE,
+        )
+        .unwrap();
+        let mut sbuf = shm.alloc(MSG_SIZE).unwrap();
+        unsafe { sbuf.as_mut_slice() }.fill(42);
+        ztimeout!(publisher.put(ke, sbuf).res_async()).unwrap();
+
+        let sample = ztimeout!(shm_sub.recv_async()).unwrap();
+        assert!(is_shm(&sample));
+        assert_eq!(sample.value.payload.contiguous(), vec![42u8; MSG_SIZE]);
+
+        let sample = ztimeout!(net_sub.recv_async()).unwrap();
+        assert!(!is_shm(&sample));
+        assert_eq!(sample.value.payload.contiguous(), vec![42u8; MSG_SIZE]);
+
+        drop((shm_sub, net_sub));
+        ztimeout!(net_subscriber.close().res_async()).unwrap();
+        ztimeout!(shm_subscriber.close().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    }
+}
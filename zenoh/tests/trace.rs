@@ -0,0 +1,121 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+mod unstable {
+    use std::time::Duration;
+    use zenoh::prelude::sync::*;
+    use zenoh::trace::TraceContext;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    fn open_sessions(endpoint: &str) -> (Session, Session) {
+        let mut config = config::peer();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.routing.set_trace_spans(Some(true)).unwrap();
+        let peer01 = zenoh::open(config).res().unwrap();
+
+        let mut config = config::peer();
+        config.connect.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.routing.set_trace_spans(Some(true)).unwrap();
+        let peer02 = zenoh::open(config).res().unwrap();
+
+        (peer01, peer02)
+    }
+
+    #[test]
+    fn pubsub() {
+        let (peer01, peer02) = open_sessions("tcp/127.0.0.1:17460");
+        let key_expr = "test/trace/pubsub";
+
+        let (tx, rx) = flume::unbounded();
+        let _sub = peer02
+            .declare_subscriber(key_expr)
+            .callback(move |sample| {
+                tx.send((sample.trace_context().copied(), TraceContext::current()))
+                    .unwrap();
+            })
+            .res()
+            .unwrap();
+        std::thread::sleep(SLEEP);
+
+        // An explicit trace context is sent as is, and is current in the subscriber callback
+        let context = TraceContext::new_root();
+        peer01
+            .put(key_expr, "explicit")
+            .with_trace_context(context)
+            .res()
+            .unwrap();
+        let (received, current) = rx.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(received, Some(context));
+        assert_eq!(current, Some(context));
+
+        // Otherwise, a child of the current trace context is sent
+        let publisher = peer01.declare_publisher(key_expr).res().unwrap();
+        {
+            let _guard = context.enter();
+            publisher.put("current").res().unwrap();
+        }
+        let (received, current) = rx.recv_timeout(TIMEOUT).unwrap();
+        let received = received.unwrap();
+        assert_eq!(received.trace_id(), context.trace_id());
+        assert_ne!(received.span_id(), context.span_id());
+        assert_eq!(current, Some(received));
+
+        // And none is sent outside of a trace
+        publisher.delete().res().unwrap();
+        let (received, current) = rx.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(received, None);
+        assert_eq!(current, None);
+    }
+
+    #[test]
+    fn queries() {
+        let (peer01, peer02) = open_sessions("tcp/127.0.0.1:17461");
+        let key_expr = "test/trace/queries";
+
+        let (tx, rx) = flume::unbounded();
+        let _qabl = peer02
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                tx.send((query.trace_context().copied(), TraceContext::current()))
+                    .unwrap();
+                query
+                    .reply(Ok(Sample::new(query.key_expr().clone(), "reply")))
+                    .res()
+                    .unwrap();
+            })
+            .res()
+            .unwrap();
+        std::thread::sleep(SLEEP);
+
+        let context = TraceContext::new_root();
+        let replies = peer01
+            .get(key_expr)
+            .with_trace_context(context)
+            .res()
+            .unwrap();
+        let (received, current) = rx.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(received, Some(context));
+        assert_eq!(current, Some(context));
+
+        // The reply is sent in a new span of the same trace
+        let reply = replies.recv_timeout(TIMEOUT).unwrap();
+        let replied = *reply.sample.unwrap().trace_context().unwrap();
+        assert_eq!(replied.trace_id(), context.trace_id());
+        assert_ne!(replied.span_id(), context.span_id());
+    }
+}
@@ -0,0 +1,299 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test a plugin compiled to WebAssembly, loaded from the `plugins` configuration -
+// 1. it's started, and may only subscribe on the allowed key expressions
+// 2. it republishes the samples it receives, and replies to queries
+// 3. a call running out of fuel fails, and the query is replied with an error
+// 4. the buffers of the samples are freed with `dealloc`, so that more samples than its memory may hold are handled
+#![cfg(all(feature = "unstable", feature = "wasm_plugins"))]
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::RuntimeBuilder;
+use zenoh_core::ztimeout;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// A minimal encoder of WebAssembly modules
+mod encoder {
+    pub const I32: u8 = 0x7f;
+    pub const BLOCK: u8 = 0x40;
+    pub const LOOP: u8 = 0x03;
+    pub const IF: u8 = 0x04;
+    pub const BR: u8 = 0x0c;
+    pub const RETURN: u8 = 0x0f;
+    pub const CALL: u8 = 0x10;
+    pub const DROP: u8 = 0x1a;
+    pub const LOCAL_GET: u8 = 0x20;
+    pub const GLOBAL_GET: u8 = 0x23;
+    pub const GLOBAL_SET: u8 = 0x24;
+    pub const I32_CONST: u8 = 0x41;
+    pub const I32_EQ: u8 = 0x46;
+    pub const I32_NE: u8 = 0x47;
+    pub const I32_LT_S: u8 = 0x48;
+    pub const I32_ADD: u8 = 0x6a;
+    pub const END: u8 = 0x0b;
+
+    pub fn uleb(mut value: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    pub fn sleb(mut value: i32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    pub fn i32_const(value: i32) -> Vec<u8> {
+        [vec![I32_CONST], sleb(value)].concat()
+    }
+
+    pub fn name(name: &str) -> Vec<u8> {
+        [uleb(name.len() as u32), name.as_bytes().to_vec()].concat()
+    }
+
+    pub fn vector(items: Vec<Vec<u8>>) -> Vec<u8> {
+        [uleb(items.len() as u32), items.concat()].concat()
+    }
+
+    pub fn section(id: u8, items: Vec<Vec<u8>>) -> Vec<u8> {
+        let contents = vector(items);
+        [vec![id], uleb(contents.len() as u32), contents].concat()
+    }
+
+    pub fn func_type(params: usize, results: usize) -> Vec<u8> {
+        [
+            vector(vec![vec![I32]; params]),
+            vector(vec![vec![I32]; results]),
+        ]
+        .concat()
+    }
+
+    pub fn body(code: Vec<Vec<u8>>) -> Vec<u8> {
+        let contents = [vec![0], code.concat(), vec![END]].concat();
+        [uleb(contents.len() as u32), contents].concat()
+    }
+}
+
+// The plugin subscribes on `wasm/in/**` and republishes the payloads on `wasm/out`.
+// It replies `42` to the queries on `wasm/query/**`, and loops forever on the queries on `wasm/spin`.
+// Its memory is a single page, allocated as a stack from which the last buffer may be freed.
+fn guest() -> Vec<u8> {
+    use encoder::*;
+    // The strings, by offset in the memory
+    let strings = [
+        (0, "wasm/in/**"),
+        (16, "wasm/out"),
+        (32, "wasm/query/**"),
+        (48, "wasm/query/answer"),
+        (80, "wasm/spin"),
+        (96, "other/**"),
+        (112, "42"),
+    ];
+    let string = |offset: i32| {
+        let (_, s) = strings.iter().find(|(o, _)| *o == offset).unwrap();
+        [i32_const(offset), i32_const(s.len() as i32)].concat()
+    };
+    let call = |function: u32| [vec![CALL], uleb(function)].concat();
+    // Returns `code` if the result on the stack is `condition` to 0, or -1 for `I32_NE`
+    let fail_if = |condition: u8, code: i32| {
+        let compared = if condition == I32_NE { -1 } else { 0 };
+        [
+            i32_const(compared),
+            vec![condition, IF, BLOCK],
+            i32_const(code),
+            vec![RETURN, END],
+        ]
+        .concat()
+    };
+    let (put, subscribe, declare_queryable, reply) = (0, 1, 2, 3);
+    [
+        b"\0asm\x01\0\0\0".to_vec(),
+        section(
+            1,
+            vec![
+                [vec![0x60], func_type(4, 1)].concat(),
+                [vec![0x60], func_type(2, 1)].concat(),
+                [vec![0x60], func_type(5, 1)].concat(),
+                [vec![0x60], func_type(1, 1)].concat(),
+                [vec![0x60], func_type(0, 1)].concat(),
+                [vec![0x60], func_type(5, 0)].concat(),
+                [vec![0x60], func_type(6, 0)].concat(),
+                [vec![0x60], func_type(2, 0)].concat(),
+            ],
+        ),
+        section(
+            2,
+            vec![
+                [name("zenoh"), name("put"), vec![0, 0]].concat(),
+                [name("zenoh"), name("subscribe"), vec![0, 1]].concat(),
+                [name("zenoh"), name("declare_queryable"), vec![0, 1]].concat(),
+                [name("zenoh"), name("reply"), vec![0, 2]].concat(),
+            ],
+        ),
+        section(3, vec![vec![3], vec![4], vec![5], vec![6], vec![7]]),
+        section(5, vec![vec![0, 1]]),
+        section(6, vec![[vec![I32, 1], i32_const(1024), vec![END]].concat()]),
+        section(
+            7,
+            vec![
+                [name("memory"), vec![2, 0]].concat(),
+                [name("alloc"), vec![0, 4]].concat(),
+                [name("start"), vec![0, 5]].concat(),
+                [name("on_sample"), vec![0, 6]].concat(),
+                [name("on_query"), vec![0, 7]].concat(),
+                [name("dealloc"), vec![0, 8]].concat(),
+            ],
+        ),
+        section(
+            10,
+            vec![
+                // alloc(len): bumps the next free offset
+                body(vec![vec![
+                    GLOBAL_GET, 0, GLOBAL_GET, 0, LOCAL_GET, 0, I32_ADD, GLOBAL_SET, 0,
+                ]]),
+                // start(): the subscription on `other/**` must be denied
+                body(vec![
+                    string(96),
+                    call(subscribe),
+                    fail_if(I32_NE, 1),
+                    string(0),
+                    call(subscribe),
+                    fail_if(I32_LT_S, 2),
+                    string(32),
+                    call(declare_queryable),
+                    fail_if(I32_LT_S, 3),
+                    string(80),
+                    call(declare_queryable),
+                    fail_if(I32_LT_S, 4),
+                    i32_const(0),
+                ]),
+                // on_sample(subscriber, key_ptr, key_len, ptr, len)
+                body(vec![
+                    string(16),
+                    vec![LOCAL_GET, 3, LOCAL_GET, 4],
+                    call(put),
+                    vec![DROP],
+                ]),
+                // on_query(queryable, query, key_ptr, key_len, ptr, len)
+                body(vec![
+                    vec![LOCAL_GET, 0, IF, BLOCK, LOOP, BLOCK, BR, 0, END, END],
+                    vec![LOCAL_GET, 1],
+                    string(48),
+                    string(112),
+                    call(reply),
+                    vec![DROP],
+                ]),
+                // dealloc(ptr, len): frees the buffer if it's the last one allocated
+                body(vec![
+                    vec![LOCAL_GET, 0, LOCAL_GET, 1, I32_ADD, GLOBAL_GET, 0, I32_EQ],
+                    vec![IF, BLOCK, LOCAL_GET, 0, GLOBAL_SET, 0, END],
+                ]),
+            ],
+        ),
+        section(
+            11,
+            strings
+                .iter()
+                .map(|(offset, s)| [vec![0], i32_const(*offset), vec![END], name(s)].concat())
+                .collect(),
+        ),
+    ]
+    .concat()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn zenoh_wasm_plugin() {
+    let dir = std::env::temp_dir().join(format!("zenoh-wasm-plugins-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("guest.wasm");
+    std::fs::write(&path, guest()).unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .insert_json5("adminspace", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/guest",
+            &format!(
+                r#"{{
+                    __path__: "{}",
+                    allow: {{ publish: ["wasm/out"], subscribe: ["wasm/in/**"], queryable: ["wasm/query/**", "wasm/spin"] }},
+                    fuel_per_call: 100000,
+                }}"#,
+                path.display()
+            ),
+        )
+        .unwrap();
+    let runtime = ztimeout!(RuntimeBuilder::new(config).build()).unwrap();
+    let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+    let zid = session.zid();
+
+    let replies = ztimeout!(session
+        .get(format!("@/peer/{zid}/status/plugins/guest/__state__"))
+        .res_async())
+    .unwrap();
+    let state = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+    assert_eq!(state.value.to_string(), r#""Started""#);
+
+    let subscriber = ztimeout!(session.declare_subscriber("wasm/out").res_async()).unwrap();
+    ztimeout!(session.put("wasm/in/a", "hello").res_async()).unwrap();
+    let sample = ztimeout!(subscriber.recv_async()).unwrap();
+    assert_eq!(sample.value.payload.contiguous().as_ref(), b"hello");
+
+    // Without being freed, the buffers of 4 of these samples would exceed the memory of the plugin
+    let payload = vec![b'x'; 16 * 1024];
+    for i in 0..8 {
+        ztimeout!(session
+            .put(format!("wasm/in/large/{i}"), payload.clone())
+            .res_async())
+        .unwrap();
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        assert_eq!(
+            sample.value.payload.contiguous().as_ref(),
+            payload.as_slice()
+        );
+    }
+
+    let replies = ztimeout!(session.get("wasm/query/**").res_async()).unwrap();
+    let reply = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+    assert_eq!(reply.key_expr.as_str(), "wasm/query/answer");
+    assert_eq!(reply.value.payload.contiguous().as_ref(), b"42");
+
+    let replies = ztimeout!(session.get("wasm/spin").res_async()).unwrap();
+    let error = ztimeout!(replies.recv_async()).unwrap().sample.unwrap_err();
+    assert!(error.to_string().contains("fuel"), "{error}");
+
+    drop(subscriber);
+    ztimeout!(session.close().res_async()).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
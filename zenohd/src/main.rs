@@ -14,12 +14,15 @@
 use clap::Parser;
 use futures::future;
 use git_version::git_version;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
-use zenoh::config::{Config, ModeDependentValue, PermissionsConf, ValidatedMap};
+use zenoh::config::{Config, ModeDependentValue, PermissionsConf, PluginLoad, ValidatedMap};
+use zenoh::plugins::{PluginsManager, PLUGIN_PREFIX};
 use zenoh::prelude::r#async::*;
 use zenoh::Result;
+use zenoh_result::bail;
 
 #[cfg(feature = "loki")]
 use url::Url;
@@ -89,6 +92,10 @@ struct Args {
     /// Configure the read and/or write permissions on the admin space. Default is read only.
     #[arg(long, value_name = "[r|w|rw|none]")]
     adminspace_permissions: Option<String>,
+    /// Validates the configuration (including the plugins configurations) and prints the effective configuration,
+    /// without starting the router. Exits with a non-zero status if the configuration is invalid.
+    #[arg(long)]
+    check_config: bool,
 }
 
 fn main() {
@@ -97,12 +104,22 @@ fn main() {
         .build()
         .unwrap()
         .block_on(async {
-            init_logging().unwrap();
+            let args = Args::parse();
+            // When checking the configuration, stdout is reserved to the effective configuration
+            init_logging(args.check_config).unwrap();
 
             tracing::info!("zenohd {}", *LONG_VERSION);
 
-            let args = Args::parse();
-            let config = config_from_args(&args);
+            let config = match config_from_args(&args) {
+                Ok(config) => config,
+                Err(e) => {
+                    println!("{e}. Exiting...");
+                    std::process::exit(-1);
+                }
+            };
+            if args.check_config {
+                std::process::exit(check_config(config).await);
+            }
             tracing::info!("Initial conf: {}", &config);
 
             let _session = match zenoh::open(config).res().await {
@@ -117,13 +134,84 @@ fn main() {
         });
 }
 
-fn config_from_args(args: &Args) -> Config {
-    let mut config = args
-        .config
-        .as_ref()
-        .map_or_else(Config::default, |conf_file| {
-            Config::from_file(conf_file).unwrap()
-        });
+/// Validates the configuration without starting anything, printing the effective configuration on stdout and
+/// the errors found on stderr. Returns the exit status of `zenohd`.
+async fn check_config(config: Config) -> i32 {
+    let mut errors = zenoh::runtime::RuntimeBuilder::new(config.clone())
+        .check()
+        .await
+        .into_iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    errors.extend(check_plugins_config(&config));
+
+    match serde_json::to_string_pretty(&config.sift_privates()) {
+        Ok(json) => println!("{json}"),
+        Err(e) => errors.push(format!("Couldn't serialize the configuration: {e}")),
+    }
+    for e in &errors {
+        eprintln!("error: {e}");
+    }
+    if errors.is_empty() {
+        eprintln!("The configuration is valid");
+        0
+    } else {
+        eprintln!("The configuration is invalid: {} error(s)", errors.len());
+        -1
+    }
+}
+
+/// Loads the plugins without starting them, and validates their configuration against the JSON schema they provide.
+fn check_plugins_config(config: &Config) -> Vec<String> {
+    let mut errors = vec![];
+    let mut manager = PluginsManager::dynamic(config.libloader(), PLUGIN_PREFIX.to_string());
+    for PluginLoad {
+        id,
+        name,
+        paths,
+        required,
+    } in config.plugins().load_requests()
+    {
+        let declared = match paths {
+            Some(paths) => manager.declare_dynamic_plugin_by_paths(&name, &id, &paths, required),
+            None => manager.declare_dynamic_plugin_by_name(&id, &name, required),
+        };
+        let loaded = declared.and_then(|declared| declared.load().map(|_| ()));
+        match loaded {
+            Ok(()) => {}
+            Err(e) if required => errors.push(format!("plugins/{id}: {e}")),
+            Err(e) => tracing::warn!("Optional plugin `{}` couldn't be loaded: {}", id, e),
+        }
+    }
+    for plugin in manager.loaded_plugins_iter() {
+        let (Some(schema), Some(plugin_config)) =
+            (plugin.config_schema(), config.plugin(plugin.id()))
+        else {
+            continue;
+        };
+        match jsonschema::JSONSchema::compile(&schema) {
+            Ok(schema) => {
+                if let Err(es) = schema.validate(plugin_config) {
+                    errors.extend(
+                        es.map(|e| format!("plugins/{}{}: {}", plugin.id(), e.instance_path, e)),
+                    );
+                }
+            }
+            Err(e) => errors.push(format!(
+                "plugins/{}: invalid configuration schema: {}",
+                plugin.id(),
+                e
+            )),
+        }
+    }
+    errors
+}
+
+fn config_from_args(args: &Args) -> Result<Config> {
+    let mut config = match &args.config {
+        Some(conf_file) => Config::from_file(conf_file)?,
+        None => Config::default(),
+    };
 
     if config.mode().is_none() {
         config.set_mode(Some(WhatAmI::Router)).unwrap();
@@ -260,27 +348,37 @@ fn config_from_args(args: &Args) -> Config {
         };
     }
     for json in &args.cfg {
-        if let Some((key, value)) = json.split_once(':') {
-            match json5::Deserializer::from_str(value) {
-                Ok(mut deserializer) => {
-                    if let Err(e) =
-                        config.insert(key.strip_prefix('/').unwrap_or(key), &mut deserializer)
-                    {
-                        tracing::warn!("Couldn't perform configuration {}: {}", json, e);
-                    }
-                }
-                Err(e) => tracing::warn!("Couldn't perform configuration {}: {}", json, e),
+        let result = match json.split_once(':') {
+            Some((key, value)) => match json5::Deserializer::from_str(value) {
+                Ok(mut deserializer) => config
+                    .insert(key.strip_prefix('/').unwrap_or(key), &mut deserializer)
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+            None => Err("expected a KEY:VALUE pair".to_string()),
+        };
+        if let Err(e) = result {
+            // Invalid overrides are only fatal when checking the configuration
+            if args.check_config {
+                bail!("Couldn't perform configuration {}: {}", json, e);
             }
+            tracing::warn!("Couldn't perform configuration {}: {}", json, e);
         }
     }
     tracing::debug!("Config: {:?}", &config);
-    config
+    Ok(config)
 }
 
-fn init_logging() -> Result<()> {
+fn init_logging(to_stderr: bool) -> Result<()> {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("z=info"));
 
+    let writer = if to_stderr {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     let fmt_layer = tracing_subscriber::fmt::Layer::new()
+        .with_writer(writer)
         .with_thread_ids(true)
         .with_thread_names(true)
         .with_level(true)